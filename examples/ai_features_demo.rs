@@ -74,6 +74,7 @@ async fn demo_adaptive_delay() -> Result<()> {
         max_delay_ms: 2500,
         sample_size: 10,
         multiplier: 1.2,
+        ..AdaptiveDelayConfig::default()
     };
 
     let controller = AdaptiveDelayController::new(config);
@@ -129,6 +130,7 @@ async fn demo_speed_presets() -> Result<()> {
             max_delay_ms: 5000,
             sample_size: 10,
             multiplier: 1.5,
+            ..AdaptiveDelayConfig::default()
         }),
         ("Medium (Balanced)", AdaptiveDelayConfig {
             mode: DelayMode::Adaptive,
@@ -136,6 +138,7 @@ async fn demo_speed_presets() -> Result<()> {
             max_delay_ms: 3000,
             sample_size: 10,
             multiplier: 1.2,
+            ..AdaptiveDelayConfig::default()
         }),
         ("Fast (Aggressive)", AdaptiveDelayConfig {
             mode: DelayMode::Adaptive,
@@ -143,6 +146,7 @@ async fn demo_speed_presets() -> Result<()> {
             max_delay_ms: 1500,
             sample_size: 10,
             multiplier: 1.1,
+            ..AdaptiveDelayConfig::default()
         }),
         ("Fixed Rate", AdaptiveDelayConfig {
             mode: DelayMode::Fixed,
@@ -150,6 +154,7 @@ async fn demo_speed_presets() -> Result<()> {
             max_delay_ms: 2000,
             sample_size: 1,
             multiplier: 1.0,
+            ..AdaptiveDelayConfig::default()
         }),
     ];
 
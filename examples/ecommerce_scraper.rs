@@ -57,7 +57,8 @@ async fn main() -> Result<()> {
     // Configure Books to Scrape source
     // This is a real website specifically designed for scraping practice
     let books_source = EcommerceSource::new("https://books.toscrape.com/catalogue/category/books_1/index.html")
-        .with_name("Books to Scrape");
+        .with_name("Books to Scrape")
+        .with_max_pages(3);
 
     println!("📡 Scraping from: {}", books_source.name());
     println!("   Category: All Books");
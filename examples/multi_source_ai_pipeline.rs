@@ -15,6 +15,7 @@ use rust_scraper_pro::{
     processors::pipeline::ProcessingPipeline,
     sources::{EcommerceSource, SourceType},
     ai::{DeepSeekClient, DataNormalizer},
+    output::atomic::write_file_ensuring_dirs,
     utils::cache::HtmlCache,
 };
 use std::sync::Arc;
@@ -96,7 +97,7 @@ async fn main() -> Result<()> {
                     source_name.replace(" ", "_").replace("-", "_").to_lowercase());
                 
                 let json = serde_json::to_string_pretty(&data)?;
-                tokio::fs::write(&filename, json).await?;
+                write_file_ensuring_dirs(&filename, json).await?;
                 println!("      💾 Saved to {}", filename);
                 
                 source_stats.push((source_name, count));
@@ -183,7 +184,7 @@ async fn main() -> Result<()> {
                             let simple_normalized = DataNormalizer::normalize_simple(processed_data);
                             
                             let json = serde_json::to_string_pretty(&simple_normalized)?;
-                            tokio::fs::write("data/normalized/final.json", json).await?;
+                            write_file_ensuring_dirs("data/normalized/final.json", json).await?;
                             
                             println!("   ✅ Simple normalization completed: {} items", simple_normalized.len());
                         }
@@ -196,7 +197,7 @@ async fn main() -> Result<()> {
                     let simple_normalized = DataNormalizer::normalize_simple(processed_data);
                     
                     let json = serde_json::to_string_pretty(&simple_normalized)?;
-                    tokio::fs::write("data/normalized/final.json", json).await?;
+                    write_file_ensuring_dirs("data/normalized/final.json", json).await?;
                     
                     println!("   ✅ Simple normalization completed: {} items", simple_normalized.len());
                 }
@@ -210,7 +211,7 @@ async fn main() -> Result<()> {
             let simple_normalized = DataNormalizer::normalize_simple(processed_data);
             
             let json = serde_json::to_string_pretty(&simple_normalized)?;
-            tokio::fs::write("data/normalized/final.json", json).await?;
+            write_file_ensuring_dirs("data/normalized/final.json", json).await?;
             
             println!("   ✅ Simple normalization completed: {} items", simple_normalized.len());
         }
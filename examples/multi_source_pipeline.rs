@@ -34,7 +34,6 @@ use rust_scraper_pro::{
     },
 };
 use std::sync::Arc;
-use tokio::time::{sleep, Duration};
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -83,36 +82,27 @@ async fn main() -> Result<()> {
         ),
     ];
 
-    let mut all_data = Vec::new();
-    let mut source_stats: Vec<(&str, String, usize, bool)> = Vec::new();
-
-    // Scrape from each source
-    for (category, source) in sources {
-        let source_name = source.name().to_string();
-        let source_url = source.base_url().to_string();
-
-        println!("\n{} Source: {}", category, source_name);
-        println!("   URL: {}", source_url);
-        println!("   Status: Scraping...");
-
-        match engine.scrape_source(source).await {
-            Ok(data) => {
-                let count = data.len();
-                println!("   ✓ Success: {} items scraped", count);
-
-                source_stats.push((category, source_name, count, true));
-                all_data.extend(data);
-
-                // Polite delay between sources
-                sleep(Duration::from_millis(2000)).await;
-            }
-            Err(e) => {
-                eprintln!("   ✗ Failed: {}", e);
-                source_stats.push((category, source_name, 0, false));
-            }
-        }
+    for (category, source) in &sources {
+        println!("\n{} Source: {}", category, source.name());
+        println!("   URL: {}", source.base_url());
     }
 
+    // The sources are on different hosts, so they can be scraped side by side;
+    // the engine's rate limiter still spaces requests to each host
+    println!("\n   Status: Scraping {} sources concurrently...", sources.len());
+    let (categories, sources): (Vec<&str>, Vec<SourceType>) = sources.into_iter().unzip();
+    let source_names: Vec<String> = sources.iter().map(|source| source.name().to_string()).collect();
+    let all_data = engine.scrape_sources(sources, 4).await?;
+
+    let source_stats: Vec<(&str, String, usize, bool)> = categories
+        .into_iter()
+        .zip(source_names)
+        .map(|(category, name)| {
+            let count = all_data.iter().filter(|item| item.source == name).count();
+            (category, name, count, count > 0)
+        })
+        .collect();
+
     println!("\n═══════════════════════════════════════════════════════════");
     println!("\n📊 Scraping Summary:");
     println!("───────────────────────────────────────────────────────────");
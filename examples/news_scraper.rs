@@ -15,10 +15,7 @@ use rust_scraper_pro::{
         config::Config,
         scraper::ScraperEngine,
     },
-    output::{
-        json::JsonOutput,
-        csv::CsvOutput,
-    },
+    output::{FileOutput, Output},
     processors::pipeline::ProcessingPipeline,
     sources::{NewsSource, Source},
     utils::{
@@ -59,60 +56,48 @@ async fn main() -> Result<()> {
     println!("📡 Scraping from: {}", hacker_news.name());
     println!("   URL: {}\n", hacker_news.base_url());
 
-    // Perform scraping
-    match engine.scrape_source(hacker_news).await {
-        Ok(data) => {
-            println!("✓ Scraped {} raw items", data.len());
-
-            // Process data through validation and deduplication pipeline
-            println!("\n🔄 Processing data through pipeline...");
-            let processed_data = engine.process_data(data).await?;
-            println!("✓ Pipeline complete: {} items after processing\n", processed_data.len());
-
-            if processed_data.is_empty() {
-                println!("⚠️  No data to export (all filtered out or no matches found)");
-                return Ok(());
-            }
-
-            // Show sample of scraped data
-            println!("📊 Sample of scraped content:");
-            println!("─────────────────────────────────────────────────────");
-            for (idx, item) in processed_data.iter().take(3).enumerate() {
-                println!("\n[{}] {}", idx + 1, item.title.as_ref().unwrap_or(&"(no title)".to_string()));
-                if let Some(author) = &item.author {
-                    println!("    By: {}", author);
-                }
-                println!("    URL: {}", item.url);
-            }
-            println!("\n─────────────────────────────────────────────────────\n");
-
-            // Export to JSON
-            println!("💾 Exporting to JSON...");
-            let json_output = JsonOutput::new();
-            json_output.export(&processed_data, "output/hacker_news.json").await?;
-            println!("✓ Saved to: output/hacker_news.json");
-
-            // Export to CSV
-            println!("💾 Exporting to CSV...");
-            let csv_output = CsvOutput::new();
-            csv_output.export(&processed_data, "output/hacker_news.csv").await?;
-            println!("✓ Saved to: output/hacker_news.csv");
-
-            // Display cache statistics
-            let stats = cache.stats();
-            println!("\n📈 Cache Statistics:");
-            println!("   Entries: {}", stats.entry_count);
-            println!("   Hit rate: {:.1}%", stats.hit_rate * 100.0);
-
-            println!("\n✅ News scraping completed successfully!");
-            println!("   {} articles scraped and exported\n", processed_data.len());
+    // Scrape, process and export to JSON and CSV in one call
+    let outputs: Vec<Box<dyn Output>> = vec![
+        Box::new(FileOutput::json("output/hacker_news.json")),
+        Box::new(FileOutput::csv("output/hacker_news.csv")),
+    ];
+    let persisted = engine.scrape_process_persist(vec![hacker_news], outputs).await?;
+    let processed_data = &persisted.items;
+    println!("✓ Pipeline complete: {} items after processing\n", processed_data.len());
+
+    if processed_data.is_empty() {
+        println!("⚠️  No data scraped (network issues, site changes or everything filtered out)");
+        println!("   Try again later or check the selectors in config/settings.toml");
+        return Ok(());
+    }
+
+    // Show sample of scraped data
+    println!("📊 Sample of scraped content:");
+    println!("─────────────────────────────────────────────────────");
+    for (idx, item) in processed_data.iter().take(3).enumerate() {
+        println!("\n[{}] {}", idx + 1, item.title.as_ref().unwrap_or(&"(no title)".to_string()));
+        if let Some(author) = &item.author {
+            println!("    By: {}", author);
         }
-        Err(e) => {
-            eprintln!("❌ Scraping failed: {}", e);
-            eprintln!("   This might be due to network issues or site changes.");
-            eprintln!("   Try again later or check the selectors in config/settings.toml");
+        println!("    URL: {}", item.url);
+    }
+    println!("\n─────────────────────────────────────────────────────\n");
+
+    for outcome in &persisted.outcomes {
+        match &outcome.result {
+            Ok(_) => println!("✓ Saved to: {}", outcome.output),
+            Err(e) => eprintln!("❌ {} failed: {}", outcome.output, e),
         }
     }
 
+    // Display cache statistics
+    let stats = cache.stats();
+    println!("\n📈 Cache Statistics:");
+    println!("   Entries: {}", stats.entry_count);
+    println!("   Hit rate: {:.1}%", stats.hit_rate * 100.0);
+
+    println!("\n✅ News scraping completed successfully!");
+    println!("   {} articles scraped and exported\n", processed_data.len());
+
     Ok(())
 }
@@ -104,6 +104,7 @@ async fn main() -> Result<()> {
                     id: uuid::Uuid::new_v4().to_string(),
                     source: format!("Reddit r/{}", subreddit),
                     url: p.url.clone(),
+                    source_url: None,
                     title: Some(p.title.clone()),
                     content: p.selftext.clone(),
                     price: None,
@@ -0,0 +1,16 @@
+-- Initial schema, identical to the table previously created by PostgresOutput::create_table
+CREATE TABLE IF NOT EXISTS {table} (
+    id VARCHAR(255) PRIMARY KEY,
+    source VARCHAR(255) NOT NULL,
+    url TEXT NOT NULL,
+    title TEXT,
+    content TEXT,
+    price DECIMAL(10,2),
+    image_url TEXT,
+    author VARCHAR(255),
+    timestamp TIMESTAMPTZ NOT NULL,
+    category VARCHAR(255),
+    metadata JSONB,
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    updated_at TIMESTAMPTZ DEFAULT NOW()
+);
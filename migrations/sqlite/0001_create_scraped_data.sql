@@ -0,0 +1,15 @@
+-- Initial schema, identical to the table previously created by SqliteOutput::create_table
+CREATE TABLE IF NOT EXISTS {table} (
+    id TEXT PRIMARY KEY,
+    source TEXT NOT NULL,
+    url TEXT NOT NULL,
+    title TEXT,
+    content TEXT,
+    price REAL,
+    image_url TEXT,
+    author TEXT,
+    timestamp DATETIME NOT NULL,
+    category TEXT,
+    metadata TEXT,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,4 @@
+-- The upsert has always set updated_at, but the SQLite table never had the column.
+-- SQLite can't add a column with a non-constant default, so backfill it instead.
+ALTER TABLE {table} ADD COLUMN updated_at DATETIME;
+UPDATE {table} SET updated_at = created_at;
@@ -0,0 +1,2 @@
+-- Page each item was scraped from, kept alongside the item's own url
+ALTER TABLE {table} ADD COLUMN source_url TEXT;
@@ -4,6 +4,7 @@
 //! to optimize scraping speed while respecting server load.
 
 use parking_lot::RwLock;
+use rand::Rng;
 use std::collections::VecDeque;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -13,6 +14,8 @@ use serde::{Deserialize, Serialize};
 pub enum DelayMode {
     Fixed,
     Adaptive,
+    /// A uniformly random delay between `min_delay_ms` and `max_delay_ms` per request
+    Random,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +25,30 @@ pub struct AdaptiveDelayConfig {
     pub max_delay_ms: u64,
     pub sample_size: usize,
     pub multiplier: f64,
+    /// Share of recent requests (0.0-1.0) that must have failed before a
+    /// failure escalates the delay
+    #[serde(default = "default_error_rate_threshold")]
+    pub error_rate_threshold: f64,
+    /// Each escalating failure multiplies the delay by this, and each success
+    /// divides it back down
+    #[serde(default = "default_error_backoff")]
+    pub error_backoff: f64,
+    /// Ceiling on the accumulated escalation; the escalated delay may exceed
+    /// `max_delay_ms` by up to this factor
+    #[serde(default = "default_max_error_escalation")]
+    pub max_error_escalation: f64,
+}
+
+fn default_error_rate_threshold() -> f64 {
+    0.3
+}
+
+fn default_error_backoff() -> f64 {
+    2.0
+}
+
+fn default_max_error_escalation() -> f64 {
+    8.0
 }
 
 impl Default for AdaptiveDelayConfig {
@@ -32,6 +59,9 @@ impl Default for AdaptiveDelayConfig {
             max_delay_ms: 2500,
             sample_size: 10,
             multiplier: 1.2, // 20% slower than average
+            error_rate_threshold: default_error_rate_threshold(),
+            error_backoff: default_error_backoff(),
+            max_error_escalation: default_max_error_escalation(),
         }
     }
 }
@@ -39,6 +69,14 @@ impl Default for AdaptiveDelayConfig {
 pub struct AdaptiveDelayController {
     config: AdaptiveDelayConfig,
     response_times: Arc<RwLock<VecDeque<Duration>>>,
+    errors: Arc<RwLock<ErrorTracker>>,
+}
+
+/// Recent request outcomes and the delay escalation they have built up
+struct ErrorTracker {
+    // `true` for each failure, over the same window as the response times
+    outcomes: VecDeque<bool>,
+    escalation: f64,
 }
 
 impl AdaptiveDelayController {
@@ -47,6 +85,10 @@ impl AdaptiveDelayController {
         Self {
             config,
             response_times: Arc::new(RwLock::new(VecDeque::with_capacity(sample_size))),
+            errors: Arc::new(RwLock::new(ErrorTracker {
+                outcomes: VecDeque::with_capacity(sample_size),
+                escalation: 1.0,
+            })),
         }
     }
 
@@ -67,10 +109,80 @@ impl AdaptiveDelayController {
         );
     }
 
-    /// Calculate the adaptive delay based on recent response times
+    /// Record a failed request (timeout, throttling, server error). Once the
+    /// recent error rate exceeds `error_rate_threshold`, each failure
+    /// multiplies the delay by `error_backoff`, up to `max_error_escalation`.
+    pub fn record_failure(&self) {
+        let mut errors = self.errors.write();
+        self.push_outcome(&mut errors, true);
+
+        let failures = errors.outcomes.iter().filter(|&&failed| failed).count();
+        let error_rate = failures as f64 / errors.outcomes.len() as f64;
+        if error_rate > self.config.error_rate_threshold {
+            errors.escalation = (errors.escalation * self.config.error_backoff.max(1.0))
+                .min(self.config.max_error_escalation.max(1.0));
+            log::debug!(
+                "Error rate {:.0}%: delay escalated {:.1}x",
+                error_rate * 100.0,
+                errors.escalation
+            );
+        }
+    }
+
+    /// Record a successful request, easing any error escalation back by one step
+    pub fn record_success(&self) {
+        let mut errors = self.errors.write();
+        self.push_outcome(&mut errors, false);
+        errors.escalation = (errors.escalation / self.config.error_backoff.max(1.0)).max(1.0);
+    }
+
+    fn push_outcome(&self, errors: &mut ErrorTracker, failed: bool) {
+        if errors.outcomes.len() >= self.config.sample_size.max(1) {
+            errors.outcomes.pop_front();
+        }
+        errors.outcomes.push_back(failed);
+    }
+
+    /// Current factor applied to the delay because of recent failures (1.0 when healthy)
+    pub fn error_escalation(&self) -> f64 {
+        self.errors.read().escalation
+    }
+
+    /// The response times currently in the sample window, oldest first
+    pub fn samples(&self) -> Vec<Duration> {
+        self.response_times.read().iter().copied().collect()
+    }
+
+    /// Replace the sample window, e.g. with samples saved by a previous run;
+    /// only the newest `sample_size` are kept
+    pub fn restore_samples(&self, samples: impl IntoIterator<Item = Duration>) {
+        let samples: Vec<Duration> = samples.into_iter().collect();
+        let skip = samples.len().saturating_sub(self.config.sample_size);
+
+        let mut times = self.response_times.write();
+        times.clear();
+        times.extend(samples.into_iter().skip(skip));
+    }
+
+    /// Calculate the delay for the configured mode, escalated while recent
+    /// requests are failing
     pub fn calculate_delay(&self) -> Duration {
+        let delay = self.base_delay();
+        let escalation = self.error_escalation();
+        if escalation > 1.0 {
+            delay.mul_f64(escalation)
+        } else {
+            delay
+        }
+    }
+
+    fn base_delay(&self) -> Duration {
         match self.config.mode {
             DelayMode::Fixed => Duration::from_millis(self.config.min_delay_ms),
+            DelayMode::Random => {
+                let max = self.config.max_delay_ms.max(self.config.min_delay_ms);
+                Duration::from_millis(rand::thread_rng().gen_range(self.config.min_delay_ms..=max))
+            }
             DelayMode::Adaptive => {
                 let times = self.response_times.read();
                 
@@ -119,9 +231,12 @@ impl AdaptiveDelayController {
         let result = f().await;
         let duration = start.elapsed();
         
-        // Only record successful requests
+        // Only successful requests say anything about the server's response time
         if result.is_ok() {
             self.record_response_time(duration);
+            self.record_success();
+        } else {
+            self.record_failure();
         }
         
         result
@@ -7,7 +7,9 @@
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::Semaphore;
 
 const DEEPSEEK_API_BASE: &str = "https://api.deepseek.com/v1";
 const DEFAULT_MODEL: &str = "deepseek-chat";
@@ -54,6 +56,8 @@ pub struct DeepSeekClient {
     api_key: String,
     base_url: String,
     model: String,
+    timeout: Duration,
+    request_limiter: Option<Arc<Semaphore>>,
 }
 
 impl DeepSeekClient {
@@ -62,23 +66,13 @@ impl DeepSeekClient {
         let api_key = std::env::var("DEEPSEEK_API_KEY")
             .context("DEEPSEEK_API_KEY environment variable not set")?;
 
-        let client = reqwest::Client::builder()
-            .timeout(Duration::from_secs(DEFAULT_TIMEOUT))
-            .build()
-            .context("Failed to build HTTP client")?;
-
-        Ok(Self {
-            client,
-            api_key,
-            base_url: DEEPSEEK_API_BASE.to_string(),
-            model: DEFAULT_MODEL.to_string(),
-        })
+        Self::with_config(api_key, None)
     }
 
     /// Create a client with custom configuration
     pub fn with_config(api_key: String, model: Option<String>) -> Result<Self> {
+        // The timeout is set per request so it can be changed after construction
         let client = reqwest::Client::builder()
-            .timeout(Duration::from_secs(DEFAULT_TIMEOUT))
             .build()
             .context("Failed to build HTTP client")?;
 
@@ -87,11 +81,39 @@ impl DeepSeekClient {
             api_key,
             base_url: DEEPSEEK_API_BASE.to_string(),
             model: model.unwrap_or_else(|| DEFAULT_MODEL.to_string()),
+            timeout: Duration::from_secs(DEFAULT_TIMEOUT),
+            request_limiter: None,
         })
     }
 
-    /// Send a completion request to DeepSeek API
-    pub async fn completion(&self, messages: Vec<DeepSeekMessage>) -> Result<DeepSeekResponse> {
+    /// How long a single API call may take before it fails (default 60s)
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Allow at most `max` API calls in flight through this client, however
+    /// many batches are running
+    pub fn with_max_concurrent_requests(self, max: usize) -> Self {
+        self.with_request_limiter(Arc::new(Semaphore::new(max.max(1))))
+    }
+
+    /// Share one cap on in-flight API calls between several clients, e.g. to
+    /// respect a provider rate limit across pipelines
+    pub fn with_request_limiter(mut self, limiter: Arc<Semaphore>) -> Self {
+        self.request_limiter = Some(limiter);
+        self
+    }
+
+    /// Point the client at a different API base URL (e.g. a proxy or mock server)
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// The HTTP request `completion` sends for these messages, with the
+    /// configured timeout applied
+    pub fn completion_request(&self, messages: Vec<DeepSeekMessage>) -> reqwest::RequestBuilder {
         let request = DeepSeekRequest {
             model: self.model.clone(),
             messages,
@@ -99,14 +121,26 @@ impl DeepSeekClient {
             max_tokens: Some(4000),
         };
 
-        log::debug!("Sending request to DeepSeek API...");
-
-        let response = self
-            .client
+        self.client
             .post(format!("{}/chat/completions", self.base_url))
             .header("Authorization", format!("Bearer {}", self.api_key))
             .header("Content-Type", "application/json")
+            .timeout(self.timeout)
             .json(&request)
+    }
+
+    /// Send a completion request to DeepSeek API
+    pub async fn completion(&self, messages: Vec<DeepSeekMessage>) -> Result<DeepSeekResponse> {
+        // Held until the response body has been read
+        let _permit = match &self.request_limiter {
+            Some(limiter) => Some(limiter.acquire().await.context("DeepSeek request limiter closed")?),
+            None => None,
+        };
+
+        log::debug!("Sending request to DeepSeek API...");
+
+        let response = self
+            .completion_request(messages)
             .send()
             .await
             .context("Failed to send request to DeepSeek API")?;
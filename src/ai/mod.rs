@@ -13,5 +13,5 @@ pub mod normalizer;
 
 pub use adaptive_delay::{AdaptiveDelayController, AdaptiveDelayConfig, DelayMode, AdaptiveDelayStats};
 pub use deepseek_client::{DeepSeekClient, DeepSeekMessage, DeepSeekRequest, DeepSeekResponse};
-pub use selector_assistant::{SelectorAssistant, DetectedSelectors};
-pub use normalizer::{DataNormalizer, NormalizedData, NormalizationStats};
+pub use selector_assistant::{SelectorAssistant, DetectedSelectors, HEURISTIC_CONFIDENCE};
+pub use normalizer::{DataNormalizer, NormalizedData, NormalizationStats, URL_PLACEHOLDER_KEY};
@@ -5,23 +5,82 @@
 
 use super::deepseek_client::{DeepSeekClient, DeepSeekMessage};
 use crate::core::models::ScrapedData;
+use crate::output::{atomic::write_file_ensuring_dirs, json::ExportEnvelope};
 use anyhow::{Context, Result};
+use futures::stream::{self, StreamExt};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::Duration;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NormalizedData {
     pub id: String,
+    #[serde(default)]
     pub title: String,
     pub price_usd: Option<f64>,
     pub image: Option<String>,
     pub category: Option<String>,
     pub source: String,
+    #[serde(default)]
     pub timestamp: String,
     #[serde(default)]
     pub metadata: HashMap<String, String>,
 }
 
+/// Set on items converted without a URL of their own, whose URL is a placeholder
+pub const URL_PLACEHOLDER_KEY: &str = "url_placeholder";
+
+impl NormalizedData {
+    /// Convert back to the shape the API and database use, keeping every item.
+    ///
+    /// Normalized items don't keep the page they came from, so the URL is
+    /// `metadata["url"]` when present, then the image URL. Failing both, it is
+    /// a placeholder under the reserved `.invalid` domain, unique per item
+    /// (e.g. `https://books-to-scrape.invalid/items/42`), and
+    /// `metadata["url_placeholder"]` is set. An empty title becomes `None` and
+    /// an unreadable timestamp becomes the time of conversion; metadata is kept.
+    pub fn into_scraped_data(self) -> ScrapedData {
+        let mut metadata = self.metadata;
+        let url = match metadata.get("url").or(self.image.as_ref()) {
+            Some(url) => url.clone(),
+            None => {
+                metadata.insert(URL_PLACEHOLDER_KEY.to_string(), "true".to_string());
+                placeholder_url(&self.source, &self.id)
+            }
+        };
+
+        let mut data = ScrapedData::new(self.source, url);
+        data.id = self.id;
+        data.title = Some(self.title).filter(|title| !title.trim().is_empty());
+        data.price = self.price_usd;
+        data.image_url = self.image;
+        data.category = self.category;
+        if let Some(timestamp) = crate::utils::time::parse_datetime(&self.timestamp) {
+            data.timestamp = timestamp;
+        }
+        data.metadata = metadata;
+        data
+    }
+}
+
+impl From<NormalizedData> for ScrapedData {
+    fn from(item: NormalizedData) -> Self {
+        item.into_scraped_data()
+    }
+}
+
+fn placeholder_url(source: &str, id: &str) -> String {
+    let host: String = source
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let host = if host.is_empty() { "unknown-source".to_string() } else { host };
+    let id: String = url::form_urlencoded::byte_serialize(id.as_bytes()).collect();
+    format!("https://{}.invalid/items/{}", host, id)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NormalizationStats {
     pub total_input: usize,
@@ -34,6 +93,8 @@ pub struct NormalizationStats {
 pub struct DataNormalizer {
     client: DeepSeekClient,
     batch_size: usize,
+    concurrency: usize,
+    batch_delay: Duration,
 }
 
 impl DataNormalizer {
@@ -41,6 +102,8 @@ impl DataNormalizer {
         Self {
             client,
             batch_size: 50, // Process in batches to avoid token limits
+            concurrency: 1,
+            batch_delay: Duration::from_millis(500),
         }
     }
 
@@ -49,6 +112,18 @@ impl DataNormalizer {
         self
     }
 
+    /// Maximum number of batches sent to the API at the same time (minimum 1)
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Delay applied before starting each batch after the first, to avoid rate limiting
+    pub fn with_batch_delay(mut self, delay: Duration) -> Self {
+        self.batch_delay = delay;
+        self
+    }
+
     /// Normalize a batch of scraped data using AI
     pub async fn normalize_batch(&self, data: Vec<ScrapedData>) -> Result<Vec<NormalizedData>> {
         if data.is_empty() {
@@ -147,22 +222,39 @@ Expected output format:
     /// Normalize all data with automatic batching
     pub async fn normalize_all(&self, data: Vec<ScrapedData>) -> Result<(Vec<NormalizedData>, NormalizationStats)> {
         let total_input = data.len();
-        let mut all_normalized = Vec::new();
 
-        log::info!("Starting normalization of {} items (batch size: {})", total_input, self.batch_size);
+        log::info!(
+            "Starting normalization of {} items (batch size: {}, concurrency: {})",
+            total_input,
+            self.batch_size,
+            self.concurrency
+        );
 
-        // Process in batches
-        for (i, chunk) in data.chunks(self.batch_size).enumerate() {
-            log::info!("Processing batch {}/{}", i + 1, (total_input + self.batch_size - 1) / self.batch_size);
-            
-            let normalized = self.normalize_batch(chunk.to_vec()).await?;
-            all_normalized.extend(normalized);
+        let total_batches = total_input.div_ceil(self.batch_size);
 
-            // Small delay between batches to avoid rate limiting
-            if i > 0 {
-                tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-            }
-        }
+        // Run up to `concurrency` batches in flight; batches may complete out of
+        // order, so each result is tagged with its index and re-sorted afterwards.
+        let mut batches: Vec<(usize, Vec<NormalizedData>)> = stream::iter(data.chunks(self.batch_size).enumerate())
+            .map(|(i, chunk)| async move {
+                // Small delay between batches to avoid rate limiting
+                if i > 0 && !self.batch_delay.is_zero() {
+                    tokio::time::sleep(self.batch_delay).await;
+                }
+
+                log::info!("Processing batch {}/{}", i + 1, total_batches);
+                self.normalize_batch(chunk.to_vec()).await.map(|normalized| (i, normalized))
+            })
+            .buffer_unordered(self.concurrency)
+            .collect::<Vec<Result<_>>>()
+            .await
+            .into_iter()
+            .collect::<Result<_>>()?;
+
+        batches.sort_by_key(|(i, _)| *i);
+        let all_normalized: Vec<NormalizedData> = batches
+            .into_iter()
+            .flat_map(|(_, normalized)| normalized)
+            .collect();
 
         let stats = NormalizationStats {
             total_input,
@@ -177,12 +269,12 @@ Expected output format:
         Ok((all_normalized, stats))
     }
 
-    /// Save normalized data to JSON file
+    /// Save normalized data to a JSON file, wrapped in the versioned export envelope
     pub async fn save_to_json(&self, data: &[NormalizedData], path: &str) -> Result<()> {
-        let json = serde_json::to_string_pretty(data)
+        let json = serde_json::to_string_pretty(&ExportEnvelope::new(data))
             .context("Failed to serialize normalized data")?;
 
-        tokio::fs::write(path, json)
+        write_file_ensuring_dirs(path, json)
             .await
             .context("Failed to write normalized data")?;
 
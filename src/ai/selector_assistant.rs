@@ -4,44 +4,143 @@
 //! for extracting product data, articles, and other structured content.
 
 use super::deepseek_client::{DeepSeekClient, DeepSeekMessage};
+use crate::output::atomic::write_file_ensuring_dirs;
 use anyhow::{Context, Result};
-use serde::{Deserialize, Serialize};
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
 
+/// Confidence reported for selectors guessed without the AI
+pub const HEURISTIC_CONFIDENCE: f32 = 0.2;
+
+// Common selectors per field, most specific first; those present in the page become the candidates
+const CONTAINER_CANDIDATES: &[&str] = &["[itemtype*=Product]", "article", ".product", ".product_pod", "li.item", ".item"];
+const TITLE_CANDIDATES: &[&str] = &["[itemprop=name]", ".product-title", ".title", "h1", "h2", "h3"];
+const PRICE_CANDIDATES: &[&str] = &["[itemprop=price]", ".price", ".price_color", ".product-price"];
+const IMAGE_CANDIDATES: &[&str] = &["[itemprop=image]", "article img", "img"];
+const CATEGORY_CANDIDATES: &[&str] = &["[itemprop=category]", ".category", ".breadcrumb li:last-child"];
+const DESCRIPTION_CANDIDATES: &[&str] = &["[itemprop=description]", ".description", ".summary", "article p", "p"];
+const AUTHOR_CANDIDATES: &[&str] = &["[itemprop=author]", "[rel=author]", ".author", ".byline"];
+const DATE_CANDIDATES: &[&str] = &["time", "[itemprop=datePublished]", ".date", ".published"];
+const LINK_CANDIDATES: &[&str] = &["article a[href]", "h2 a[href]", "h3 a[href]", "a[href]"];
+
+/// Selectors for one site. Each field holds candidate selectors in order of
+/// preference; the extractor uses the first one that matches.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DetectedSelectors {
     pub domain: String,
-    pub title: Option<String>,
-    pub price: Option<String>,
-    pub image: Option<String>,
-    pub category: Option<String>,
-    pub description: Option<String>,
-    pub author: Option<String>,
-    pub date: Option<String>,
-    pub link: Option<String>,
+    /// Element wrapping one item on a listing page; without it the whole page is one item
+    #[serde(default, deserialize_with = "candidates")]
+    pub container: Vec<String>,
+    #[serde(default, deserialize_with = "candidates")]
+    pub title: Vec<String>,
+    #[serde(default, deserialize_with = "candidates")]
+    pub price: Vec<String>,
+    #[serde(default, deserialize_with = "candidates")]
+    pub image: Vec<String>,
+    #[serde(default, deserialize_with = "candidates")]
+    pub category: Vec<String>,
+    #[serde(default, deserialize_with = "candidates")]
+    pub description: Vec<String>,
+    #[serde(default, deserialize_with = "candidates")]
+    pub author: Vec<String>,
+    #[serde(default, deserialize_with = "candidates")]
+    pub date: Vec<String>,
+    #[serde(default, deserialize_with = "candidates")]
+    pub link: Vec<String>,
     #[serde(default)]
     pub metadata: HashMap<String, String>,
     pub confidence: f32,
     pub generated_at: String,
 }
 
+impl DetectedSelectors {
+    /// Candidate selectors for a field by name, e.g. `"price"`; empty for unknown fields
+    pub fn candidates(&self, field: &str) -> &[String] {
+        match field {
+            "container" => &self.container,
+            "title" => &self.title,
+            "price" => &self.price,
+            "image" => &self.image,
+            "category" => &self.category,
+            "description" => &self.description,
+            "author" => &self.author,
+            "date" => &self.date,
+            "link" => &self.link,
+            _ => &[],
+        }
+    }
+
+    /// Guess selectors without the AI by checking which common selectors
+    /// (`article`, `h1`/`h2`, `.price`, `img`, `time`, ...) occur in the page,
+    /// looking for fields inside the guessed containers when there are any.
+    /// Marked with [`HEURISTIC_CONFIDENCE`] and `metadata["strategy"] = "heuristic"`.
+    pub fn heuristic(domain: &str, html: &str) -> Self {
+        let document = Html::parse_document(html);
+        let matches_in = |scope: &[ElementRef], candidate: &str| {
+            Selector::parse(candidate)
+                .is_ok_and(|selector| scope.iter().any(|element| element.select(&selector).next().is_some()))
+        };
+
+        let present = |scope: &[ElementRef], candidates: &[&str]| -> Vec<String> {
+            candidates
+                .iter()
+                .filter(|candidate| matches_in(scope, candidate))
+                .map(|candidate| candidate.to_string())
+                .collect()
+        };
+
+        let root = [document.root_element()];
+        let container = present(&root, CONTAINER_CANDIDATES);
+        let scope = match container.first().and_then(|container| Selector::parse(container).ok()) {
+            Some(selector) => document.select(&selector).collect(),
+            None => root.to_vec(),
+        };
+
+        Self {
+            domain: domain.to_string(),
+            container,
+            title: present(&scope, TITLE_CANDIDATES),
+            price: present(&scope, PRICE_CANDIDATES),
+            image: present(&scope, IMAGE_CANDIDATES),
+            category: present(&scope, CATEGORY_CANDIDATES),
+            description: present(&scope, DESCRIPTION_CANDIDATES),
+            author: present(&scope, AUTHOR_CANDIDATES),
+            date: present(&scope, DATE_CANDIDATES),
+            link: present(&scope, LINK_CANDIDATES),
+            metadata: HashMap::from([("strategy".to_string(), "heuristic".to_string())]),
+            confidence: HEURISTIC_CONFIDENCE,
+            generated_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
 pub struct SelectorAssistant {
-    client: DeepSeekClient,
+    client: Option<DeepSeekClient>,
     selectors_dir: PathBuf,
 }
 
 impl SelectorAssistant {
     pub fn new(client: DeepSeekClient) -> Self {
         Self {
-            client,
+            client: Some(client),
             selectors_dir: PathBuf::from("selectors"),
         }
     }
 
     pub fn with_selectors_dir(client: DeepSeekClient, dir: PathBuf) -> Self {
         Self {
-            client,
+            client: Some(client),
+            selectors_dir: dir,
+        }
+    }
+
+    /// An assistant with no AI behind it, e.g. when no API key is configured:
+    /// cached selectors are still used, anything else is guessed heuristically
+    pub fn heuristic_only(dir: PathBuf) -> Self {
+        Self {
+            client: None,
             selectors_dir: dir,
         }
     }
@@ -64,18 +163,20 @@ Rules:
 2. Prefer class names and data attributes over complex paths
 3. Focus on selectors that are stable and unlikely to change
 4. Return ONLY valid JSON, no additional text
-5. If a field is not found, use null
+5. Give up to 3 candidate selectors per field, most reliable first; later candidates are tried when earlier ones match nothing
+6. If a field is not found, use an empty list
 
 Return JSON in this exact format:
 {
-  "title": "CSS selector for title",
-  "price": "CSS selector for price",
-  "image": "CSS selector for image (src attribute)",
-  "category": "CSS selector for category",
-  "description": "CSS selector for description/content",
-  "author": "CSS selector for author",
-  "date": "CSS selector for date",
-  "link": "CSS selector for link",
+  "container": ["CSS selectors for the element wrapping each product or article"],
+  "title": ["CSS selectors for title"],
+  "price": ["CSS selectors for price"],
+  "image": ["CSS selectors for image (src attribute)"],
+  "category": ["CSS selectors for category"],
+  "description": ["CSS selectors for description/content"],
+  "author": ["CSS selectors for author"],
+  "date": ["CSS selectors for date"],
+  "link": ["CSS selectors for link"],
   "confidence": 0.85
 }"#;
 
@@ -95,7 +196,8 @@ Return JSON in this exact format:
             },
         ];
 
-        let response = self.client.completion(messages).await
+        let client = self.client.as_ref().context("No DeepSeek client configured")?;
+        let response = client.completion(messages).await
             .context("Failed to get selector recommendations from DeepSeek")?;
 
         let content = response
@@ -110,6 +212,7 @@ Return JSON in this exact format:
 
         let detected = DetectedSelectors {
             domain: domain.to_string(),
+            container: selector_data.container,
             title: selector_data.title,
             price: selector_data.price,
             image: selector_data.image,
@@ -134,18 +237,13 @@ Return JSON in this exact format:
 
     /// Save detected selectors to file
     pub async fn save_selectors(&self, selectors: &DetectedSelectors) -> Result<PathBuf> {
-        // Create selectors directory if it doesn't exist
-        tokio::fs::create_dir_all(&self.selectors_dir)
-            .await
-            .context("Failed to create selectors directory")?;
-
         let filename = format!("{}.selectors.json", sanitize_filename(&selectors.domain));
         let file_path = self.selectors_dir.join(&filename);
 
         let json = serde_json::to_string_pretty(selectors)
             .context("Failed to serialize selectors")?;
 
-        tokio::fs::write(&file_path, json)
+        write_file_ensuring_dirs(&file_path, json)
             .await
             .context("Failed to write selectors file")?;
 
@@ -176,7 +274,11 @@ Return JSON in this exact format:
         file_path.exists()
     }
 
-    /// Get or detect selectors (load from cache or generate new)
+    /// Get or detect selectors (load from cache or generate new).
+    ///
+    /// If the AI can't be reached, heuristic selectors are returned instead of
+    /// an error (see [`DetectedSelectors::heuristic`]). They aren't cached, so
+    /// the AI is asked again next time.
     pub async fn get_or_detect_selectors(
         &self,
         domain: &str,
@@ -184,29 +286,63 @@ Return JSON in this exact format:
     ) -> Result<DetectedSelectors> {
         if self.has_selectors(domain).await {
             log::info!("Using cached selectors for {}", domain);
-            self.load_selectors(domain).await
-        } else {
-            log::info!("No cached selectors found, detecting new ones for {}", domain);
-            let selectors = self.detect_selectors(domain, html_sample).await?;
-            self.save_selectors(&selectors).await?;
-            Ok(selectors)
+            return self.load_selectors(domain).await;
+        }
+
+        log::info!("No cached selectors found, detecting new ones for {}", domain);
+        match self.detect_selectors(domain, html_sample).await {
+            Ok(selectors) => {
+                self.save_selectors(&selectors).await?;
+                Ok(selectors)
+            }
+            Err(e) => {
+                log::warn!("AI selector detection unavailable for {} ({:#}); falling back to heuristics", domain, e);
+                Ok(DetectedSelectors::heuristic(domain, html_sample))
+            }
         }
     }
 }
 
 #[derive(Debug, Deserialize)]
 struct SelectorData {
-    title: Option<String>,
-    price: Option<String>,
-    image: Option<String>,
-    category: Option<String>,
-    description: Option<String>,
-    author: Option<String>,
-    date: Option<String>,
-    link: Option<String>,
+    #[serde(default, deserialize_with = "candidates")]
+    container: Vec<String>,
+    #[serde(default, deserialize_with = "candidates")]
+    title: Vec<String>,
+    #[serde(default, deserialize_with = "candidates")]
+    price: Vec<String>,
+    #[serde(default, deserialize_with = "candidates")]
+    image: Vec<String>,
+    #[serde(default, deserialize_with = "candidates")]
+    category: Vec<String>,
+    #[serde(default, deserialize_with = "candidates")]
+    description: Vec<String>,
+    #[serde(default, deserialize_with = "candidates")]
+    author: Vec<String>,
+    #[serde(default, deserialize_with = "candidates")]
+    date: Vec<String>,
+    #[serde(default, deserialize_with = "candidates")]
+    link: Vec<String>,
     confidence: f32,
 }
 
+/// Read a list of candidate selectors, also accepting a single selector or
+/// null as written by earlier versions (and sometimes still by the AI)
+fn candidates<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Candidates {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(match Option::<Candidates>::deserialize(deserializer)? {
+        Some(Candidates::One(selector)) => vec![selector],
+        Some(Candidates::Many(selectors)) => selectors,
+        None => Vec::new(),
+    })
+}
+
 /// Sanitize filename for cross-platform compatibility
 fn sanitize_filename(name: &str) -> String {
     name.replace("://", "_")
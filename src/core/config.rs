@@ -1,6 +1,10 @@
 use crate::core::models::ScrapingConfig;
+use crate::processors::{pipeline::PipelineConfig, rules::AlertsConfig};
+use crate::sources::{ConfiguredSource, EcommerceSource, NewsSource, RequestDefaults, SourceType};
+use crate::utils::error::ScraperError;
 use anyhow::Result;
 use serde::Deserialize;
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
 
@@ -26,6 +30,10 @@ pub struct Selectors {
 pub struct AppConfig {
     pub scraping: ScrapingConfig,
     pub sources: Vec<SourceConfig>,
+    #[serde(default)]
+    pub pipeline: PipelineConfig,
+    #[serde(default)]
+    pub alerts: AlertsConfig,
 }
 
 impl AppConfig {
@@ -36,5 +44,75 @@ impl AppConfig {
     }
 }
 
+/// One `[[news]]` or `[[ecommerce]]` entry of `config/sources.toml`
+#[derive(Debug, Deserialize)]
+pub struct SourceEntry {
+    pub name: String,
+    pub url: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Sent with every request for this source, e.g. `{ "Accept-Language" = "de-DE" }`
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// Sent as a `Cookie` header with every request for this source
+    #[serde(default)]
+    pub cookies: BTreeMap<String, String>,
+    /// Strip scripts, styles and comments from pages before parsing
+    #[serde(default)]
+    pub pre_clean: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl SourceEntry {
+    fn request_defaults(&self) -> Result<RequestDefaults> {
+        let request = RequestDefaults {
+            headers: self.headers.clone(),
+            cookies: self.cookies.clone(),
+        };
+        request
+            .check()
+            .map_err(|e| ScraperError::ConfigError(format!("source '{}': {}", self.name, e)))?;
+        Ok(request)
+    }
+}
+
+/// The sources declared in `config/sources.toml`. Only the news and
+/// e-commerce tables are read; other tables are ignored.
+#[derive(Debug, Default, Deserialize)]
+pub struct SourcesConfig {
+    #[serde(default)]
+    pub news: Vec<SourceEntry>,
+    #[serde(default)]
+    pub ecommerce: Vec<SourceEntry>,
+}
+
+impl SourcesConfig {
+    pub async fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let config: SourcesConfig = toml::from_str(&content)?;
+        Ok(config)
+    }
+
+    /// The enabled sources, each carrying its headers and cookies. Fails on a
+    /// header or cookie that can't be sent.
+    pub fn into_sources(self) -> Result<Vec<ConfiguredSource<SourceType>>> {
+        let news = self.news.into_iter().filter(|entry| entry.enabled).map(|entry| {
+            let request = entry.request_defaults()?;
+            let source = NewsSource::new(&entry.url).with_name(&entry.name);
+            Ok(ConfiguredSource::new(SourceType::News(source), request).with_pre_clean(entry.pre_clean))
+        });
+        let ecommerce = self.ecommerce.into_iter().filter(|entry| entry.enabled).map(|entry| {
+            let request = entry.request_defaults()?;
+            let source = EcommerceSource::new(&entry.url).with_name(&entry.name);
+            Ok(ConfiguredSource::new(SourceType::Ecommerce(source), request).with_pre_clean(entry.pre_clean))
+        });
+
+        news.chain(ecommerce).collect()
+    }
+}
+
 // Alias for backward compatibility
 pub type Config = AppConfig;
\ No newline at end of file
@@ -0,0 +1,260 @@
+//! Recursive crawling support
+//!
+//! Holds the serializable crawl state (frontier, visited set and collected
+//! results) so long crawls can be checkpointed to disk and resumed later.
+
+use crate::core::models::ScrapedData;
+use crate::utils::error::ScraperError;
+use anyhow::{Context, Result};
+use regex::RegexSet;
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use url::Url;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrawlConfig {
+    pub max_depth: usize,
+    /// Most new links followed from any one page; links under the page's own
+    /// path come first, then the rest in page order. `None` follows them all.
+    #[serde(default)]
+    pub max_links_per_page: Option<usize>,
+    /// Regexes matched against discovered links; when any are given, only
+    /// links matching at least one are followed (e.g. `/product/`)
+    #[serde(default)]
+    pub include_patterns: Vec<String>,
+    /// Links matching any of these are never followed, even when included
+    /// (e.g. `/cart`, `[?&]add-to-cart=`)
+    #[serde(default)]
+    pub exclude_patterns: Vec<String>,
+    pub same_host_only: bool,
+    pub checkpoint_path: Option<PathBuf>,
+    pub checkpoint_every: usize,
+    pub max_items: Option<usize>,
+    pub max_duration: Option<Duration>,
+    pub concurrency: usize,
+    pub max_concurrent_per_host: usize,
+}
+
+impl Default for CrawlConfig {
+    fn default() -> Self {
+        Self {
+            max_depth: 2,
+            max_links_per_page: None,
+            include_patterns: Vec::new(),
+            exclude_patterns: Vec::new(),
+            same_host_only: true,
+            checkpoint_path: None,
+            checkpoint_every: 10, // pages between checkpoint writes
+            max_items: None,
+            max_duration: None,
+            concurrency: 1,
+            max_concurrent_per_host: 2, // stay polite when crawling a single site
+        }
+    }
+}
+
+/// The include/exclude rules of a [`CrawlConfig`], compiled once per crawl
+#[derive(Debug, Clone)]
+pub struct UrlFilter {
+    include: RegexSet,
+    exclude: RegexSet,
+}
+
+impl UrlFilter {
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self> {
+        let compile = |patterns: &[String]| {
+            RegexSet::new(patterns)
+                .map_err(|e| ScraperError::ConfigError(format!("Invalid crawl URL pattern: {}", e)))
+        };
+        Ok(Self {
+            include: compile(include)?,
+            exclude: compile(exclude)?,
+        })
+    }
+
+    pub fn from_config(config: &CrawlConfig) -> Result<Self> {
+        Self::new(&config.include_patterns, &config.exclude_patterns)
+    }
+
+    /// Whether a discovered link may be followed
+    pub fn allows(&self, url: &str) -> bool {
+        (self.include.is_empty() || self.include.is_match(url)) && !self.exclude.is_match(url)
+    }
+}
+
+/// Why a crawl run ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StopReason {
+    Completed,
+    MaxItems,
+    MaxDuration,
+}
+
+/// Snapshot reported to the progress callback after each crawled page
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScrapeProgress {
+    pub pages_fetched: usize,
+    pub items_collected: usize,
+    pub current_url: String,
+}
+
+/// Called with a [`ScrapeProgress`] after each page, e.g. to drive a progress bar
+pub type ProgressCallback = Arc<dyn Fn(ScrapeProgress) + Send + Sync>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrawlTarget {
+    pub url: String,
+    pub depth: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CrawlState {
+    pub frontier: VecDeque<CrawlTarget>,
+    pub visited: HashSet<String>,
+    pub results: Vec<ScrapedData>,
+    pub pages_fetched: usize,
+    #[serde(default)]
+    pub stop_reason: Option<StopReason>,
+}
+
+impl CrawlState {
+    pub fn new(start_url: &str) -> Self {
+        let mut state = Self::default();
+        state.enqueue(start_url, 0);
+        state
+    }
+
+    /// Queue a URL unless it was already visited or is pending
+    pub fn enqueue(&mut self, url: &str, depth: usize) -> bool {
+        if self.visited.contains(url) || self.frontier.iter().any(|target| target.url == url) {
+            return false;
+        }
+
+        self.frontier.push_back(CrawlTarget {
+            url: url.to_string(),
+            depth,
+        });
+        true
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.frontier.is_empty()
+    }
+
+    /// Persist the state to a JSON checkpoint file
+    pub async fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        // Write to a sibling file first so a crash mid-write never corrupts the last checkpoint
+        let tmp_path = path.with_extension("tmp");
+        let json = serde_json::to_string(self).context("Failed to serialize crawl state")?;
+        tokio::fs::write(&tmp_path, json)
+            .await
+            .context("Failed to write crawl checkpoint")?;
+        tokio::fs::rename(&tmp_path, path)
+            .await
+            .context("Failed to move crawl checkpoint into place")?;
+
+        log::debug!(
+            "Saved crawl checkpoint to {} ({} pending, {} visited)",
+            path.display(),
+            self.frontier.len(),
+            self.visited.len()
+        );
+        Ok(())
+    }
+
+    /// Load a previously saved checkpoint
+    pub async fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = tokio::fs::read_to_string(path.as_ref())
+            .await
+            .context("Failed to read crawl checkpoint")?;
+        let state = serde_json::from_str(&content).context("Failed to parse crawl checkpoint")?;
+        Ok(state)
+    }
+}
+
+/// Extract absolute http(s) links from a page, resolved against the page URL
+pub fn extract_links(html: &str, page_url: &str) -> Vec<String> {
+    let Ok(base) = Url::parse(page_url) else {
+        return Vec::new();
+    };
+
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("a[href]").unwrap();
+
+    let mut links = Vec::new();
+    for element in document.select(&selector) {
+        let Some(href) = element.value().attr("href") else {
+            continue;
+        };
+
+        if let Ok(mut url) = base.join(href) {
+            if url.scheme() != "http" && url.scheme() != "https" {
+                continue;
+            }
+            url.set_fragment(None);
+
+            let link = url.to_string();
+            if !links.contains(&link) {
+                links.push(link);
+            }
+        }
+    }
+
+    links
+}
+
+/// The page's `<link rel="canonical">` target, resolved against the page URL
+pub fn canonical_url(html: &str, page_url: &str) -> Option<String> {
+    let base = Url::parse(page_url).ok()?;
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("link[rel][href]").unwrap();
+
+    let href = document
+        .select(&selector)
+        .find(|link| {
+            link.value()
+                .attr("rel")
+                .is_some_and(|rel| rel.split_ascii_whitespace().any(|rel| rel.eq_ignore_ascii_case("canonical")))
+        })?
+        .value()
+        .attr("href")?;
+
+    let mut url = base.join(href.trim()).ok()?;
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return None;
+    }
+    url.set_fragment(None);
+    Some(url.to_string())
+}
+
+/// Order links so those under `page_url`'s directory (e.g. `/books/` for
+/// `/books/index.html`) come first, keeping page order within each group
+pub fn prioritize_same_path(links: Vec<String>, page_url: &str) -> Vec<String> {
+    let Ok(page) = Url::parse(page_url) else {
+        return links;
+    };
+    let directory = &page.path()[..=page.path().rfind('/').unwrap_or(0)];
+
+    let (mut same_path, other): (Vec<_>, Vec<_>) = links.into_iter().partition(|link| {
+        Url::parse(link).is_ok_and(|url| url.host_str() == page.host_str() && url.path().starts_with(directory))
+    });
+    same_path.extend(other);
+    same_path
+}
+
+/// Check whether two URLs share the same host
+pub fn same_host(a: &str, b: &str) -> bool {
+    match (Url::parse(a), Url::parse(b)) {
+        (Ok(a), Ok(b)) => a.host_str() == b.host_str() && a.port_or_known_default() == b.port_or_known_default(),
+        _ => false,
+    }
+}
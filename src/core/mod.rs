@@ -1,7 +1,13 @@
 pub mod config;
+pub mod crawler;
 pub mod models;
+pub mod pagination;
 pub mod scraper;
+pub mod stats;
 
-pub use config::{AppConfig, Config, SourceConfig, Selectors};
-pub use models::{ScrapedData, ScrapingConfig};
-pub use scraper::ScraperEngine;
+pub use config::{AppConfig, Config, SourceConfig, SourceEntry, SourcesConfig, Selectors};
+pub use crawler::{CrawlConfig, CrawlState, ProgressCallback, ScrapeProgress, StopReason, UrlFilter};
+pub use models::{CategoryCount, MergePolicy, ScrapedData, ScrapingConfig, SourceSummary};
+pub use pagination::PaginationConfig;
+pub use scraper::{BatchScrape, BudgetedScrape, FetchContext, ScraperEngine, UrlOutcome};
+pub use stats::{EngineMonitor, EngineStats, HostHealth, HostStats, SourceActivity};
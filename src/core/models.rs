@@ -1,12 +1,21 @@
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+/// Metadata key holding which fields the source found, as a JSON object of
+/// field name to `"found"` or `"missing"` (see [`ScrapedData::record_extraction`])
+pub const EXTRACTION_KEY: &str = "_extraction";
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, utoipa::ToSchema)]
 pub struct ScrapedData {
     pub id: String,
     pub source: String,
     pub url: String,
+    /// Page the item was scraped from, when it differs from the item's own `url`
+    /// (listings, crawls); set by the engine for every fetched page
+    #[serde(default)]
+    #[sqlx(default)]
+    pub source_url: Option<String>,
     pub title: Option<String>,
     pub content: Option<String>,
     pub price: Option<f64>,
@@ -23,6 +32,7 @@ impl ScrapedData {
             id: uuid::Uuid::new_v4().to_string(),
             source,
             url,
+            source_url: None,
             title: None,
             content: None,
             price: None,
@@ -34,6 +44,20 @@ impl ScrapedData {
         }
     }
 
+    /// Replace the random id with one derived from `source` and `url`, so
+    /// re-scraping the same item yields the same id and database upserts
+    /// update it instead of inserting a duplicate
+    pub fn with_stable_id(mut self) -> Self {
+        self.id = Self::stable_id(&self.source, &self.url);
+        self
+    }
+
+    /// UUIDv5 of the URL within a namespace derived from the source name
+    pub fn stable_id(source: &str, url: &str) -> String {
+        let source_namespace = uuid::Uuid::new_v5(&uuid::Uuid::NAMESPACE_URL, source.as_bytes());
+        uuid::Uuid::new_v5(&source_namespace, url.as_bytes()).to_string()
+    }
+
     pub fn with_title(mut self, title: String) -> Self {
         self.title = Some(title);
         self
@@ -57,6 +81,214 @@ impl ScrapedData {
     pub fn add_metadata(&mut self, key: String, value: String) {
         self.metadata.insert(key, value);
     }
+
+    /// Which of the optional content fields are filled in, by field name
+    pub fn field_presence(&self) -> BTreeMap<&'static str, bool> {
+        BTreeMap::from([
+            ("title", self.title.is_some()),
+            ("content", self.content.is_some()),
+            ("price", self.price.is_some()),
+            ("image_url", self.image_url.is_some()),
+            ("author", self.author.is_some()),
+            ("category", self.category.is_some()),
+        ])
+    }
+
+    /// Note in `metadata["_extraction"]` which fields the source managed to
+    /// extract, before later processing fills in or drops any of them
+    pub fn record_extraction(&mut self) {
+        let outcomes: BTreeMap<&str, &str> = self
+            .field_presence()
+            .into_iter()
+            .map(|(field, found)| (field, if found { "found" } else { "missing" }))
+            .collect();
+        let outcomes = serde_json::to_string(&outcomes).expect("string map serializes");
+        self.metadata.insert(EXTRACTION_KEY.to_string(), outcomes);
+    }
+
+    /// The outcomes stored by [`record_extraction`](Self::record_extraction),
+    /// as field name -> found
+    pub fn extraction(&self) -> Option<BTreeMap<String, bool>> {
+        let outcomes: BTreeMap<String, String> = serde_json::from_str(self.metadata.get(EXTRACTION_KEY)?).ok()?;
+        Some(outcomes.into_iter().map(|(field, outcome)| (field, outcome == "found")).collect())
+    }
+
+    /// Combine another scrape of the same item into this one, e.g. a detail
+    /// page into its listing entry. Empty fields are filled from `other`,
+    /// metadata is unioned, and values both sides have keep this item's
+    /// (see [`ScrapedData::merge_with`] to prefer `other` instead).
+    pub fn merge(&mut self, other: &ScrapedData) {
+        self.merge_with(other, MergePolicy::KeepExisting);
+    }
+
+    /// Like [`ScrapedData::merge`], resolving conflicting values by `policy`.
+    /// The id, source, URL and source URL are always kept; the timestamp becomes the newer one.
+    pub fn merge_with(&mut self, other: &ScrapedData, policy: MergePolicy) {
+        fn pick<T: Clone>(mine: &mut Option<T>, theirs: &Option<T>, policy: MergePolicy) {
+            if theirs.is_some() && (mine.is_none() || policy == MergePolicy::PreferOther) {
+                *mine = theirs.clone();
+            }
+        }
+
+        pick(&mut self.title, &other.title, policy);
+        pick(&mut self.content, &other.content, policy);
+        pick(&mut self.price, &other.price, policy);
+        pick(&mut self.image_url, &other.image_url, policy);
+        pick(&mut self.author, &other.author, policy);
+        pick(&mut self.category, &other.category, policy);
+
+        for (key, value) in &other.metadata {
+            if policy == MergePolicy::PreferOther || !self.metadata.contains_key(key) {
+                self.metadata.insert(key.clone(), value.clone());
+            }
+        }
+
+        self.timestamp = self.timestamp.max(other.timestamp);
+    }
+}
+
+/// Which side wins when both scrapes being merged have a value
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MergePolicy {
+    /// Keep this item's values, only filling in what it lacks
+    #[default]
+    KeepExisting,
+    /// Overwrite with the other scrape's values, e.g. when it is more authoritative
+    PreferOther,
+}
+
+/// Fields of an item to change, as sent to `PATCH /api/item/{id}`. Fields left
+/// out (or `null`) keep their value; `id`, `source` and `timestamp` can't be
+/// changed and are rejected as unknown.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ItemPatch {
+    pub url: Option<String>,
+    pub title: Option<String>,
+    pub content: Option<String>,
+    pub price: Option<f64>,
+    pub image_url: Option<String>,
+    pub author: Option<String>,
+    pub category: Option<String>,
+}
+
+impl ItemPatch {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn apply(&self, item: &mut ScrapedData) {
+        if let Some(url) = &self.url {
+            item.url = url.clone();
+        }
+        for (value, field) in [
+            (&self.title, &mut item.title),
+            (&self.content, &mut item.content),
+            (&self.image_url, &mut item.image_url),
+            (&self.author, &mut item.author),
+            (&self.category, &mut item.category),
+        ] {
+            if value.is_some() {
+                *field = value.clone();
+            }
+        }
+        if self.price.is_some() {
+            item.price = self.price;
+        }
+    }
+}
+
+/// Item count and most recent scrape time for one source
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct SourceSummary {
+    pub source: String,
+    pub count: usize,
+    pub last_scraped: DateTime<Utc>,
+}
+
+impl SourceSummary {
+    /// Fold items into one summary per source, sorted by source name
+    pub fn summarize(items: &[ScrapedData]) -> Vec<Self> {
+        let mut summaries: HashMap<&str, Self> = HashMap::new();
+        for item in items {
+            summaries
+                .entry(item.source.as_str())
+                .and_modify(|summary| {
+                    summary.count += 1;
+                    summary.last_scraped = summary.last_scraped.max(item.timestamp);
+                })
+                .or_insert_with(|| Self {
+                    source: item.source.clone(),
+                    count: 1,
+                    last_scraped: item.timestamp,
+                });
+        }
+
+        let mut summaries: Vec<Self> = summaries.into_values().collect();
+        summaries.sort_by(|a, b| a.source.cmp(&b.source));
+        summaries
+    }
+}
+
+/// Number of items filed under one category
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CategoryCount {
+    pub category: String,
+    pub count: usize,
+}
+
+impl CategoryCount {
+    /// Count items per category, skipping uncategorized ones, sorted by category name
+    pub fn count(items: &[ScrapedData]) -> Vec<Self> {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for category in items.iter().filter_map(|item| item.category.as_deref()) {
+            *counts.entry(category).or_insert(0) += 1;
+        }
+
+        let mut counts: Vec<Self> = counts
+            .into_iter()
+            .map(|(category, count)| Self { category: category.to_string(), count })
+            .collect();
+        counts.sort_by(|a, b| a.category.cmp(&b.category));
+        counts
+    }
+}
+
+/// How often one source's items had a field extracted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ExtractionRate {
+    pub source: String,
+    pub field: String,
+    pub found: usize,
+    pub total: usize,
+    /// `found / total`, between 0 and 1
+    pub rate: f64,
+}
+
+impl ExtractionRate {
+    /// Tally the recorded extraction outcomes per source and field, sorted by
+    /// source then field; items without recorded outcomes are skipped
+    pub fn summarize(items: &[ScrapedData]) -> Vec<Self> {
+        let mut tallies: BTreeMap<(&str, String), (usize, usize)> = BTreeMap::new();
+        for item in items {
+            for (field, found) in item.extraction().unwrap_or_default() {
+                let (hits, total) = tallies.entry((item.source.as_str(), field)).or_default();
+                *hits += usize::from(found);
+                *total += 1;
+            }
+        }
+
+        tallies
+            .into_iter()
+            .map(|((source, field), (found, total))| Self {
+                source: source.to_string(),
+                field,
+                found,
+                total,
+                rate: found as f64 / total as f64,
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -66,6 +298,21 @@ pub struct ScrapingConfig {
     pub max_retries: u32,
     pub user_agent: String,
     pub follow_robots_txt: bool,
+    /// Response headers (e.g. `Server`, `Cache-Control`) copied into each
+    /// scraped item's metadata as `http_<name>`
+    #[serde(default)]
+    pub capture_headers: Vec<String>,
+    /// Fetch and scrape a source again, up to this many times, when the
+    /// request succeeds but the page yields no items (content that didn't load)
+    #[serde(default)]
+    pub empty_result_retries: u32,
+    /// Wait between those attempts
+    #[serde(default = "default_empty_result_retry_delay_ms")]
+    pub empty_result_retry_delay_ms: u64,
+}
+
+fn default_empty_result_retry_delay_ms() -> u64 {
+    2000
 }
 
 impl Default for ScrapingConfig {
@@ -76,6 +323,9 @@ impl Default for ScrapingConfig {
             max_retries: 3,
             user_agent: "RustScraperPro/1.0".to_string(),
             follow_robots_txt: true,
+            capture_headers: Vec::new(),
+            empty_result_retries: 0,
+            empty_result_retry_delay_ms: default_empty_result_retry_delay_ms(),
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,50 @@
+//! Page-number pagination
+//!
+//! Covers listings that paginate with `?page=2` (or a `{page}` placeholder in
+//! the path) rather than links that can be followed.
+
+use url::Url;
+
+#[derive(Debug, Clone)]
+pub struct PaginationConfig {
+    /// Query parameter carrying the page number, used when the URL has no `{page}` placeholder
+    pub param: String,
+    pub first_page: usize,
+    pub max_pages: usize,
+}
+
+impl Default for PaginationConfig {
+    fn default() -> Self {
+        Self {
+            param: "page".to_string(),
+            first_page: 1,
+            max_pages: 10,
+        }
+    }
+}
+
+impl PaginationConfig {
+    /// URL of the given page: fills in a `{page}` placeholder if there is one,
+    /// otherwise sets (or replaces) the page query parameter
+    pub fn page_url(&self, base_url: &str, page: usize) -> String {
+        if base_url.contains("{page}") {
+            return base_url.replace("{page}", &page.to_string());
+        }
+
+        let Ok(mut url) = Url::parse(base_url) else {
+            return base_url.to_string();
+        };
+
+        let pairs: Vec<(String, String)> = url
+            .query_pairs()
+            .filter(|(key, _)| key != self.param.as_str())
+            .map(|(key, value)| (key.into_owned(), value.into_owned()))
+            .collect();
+        url.query_pairs_mut()
+            .clear()
+            .extend_pairs(pairs)
+            .append_pair(&self.param, &page.to_string());
+
+        url.to_string()
+    }
+}
@@ -1,20 +1,185 @@
 use crate::{
+    core::crawler::{self, CrawlConfig, CrawlState, ProgressCallback, ScrapeProgress, StopReason},
+    core::stats::{EngineActivity, EngineMonitor, EngineStats},
     core::models::{ScrapedData, ScrapingConfig},
+    core::pagination::PaginationConfig,
+    output::sink::Output,
     processors::pipeline::ProcessingPipeline,
-    sources::source::Source,
-    utils::{error::ScraperError, rate_limiter::RateLimiter, cache::HtmlCache},
+    sources::source::{RequestDefaults, Source},
+    utils::{backoff::{Backoff, Jitter}, error::ScraperError, html::strip_noise, rate_limiter::{HostLimiter, PolitenessController}, cache::HtmlCache, raw_html::{RawHtmlStore, RAW_HTML_PATH_KEY}, robots::RobotsRules},
 };
 use anyhow::Result;
+use futures::stream::{self, StreamExt};
 use scraper::{Html, Selector};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
 
+// Detail pages fetched at once from one host during a listing/detail scrape
+const DETAIL_PAGES_PER_HOST: usize = 2;
+
+// Sources scraped at once by `scrape_process_persist`
+const PERSIST_SOURCE_CONCURRENCY: usize = 4;
+
+// First delay before retrying a failed request; doubles with each retry
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// Outcome of a fetch, handed to callers that asked for the same URL while it
+/// was in flight. Only errors every caller would get alike are shared.
+type SharedFetch = std::result::Result<(String, FetchContext), Option<Arc<ScraperError>>>;
+/// In-flight fetches by URL and the request defaults they were sent with, so
+/// sources sending different headers or cookies never share a response
+type InFlightKey = (String, Option<RequestDefaults>);
+type InFlight = parking_lot::Mutex<HashMap<InFlightKey, watch::Receiver<Option<SharedFetch>>>>;
+
+/// How a page was fetched, attached to every item scraped from it
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FetchContext {
+    pub url: String,
+    /// Captured response headers by lowercase name (see
+    /// `ScrapingConfig::capture_headers`); empty for pages served from the cache
+    pub headers: HashMap<String, String>,
+    /// Where the page's HTML was archived, when the engine has a [`RawHtmlStore`]
+    pub raw_html_path: Option<PathBuf>,
+    /// Lowercase MIME type the page was served with; `None` for pages served
+    /// from the cache or sent without a `Content-Type`
+    pub content_type: Option<String>,
+}
+
+impl FetchContext {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            headers: HashMap::new(),
+            raw_html_path: None,
+            content_type: None,
+        }
+    }
+
+    /// Metadata key a captured header is stored under, e.g. `http_cache_control`
+    pub fn metadata_key(header: &str) -> String {
+        format!("http_{}", header.to_ascii_lowercase().replace('-', "_"))
+    }
+}
+
+/// Run a source over a fetched page, recording the page as each item's
+/// `source_url` unless the source already set one, and which fields were
+/// extracted, any captured headers and the archived page path in its metadata
+async fn scrape_page(source: &impl Source, html: &str, context: &FetchContext) -> Result<Vec<ScrapedData>> {
+    let mut items = if source.pre_clean() {
+        source.scrape_at(&strip_noise(html), &context.url).await?
+    } else {
+        source.scrape_at(html, &context.url).await?
+    };
+    for item in items.iter_mut() {
+        item.source_url.get_or_insert_with(|| context.url.clone());
+        item.record_extraction();
+        for (header, value) in &context.headers {
+            item.metadata.insert(FetchContext::metadata_key(header), value.clone());
+        }
+        if let Some(path) = &context.raw_html_path {
+            item.metadata.insert(RAW_HTML_PATH_KEY.to_string(), path.display().to_string());
+        }
+    }
+    Ok(items)
+}
+
+/// Key `url` is cached under when fetched for `source`. Pages requested with
+/// headers or cookies (a language, a session) can differ from the plain page,
+/// so they are cached apart, under a digest rather than the cookie values.
+fn cache_key(url: &str, source: &impl Source) -> String {
+    let Some(request) = source.request_defaults().filter(|request| !request.is_empty()) else {
+        return url.to_string();
+    };
+    let mut fingerprint = String::new();
+    for (kind, values) in [("header", &request.headers), ("cookie", &request.cookies)] {
+        for (name, value) in values {
+            fingerprint.push_str(&format!("{} {}={}\n", kind, name, value));
+        }
+    }
+    format!("{}#request-{:x}", url, md5::compute(fingerprint))
+}
+
+/// What happened to one URL of a [`ScraperEngine::scrape_urls`] batch
+#[derive(Debug, Clone, PartialEq)]
+pub struct UrlOutcome {
+    pub url: String,
+    /// Number of items scraped, or why the URL failed
+    pub result: std::result::Result<usize, String>,
+}
+
+/// Items from a batch of URLs, plus a per-URL report in input order
+#[derive(Debug, Clone, Default)]
+pub struct BatchScrape {
+    pub items: Vec<ScrapedData>,
+    pub outcomes: Vec<UrlOutcome>,
+}
+
+impl BatchScrape {
+    pub fn succeeded(&self) -> usize {
+        self.outcomes.iter().filter(|outcome| outcome.result.is_ok()).count()
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &UrlOutcome> {
+        self.outcomes.iter().filter(|outcome| outcome.result.is_err())
+    }
+}
+
+/// What happened to one output of a [`ScraperEngine::scrape_process_persist`] run
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputOutcome {
+    /// The output's [`describe`](Output::describe) text
+    pub output: String,
+    /// Number of items written, or why the write failed
+    pub result: std::result::Result<usize, String>,
+}
+
+/// Processed items, plus a per-output report in the order outputs were given
+#[derive(Debug, Clone, Default)]
+pub struct PersistedScrape {
+    pub items: Vec<ScrapedData>,
+    pub outcomes: Vec<OutputOutcome>,
+}
+
+impl PersistedScrape {
+    pub fn failures(&self) -> impl Iterator<Item = &OutputOutcome> {
+        self.outcomes.iter().filter(|outcome| outcome.result.is_err())
+    }
+}
+
+/// Items from a scrape that had a time budget
+#[derive(Debug, Clone, Default)]
+pub struct BudgetedScrape {
+    pub items: Vec<ScrapedData>,
+    /// The budget ran out and the scrape was cancelled; `items` holds what
+    /// was collected before that
+    pub timed_out: bool,
+}
+
+/// Scraping engine that fetches, parses and processes sources.
+///
+/// Public methods return `anyhow::Result`, but failures raised by the engine
+/// itself always carry a [`ScraperError`] that callers can recover with
+/// `err.downcast_ref::<ScraperError>()` to branch on the cause (e.g. a
+/// rate limit or a blocked request).
 pub struct ScraperEngine {
-    _config: ScrapingConfig,
+    config: ScrapingConfig,
     pipeline: ProcessingPipeline,
-    rate_limiter: Arc<Mutex<RateLimiter>>,
+    politeness: Arc<PolitenessController>,
     client: reqwest::Client,
     cache: Option<Arc<HtmlCache>>,
+    #[cfg(feature = "redis")]
+    shared_cache: Option<Arc<crate::utils::redis_cache::RedisHtmlCache>>,
+    raw_html: Option<RawHtmlStore>,
+    in_flight: InFlight,
+    progress: Option<ProgressCallback>,
+    activity: Arc<EngineActivity>,
+    retry_backoff: Backoff,
+    /// Parsed robots.txt per origin, fetched on first use
+    robots: parking_lot::Mutex<HashMap<String, Arc<RobotsRules>>>,
 }
 
 impl ScraperEngine {
@@ -33,49 +198,793 @@ impl ScraperEngine {
         let rate_limit_ms = scraping_config.rate_limit_ms;
 
         Self {
-            _config: scraping_config,
+            config: scraping_config,
             pipeline,
-            rate_limiter: Arc::new(Mutex::new(RateLimiter::new(rate_limit_ms))),
+            politeness: Arc::new(PolitenessController::new(rate_limit_ms)),
             client,
             cache,
+            #[cfg(feature = "redis")]
+            shared_cache: None,
+            raw_html: None,
+            in_flight: InFlight::default(),
+            progress: None,
+            activity: Arc::new(EngineActivity::default()),
+            retry_backoff: Backoff::new(RETRY_BASE_DELAY, RETRY_MAX_DELAY).with_jitter(Jitter::random(0.1)),
+            robots: parking_lot::Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Replace the default politeness controller (configured interval only),
+    /// e.g. to enable adaptive delays
+    pub fn with_politeness(mut self, politeness: PolitenessController) -> Self {
+        self.politeness = Arc::new(politeness);
+        self
+    }
+
+    /// Replace the delays between retries of a failed request (500ms, 1s,
+    /// 2s, ... with ±10% jitter by default)
+    pub fn with_retry_backoff(mut self, backoff: Backoff) -> Self {
+        self.retry_backoff = backoff;
+        self
+    }
+
+    /// Report crawl progress after every page; without a callback nothing is tracked
+    pub fn with_progress(mut self, callback: impl Fn(ScrapeProgress) + Send + Sync + 'static) -> Self {
+        self.progress = Some(Arc::new(callback));
+        self
+    }
+
+    /// Also look pages up in (and store them to) a cache shared with other
+    /// scraper instances, consulted after the local cache misses
+    #[cfg(feature = "redis")]
+    pub fn with_shared_cache(mut self, cache: Arc<crate::utils::redis_cache::RedisHtmlCache>) -> Self {
+        self.shared_cache = Some(cache);
+        self
+    }
+
+    /// Archive every fetched page, recording its path in each item's
+    /// `metadata["raw_html_path"]` so the page can be parsed again later
+    pub fn with_raw_html_store(mut self, store: RawHtmlStore) -> Self {
+        self.raw_html = Some(store);
+        self
+    }
+
+    /// Per-host delay state consulted before every fetch
+    pub fn politeness(&self) -> &PolitenessController {
+        &self.politeness
+    }
+
+    /// Handle for reading [`EngineStats`] while the engine is busy elsewhere,
+    /// e.g. from the API server
+    pub fn monitor(&self) -> EngineMonitor {
+        EngineMonitor {
+            politeness: self.politeness.clone(),
+            cache: self.cache.clone(),
+            activity: self.activity.clone(),
         }
     }
 
+    /// Cache, per-host delay and per-source activity in one snapshot
+    pub fn stats(&self) -> EngineStats {
+        self.monitor().snapshot()
+    }
+
+    /// Fetch and scrape the source's base URL, then follow its
+    /// [`next_page`](Source::next_page) links (with the usual politeness
+    /// delay) until `source.max_pages()` pages are done or there is no next
+    /// page. Items from every page come back in page order.
+    ///
+    /// When `follow_robots_txt` is on, a URL the host's robots.txt disallows
+    /// isn't fetched: a disallowed base URL yields no items, a disallowed
+    /// next page ends the pagination.
     pub async fn scrape_source(&mut self, source: impl Source) -> Result<Vec<ScrapedData>> {
+        self.scrape_source_pages(&source).await
+    }
+
+    /// [`scrape_source`](Self::scrape_source) for several sources, up to
+    /// `concurrency` at once. Requests to one host are still spaced by the
+    /// politeness controller, so independent hosts gain the most.
+    ///
+    /// A source that fails is logged and skipped rather than failing the
+    /// batch. Items keep the order of `sources`.
+    pub async fn scrape_sources<S: Source>(&mut self, sources: Vec<S>, concurrency: usize) -> Result<Vec<ScrapedData>> {
+        log::info!("Scraping {} sources, up to {} at once", sources.len(), concurrency.max(1));
+        let engine = &*self;
+
+        let mut scrapes = stream::iter(&sources)
+            .map(|source| async move { (source.name(), engine.scrape_source_pages(source).await) })
+            .buffered(concurrency.max(1));
+
+        let mut results = Vec::new();
+        let mut failed = 0;
+        while let Some((name, scraped)) = scrapes.next().await {
+            match scraped {
+                Ok(items) => results.extend(items),
+                Err(e) => {
+                    failed += 1;
+                    log::warn!("Skipping {}: {:#}", name, e);
+                }
+            }
+        }
+
+        log::info!(
+            "Scraped {} items from {} of {} sources",
+            results.len(),
+            sources.len() - failed,
+            sources.len()
+        );
+        Ok(results)
+    }
+
+    /// Scrape `sources` (see [`scrape_sources`](Self::scrape_sources)), run
+    /// the items through the processing pipeline and write them to every
+    /// output in turn.
+    ///
+    /// An output that fails is logged and recorded in the outcomes without
+    /// stopping the others, so e.g. a database outage still leaves the file
+    /// exports written. Only a pipeline failure is an error.
+    pub async fn scrape_process_persist<S: Source>(
+        &mut self,
+        sources: Vec<S>,
+        outputs: Vec<Box<dyn Output>>,
+    ) -> Result<PersistedScrape> {
+        let scraped = self.scrape_sources(sources, PERSIST_SOURCE_CONCURRENCY).await?;
+        let items = self.process_data(scraped).await?;
+
+        let mut outcomes = Vec::with_capacity(outputs.len());
+        for output in &outputs {
+            let result = match output.write(&items).await {
+                Ok(written) => {
+                    log::info!("Wrote {} items to {}", written, output.describe());
+                    Ok(written)
+                }
+                Err(e) => {
+                    log::error!("Failed to write to {}: {:#}", output.describe(), e);
+                    Err(format!("{:#}", e))
+                }
+            };
+            outcomes.push(OutputOutcome {
+                output: output.describe(),
+                result,
+            });
+        }
+
+        Ok(PersistedScrape { items, outcomes })
+    }
+
+    async fn scrape_source_pages(&self, source: &impl Source) -> Result<Vec<ScrapedData>> {
+        let mut results = Vec::new();
+        self.collect_source_pages(source, &mut results).await?;
+        Ok(results)
+    }
+
+    // Items go straight into `results` so they survive the future being cancelled
+    async fn collect_source_pages(&self, source: &impl Source, results: &mut Vec<ScrapedData>) -> Result<()> {
         log::info!("Starting to scrape from: {}", source.name());
-        
-        // Apply rate limiting
-        self.rate_limiter.lock().await.wait().await;
-        
-        let html_content = self.fetch_url_with_cache(source.base_url()).await?;
-        let scraped_data = source.scrape(&html_content).await?;
-        
-        Ok(scraped_data)
+
+        let mut url = source.base_url().to_string();
+        if !self.allowed_by_robots(&url).await {
+            log::warn!("robots.txt disallows {}; skipping {}", url, source.name());
+            return Ok(());
+        }
+
+        let max_pages = source.max_pages().max(1);
+        let mut visited = HashSet::new();
+        for page in 1..=max_pages {
+            visited.insert(url.clone());
+            let (items, html) = self.scrape_url_retrying_empty(source, &url).await?;
+            results.extend(items);
+
+            if page == max_pages {
+                break;
+            }
+            let Some(next) = source.next_page(&html, &url) else {
+                break;
+            };
+            if visited.contains(&next) {
+                log::warn!("{} links back to already scraped {}; stopping", source.name(), next);
+                break;
+            }
+            if !self.allowed_by_robots(&next).await {
+                log::warn!("robots.txt disallows {}; stopping {} after {} pages", next, source.name(), page);
+                break;
+            }
+            log::debug!("Following {} to page {} ({})", source.name(), page + 1, next);
+            url = next;
+        }
+
+        if visited.len() > 1 {
+            log::info!("Collected {} items from {} pages of {}", results.len(), visited.len(), source.name());
+        }
+        Ok(())
+    }
+
+    /// One page's items and HTML, fetching the page again (up to
+    /// `empty_result_retries` times) while it parses to nothing
+    async fn scrape_url_retrying_empty(&self, source: &impl Source, url: &str) -> Result<(Vec<ScrapedData>, String)> {
+        let retries = self.config.empty_result_retries;
+        let mut attempt = 0;
+        loop {
+            // Apply per-host politeness delay
+            self.politeness.wait(url).await;
+
+            let (html_content, context) = self.fetch_url_with_cache(url, source).await?;
+            let scraped_data = scrape_page(source, &html_content, &context).await?;
+            if !scraped_data.is_empty() || attempt >= retries {
+                if scraped_data.is_empty() && retries > 0 {
+                    log::warn!("{} still had no items after {} retries", source.name(), retries);
+                }
+                return Ok((scraped_data, html_content));
+            }
+
+            attempt += 1;
+            log::warn!(
+                "{} returned a page without items; retrying ({}/{})",
+                source.name(),
+                attempt,
+                retries
+            );
+            // The empty page must not be served again from the cache
+            self.forget_cached(url, source).await;
+            tokio::time::sleep(Duration::from_millis(self.config.empty_result_retry_delay_ms)).await;
+        }
+    }
+
+    /// Like [`scrape_source`](Self::scrape_source), but gives up once
+    /// `max_scrape_duration` has passed (rate-limit waits included), so one
+    /// slow source can't hold up a run over many sources. A cancelled scrape
+    /// returns the items from the pages completed so far and `timed_out`
+    /// rather than an error.
+    pub async fn scrape_source_within(
+        &mut self,
+        source: impl Source,
+        max_scrape_duration: Duration,
+    ) -> Result<BudgetedScrape> {
+        let mut items = Vec::new();
+        let collected = tokio::time::timeout(max_scrape_duration, self.collect_source_pages(&source, &mut items)).await;
+        let timed_out = match collected {
+            Ok(result) => {
+                result?;
+                false
+            }
+            Err(_) => {
+                log::warn!(
+                    "Scraping {} exceeded its budget of {:?}; keeping {} items",
+                    source.name(),
+                    max_scrape_duration,
+                    items.len()
+                );
+                true
+            }
+        };
+        Ok(BudgetedScrape { items, timed_out })
+    }
+
+    /// Recursively crawl a source starting from its base URL
+    pub async fn crawl(&mut self, source: &impl Source, config: &CrawlConfig) -> Result<CrawlState> {
+        log::info!("Starting crawl from: {}", source.base_url());
+        self.resume_from(CrawlState::new(source.base_url()), source, config).await
+    }
+
+    /// Continue a crawl from a previously saved (or in-progress) state.
+    ///
+    /// When `config.checkpoint_path` is set, the state is persisted every
+    /// `checkpoint_every` pages and whenever a fetch fails, so an interrupted
+    /// crawl can be picked up again with `CrawlState::load`.
+    ///
+    /// `max_items` and `max_duration` are checked between fetches; when either
+    /// is hit the crawl ends early and returns what has been collected so far.
+    ///
+    /// Up to `config.concurrency` pages are fetched at once, but never more than
+    /// `config.max_concurrent_per_host` against the same host.
+    ///
+    /// Discovered links are followed only if they pass `include_patterns` and
+    /// `exclude_patterns`; the start URL is always fetched. Links already
+    /// queued or visited don't count towards `max_links_per_page`.
+    ///
+    /// Pages are deduplicated by their `<link rel="canonical">`: a page whose
+    /// canonical URL was already crawled is dropped, and items that only carry
+    /// the page's URL get the canonical one instead.
+    pub async fn resume_from(
+        &mut self,
+        mut state: CrawlState,
+        source: &impl Source,
+        config: &CrawlConfig,
+    ) -> Result<CrawlState> {
+        let started = std::time::Instant::now();
+        let host_limiter = HostLimiter::new(config.max_concurrent_per_host);
+        let url_filter = crawler::UrlFilter::from_config(config)?;
+        state.stop_reason = None;
+
+        while state.stop_reason.is_none() && !state.frontier.is_empty() {
+            // Take the next wave of unvisited pages to fetch concurrently
+            let mut wave = Vec::new();
+            while wave.len() < config.concurrency.max(1) {
+                let Some(target) = state.frontier.pop_front() else {
+                    break;
+                };
+                if state.visited.insert(target.url.clone()) {
+                    wave.push(target);
+                }
+            }
+
+            let fetches = wave.iter().map(|target| async {
+                let _permit = host_limiter.acquire(&target.url).await;
+                self.politeness.wait(&target.url).await;
+
+                // Checked after the rate-limit wait so a long wait can't push the fetch past the budget
+                if config.max_duration.is_some_and(|max| started.elapsed() >= max) {
+                    return None;
+                }
+                Some(self.fetch_url_with_cache(&target.url, source).await)
+            });
+            let outcomes = futures::future::join_all(fetches).await;
+
+            let mut requeue = Vec::new();
+            let mut first_error = None;
+
+            for (target, outcome) in wave.into_iter().zip(outcomes) {
+                let (html, context) = match outcome {
+                    Some(Ok(page)) if state.stop_reason.is_none() => page,
+                    Some(Ok(_)) => {
+                        requeue.push(target);
+                        continue;
+                    }
+                    Some(Err(e)) => {
+                        first_error.get_or_insert(e);
+                        requeue.push(target);
+                        continue;
+                    }
+                    None => {
+                        if state.stop_reason.is_none() {
+                            log::info!("Stopping crawl: time budget of {:?} exhausted", config.max_duration.unwrap_or_default());
+                            state.stop_reason = Some(StopReason::MaxDuration);
+                        }
+                        requeue.push(target);
+                        continue;
+                    }
+                };
+
+                // Variants of an already crawled page (tracking parameters, alternate paths) are skipped
+                let canonical = crawler::canonical_url(&html, &target.url).filter(|canonical| *canonical != target.url);
+                if let Some(canonical) = &canonical
+                    && !state.visited.insert(canonical.clone())
+                {
+                    log::debug!("Skipping {}: same page as {}", target.url, canonical);
+                    continue;
+                }
+
+                let mut items = scrape_page(source, &html, &context).await?;
+                if let Some(canonical) = &canonical {
+                    for item in items.iter_mut().filter(|item| item.url == source.base_url() || item.url == target.url) {
+                        item.url = canonical.clone();
+                    }
+                }
+                state.results.extend(items);
+                state.pages_fetched += 1;
+
+                if let Some(max_items) = config.max_items
+                    && state.results.len() >= max_items
+                {
+                    state.results.truncate(max_items);
+                    self.report_progress(state.pages_fetched, state.results.len(), &target.url);
+                    log::info!("Stopping crawl: collected the maximum of {} items", max_items);
+                    state.stop_reason = Some(StopReason::MaxItems);
+                    continue;
+                }
+                self.report_progress(state.pages_fetched, state.results.len(), &target.url);
+
+                if target.depth < config.max_depth {
+                    let mut links = crawler::extract_links(&html, &target.url);
+                    links.retain(|link| {
+                        (!config.same_host_only || crawler::same_host(link, &target.url)) && url_filter.allows(link)
+                    });
+                    if config.max_links_per_page.is_some() {
+                        links = crawler::prioritize_same_path(links, &target.url);
+                    }
+
+                    let max_links = config.max_links_per_page.unwrap_or(usize::MAX);
+                    let mut followed = 0;
+                    for link in links {
+                        if followed >= max_links {
+                            break;
+                        }
+                        if state.enqueue(&link, target.depth + 1) {
+                            followed += 1;
+                        }
+                    }
+                }
+
+                if let Some(path) = &config.checkpoint_path
+                    && state.pages_fetched.is_multiple_of(config.checkpoint_every.max(1))
+                {
+                    state.save(path).await?;
+                }
+            }
+
+            // Put unprocessed pages back in their original order so a resumed crawl retries them
+            for target in requeue.into_iter().rev() {
+                state.visited.remove(&target.url);
+                state.frontier.push_front(target);
+            }
+
+            if let Some(e) = first_error {
+                if let Some(path) = &config.checkpoint_path {
+                    state.save(path).await?;
+                }
+                return Err(e);
+            }
+        }
+
+        if state.stop_reason.is_none() {
+            state.stop_reason = Some(StopReason::Completed);
+        }
+
+        if let Some(path) = &config.checkpoint_path {
+            state.save(path).await?;
+        }
+
+        log::info!(
+            "Crawl finished: {} pages fetched, {} items collected",
+            state.pages_fetched,
+            state.results.len()
+        );
+        Ok(state)
+    }
+
+    /// Fetch numbered pages of a listing (`?page=N` or a `{page}` placeholder in
+    /// the source's base URL) and aggregate their items.
+    ///
+    /// Stops after `config.max_pages`, or as soon as a page adds nothing new,
+    /// which also covers sites that keep serving the last page past the end.
+    pub async fn scrape_pages(&mut self, source: &impl Source, config: &PaginationConfig) -> Result<Vec<ScrapedData>> {
+        let mut results = Vec::new();
+        self.collect_pages(source, config, &mut results).await?;
+        Ok(results)
+    }
+
+    /// Like [`scrape_pages`](Self::scrape_pages), but stops once
+    /// `max_scrape_duration` has passed, cancelling the page in progress and
+    /// returning the items from the pages completed so far as `timed_out`.
+    pub async fn scrape_pages_within(
+        &mut self,
+        source: &impl Source,
+        config: &PaginationConfig,
+        max_scrape_duration: Duration,
+    ) -> Result<BudgetedScrape> {
+        let mut items = Vec::new();
+        let collected = tokio::time::timeout(max_scrape_duration, self.collect_pages(source, config, &mut items)).await;
+        let timed_out = match collected {
+            Ok(result) => {
+                result?;
+                false
+            }
+            Err(_) => {
+                log::warn!(
+                    "Scraping {} exceeded its budget of {:?}; keeping {} items",
+                    source.name(),
+                    max_scrape_duration,
+                    items.len()
+                );
+                true
+            }
+        };
+        Ok(BudgetedScrape { items, timed_out })
+    }
+
+    // Items go straight into `results` so they survive the future being cancelled
+    async fn collect_pages(
+        &self,
+        source: &impl Source,
+        config: &PaginationConfig,
+        results: &mut Vec<ScrapedData>,
+    ) -> Result<()> {
+        let mut seen = HashSet::new();
+
+        for (fetched, page) in (config.first_page..).take(config.max_pages).enumerate() {
+            let url = config.page_url(source.base_url(), page);
+            self.politeness.wait(&url).await;
+            let (html, context) = self.fetch_url_with_cache(&url, source).await?;
+
+            let before = results.len();
+            for item in scrape_page(source, &html, &context).await? {
+                if seen.insert((item.url.clone(), item.title.clone())) {
+                    results.push(item);
+                }
+            }
+            self.report_progress(fetched + 1, results.len(), &url);
+
+            if results.len() == before {
+                log::info!("Stopping pagination at page {}: no new items", page);
+                break;
+            }
+        }
+
+        log::info!("Collected {} items from paginated {}", results.len(), source.name());
+        Ok(())
+    }
+
+    /// Two-phase scrape: take the items on `listing`'s page, fetch each item's own
+    /// URL and merge what `detail` extracts there into it (see [`ScrapedData::merge`]).
+    ///
+    /// Detail pages are fetched concurrently, a few per host, each once even if
+    /// several items link to it. A detail page that fails to load or yields
+    /// nothing leaves its listing item as it was.
+    pub async fn scrape_listing_then_details(
+        &mut self,
+        listing: &impl Source,
+        detail: &impl Source,
+    ) -> Result<Vec<ScrapedData>> {
+        log::info!("Scraping listing from: {}", listing.name());
+        self.politeness.wait(listing.base_url()).await;
+        let (html, context) = self.fetch_url_with_cache(listing.base_url(), listing).await?;
+        let mut items = scrape_page(listing, &html, &context).await?;
+
+        let detail_urls: Vec<String> = items
+            .iter()
+            .map(|item| item.url.clone())
+            .filter(|url| url != listing.base_url() && url.starts_with("http"))
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+
+        let host_limiter = HostLimiter::new(DETAIL_PAGES_PER_HOST);
+        let fetches = detail_urls.iter().map(|url| async {
+            let _permit = host_limiter.acquire(url).await;
+            self.politeness.wait(url).await;
+            let (html, context) = self.fetch_url_with_cache(url, detail).await?;
+            scrape_page(detail, &html, &context).await
+        });
+        let outcomes = futures::future::join_all(fetches).await;
+
+        let mut details = HashMap::new();
+        for (url, outcome) in detail_urls.iter().zip(outcomes) {
+            match outcome {
+                Ok(scraped) => match scraped.into_iter().next() {
+                    Some(found) => {
+                        details.insert(url.as_str(), found);
+                    }
+                    None => log::warn!("No details found on {}", url),
+                },
+                Err(e) => log::warn!("Failed to scrape detail page {}: {}", url, e),
+            }
+        }
+
+        for item in items.iter_mut() {
+            if let Some(found) = details.get(item.url.as_str()) {
+                item.merge(found);
+            }
+        }
+
+        log::info!(
+            "Merged details from {} of {} pages into {} listing items",
+            details.len(),
+            detail_urls.len(),
+            items.len()
+        );
+        Ok(items)
+    }
+
+    /// Scrape each of `urls` with `source`, instead of its base URL, fetching
+    /// up to `concurrency` at once (cache and per-host politeness still apply).
+    ///
+    /// A URL that fails to load or parse is recorded in the returned outcomes
+    /// and doesn't stop the batch. Items keep the order of `urls`.
+    pub async fn scrape_urls(
+        &mut self,
+        urls: Vec<String>,
+        source: impl Source,
+        concurrency: usize,
+    ) -> Result<BatchScrape> {
+        log::info!("Scraping {} URLs with {}", urls.len(), source.name());
+        let source = &source;
+        let engine = &*self;
+
+        let mut fetches = stream::iter(urls)
+            .map(|url| async move {
+                engine.politeness.wait(&url).await;
+                let scraped = match engine.fetch_url_with_cache(&url, source).await {
+                    Ok((html, context)) => scrape_page(source, &html, &context).await,
+                    Err(e) => Err(e),
+                };
+                (url, scraped)
+            })
+            .buffered(concurrency.max(1));
+
+        let mut batch = BatchScrape::default();
+        while let Some((url, scraped)) = fetches.next().await {
+            let result = match scraped {
+                Ok(items) => {
+                    let count = items.len();
+                    batch.items.extend(items);
+                    Ok(count)
+                }
+                Err(e) => {
+                    log::warn!("Failed to scrape {}: {}", url, e);
+                    Err(e.to_string())
+                }
+            };
+            engine.report_progress(batch.outcomes.len() + 1, batch.items.len(), &url);
+            batch.outcomes.push(UrlOutcome { url, result });
+        }
+
+        log::info!(
+            "Scraped {} items from {} of {} URLs",
+            batch.items.len(),
+            batch.succeeded(),
+            batch.outcomes.len()
+        );
+        Ok(batch)
+    }
+
+    fn report_progress(&self, pages_fetched: usize, items_collected: usize, url: &str) {
+        if let Some(callback) = &self.progress {
+            callback(ScrapeProgress {
+                pages_fetched,
+                items_collected,
+                current_url: url.to_string(),
+            });
+        }
     }
 
     pub async fn process_data(&self, data: Vec<ScrapedData>) -> Result<Vec<ScrapedData>> {
         self.pipeline.process(data).await
     }
 
-    async fn fetch_url_with_cache(&self, url: &str) -> Result<String> {
+    /// Fetch a page, unless the same URL is already being fetched with the
+    /// same request defaults: concurrent callers wait for that one request
+    /// and share its result instead of all hitting the origin. A waiter whose
+    /// leader failed for a reason specific to that attempt (e.g. a dropped
+    /// connection) fetches again itself, and one whose source disagrees with
+    /// the leader's about the response's content type gets its own verdict.
+    async fn fetch_url_with_cache(&self, url: &str, source: &impl Source) -> Result<(String, FetchContext)> {
+        let key: InFlightKey = (url.to_string(), source.request_defaults().cloned());
+        let (sender, mut receiver) = {
+            let mut in_flight = self.in_flight.lock();
+            match in_flight.get(&key) {
+                Some(receiver) => (None, receiver.clone()),
+                None => {
+                    let (sender, receiver) = watch::channel(None);
+                    in_flight.insert(key.clone(), receiver.clone());
+                    (Some(sender), receiver)
+                }
+            }
+        };
+
+        let Some(sender) = sender else {
+            log::debug!("Waiting for in-flight fetch of {}", url);
+            // Err when the leader was cancelled before finishing
+            if let Ok(shared) = receiver.wait_for(Option::is_some).await {
+                match shared.clone().expect("waited for a result") {
+                    Ok(page) => {
+                        if let Some(mime) = &page.1.content_type
+                            && !source.accepts_content_type(mime)
+                        {
+                            return Err(ScraperError::UnsupportedContentType(mime.clone()).into());
+                        }
+                        return Ok(page);
+                    }
+                    Err(Some(e)) => match &*e {
+                        ScraperError::UnsupportedContentType(mime) if source.accepts_content_type(mime) => {}
+                        e => return Err(e.response_error_copy().expect("only response errors are shared").into()),
+                    },
+                    Err(None) => {}
+                }
+            }
+            return self.fetch_url_uncoalesced(url, source).await;
+        };
+
+        // Removes the entry even if this fetch is cancelled midway
+        struct Landed<'a>(&'a InFlight, InFlightKey);
+        impl Drop for Landed<'_> {
+            fn drop(&mut self) {
+                self.0.lock().remove(&self.1);
+            }
+        }
+        let _landed = Landed(&self.in_flight, key);
+
+        let result = self.fetch_url_uncoalesced(url, source).await;
+        let shared = match &result {
+            Ok(page) => Ok(page.clone()),
+            Err(e) => Err(e
+                .downcast_ref::<ScraperError>()
+                .and_then(ScraperError::response_error_copy)
+                .map(Arc::new)),
+        };
+        sender.send_replace(Some(shared));
+        result
+    }
+
+    async fn forget_cached(&self, url: &str, source: &impl Source) {
+        let key = cache_key(url, source);
+        if let Some(cache) = &self.cache
+            && let Err(e) = cache.remove_html(&key).await
+        {
+            log::warn!("Failed to drop cached HTML for {}: {}", url, e);
+        }
+
+        #[cfg(feature = "redis")]
+        if let Some(shared) = &self.shared_cache
+            && let Err(e) = shared.remove_html(&key).await
+        {
+            log::warn!("Failed to drop shared cached HTML for {}: {}", url, e);
+        }
+    }
+
+    async fn fetch_url_uncoalesced(&self, url: &str, source: &impl Source) -> Result<(String, FetchContext)> {
+        let result = self.fetch_url_uncounted(url, source).await;
+        self.activity.record_page(source.name(), result.is_ok());
+        result
+    }
+
+    async fn fetch_url_uncounted(&self, url: &str, source: &impl Source) -> Result<(String, FetchContext)> {
+        let mut context = FetchContext::new(url);
+        let key = cache_key(url, source);
+
         // Check cache first
         if let Some(cache) = &self.cache {
-            if let Some(cached_html) = cache.get_html(url).await {
+            if let Some(cached_html) = cache.get_html(&key).await {
                 log::debug!("Cache hit for URL: {}", url);
-                return Ok(cached_html);
+                context.raw_html_path = self.archive_html(url, &cached_html).await;
+                return Ok((cached_html, context));
+            }
+        }
+
+        #[cfg(feature = "redis")]
+        if let Some(shared) = &self.shared_cache
+            && let Some(cached_html) = shared.get_html(&key).await
+        {
+            log::debug!("Shared cache hit for URL: {}", url);
+            if let Some(cache) = &self.cache
+                && let Err(e) = cache.set_html(&key, &cached_html).await
+            {
+                log::warn!("Failed to cache HTML for {}: {}", url, e);
             }
+            context.raw_html_path = self.archive_html(url, &cached_html).await;
+            return Ok((cached_html, context));
         }
 
+        let result = self.fetch_from_network(url, &key, source, context).await;
+        self.activity.record_request(url, result.is_ok());
+        result
+    }
+
+    async fn fetch_from_network(
+        &self,
+        url: &str,
+        cache_key: &str,
+        source: &impl Source,
+        mut context: FetchContext,
+    ) -> Result<(String, FetchContext)> {
         log::debug!("Fetching URL: {}", url);
-        let response = self
-            .client
-            .get(url)
-            .send()
-            .await
-            .map_err(ScraperError::RequestError)?;
+        let response = self.send_with_retries(url, source).await?;
 
         if !response.status().is_success() {
-            return Err(ScraperError::HttpError(response.status()).into());
+            let retry_after = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse::<u64>().ok());
+            return Err(ScraperError::from_status(response.status(), retry_after).into());
+        }
+
+        // Refuse bodies the source can't parse (JSON, PDFs, ...); a missing header is given the benefit of the doubt
+        if let Some(mime) = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.split(';').next().unwrap_or_default().trim().to_ascii_lowercase())
+        {
+            if !source.accepts_content_type(&mime) {
+                return Err(ScraperError::UnsupportedContentType(mime).into());
+            }
+            context.content_type = Some(mime);
+        }
+
+        for name in &self.config.capture_headers {
+            if let Some(value) = response.headers().get(name.as_str()).and_then(|v| v.to_str().ok()) {
+                context.headers.insert(name.to_ascii_lowercase(), value.to_string());
+            }
         }
 
         let content = response
@@ -85,12 +994,140 @@ impl ScraperEngine {
 
         // Store in cache
         if let Some(cache) = &self.cache {
-            if let Err(e) = cache.set_html(url, &content).await {
+            if let Err(e) = cache.set_html(cache_key, &content).await {
                 log::warn!("Failed to cache HTML for {}: {}", url, e);
             }
         }
+        #[cfg(feature = "redis")]
+        if let Some(shared) = &self.shared_cache
+            && let Err(e) = shared.set_html(cache_key, &content).await
+        {
+            log::warn!("Failed to store HTML for {} in the shared cache: {:#}", url, e);
+        }
+
+        context.raw_html_path = self.archive_html(url, &content).await;
+        Ok((content, context))
+    }
+
+    /// Whether robots.txt lets us fetch `url`; always true with
+    /// `follow_robots_txt` off. The host's `Crawl-delay` is handed to the
+    /// politeness controller when its robots.txt is first read.
+    async fn allowed_by_robots(&self, url: &str) -> bool {
+        if !self.config.follow_robots_txt {
+            return true;
+        }
+        let Ok(parsed) = url::Url::parse(url) else {
+            return true;
+        };
+
+        let origin = parsed.origin().ascii_serialization();
+        let cached = self.robots.lock().get(&origin).cloned();
+        let rules = match cached {
+            Some(rules) => rules,
+            None => {
+                let rules = Arc::new(self.fetch_robots(&origin).await);
+                if let Some(delay) = rules.crawl_delay() {
+                    self.politeness.set_crawl_delay(url, delay);
+                }
+                self.robots.lock().insert(origin, rules.clone());
+                rules
+            }
+        };
+        rules.is_allowed(&parsed)
+    }
+
+    /// A missing or unreachable robots.txt allows everything
+    async fn fetch_robots(&self, origin: &str) -> RobotsRules {
+        let robots_url = format!("{}/robots.txt", origin);
+        let body = match self.client.get(&robots_url).send().await {
+            Ok(response) if response.status().is_success() => response.text().await,
+            Ok(response) => {
+                log::debug!("No robots.txt at {} ({})", robots_url, response.status());
+                return RobotsRules::allow_all();
+            }
+            Err(e) => Err(e),
+        };
+
+        match body {
+            Ok(body) => RobotsRules::parse(&body, &self.config.user_agent),
+            Err(e) => {
+                log::warn!("Failed to fetch {}: {}; assuming no restrictions", robots_url, e);
+                RobotsRules::allow_all()
+            }
+        }
+    }
+
+    /// Send the request, retrying connection failures, timeouts and 5xx
+    /// responses up to `max_retries` times with exponential backoff. Other
+    /// statuses are returned as they are, without retrying.
+    async fn send_with_retries(&self, url: &str, source: &impl Source) -> Result<reqwest::Response> {
+        let max_retries = self.config.max_retries;
+        let mut attempt = 0;
+        loop {
+            let mut request = self.client.get(url);
+            if let Some(defaults) = source.request_defaults() {
+                for (name, value) in &defaults.headers {
+                    request = request.header(name.as_str(), value.as_str());
+                }
+                if let Some(cookies) = defaults.cookie_header() {
+                    request = request.header(reqwest::header::COOKIE, cookies);
+                }
+            }
+
+            let started = Instant::now();
+            let failure = match request.send().await {
+                Ok(response) => {
+                    self.politeness.record_response_time(url, started.elapsed());
 
-        Ok(content)
+                    // Throttling and overload count against the host; a missing page doesn't
+                    let status = response.status();
+                    let throttled = status.is_server_error()
+                        || matches!(
+                            status,
+                            reqwest::StatusCode::TOO_MANY_REQUESTS
+                                | reqwest::StatusCode::FORBIDDEN
+                                | reqwest::StatusCode::UNAUTHORIZED
+                        );
+                    self.politeness.record_outcome(url, !throttled);
+
+                    if !status.is_server_error() || attempt >= max_retries {
+                        return Ok(response);
+                    }
+                    format!("status {}", status)
+                }
+                Err(e) => {
+                    self.politeness.record_outcome(url, false);
+                    if !(e.is_connect() || e.is_timeout()) || attempt >= max_retries {
+                        return Err(ScraperError::RequestError(e).into());
+                    }
+                    e.to_string()
+                }
+            };
+
+            let delay = self.retry_backoff.delay(attempt);
+            attempt += 1;
+            log::warn!(
+                "Retrying {} in {:?} (attempt {}/{}) after {}",
+                url,
+                delay,
+                attempt,
+                max_retries,
+                failure
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Failing to archive a page is logged rather than failing its scrape
+    async fn archive_html(&self, url: &str, html: &str) -> Option<PathBuf> {
+        let store = self.raw_html.as_ref()?;
+        match store.save(html).await {
+            Ok(path) => Some(path),
+            Err(e) => {
+                log::warn!("Failed to archive HTML for {}: {:#}", url, e);
+                None
+            }
+        }
     }
 
     pub fn parse_html(html: &str) -> Html {
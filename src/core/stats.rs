@@ -0,0 +1,181 @@
+//! Engine health snapshots
+//!
+//! Gathers what the engine knows about its own state (cache hit rates,
+//! per-host delays and failures, when each source was last scraped) into one
+//! serializable [`EngineStats`], served by the API at `/api/engine-stats`.
+
+use crate::utils::{
+    cache::{CacheStats, HtmlCache},
+    rate_limiter::{host_key, PolitenessController},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use utoipa::ToSchema;
+
+/// How a host has been answering lately
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HostHealth {
+    Healthy,
+    /// Recent requests failed often enough that its delay is escalated
+    Throttled,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct HostStats {
+    pub host: String,
+    /// Requests sent over the network (cache hits aren't counted)
+    pub requests: u64,
+    pub errors: u64,
+    pub last_request: Option<DateTime<Utc>>,
+    /// Delay currently enforced between requests
+    pub delay_ms: u64,
+    pub crawl_delay_ms: Option<u64>,
+    /// Average of the recent response times, when adaptive delays are enabled
+    pub avg_response_ms: Option<u64>,
+    pub error_escalation: f64,
+    pub health: HostHealth,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct SourceActivity {
+    pub source: String,
+    /// Pages fetched for the source, including cache hits
+    pub pages: u64,
+    pub errors: u64,
+    pub last_scraped: Option<DateTime<Utc>>,
+}
+
+/// Point-in-time view of a [`ScraperEngine`](crate::core::scraper::ScraperEngine)
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct EngineStats {
+    pub generated_at: DateTime<Utc>,
+    /// `None` when the engine runs without an HTML cache
+    pub cache: Option<CacheStats>,
+    /// Sorted by host
+    pub hosts: Vec<HostStats>,
+    /// Sorted by source name
+    pub sources: Vec<SourceActivity>,
+    pub total_requests: u64,
+    pub total_errors: u64,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    count: u64,
+    errors: u64,
+    last: Option<DateTime<Utc>>,
+}
+
+impl Counters {
+    fn record(&mut self, ok: bool) {
+        self.count += 1;
+        if !ok {
+            self.errors += 1;
+        }
+        self.last = Some(Utc::now());
+    }
+}
+
+/// Request and page counts the engine records as it fetches
+#[derive(Debug, Default)]
+pub(crate) struct EngineActivity {
+    hosts: Mutex<HashMap<String, Counters>>,
+    sources: Mutex<HashMap<String, Counters>>,
+}
+
+impl EngineActivity {
+    pub(crate) fn record_request(&self, url: &str, ok: bool) {
+        self.hosts.lock().unwrap().entry(host_key(url)).or_default().record(ok);
+    }
+
+    pub(crate) fn record_page(&self, source: &str, ok: bool) {
+        self.sources.lock().unwrap().entry(source.to_string()).or_default().record(ok);
+    }
+}
+
+/// Cheap handle onto an engine's stats that outlives borrows of the engine,
+/// e.g. for the API server. Take it after the engine is fully configured:
+/// replacing the politeness controller afterwards isn't seen by the handle.
+#[derive(Clone)]
+pub struct EngineMonitor {
+    pub(crate) politeness: Arc<PolitenessController>,
+    pub(crate) cache: Option<Arc<HtmlCache>>,
+    pub(crate) activity: Arc<EngineActivity>,
+}
+
+impl EngineMonitor {
+    pub fn snapshot(&self) -> EngineStats {
+        let mut requests: HashMap<String, (u64, u64, Option<DateTime<Utc>>)> = self
+            .activity
+            .hosts
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(host, counters)| (host.clone(), (counters.count, counters.errors, counters.last)))
+            .collect();
+
+        let mut hosts: Vec<HostStats> = self
+            .politeness
+            .host_stats()
+            .into_iter()
+            .map(|delays| {
+                let (requests, errors, last_request) = requests.remove(&delays.host).unwrap_or_default();
+                HostStats {
+                    requests,
+                    errors,
+                    last_request,
+                    delay_ms: delays.delay.as_millis() as u64,
+                    crawl_delay_ms: delays.crawl_delay.map(|delay| delay.as_millis() as u64),
+                    avg_response_ms: delays
+                        .adaptive
+                        .filter(|adaptive| adaptive.samples > 0)
+                        .map(|adaptive| adaptive.avg_response_time.as_millis() as u64),
+                    error_escalation: delays.error_escalation,
+                    health: if delays.error_escalation > 1.0 { HostHealth::Throttled } else { HostHealth::Healthy },
+                    host: delays.host,
+                }
+            })
+            .collect();
+
+        // Hosts fetched without going through the politeness controller
+        hosts.extend(requests.into_iter().map(|(host, (requests, errors, last_request))| HostStats {
+            host,
+            requests,
+            errors,
+            last_request,
+            delay_ms: 0,
+            crawl_delay_ms: None,
+            avg_response_ms: None,
+            error_escalation: 1.0,
+            health: HostHealth::Healthy,
+        }));
+        hosts.sort_by(|a, b| a.host.cmp(&b.host));
+
+        let mut sources: Vec<SourceActivity> = self
+            .activity
+            .sources
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(source, counters)| SourceActivity {
+                source: source.clone(),
+                pages: counters.count,
+                errors: counters.errors,
+                last_scraped: counters.last,
+            })
+            .collect();
+        sources.sort_by(|a, b| a.source.cmp(&b.source));
+
+        EngineStats {
+            generated_at: Utc::now(),
+            cache: self.cache.as_ref().map(|cache| cache.stats()),
+            total_requests: hosts.iter().map(|host| host.requests).sum(),
+            total_errors: hosts.iter().map(|host| host.errors).sum(),
+            hosts,
+            sources,
+        }
+    }
+}
@@ -4,6 +4,7 @@ pub mod processors;
 pub mod output;
 pub mod utils;
 pub mod ai;
+pub mod selftest;
 
 // Re-exports for easier access
 pub use core::models::ScrapedData;
@@ -12,8 +13,9 @@ pub use processors::pipeline::ProcessingPipeline;
 pub use output::{
     json::JsonOutput,
     csv::CsvOutput,
-    database::{DatabaseOutput, PostgresOutput, SqliteOutput},
+    database::{ConflictStrategy, DatabaseOutput, PostgresOutput, RegeneratedIds, SqlArg, SqliteOutput},
     api::ApiServer,
+    sink::{FileOutput, Output},
 };
 pub use utils::cache::HtmlCache;
 
@@ -25,11 +27,13 @@ pub mod prelude {
     pub use crate::output::{
         json::JsonOutput,
         csv::CsvOutput,
-        database::{DatabaseOutput, PostgresOutput, SqliteOutput},
+        database::{ConflictStrategy, DatabaseOutput, PostgresOutput, RegeneratedIds, SqlArg, SqliteOutput},
+        sink::{FileOutput, Output},
     };
     pub use crate::utils::cache::HtmlCache;
+    pub use crate::utils::raw_html::RawHtmlStore;
     pub use crate::core::config::Config;
     pub use crate::core::scraper::ScraperEngine;
-    pub use crate::sources::{NewsSource, EcommerceSource, SocialSource, CustomSource};
+    pub use crate::sources::{NewsSource, EcommerceSource, SocialSource, CustomSource, FeedSource, TableSource, AiSelectorSource, FileSource, DirectorySource};
     pub use std::sync::Arc;
 }
\ No newline at end of file
@@ -1,49 +1,232 @@
-use crate::core::models::ScrapedData;
-use crate::output::database::PostgresOutput;
+use crate::core::models::{CategoryCount, ExtractionRate, ItemPatch, ScrapedData, SourceSummary};
+use crate::core::stats::{EngineMonitor, EngineStats, HostHealth, HostStats, SourceActivity};
+use crate::output::bundle::{BundleMetadata, BundleOutput};
+use crate::output::database::{DatabaseOutput, PostgresOutput, SqlArg, TableStats, PG_SELECT_COLUMNS};
+use crate::output::store::ItemStore;
+use crate::utils::cache::{CacheStats, HtmlCache};
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 use axum::{
-    extract::{Query, State},
-    http::{StatusCode, Method, Uri},
-    response::{Json, IntoResponse},
+    extract::{
+        rejection::{JsonRejection, QueryRejection},
+        Path as UrlPath, Query, RawQuery, State,
+    },
+    http::{header, HeaderMap, StatusCode, Method, Uri},
+    response::{Json, IntoResponse, Response},
     routing::{get, post},
     Router,
 };
-use serde::Deserialize;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
 use std::net::SocketAddr;
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use tokio::sync::RwLock;
+use tower_http::compression::CompressionLayer;
 use tower_http::cors::{CorsLayer, Any};
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
 use tower_http::trace::TraceLayer;
+use utoipa::{IntoParams, OpenApi, ToSchema};
+use utoipa_swagger_ui::SwaggerUi;
 
-pub type SharedData = Arc<RwLock<Vec<ScrapedData>>>;
+/// The API's in-memory items; build one with [`IndexedStore::into_shared`](crate::output::store::IndexedStore::into_shared)
+pub type SharedData = Arc<RwLock<dyn ItemStore>>;
 pub type SharedDatabase = Option<Arc<PostgresOutput>>;
 
-#[derive(Debug, Deserialize)]
+/// Most items one request returns, whatever `limit` asks for
+pub const MAX_PAGE_SIZE: usize = 1000;
+
+/// Items `/api/data` returns when no `limit` is given
+pub const DEFAULT_PAGE_SIZE: usize = 50;
+
+#[derive(Debug, Deserialize, IntoParams, ToSchema)]
+#[into_params(parameter_in = Query)]
 pub struct SearchQuery {
     pub query: Option<String>,
     pub source: Option<String>,
+    /// Capped at 1000
     pub limit: Option<usize>,
     pub offset: Option<usize>,
     pub category: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+impl SearchQuery {
+    /// `limit`, or `default` when it isn't given, capped at [`MAX_PAGE_SIZE`]
+    pub fn page_size(&self, default: usize) -> usize {
+        self.limit.unwrap_or(default).min(MAX_PAGE_SIZE)
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams, ToSchema)]
+#[into_params(parameter_in = Query)]
 pub struct ExportQuery {
     pub format: Option<String>,
     pub source: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AppendResponse {
+    pub status: String,
+    pub inserted: usize,
+    pub skipped: usize,
+    pub dropped: usize,
+    pub items_count: usize,
+}
+
+/// Body of every API error response, e.g.
+/// `{"code": "not_found", "message": "No HTML cache configured"}`
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiError {
+    /// The HTTP status as a stable snake_case name, e.g. `not_found`
+    pub code: String,
+    pub message: String,
+    /// Extra context, where a handler has any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+    #[serde(skip, default = "default_error_status")]
+    status: StatusCode,
+}
+
+fn default_error_status() -> StatusCode {
+    StatusCode::INTERNAL_SERVER_ERROR
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        let code = status
+            .canonical_reason()
+            .unwrap_or("error")
+            .to_lowercase()
+            .replace([' ', '-'], "_");
+
+        Self {
+            code,
+            message: message.into(),
+            details: None,
+            status,
+        }
+    }
+
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, message)
+    }
+
+    pub fn with_details(mut self, details: serde_json::Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(self)).into_response()
+    }
+}
+
+// A query parameter that doesn't parse, e.g. `limit=ten` or `offset=-1`
+impl From<QueryRejection> for ApiError {
+    fn from(rejection: QueryRejection) -> Self {
+        Self::bad_request(rejection.body_text())
+    }
+}
+
+// Malformed or mistyped request bodies get the same shape as every other error
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        Self::new(rejection.status(), rejection.body_text())
+    }
+}
+
+/// OpenAPI description of the HTTP API, served at `/api/openapi.json`
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Rust Scraper Pro API", description = "Query, export and update scraped data"),
+    paths(
+        get_data,
+        search_data,
+        get_item,
+        patch_item,
+        get_sources,
+        get_source_summaries,
+        get_categories,
+        get_extraction_rates,
+        get_stats,
+        health_check,
+        export_json,
+        export_csv,
+        export_bundle,
+        update_data,
+        append_items,
+        trigger_scrape,
+        cache_stats,
+        clear_cache,
+        engine_stats,
+    ),
+    components(schemas(ScrapedData, ItemPatch, SearchQuery, ExportQuery, AppendResponse, ApiError, CacheStats, SourceSummary, CategoryCount, ExtractionRate, EngineStats, HostStats, HostHealth, SourceActivity)),
+    tags(
+        (name = "data", description = "Query, export and update scraped items"),
+        (name = "system", description = "Health and scrape control"),
+    )
+)]
+pub struct ApiDoc;
+
 #[derive(Clone)]
 pub struct AppState {
     pub data: SharedData,
     pub database: SharedDatabase,
+    /// HTML cache shared with the scraper, if one is wired in
+    pub cache: Option<Arc<HtmlCache>>,
+    /// Stats of the scraper engine feeding this server, if one is wired in
+    pub engine: Option<EngineMonitor>,
+    /// Bumped on every write through the API, feeds into response ETags
+    pub version: Arc<AtomicU64>,
+}
+
+impl AppState {
+    pub fn bump_version(&self) {
+        self.version.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Weak ETag for a dataset, derived from the write version, item count and
+    /// newest timestamp. The query string is mixed in so filtered views of the
+    /// same data get distinct tags.
+    pub fn etag(&self, items: &[ScrapedData], query: Option<&str>) -> String {
+        self.etag_for(items.len(), items.iter().map(|item| item.timestamp).max(), query)
+    }
+
+    /// [`etag`](Self::etag) from a dataset's size and newest timestamp, for
+    /// data that isn't loaded, e.g. a whole database table
+    pub fn etag_for(&self, count: usize, newest: Option<DateTime<Utc>>, query: Option<&str>) -> String {
+        let mut hasher = DefaultHasher::new();
+        self.version.load(Ordering::Relaxed).hash(&mut hasher);
+        count.hash(&mut hasher);
+        newest.hash(&mut hasher);
+        query.unwrap_or_default().hash(&mut hasher);
+
+        format!("W/\"{:016x}\"", hasher.finish())
+    }
 }
 
+const DEFAULT_FRONTEND_DIR: &str = "frontend/dist";
+
 pub struct ApiServer {
     state: AppState,
     port: u16,
+    frontend_dir: Option<PathBuf>,
 }
 
 impl ApiServer {
@@ -52,11 +235,39 @@ impl ApiServer {
             state: AppState {
                 data,
                 database,
+                cache: None,
+                engine: None,
+                version: Arc::new(AtomicU64::new(0)),
             },
             port: port.unwrap_or(3000),
+            frontend_dir: None,
         }
     }
 
+    /// Serve the built frontend from `dir` instead of `frontend/dist`
+    pub fn with_frontend_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.frontend_dir = Some(dir.into());
+        self
+    }
+
+    pub fn frontend_dir(&self) -> PathBuf {
+        self.frontend_dir
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_FRONTEND_DIR))
+    }
+
+    /// Expose the scraper's HTML cache through `/api/cache/*` and reuse it for API-triggered scrapes
+    pub fn with_cache(mut self, cache: Arc<HtmlCache>) -> Self {
+        self.state.cache = Some(cache);
+        self
+    }
+
+    /// Report the scraper engine's health through `/api/engine-stats`
+    pub fn with_engine_monitor(mut self, monitor: EngineMonitor) -> Self {
+        self.state.engine = Some(monitor);
+        self
+    }
+
     pub async fn run(&self) -> Result<()> {
         let app = self.create_app();
         let addr = SocketAddr::from(([127, 0, 0, 1], self.port));
@@ -71,11 +282,11 @@ impl ApiServer {
         Ok(())
     }
 
-    fn create_app(&self) -> Router {
+    pub fn create_app(&self) -> Router {
         // Configure CORS for development (allow React dev server on 5173)
         let cors = CorsLayer::new()
             .allow_origin(Any)
-            .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE, Method::OPTIONS])
+            .allow_methods([Method::GET, Method::POST, Method::PUT, Method::PATCH, Method::DELETE, Method::OPTIONS])
             .allow_headers(Any)
             .allow_credentials(false);
 
@@ -83,28 +294,46 @@ impl ApiServer {
         let api_routes = Router::new()
             .route("/api/data", get(get_data))
             .route("/api/search", get(search_data))
+            .route("/api/item/{id}", get(get_item).patch(patch_item))
             .route("/api/sources", get(get_sources))
+            .route("/api/sources/summary", get(get_source_summaries))
+            .route("/api/categories", get(get_categories))
+            .route("/api/extraction", get(get_extraction_rates))
             .route("/api/stats", get(get_stats))
             .route("/api/health", get(health_check))
             .route("/api/export/json", get(export_json))
             .route("/api/export/csv", get(export_csv))
+            .route("/api/export/bundle", get(export_bundle))
             .route("/api/update", post(update_data))
+            .route("/api/items", post(append_items))
             .route("/api/scrape", post(trigger_scrape))
+            .route("/api/cache/stats", get(cache_stats))
+            .route("/api/cache/clear", post(clear_cache))
+            .route("/api/engine-stats", get(engine_stats))
             .with_state(self.state.clone())
+            .merge(SwaggerUi::new("/api/docs").url("/api/openapi.json", ApiDoc::openapi()))
+            // gzip/br based on Accept-Encoding; tiny bodies are left as-is
+            .layer(CompressionLayer::new())
             .layer(cors)
-            .layer(TraceLayer::new_for_http());
+            // Layers run bottom-up: an id is assigned (unless the client sent one),
+            // recorded on the request span so every log line inside carries it,
+            // then copied onto the response as X-Request-Id
+            .layer(PropagateRequestIdLayer::x_request_id())
+            .layer(TraceLayer::new_for_http().make_span_with(request_span))
+            .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid));
 
         // Check if frontend dist folder exists
-        let frontend_path = PathBuf::from("frontend/dist");
+        let frontend_path = self.frontend_dir();
 
         if frontend_path.exists() && frontend_path.is_dir() {
-            log::info!("Serving static files from frontend/dist");
+            log::info!("Serving static files from {}", frontend_path.display());
 
             // Serve static files with fallback to index.html for SPA routing
             // We need to handle this manually to get proper 200 status codes for SPA routes
+            let root = Arc::new(frontend_path);
             Router::new()
                 .merge(api_routes)
-                .fallback(handle_frontend)
+                .fallback(move |uri: Uri, headers: HeaderMap| handle_frontend(root.clone(), uri, headers))
         } else {
             log::warn!("Frontend dist folder not found at {:?}. Only serving API endpoints.", frontend_path);
             log::warn!("Run 'make build-frontend' or 'cd frontend && npm install && npm run build' to build the frontend.");
@@ -117,7 +346,8 @@ impl ApiServer {
 
         // Update in-memory data
         let mut data = self.state.data.write().await;
-        *data = new_data.clone();
+        data.replace_all(new_data.clone());
+        self.state.bump_version();
         log::info!("API in-memory data updated with {} items", data.len());
 
         // Update database if available
@@ -136,6 +366,19 @@ impl ApiServer {
     }
 }
 
+// Span wrapping one API request, tagged with the id assigned by `SetRequestIdLayer`
+fn request_span<B>(request: &axum::http::Request<B>) -> tracing::Span {
+    let request_id = request
+        .headers()
+        .get("x-request-id")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+
+    tracing::info_span!("request", id = %request_id, method = %request.method(), uri = %request.uri())
+}
+
+#[utoipa::path(get, path = "/api/health", tag = "system",
+    responses((status = 200, description = "Service is up", body = HashMap<String, String>)))]
 async fn health_check() -> (StatusCode, Json<HashMap<&'static str, &'static str>>) {
     let mut response = HashMap::new();
     response.insert("status", "healthy");
@@ -145,15 +388,30 @@ async fn health_check() -> (StatusCode, Json<HashMap<&'static str, &'static str>
     (StatusCode::OK, Json(response))
 }
 
+#[utoipa::path(get, path = "/api/data", tag = "data", params(SearchQuery),
+    responses(
+        (status = 200, description = "Filtered page of scraped items", body = Vec<ScrapedData>),
+        (status = 304, description = "Data unchanged since the ETag sent in If-None-Match"),
+        (status = 400, description = "A query parameter could not be parsed", body = ApiError),
+    ))]
 async fn get_data(
     State(state): State<AppState>,
-    Query(params): Query<SearchQuery>,
-) -> (StatusCode, Json<Vec<ScrapedData>>) {
+    params: Result<Query<SearchQuery>, QueryRejection>,
+    RawQuery(raw_query): RawQuery,
+    headers: HeaderMap,
+) -> Response {
+    let params = match params {
+        Ok(Query(params)) => params,
+        Err(rejection) => return ApiError::from(rejection).into_response(),
+    };
+    let offset = params.offset.unwrap_or(0);
+    let limit = params.page_size(DEFAULT_PAGE_SIZE);
+
     // Try database first if available
     if let Some(db) = state.database.as_ref() {
         match db.get_all(
-            params.limit.map(|l| l as i64),
-            params.offset.map(|o| o as i64)
+            Some(limit as i64),
+            Some(offset as i64)
         ).await {
             Ok(mut results) => {
                 // Apply additional filters
@@ -177,7 +435,8 @@ async fn get_data(
                 }
 
                 log::info!("Retrieved {} items from database", results.len());
-                return (StatusCode::OK, Json(results));
+                let etag = state.etag(&results, raw_query.as_deref());
+                return conditional_json(&headers, etag, results);
             }
             Err(e) => {
                 log::warn!("Database query failed, falling back to in-memory: {}", e);
@@ -187,85 +446,204 @@ async fn get_data(
 
     // Fallback to in-memory data
     let data_guard = state.data.read().await;
-    let mut results: Vec<ScrapedData> = data_guard.clone();
+    let etag = state.etag(data_guard.items(), raw_query.as_deref());
+    if etag_matches(&headers, &etag) {
+        return not_modified(etag);
+    }
 
-    // Apply filters
-    if let Some(query) = &params.query {
-        results.retain(|item| {
-            item.title.as_ref().map(|t| t.to_lowercase().contains(&query.to_lowercase())).unwrap_or(false) ||
-            item.content.as_ref().map(|c| c.to_lowercase().contains(&query.to_lowercase())).unwrap_or(false)
-        });
+    // Source and category narrow the items through the store's indexes; only
+    // the requested page is copied out
+    let query = params.query.as_ref().map(|query| query.to_lowercase());
+    let paginated_results: Vec<ScrapedData> = data_guard
+        .select(params.source.as_deref(), params.category.as_deref())
+        .into_iter()
+        .filter(|item| query.as_ref().is_none_or(|query| matches_text(item, query)))
+        .skip(offset)
+        .take(limit)
+        .cloned()
+        .collect();
+
+    log::info!("Retrieved {} items from in-memory cache", paginated_results.len());
+    conditional_json(&headers, etag, paginated_results)
+}
+
+#[utoipa::path(get, path = "/api/item/{id}", tag = "data",
+    params(("id" = String, Path, description = "Item id")),
+    responses(
+        (status = 200, description = "The item with this id", body = ScrapedData),
+        (status = 404, description = "No item has this id", body = ApiError),
+    ))]
+async fn get_item(State(state): State<AppState>, UrlPath(id): UrlPath<String>) -> Result<Json<ScrapedData>, ApiError> {
+    if let Some(db) = state.database.as_ref() {
+        match db.get_by_id(&id).await {
+            Ok(Some(item)) => return Ok(Json(item)),
+            // Not stored yet, or only held in memory
+            Ok(None) => {}
+            Err(e) => log::warn!("Database lookup of item {} failed, falling back to in-memory: {}", id, e),
+        }
     }
 
-    if let Some(source) = &params.source {
-        results.retain(|item| item.source.to_lowercase().contains(&source.to_lowercase()));
+    let data_guard = state.data.read().await;
+    data_guard
+        .get(&id)
+        .cloned()
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found(format!("No item with id '{}'", id)))
+}
+
+#[utoipa::path(patch, path = "/api/item/{id}", tag = "data", request_body = ItemPatch,
+    params(("id" = String, Path, description = "Item id")),
+    responses(
+        (status = 200, description = "The item with the given fields changed", body = ScrapedData),
+        (status = 400, description = "Malformed body or no fields to change", body = ApiError),
+        (status = 404, description = "No item has this id", body = ApiError),
+        (status = 422, description = "Unknown field, or the changed item fails validation", body = ApiError),
+        (status = 500, description = "The database update failed; nothing was changed", body = ApiError),
+    ))]
+async fn patch_item(
+    State(state): State<AppState>,
+    UrlPath(id): UrlPath<String>,
+    payload: Result<Json<ItemPatch>, JsonRejection>,
+) -> Result<Json<ScrapedData>, ApiError> {
+    use crate::processors::validator::Validator;
+
+    let Json(changes) = payload?;
+    if changes.is_empty() {
+        return Err(ApiError::bad_request("No fields to change"));
     }
 
-    if let Some(category) = &params.category {
-        results.retain(|item|
-            item.category.as_ref()
-                .map(|c| c.to_lowercase().contains(&category.to_lowercase()))
-                .unwrap_or(false)
-        );
+    // Held across the database write so concurrent patches apply in order
+    let mut data_guard = state.data.write().await;
+    let in_memory = data_guard.contains(&id);
+    let current = match data_guard.get(&id) {
+        Some(item) => Some(item.clone()),
+        None => match state.database.as_ref() {
+            Some(db) => db.get_by_id(&id).await.map_err(|e| ApiError::internal(format!("Failed to look up item: {}", e)))?,
+            None => None,
+        },
+    };
+    let Some(mut updated) = current else {
+        return Err(ApiError::not_found(format!("No item with id '{}'", id)));
+    };
+
+    changes.apply(&mut updated);
+    if let Err(reason) = Validator::new().check(&updated) {
+        return Err(ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, format!("Updated item is invalid: {}", reason)));
     }
 
-    // Apply pagination
-    let offset = params.offset.unwrap_or(0);
-    let limit = params.limit.unwrap_or(50);
+    // A failed write leaves memory alone too, so the two never disagree
+    let mut changed = in_memory;
+    if let Some(db) = state.database.as_ref() {
+        match db.update_fields(&id, &changes).await {
+            Ok(Some(_)) => {
+                log::info!("Updated item {} in database", id);
+                changed = true;
+            }
+            Ok(None) => log::debug!("Item {} is only held in memory", id),
+            Err(e) => {
+                log::error!("Failed to update item {} in database: {:#}", id, e);
+                return Err(ApiError::internal(format!("Failed to update item: {}", e)));
+            }
+        }
+    }
 
-    let end_index = std::cmp::min(offset + limit, results.len());
-    let paginated_results = if offset < results.len() {
-        results[offset..end_index].to_vec()
-    } else {
-        Vec::new()
-    };
+    if in_memory {
+        data_guard.upsert(updated.clone());
+    }
+    // Items held only in the database change the served data too
+    if changed {
+        state.bump_version();
+    }
 
-    log::info!("Retrieved {} items from in-memory cache", paginated_results.len());
-    (StatusCode::OK, Json(paginated_results))
+    Ok(Json(updated))
 }
 
+#[utoipa::path(get, path = "/api/search", tag = "data", params(SearchQuery),
+    responses(
+        (status = 200, description = "Items matching the filters, up to `limit` (at most 1000)", body = Vec<ScrapedData>),
+        (status = 400, description = "A query parameter could not be parsed", body = ApiError),
+    ))]
 async fn search_data(
     State(state): State<AppState>,
-    Query(params): Query<SearchQuery>,
-) -> (StatusCode, Json<Vec<ScrapedData>>) {
-    let data_guard = state.data.read().await;
-    
+    params: Result<Query<SearchQuery>, QueryRejection>,
+) -> Result<(StatusCode, Json<Vec<ScrapedData>>), ApiError> {
+    let Query(params) = params?;
+
+    let offset = params.offset.unwrap_or(0);
+    let limit = params.page_size(MAX_PAGE_SIZE);
     let query = params.query.unwrap_or_default().to_lowercase();
-    let source_filter = params.source.map(|s| s.to_lowercase());
-    let category_filter = params.category.map(|c| c.to_lowercase());
 
+    if let Some(db) = state.database.as_ref() {
+        let (sql, args) = search_sql(db.table_name(), params.source.as_deref(), params.category.as_deref(), &query, offset, limit);
+        match db.query_with_args(&sql, &args).await {
+            Ok(results) => return Ok((StatusCode::OK, Json(results))),
+            Err(e) => log::warn!("Database search failed, falling back to in-memory: {:#}", e),
+        }
+    }
+
+    let data_guard = state.data.read().await;
     let results: Vec<ScrapedData> = data_guard
-        .iter()
-        .filter(|item| {
-            // Text search in title and content
-            let matches_text = query.is_empty() || 
-                item.title.as_ref().map(|t| t.to_lowercase().contains(&query)).unwrap_or(false) ||
-                item.content.as_ref().map(|c| c.to_lowercase().contains(&query)).unwrap_or(false);
-            
-            // Source filter
-            let matches_source = if let Some(ref source) = source_filter {
-                item.source.to_lowercase().contains(source)
-            } else {
-                true
-            };
-
-            // Category filter
-            let matches_category = if let Some(ref category) = category_filter {
-                item.category.as_ref()
-                    .map(|c| c.to_lowercase().contains(category))
-                    .unwrap_or(false)
-            } else {
-                true
-            };
-
-            matches_text && matches_source && matches_category
-        })
+        .select(params.source.as_deref(), params.category.as_deref())
+        .into_iter()
+        // Text search in title and content
+        .filter(|item| query.is_empty() || matches_text(item, &query))
+        .skip(offset)
+        .take(limit)
         .cloned()
         .collect();
 
-    (StatusCode::OK, Json(results))
+    Ok((StatusCode::OK, Json(results)))
+}
+
+// The search as one query, newest first like the in-memory fallback: source,
+// category and text are case-insensitive substring matches, bound as arguments
+fn search_sql(
+    table: &str,
+    source: Option<&str>,
+    category: Option<&str>,
+    query: &str,
+    offset: usize,
+    limit: usize,
+) -> (String, Vec<SqlArg>) {
+    let mut conditions = Vec::new();
+    let mut args = Vec::new();
+    let mut pattern = |value: &str| {
+        let escaped = value.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+        args.push(SqlArg::Text(format!("%{}%", escaped)));
+        args.len()
+    };
+
+    if let Some(source) = source {
+        conditions.push(format!("source ILIKE ${}", pattern(source)));
+    }
+    if let Some(category) = category {
+        conditions.push(format!("category ILIKE ${}", pattern(category)));
+    }
+    if !query.is_empty() {
+        let n = pattern(query);
+        conditions.push(format!("(title ILIKE ${n} OR content ILIKE ${n})"));
+    }
+    let filter = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+
+    args.push(SqlArg::Integer(limit as i64));
+    args.push(SqlArg::Integer(offset as i64));
+    let sql = format!(
+        "SELECT {} FROM {} {} ORDER BY timestamp DESC LIMIT ${} OFFSET ${}",
+        PG_SELECT_COLUMNS,
+        table,
+        filter,
+        args.len() - 1,
+        args.len()
+    );
+    (sql, args)
 }
 
+#[utoipa::path(get, path = "/api/sources", tag = "data",
+    responses((status = 200, description = "Distinct source names", body = Vec<String>)))]
 async fn get_sources(State(state): State<AppState>) -> (StatusCode, Json<Vec<String>>) {
     // Try database first
     if let Some(db) = state.database.as_ref() {
@@ -277,119 +655,532 @@ async fn get_sources(State(state): State<AppState>) -> (StatusCode, Json<Vec<Str
 
     // Fallback to in-memory
     let data_guard = state.data.read().await;
-    let mut sources: Vec<String> = data_guard
-        .iter()
-        .map(|item| item.source.clone())
-        .collect();
-    
-    sources.sort();
-    sources.dedup();
-    
+    let sources: Vec<String> = data_guard.sources().into_iter().map(String::from).collect();
+
     (StatusCode::OK, Json(sources))
 }
 
+#[utoipa::path(get, path = "/api/sources/summary", tag = "data",
+    responses((status = 200, description = "Item count and last scrape time per source", body = Vec<SourceSummary>)))]
+async fn get_source_summaries(State(state): State<AppState>) -> (StatusCode, Json<Vec<SourceSummary>>) {
+    if let Some(db) = state.database.as_ref() {
+        match db.get_source_summaries().await {
+            Ok(summaries) => return (StatusCode::OK, Json(summaries)),
+            Err(e) => log::warn!("Failed to summarize sources from database: {}", e),
+        }
+    }
+
+    let data_guard = state.data.read().await;
+    (StatusCode::OK, Json(SourceSummary::summarize(data_guard.items())))
+}
+
+#[utoipa::path(get, path = "/api/categories", tag = "data",
+    responses((status = 200, description = "Distinct categories with item counts", body = Vec<CategoryCount>)))]
+async fn get_categories(State(state): State<AppState>) -> (StatusCode, Json<Vec<CategoryCount>>) {
+    if let Some(db) = state.database.as_ref() {
+        match db.get_categories().await {
+            Ok(categories) => return (StatusCode::OK, Json(categories)),
+            Err(e) => log::warn!("Failed to get categories from database: {}", e),
+        }
+    }
+
+    let data_guard = state.data.read().await;
+    (StatusCode::OK, Json(CategoryCount::count(data_guard.items())))
+}
+
+#[utoipa::path(get, path = "/api/extraction", tag = "data",
+    responses((status = 200, description = "Share of items per source that had each field extracted", body = Vec<ExtractionRate>)))]
+async fn get_extraction_rates(State(state): State<AppState>) -> (StatusCode, Json<Vec<ExtractionRate>>) {
+    if let Some(db) = state.database.as_ref() {
+        match db.get_extraction_rates().await {
+            Ok(rates) => return (StatusCode::OK, Json(rates)),
+            Err(e) => log::warn!("Failed to tally extraction rates in database: {}", e),
+        }
+    }
+
+    let data_guard = state.data.read().await;
+    (StatusCode::OK, Json(ExtractionRate::summarize(data_guard.items())))
+}
+
+#[utoipa::path(get, path = "/api/stats", tag = "data",
+    responses((status = 200, description = "Item counts", body = HashMap<String, usize>)))]
 async fn get_stats(State(state): State<AppState>) -> (StatusCode, Json<HashMap<String, usize>>) {
+    if let Some(db) = state.database.as_ref() {
+        match db.get_stats().await {
+            Ok(stats) => return (StatusCode::OK, Json(stats_map(&stats))),
+            Err(e) => log::warn!("Failed to count items in database: {}", e),
+        }
+    }
+
     let data_guard = state.data.read().await;
-    let mut stats = HashMap::new();
-    
-    stats.insert("total_items".to_string(), data_guard.len());
-    
-    let source_counts: HashMap<String, usize> = data_guard
-        .iter()
-        .fold(HashMap::new(), |mut acc, item| {
-            *acc.entry(item.source.clone()).or_insert(0) += 1;
-            acc
-        });
-    
-    stats.insert("unique_sources".to_string(), source_counts.len());
-    
-    let items_with_content = data_guard.iter().filter(|item| item.content.is_some()).count();
-    stats.insert("items_with_content".to_string(), items_with_content);
-    
-    let items_with_price = data_guard.iter().filter(|item| item.price.is_some()).count();
-    stats.insert("items_with_price".to_string(), items_with_price);
+    let items = data_guard.items();
+    let sources: HashSet<&str> = items.iter().map(|item| item.source.as_str()).collect();
+    let stats = TableStats {
+        total_items: items.len(),
+        unique_sources: sources.len(),
+        items_with_content: items.iter().filter(|item| item.content.is_some()).count(),
+        items_with_price: items.iter().filter(|item| item.price.is_some()).count(),
+        newest: None,
+    };
+    (StatusCode::OK, Json(stats_map(&stats)))
+}
 
-    (StatusCode::OK, Json(stats))
+fn stats_map(stats: &TableStats) -> HashMap<String, usize> {
+    HashMap::from([
+        ("total_items".to_string(), stats.total_items),
+        ("unique_sources".to_string(), stats.unique_sources),
+        ("items_with_content".to_string(), stats.items_with_content),
+        ("items_with_price".to_string(), stats.items_with_price),
+    ])
 }
 
-async fn export_json(State(state): State<AppState>) -> (StatusCode, Json<Vec<ScrapedData>>) {
+#[utoipa::path(get, path = "/api/export/json", tag = "data",
+    responses(
+        (status = 200, description = "Every stored item", body = Vec<ScrapedData>),
+        (status = 304, description = "Data unchanged since the ETag sent in If-None-Match"),
+    ))]
+async fn export_json(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    // The in-memory store may hold only the newest items (see
+    // `IndexedStore::with_max_items`), so the whole table is streamed instead
+    if let Some(db) = state.database.clone() {
+        match db.get_stats().await {
+            Ok(stats) => {
+                let etag = state.etag_for(stats.total_items, stats.newest, None);
+                if etag_matches(&headers, &etag) {
+                    return not_modified(etag);
+                }
+                return (
+                    StatusCode::OK,
+                    [(header::CONTENT_TYPE, "application/json".to_string()), (header::ETAG, etag)],
+                    stream_json_from_database(db),
+                )
+                    .into_response();
+            }
+            Err(e) => log::warn!("Failed to read items from database, falling back to in-memory: {}", e),
+        }
+    }
+
     let data_guard = state.data.read().await;
-    (StatusCode::OK, Json(data_guard.clone()))
+    let etag = state.etag(data_guard.items(), None);
+    if etag_matches(&headers, &etag) {
+        return not_modified(etag);
+    }
+    conditional_json(&headers, etag, data_guard.items())
 }
 
-async fn export_csv(State(state): State<AppState>) -> (StatusCode, String) {
+// Case-insensitive text search over title and content; `query` is lowercase
+fn matches_text(item: &ScrapedData, query: &str) -> bool {
+    item.title.as_ref().is_some_and(|title| title.to_lowercase().contains(query))
+        || item.content.as_ref().is_some_and(|content| content.to_lowercase().contains(query))
+}
+
+// Whether the client's If-None-Match already names this ETag (weak comparison)
+fn etag_matches(headers: &HeaderMap, etag: &str) -> bool {
+    let opaque = etag.trim_start_matches("W/");
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == opaque)
+}
+
+fn not_modified(etag: String) -> Response {
+    (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response()
+}
+
+// JSON body tagged with an ETag, or 304 when the client already has it
+fn conditional_json<T: Serialize>(headers: &HeaderMap, etag: String, body: T) -> Response {
+    if etag_matches(headers, &etag) {
+        return not_modified(etag);
+    }
+    (StatusCode::OK, [(header::ETAG, etag)], Json(body)).into_response()
+}
+
+#[utoipa::path(get, path = "/api/export/csv", tag = "data",
+    responses(
+        (status = 200, description = "Every stored item as CSV", body = String, content_type = "text/csv"),
+        (status = 500, description = "CSV generation failed", body = ApiError),
+    ))]
+async fn export_csv(State(state): State<AppState>) -> Response {
+    // With a database, stream rows straight from it instead of the in-memory copy
+    if let Some(db) = state.database.clone() {
+        return stream_csv_from_database(db);
+    }
+
     let data_guard = state.data.read().await;
     
     let mut wtr = csv::Writer::from_writer(Vec::new());
     
     // Write header
-    if wtr.write_record(&["id", "source", "url", "title", "content", "price", "author", "timestamp", "category"]).is_err() {
-        return (StatusCode::INTERNAL_SERVER_ERROR, "CSV generation error".to_string());
+    if wtr.write_record(CSV_HEADER).is_err() {
+        return ApiError::internal("CSV generation error").into_response();
     }
 
-    for item in data_guard.iter() {
-        if wtr.write_record(&[
-            &item.id,
-            &item.source,
-            &item.url,
-            item.title.as_deref().unwrap_or(""),
-            item.content.as_deref().unwrap_or(""),
-            &item.price.map(|p| p.to_string()).unwrap_or_default(),
-            item.author.as_deref().unwrap_or(""),
-            &item.timestamp.to_rfc3339(),
-            item.category.as_deref().unwrap_or(""),
-        ]).is_err() {
-            return (StatusCode::INTERNAL_SERVER_ERROR, "CSV generation error".to_string());
+    for item in data_guard.items() {
+        if wtr.write_record(csv_fields(item)).is_err() {
+            return ApiError::internal("CSV generation error").into_response();
         }
     }
 
     match wtr.into_inner() {
         Ok(bytes) => {
             let csv_string = String::from_utf8_lossy(&bytes).to_string();
-            (StatusCode::OK, csv_string)
+            (StatusCode::OK, csv_string).into_response()
+        }
+        Err(_) => ApiError::internal("CSV generation error").into_response(),
+    }
+}
+
+#[utoipa::path(get, path = "/api/export/bundle", tag = "data",
+    responses(
+        (status = 200, description = "Zip of data.json, data.csv, metadata.json and a README of the schema",
+            body = Vec<u8>, content_type = "application/zip"),
+        (status = 500, description = "Building the archive failed", body = ApiError),
+    ))]
+async fn export_bundle(State(state): State<AppState>) -> Response {
+    let mut built = None;
+    if let Some(db) = state.database.clone() {
+        match bundle_from_database(db).await {
+            Ok(body) => built = Some(Ok(body)),
+            Err(e) => log::warn!("Failed to bundle items from database, falling back to in-memory: {:#}", e),
+        }
+    }
+    let built = match built {
+        Some(built) => built,
+        None => BundleOutput::new().build(state.data.read().await.items()).map(axum::body::Body::from),
+    };
+    match built {
+        Ok(body) => {
+            let disposition = format!(
+                "attachment; filename=\"scraped-data-{}.zip\"",
+                chrono::Utc::now().format("%Y%m%d_%H%M%S")
+            );
+            (
+                StatusCode::OK,
+                [(header::CONTENT_TYPE, "application/zip".to_string()), (header::CONTENT_DISPOSITION, disposition)],
+                body,
+            )
+                .into_response()
+        }
+        Err(e) => {
+            log::error!("Failed to build export bundle: {:#}", e);
+            ApiError::internal("Bundle generation error").into_response()
         }
-        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "CSV generation error".to_string()),
     }
 }
 
+const CSV_HEADER: [&str; 9] = ["id", "source", "url", "title", "content", "price", "author", "timestamp", "category"];
+
+fn csv_fields(item: &ScrapedData) -> [String; 9] {
+    [
+        item.id.clone(),
+        item.source.clone(),
+        item.url.clone(),
+        item.title.clone().unwrap_or_default(),
+        item.content.clone().unwrap_or_default(),
+        item.price.map(|p| p.to_string()).unwrap_or_default(),
+        item.author.clone().unwrap_or_default(),
+        item.timestamp.to_rfc3339(),
+        item.category.clone().unwrap_or_default(),
+    ]
+}
+
+// Encode a single CSV record, quoting as needed
+fn csv_line<I, T>(record: I) -> Result<bytes::Bytes>
+where
+    I: IntoIterator<Item = T>,
+    T: AsRef<[u8]>,
+{
+    let mut wtr = csv::Writer::from_writer(Vec::new());
+    wtr.write_record(record)?;
+    Ok(wtr.into_inner().map_err(|e| e.into_error())?.into())
+}
+
+// Feed database rows into the response body one record at a time, after
+// `header` and followed by `footer`. A row that fails to load aborts the body,
+// so the client sees a truncated download rather than a silently incomplete file.
+fn stream_from_database<F>(
+    db: Arc<PostgresOutput>,
+    header: Result<bytes::Bytes>,
+    mut encode: F,
+    footer: &'static [u8],
+) -> axum::body::Body
+where
+    F: FnMut(&ScrapedData) -> Result<bytes::Bytes> + Send + 'static,
+{
+    use futures::{SinkExt, StreamExt};
+
+    let (mut tx, rx) = futures::channel::mpsc::channel::<Result<bytes::Bytes>>(64);
+
+    tokio::spawn(async move {
+        if tx.send(header).await.is_err() {
+            return;
+        }
+
+        let mut rows = std::pin::pin!(db.get_all_stream());
+        while let Some(row) = rows.next().await {
+            let encoded = row.and_then(|item| encode(&item));
+            let failed = encoded.is_err();
+            if let Err(e) = &encoded {
+                log::error!("Export stream failed: {}", e);
+            }
+            // Stop on error or once the client has gone away
+            if tx.send(encoded).await.is_err() || failed {
+                return;
+            }
+        }
+        let _ = tx.send(Ok(bytes::Bytes::from_static(footer))).await;
+    });
+
+    axum::body::Body::from_stream(rx)
+}
+
+fn stream_csv_from_database(db: Arc<PostgresOutput>) -> Response {
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, "text/csv")],
+        stream_from_database(db, csv_line(CSV_HEADER), |item| csv_line(csv_fields(item)), b""),
+    )
+        .into_response()
+}
+
+// The rows as one JSON array, written element by element
+fn stream_json_from_database(db: Arc<PostgresOutput>) -> axum::body::Body {
+    let mut first = true;
+    let encode = move |item: &ScrapedData| {
+        let mut element = if std::mem::take(&mut first) { Vec::new() } else { b",".to_vec() };
+        serde_json::to_writer(&mut element, item)?;
+        Ok(element.into())
+    };
+    stream_from_database(db, Ok(bytes::Bytes::from_static(b"[")), encode, b"]")
+}
+
+// Write the bundle into a temporary file, reading the rows once per file in
+// the archive, then stream the file back; neither the items nor the archive
+// are held in memory. The file is removed once the body is dropped.
+async fn bundle_from_database(db: Arc<PostgresOutput>) -> Result<axum::body::Body> {
+    use futures::StreamExt;
+    use std::cell::RefCell;
+    use std::io::Write;
+    use tokio::io::AsyncReadExt;
+
+    struct TempFile(PathBuf);
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            if let Err(e) = std::fs::remove_file(&self.0) {
+                log::warn!("Failed to remove temporary bundle {}: {}", self.0.display(), e);
+            }
+        }
+    }
+
+    let sources = db
+        .get_source_summaries()
+        .await?
+        .into_iter()
+        .map(|summary| (summary.source, summary.count))
+        .collect();
+    let metadata = BundleMetadata::from_source_counts(sources);
+
+    let temp = TempFile(std::env::temp_dir().join(format!("scraped-data-{}.zip", uuid::Uuid::new_v4())));
+    let path = temp.0.clone();
+    let runtime = tokio::runtime::Handle::current();
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        let file = std::io::BufWriter::new(std::fs::File::create(&path)?);
+        let failure = RefCell::new(None);
+        let runtime = &runtime;
+        let rows = || {
+            let mut rows = Box::pin(db.get_all_stream());
+            std::iter::from_fn(move || runtime.block_on(rows.next()))
+                .map_while(|row| row.map_err(|e| *failure.borrow_mut() = Some(e)).ok())
+        };
+        let mut file = BundleOutput::new().write(file, &metadata, rows)?;
+        if let Some(e) = failure.into_inner() {
+            return Err(e);
+        }
+        file.flush()?;
+        Ok(())
+    })
+    .await??;
+
+    let file = tokio::fs::File::open(&temp.0).await?;
+    let chunks = futures::stream::unfold(Some((file, temp)), |state| async move {
+        let (mut file, temp) = state?;
+        let mut chunk = vec![0; 64 * 1024];
+        match file.read(&mut chunk).await {
+            Ok(0) => None,
+            Ok(read) => {
+                chunk.truncate(read);
+                Some((Ok(bytes::Bytes::from(chunk)), Some((file, temp))))
+            }
+            Err(e) => Some((Err(e), None)),
+        }
+    });
+    Ok(axum::body::Body::from_stream(chunks))
+}
+
+#[utoipa::path(post, path = "/api/update", tag = "data", request_body = Vec<ScrapedData>,
+    responses(
+        (status = 200, description = "Stored data replaced; invalid items are counted under `dropped`", body = HashMap<String, String>),
+        (status = 400, description = "Body is not a JSON array of items", body = ApiError),
+    ))]
 async fn update_data(
     State(state): State<AppState>,
-    Json(new_data): Json<Vec<ScrapedData>>,
-) -> (StatusCode, Json<HashMap<&'static str, String>>) {
+    payload: Result<Json<Vec<ScrapedData>>, JsonRejection>,
+) -> Result<(StatusCode, Json<HashMap<&'static str, String>>), ApiError> {
+    let Json(new_data) = payload?;
+    let (new_data, dropped) = validate_incoming(new_data).await;
+
     let mut data_guard = state.data.write().await;
-    let count = new_data.len();
-    *data_guard = new_data;
+    data_guard.replace_all(new_data);
+    let count = data_guard.len();
+    state.bump_version();
 
     let mut response = HashMap::new();
     response.insert("status", "success".to_string());
     response.insert("message", "Data updated successfully".to_string());
     response.insert("items_count", count.to_string());
+    response.insert("dropped", dropped.to_string());
 
-    (StatusCode::OK, Json(response))
+    Ok((StatusCode::OK, Json(response)))
+}
+
+// Drop posted items that fail the same checks scraped data goes through,
+// returning the survivors and how many were rejected
+async fn validate_incoming(items: Vec<ScrapedData>) -> (Vec<ScrapedData>, usize) {
+    use crate::processors::validator::Validator;
+
+    let received = items.len();
+    let valid = Validator::new().validate(items).await.unwrap_or_default();
+    let dropped = received - valid.len();
+
+    if dropped > 0 {
+        log::warn!("Rejected {} of {} posted items that failed validation", dropped, received);
+    }
+
+    (valid, dropped)
+}
+
+// Additive counterpart to update_data: appends new items, skipping ids already stored
+#[utoipa::path(post, path = "/api/items", tag = "data", request_body = Vec<ScrapedData>,
+    responses(
+        (status = 200, description = "Items appended", body = AppendResponse),
+        (status = 400, description = "Body is not a JSON array of items", body = ApiError),
+        (status = 500, description = "Items could not be saved to the database; nothing was appended", body = ApiError),
+    ))]
+async fn append_items(
+    State(state): State<AppState>,
+    payload: Result<Json<Vec<ScrapedData>>, JsonRejection>,
+) -> Result<(StatusCode, Json<AppendResponse>), ApiError> {
+    use crate::output::database::DatabaseOutput;
+
+    let Json(new_items) = payload?;
+    let (new_items, dropped) = validate_incoming(new_items).await;
+    let received = new_items.len();
+
+    // Items evicted from memory are still stored, so they aren't new either
+    let stored = match state.database.as_ref() {
+        Some(db) => {
+            let ids: Vec<String> = new_items.iter().map(|item| item.id.clone()).collect();
+            db.existing_ids(&ids).await.unwrap_or_else(|e| {
+                log::warn!("Failed to look up posted ids in database: {:#}", e);
+                HashSet::new()
+            })
+        }
+        None => HashSet::new(),
+    };
+
+    // Held across the save so a concurrent append can't claim the same ids
+    let mut data_guard = state.data.write().await;
+    let mut seen = HashSet::new();
+    let inserted: Vec<ScrapedData> = new_items
+        .into_iter()
+        .filter(|item| !stored.contains(&item.id) && !data_guard.contains(&item.id) && seen.insert(item.id.clone()))
+        .collect();
+    let inserted_count = inserted.len();
+
+    // Saved before memory is touched, so a failed write changes nothing
+    if let Some(db) = state.database.as_ref()
+        && !inserted.is_empty()
+    {
+        let count = db
+            .save(&inserted)
+            .await
+            .map_err(|e| ApiError::internal(format!("Failed to save appended items: {}", e)))?;
+        log::info!("Saved {} appended items to database", count);
+    }
+
+    for item in inserted {
+        data_guard.upsert(item);
+    }
+    if inserted_count > 0 {
+        state.bump_version();
+    }
+    let items_count = data_guard.len();
+    drop(data_guard);
+
+    log::info!("Appended {} of {} posted items ({} total)", inserted_count, received, items_count);
+
+    Ok((
+        StatusCode::OK,
+        Json(AppendResponse {
+            status: "success".to_string(),
+            inserted: inserted_count,
+            skipped: received - inserted_count,
+            dropped,
+            items_count,
+        }),
+    ))
 }
 
 // Frontend handler - serves static files or index.html for SPA routing
-async fn handle_frontend(uri: Uri) -> impl IntoResponse {
+async fn handle_frontend(root: Arc<PathBuf>, uri: Uri, headers: HeaderMap) -> Response {
     let path = uri.path();
 
     // API routes should never reach here (they're handled by api_routes)
     if path.starts_with("/api/") {
-        return (StatusCode::NOT_FOUND, "API endpoint not found").into_response();
+        return ApiError::not_found("API endpoint not found").into_response();
     }
 
-    // Try to serve the file from frontend/dist
-    let file_path = format!("frontend/dist{}", path);
-    let file_path = std::path::Path::new(&file_path);
-
     // If path is "/" or empty, serve index.html
     if path == "/" || path.is_empty() {
-        return serve_index_html().await;
+        return serve_index_html(&root).await;
     }
 
-    // Check if the file exists and is a file (not a directory)
-    if file_path.exists() && file_path.is_file() {
+    // Try to serve the file from the frontend root
+    let file_path = match resolve_frontend_file(&root, path).await {
+        Ok(file_path) => file_path,
+        Err(status) => {
+            log::warn!("Rejected frontend request outside the dist root: {}", path);
+            return (status, "Forbidden").into_response();
+        }
+    };
+
+    if let Some(file_path) = file_path {
+        // Prefer a precompressed sibling (app.js.br / app.js.gz) the client can decode
+        for (encoding, extension) in [("br", "br"), ("gzip", "gz")] {
+            if !accepts_encoding(&headers, encoding) {
+                continue;
+            }
+            let compressed_path = format!("{}.{}", path, extension);
+            if let Ok(Some(compressed)) = resolve_frontend_file(&root, &compressed_path).await
+                && let Ok(content) = tokio::fs::read(&compressed).await
+            {
+                return (
+                    StatusCode::OK,
+                    [
+                        (header::CONTENT_TYPE, get_content_type(path)),
+                        (header::CONTENT_ENCODING, encoding),
+                        (header::VARY, "accept-encoding"),
+                    ],
+                    content,
+                ).into_response();
+            }
+        }
+
         // Serve the file
-        match tokio::fs::read(file_path).await {
+        match tokio::fs::read(&file_path).await {
             Ok(content) => {
                 // Determine content type from file extension
                 let content_type = get_content_type(path);
@@ -399,17 +1190,76 @@ async fn handle_frontend(uri: Uri) -> impl IntoResponse {
                     content
                 ).into_response();
             }
-            Err(_) => return serve_index_html().await,
+            Err(_) => return serve_index_html(&root).await,
         }
     }
 
+    // A missing asset must not be answered with index.html: the browser would get
+    // HTML with a 200 where it expected JS/CSS/an image
+    if is_asset_path(path) {
+        return (StatusCode::NOT_FOUND, "Asset not found").into_response();
+    }
+
     // For all other routes (non-existent files), serve index.html for SPA routing
-    serve_index_html().await
+    serve_index_html(&root).await
+}
+
+// Map a request path to an existing file under the frontend root. Anything that
+// would resolve outside the root (`..` segments, symlinks pointing elsewhere) is
+// refused with 403 rather than read.
+async fn resolve_frontend_file(root: &Path, path: &str) -> Result<Option<PathBuf>, StatusCode> {
+    let relative = Path::new(path.trim_start_matches('/'));
+    if relative
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_) | Component::CurDir))
+    {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let (Ok(root), Ok(file_path)) = (
+        tokio::fs::canonicalize(root).await,
+        tokio::fs::canonicalize(root.join(relative)).await,
+    ) else {
+        return Ok(None);
+    };
+
+    if !file_path.starts_with(&root) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let is_file = tokio::fs::metadata(&file_path).await.map(|meta| meta.is_file()).unwrap_or(false);
+    Ok(is_file.then_some(file_path))
+}
+
+// Whether Accept-Encoding lists `encoding` (or `*`) without refusing it via q=0
+fn accepts_encoding(headers: &HeaderMap, encoding: &str) -> bool {
+    headers
+        .get_all(header::ACCEPT_ENCODING)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|entry| {
+            let mut parts = entry.split(';').map(str::trim);
+            let name = parts.next().unwrap_or_default();
+            let refused = parts.any(|param| {
+                param
+                    .strip_prefix("q=")
+                    .and_then(|q| q.parse::<f32>().ok())
+                    .is_some_and(|q| q == 0.0)
+            });
+            (name.eq_ignore_ascii_case(encoding) || name == "*") && !refused
+        })
+}
+
+// Paths whose last segment has a file extension are asset requests, everything else is an SPA route
+fn is_asset_path(path: &str) -> bool {
+    let last_segment = path.rsplit('/').next().unwrap_or_default();
+    Path::new(last_segment).extension().is_some()
 }
 
 // Helper to serve index.html
-async fn serve_index_html() -> axum::response::Response {
-    match tokio::fs::read_to_string("frontend/dist/index.html").await {
+async fn serve_index_html(root: &Path) -> Response {
+    match tokio::fs::read_to_string(root.join("index.html")).await {
         Ok(content) => (
             StatusCode::OK,
             [(axum::http::header::CONTENT_TYPE, "text/html; charset=utf-8")],
@@ -423,16 +1273,19 @@ async fn serve_index_html() -> axum::response::Response {
 }
 
 // Handler for triggering a new scrape
+#[utoipa::path(post, path = "/api/scrape", tag = "system",
+    responses(
+        (status = 200, description = "Scrape finished and stored data replaced", body = HashMap<String, serde_json::Value>),
+        (status = 500, description = "Config, scrape or processing failure", body = ApiError),
+    ))]
 async fn trigger_scrape(
     State(state): State<AppState>,
-) -> (StatusCode, Json<HashMap<String, serde_json::Value>>) {
+) -> Result<(StatusCode, Json<HashMap<String, serde_json::Value>>), ApiError> {
     use crate::core::config::Config;
     use crate::core::scraper::ScraperEngine;
     use crate::processors::pipeline::ProcessingPipeline;
     use crate::sources::{EcommerceSource, SourceType};
-    use crate::utils::cache::HtmlCache;
     use crate::output::database::DatabaseOutput;
-    use std::sync::Arc;
 
     log::info!("API: Triggering new scrape request");
 
@@ -441,15 +1294,15 @@ async fn trigger_scrape(
         Ok(cfg) => cfg,
         Err(e) => {
             log::error!("Failed to load config: {}", e);
-            let mut response = HashMap::new();
-            response.insert("status".to_string(), serde_json::Value::String("error".to_string()));
-            response.insert("message".to_string(), serde_json::Value::String(format!("Failed to load config: {}", e)));
-            return (StatusCode::INTERNAL_SERVER_ERROR, Json(response));
+            return Err(ApiError::internal(format!("Failed to load config: {}", e)));
         }
     };
 
-    let cache = Arc::new(HtmlCache::new_html_cache(1000, 3600));
-    let pipeline = ProcessingPipeline::new();
+    let cache = state
+        .cache
+        .clone()
+        .unwrap_or_else(|| Arc::new(HtmlCache::new_html_cache(1000, 3600)));
+    let pipeline = ProcessingPipeline::from_config(&config.pipeline);
     let mut engine = ScraperEngine::new(config, pipeline, Some(cache));
 
     // Scrape from books.toscrape.com
@@ -470,10 +1323,7 @@ async fn trigger_scrape(
             }
             Err(e) => {
                 log::error!("Failed to scrape: {}", e);
-                let mut response = HashMap::new();
-                response.insert("status".to_string(), serde_json::Value::String("error".to_string()));
-                response.insert("message".to_string(), serde_json::Value::String(format!("Scraping failed: {}", e)));
-                return (StatusCode::INTERNAL_SERVER_ERROR, Json(response));
+                return Err(ApiError::internal(format!("Scraping failed: {}", e)));
             }
         }
     }
@@ -483,10 +1333,7 @@ async fn trigger_scrape(
         Ok(data) => data,
         Err(e) => {
             log::error!("Failed to process data: {}", e);
-            let mut response = HashMap::new();
-            response.insert("status".to_string(), serde_json::Value::String("error".to_string()));
-            response.insert("message".to_string(), serde_json::Value::String(format!("Processing failed: {}", e)));
-            return (StatusCode::INTERNAL_SERVER_ERROR, Json(response));
+            return Err(ApiError::internal(format!("Processing failed: {}", e)));
         }
     };
 
@@ -503,7 +1350,8 @@ async fn trigger_scrape(
     // Update in-memory data
     {
         let mut data_guard = state.data.write().await;
-        *data_guard = processed_data;
+        data_guard.replace_all(processed_data);
+        state.bump_version();
         log::info!("Updated in-memory data with {} items", data_guard.len());
     }
 
@@ -513,7 +1361,62 @@ async fn trigger_scrape(
     response.insert("items_scraped".to_string(), serde_json::Value::Number(items_count.into()));
 
     log::info!("Scrape request completed: {} items", items_count);
-    (StatusCode::OK, Json(response))
+    Ok((StatusCode::OK, Json(response)))
+}
+
+#[utoipa::path(get, path = "/api/cache/stats", tag = "system",
+    responses(
+        (status = 200, description = "HTML cache entry count and hit/miss rates", body = CacheStats),
+        (status = 404, description = "No HTML cache configured", body = ApiError),
+    ))]
+async fn cache_stats(State(state): State<AppState>) -> Response {
+    match state.cache.as_ref() {
+        Some(cache) => (StatusCode::OK, Json(cache.stats())).into_response(),
+        None => no_cache_error().into_response(),
+    }
+}
+
+#[utoipa::path(post, path = "/api/cache/clear", tag = "system",
+    responses(
+        (status = 200, description = "HTML cache emptied", body = HashMap<String, String>),
+        (status = 404, description = "No HTML cache configured", body = ApiError),
+        (status = 500, description = "Failed to clear the cache", body = ApiError),
+    ))]
+async fn clear_cache(State(state): State<AppState>) -> Response {
+    let Some(cache) = state.cache.as_ref() else {
+        return no_cache_error().into_response();
+    };
+
+    let cleared = cache.stats().entry_count;
+    match cache.clear().await {
+        Ok(()) => {
+            log::info!("API: Cleared {} cached pages", cleared);
+            let mut response = HashMap::new();
+            response.insert("status", "success".to_string());
+            response.insert("cleared", cleared.to_string());
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(e) => {
+            log::error!("Failed to clear cache: {}", e);
+            ApiError::internal(format!("Failed to clear cache: {}", e)).into_response()
+        }
+    }
+}
+
+#[utoipa::path(get, path = "/api/engine-stats", tag = "system",
+    responses(
+        (status = 200, description = "Cache, per-host and per-source scraper health", body = EngineStats),
+        (status = 404, description = "No scraper engine wired into the server", body = ApiError),
+    ))]
+async fn engine_stats(State(state): State<AppState>) -> Response {
+    match state.engine.as_ref() {
+        Some(engine) => (StatusCode::OK, Json(engine.snapshot())).into_response(),
+        None => ApiError::not_found("No scraper engine configured").into_response(),
+    }
+}
+
+fn no_cache_error() -> ApiError {
+    ApiError::not_found("No HTML cache configured")
 }
 
 // Helper to determine content type from file extension
@@ -0,0 +1,76 @@
+//! Crash-safe file writes for the exporters
+//!
+//! Output is written to a temporary file next to the target and renamed over
+//! it only once complete, so readers see either the old file or the new one,
+//! never a truncated mix. [`write_file_ensuring_dirs`] covers the simpler
+//! writes that only need their directory to exist.
+
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Write `path` through `write`, replacing it atomically on success.
+///
+/// Missing parent directories are created. If `write` fails, the temporary
+/// file is removed and any existing file at `path` is left untouched.
+pub fn write_atomically<P, F>(path: P, write: F) -> Result<()>
+where
+    P: AsRef<Path>,
+    F: FnOnce(&mut BufWriter<File>) -> Result<()>,
+{
+    let path = path.as_ref();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let tmp_path = temp_path_for(path);
+    let result = write_and_sync(&tmp_path, write).and_then(|()| {
+        std::fs::rename(&tmp_path, path)
+            .with_context(|| format!("Failed to move {} into place", path.display()))
+    });
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+/// Write `contents` to `path`, creating missing parent directories first
+pub async fn write_file_ensuring_dirs<P, C>(path: P, contents: C) -> Result<()>
+where
+    P: AsRef<Path>,
+    C: AsRef<[u8]>,
+{
+    let path = path.as_ref();
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    tokio::fs::write(path, contents)
+        .await
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn write_and_sync<F>(tmp_path: &Path, write: F) -> Result<()>
+where
+    F: FnOnce(&mut BufWriter<File>) -> Result<()>,
+{
+    let file = File::create(tmp_path)
+        .with_context(|| format!("Failed to create {}", tmp_path.display()))?;
+    let mut writer = BufWriter::new(file);
+    write(&mut writer)?;
+    writer.flush()?;
+    writer.get_ref().sync_all()?;
+    Ok(())
+}
+
+// Same directory as the target, so the rename never crosses filesystems
+fn temp_path_for(path: &Path) -> PathBuf {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.{}.tmp", file_name, uuid::Uuid::new_v4().simple()))
+}
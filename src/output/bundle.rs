@@ -0,0 +1,138 @@
+//! Zip archive bundling every export format
+//!
+//! One download for sharing a dataset: the JSON and CSV exports, a
+//! `metadata.json` summary and a README describing the fields.
+
+use crate::core::models::ScrapedData;
+use crate::output::{
+    atomic::write_atomically,
+    csv::write_csv,
+    json::{write_envelope, SCHEMA_VERSION},
+};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::borrow::Borrow;
+use std::io::{Cursor, Seek, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+pub const BUNDLE_JSON: &str = "data.json";
+pub const BUNDLE_CSV: &str = "data.csv";
+pub const BUNDLE_METADATA: &str = "metadata.json";
+pub const BUNDLE_README: &str = "README.md";
+
+/// Contents of `metadata.json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleMetadata {
+    pub item_count: usize,
+    /// Item count per source
+    pub sources: BTreeMap<String, usize>,
+    pub generated_at: DateTime<Utc>,
+    /// Layout version of `data.json`
+    pub schema_version: u32,
+}
+
+impl BundleMetadata {
+    pub fn describe(data: &[ScrapedData]) -> Self {
+        let mut sources = BTreeMap::new();
+        for item in data {
+            *sources.entry(item.source.clone()).or_insert(0) += 1;
+        }
+        Self::from_source_counts(sources)
+    }
+
+    /// Metadata for items already counted per source, e.g. by the database
+    pub fn from_source_counts(sources: BTreeMap<String, usize>) -> Self {
+        Self {
+            item_count: sources.values().sum(),
+            sources,
+            generated_at: Utc::now(),
+            schema_version: SCHEMA_VERSION,
+        }
+    }
+}
+
+const README: &str = "# Scraped data export
+
+| File | Contents |
+|------|----------|
+| `data.json` | Every item, wrapped in `{\"schema_version\", \"generated_at\", \"items\"}` |
+| `data.csv` | The same items, one row each (metadata omitted) |
+| `metadata.json` | Item count, items per source and when the export was made |
+
+## Item fields
+
+| Field | Type | Description |
+|-------|------|-------------|
+| `id` | string | Unique item id |
+| `source` | string | Name of the source the item was scraped from |
+| `url` | string | The item's own URL |
+| `source_url` | string, optional | Page the item was found on, when different from `url` (JSON only) |
+| `title` | string, optional | |
+| `content` | string, optional | Body text or description |
+| `price` | number, optional | Numeric price; the currency is in `metadata.currency` when known |
+| `image_url` | string, optional | |
+| `author` | string, optional | |
+| `timestamp` | RFC 3339 string | Publish time when the page gave one, otherwise the scrape time |
+| `category` | string, optional | |
+| `metadata` | object of strings | Source-specific extras (JSON only) |
+";
+
+pub struct BundleOutput;
+
+impl Default for BundleOutput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BundleOutput {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// The zip archive as bytes, e.g. to send as a download
+    pub fn build(&self, data: &[ScrapedData]) -> Result<Vec<u8>> {
+        let metadata = BundleMetadata::describe(data);
+        Ok(self.write(Cursor::new(Vec::new()), &metadata, || data)?.into_inner())
+    }
+
+    /// Write the archive to `out`, asking `items` for the dataset twice, once
+    /// for `data.json` and once for `data.csv`, so it never has to be held in
+    /// memory; `metadata` should describe the same items. Returns `out`.
+    pub fn write<W, F, I>(&self, out: W, metadata: &BundleMetadata, mut items: F) -> Result<W>
+    where
+        W: Write + Seek,
+        F: FnMut() -> I,
+        I: IntoIterator,
+        I::Item: Borrow<ScrapedData>,
+    {
+        let mut zip = ZipWriter::new(out);
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+        zip.start_file(BUNDLE_JSON, options)?;
+        write_envelope(&mut zip, items(), true)?;
+
+        zip.start_file(BUNDLE_CSV, options)?;
+        write_csv(&mut zip, items())?;
+
+        zip.start_file(BUNDLE_METADATA, options)?;
+        serde_json::to_writer_pretty(&mut zip, metadata)?;
+
+        zip.start_file(BUNDLE_README, options)?;
+        zip.write_all(README.as_bytes())?;
+
+        Ok(zip.finish()?)
+    }
+
+    pub async fn export<P: AsRef<Path>>(&self, data: &[ScrapedData], path: P) -> Result<()> {
+        let bytes = self.build(data)?;
+        write_atomically(path, |file| Ok(file.write_all(&bytes)?))?;
+
+        log::info!("Exported {} items to a zip bundle", data.len());
+        Ok(())
+    }
+}
@@ -1,7 +1,9 @@
 use crate::core::models::ScrapedData;
+use crate::output::atomic::write_atomically;
 use anyhow::Result;
 use csv::Writer;
-use std::fs::File;
+use std::borrow::Borrow;
+use std::io::Write;
 use std::path::Path;
 
 pub struct CsvOutput;
@@ -12,74 +14,80 @@ impl CsvOutput {
     }
 
     pub async fn export<P: AsRef<Path>>(&self, data: &[ScrapedData], path: P) -> Result<()> {
-        // Create directory if it doesn't exist
-        if let Some(parent) = path.as_ref().parent() {
-            std::fs::create_dir_all(parent)?;
-        }
+        write_atomically(path, |file| write_csv(file, data))?;
 
-        let file = File::create(path)?;
-        let mut wtr = Writer::from_writer(file);
+        log::info!("Exported {} items to CSV", data.len());
+        Ok(())
+    }
 
-        // Write header
-        wtr.write_record(&[
-            "id", "source", "url", "title", "content", "price", "image_url", 
-            "author", "timestamp", "category"
-        ])?;
+    pub async fn export_with_metadata<P: AsRef<Path>>(&self, data: &[ScrapedData], path: P) -> Result<()> {
+        write_atomically(path, |file| {
+            let mut wtr = Writer::from_writer(file);
 
-        for item in data {
+            // Extended header with metadata
             wtr.write_record(&[
-                &item.id,
-                &item.source,
-                &item.url,
-                item.title.as_deref().unwrap_or(""),
-                item.content.as_deref().unwrap_or(""),
-                &item.price.map(|p| p.to_string()).unwrap_or_default(),
-                item.image_url.as_deref().unwrap_or(""),
-                item.author.as_deref().unwrap_or(""),
-                &item.timestamp.to_rfc3339(),
-                item.category.as_deref().unwrap_or(""),
+                "id", "source", "url", "title", "content", "price", "image_url", 
+                "author", "timestamp", "category", "metadata"
             ])?;
-        }
 
-        wtr.flush()?;
-        log::info!("Exported {} items to CSV", data.len());
+            for item in data {
+                let metadata_json = serde_json::to_string(&item.metadata).unwrap_or_default();
+            
+                wtr.write_record(&[
+                    &item.id,
+                    &item.source,
+                    &item.url,
+                    item.title.as_deref().unwrap_or(""),
+                    item.content.as_deref().unwrap_or(""),
+                    &item.price.map(|p| p.to_string()).unwrap_or_default(),
+                    item.image_url.as_deref().unwrap_or(""),
+                    item.author.as_deref().unwrap_or(""),
+                    &item.timestamp.to_rfc3339(),
+                    item.category.as_deref().unwrap_or(""),
+                    &metadata_json,
+                ])?;
+            }
+
+            wtr.flush()?;
+            Ok(())
+        })?;
+
+        log::info!("Exported {} items to CSV with metadata", data.len());
         Ok(())
     }
+}
 
-    pub async fn export_with_metadata<P: AsRef<Path>>(&self, data: &[ScrapedData], path: P) -> Result<()> {
-        if let Some(parent) = path.as_ref().parent() {
-            std::fs::create_dir_all(parent)?;
-        }
+/// The rows `CsvOutput::export` writes, to any writer
+pub(crate) fn write_csv<W, I>(writer: W, data: I) -> Result<()>
+where
+    W: Write,
+    I: IntoIterator,
+    I::Item: Borrow<ScrapedData>,
+{
+    let mut wtr = Writer::from_writer(writer);
 
-        let file = File::create(path)?;
-        let mut wtr = Writer::from_writer(file);
+    // Write header
+    wtr.write_record(&[
+        "id", "source", "url", "title", "content", "price", "image_url", 
+        "author", "timestamp", "category"
+    ])?;
 
-        // Extended header with metadata
+    for item in data {
+        let item = item.borrow();
         wtr.write_record(&[
-            "id", "source", "url", "title", "content", "price", "image_url", 
-            "author", "timestamp", "category", "metadata"
+            &item.id,
+            &item.source,
+            &item.url,
+            item.title.as_deref().unwrap_or(""),
+            item.content.as_deref().unwrap_or(""),
+            &item.price.map(|p| p.to_string()).unwrap_or_default(),
+            item.image_url.as_deref().unwrap_or(""),
+            item.author.as_deref().unwrap_or(""),
+            &item.timestamp.to_rfc3339(),
+            item.category.as_deref().unwrap_or(""),
         ])?;
-
-        for item in data {
-            let metadata_json = serde_json::to_string(&item.metadata).unwrap_or_default();
-            
-            wtr.write_record(&[
-                &item.id,
-                &item.source,
-                &item.url,
-                item.title.as_deref().unwrap_or(""),
-                item.content.as_deref().unwrap_or(""),
-                &item.price.map(|p| p.to_string()).unwrap_or_default(),
-                item.image_url.as_deref().unwrap_or(""),
-                item.author.as_deref().unwrap_or(""),
-                &item.timestamp.to_rfc3339(),
-                item.category.as_deref().unwrap_or(""),
-                &metadata_json,
-            ])?;
-        }
-
-        wtr.flush()?;
-        log::info!("Exported {} items to CSV with metadata", data.len());
-        Ok(())
     }
-}
\ No newline at end of file
+
+    wtr.flush()?;
+    Ok(())
+}
@@ -1,21 +1,256 @@
-use crate::core::models::ScrapedData;
+use crate::core::models::{CategoryCount, ExtractionRate, ItemPatch, ScrapedData, SourceSummary, EXTRACTION_KEY};
 use anyhow::{Context, Result};
 use async_trait::async_trait;
-use sqlx::{postgres::PgPoolOptions, Pool, Postgres, SqlitePool, Row};
+use futures::{Stream, StreamExt};
+use sqlx::{
+    migrate::Migrator,
+    postgres::{PgPoolOptions, PgRow},
+    sqlite::SqliteRow,
+    Executor, Pool, Postgres, SqlitePool, Row,
+};
 use chrono::{DateTime, Utc};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+static POSTGRES_MIGRATIONS: Migrator = sqlx::migrate!("./migrations/postgres");
+static SQLITE_MIGRATIONS: Migrator = sqlx::migrate!("./migrations/sqlite");
+
+// Migration files name their table `{table}`, which is swapped for the
+// configured one when they run
+const TABLE_PLACEHOLDER: &str = "{table}";
+
+/// Rows written per transaction when `DB_BATCH_SIZE` isn't set
+pub const DEFAULT_BATCH_SIZE: usize = 1000;
+
+// Bigger batches mean fewer round-trips and commits, but longer-held locks and
+// more work lost when an item fails, since a failure rolls back its whole batch
+fn batch_size_from_env() -> usize {
+    match std::env::var("DB_BATCH_SIZE") {
+        Ok(value) => match value.trim().parse::<usize>() {
+            Ok(size) if size > 0 => size,
+            _ => {
+                log::warn!("Ignoring invalid DB_BATCH_SIZE '{}', using {}", value, DEFAULT_BATCH_SIZE);
+                DEFAULT_BATCH_SIZE
+            }
+        },
+        Err(_) => DEFAULT_BATCH_SIZE,
+    }
+}
+
+struct PendingMigration {
+    version: i64,
+    description: String,
+    sql: String,
+    record: String,
+}
+
+fn migrations_table_sql(table: &str) -> String {
+    format!(
+        "CREATE TABLE IF NOT EXISTS {}_migrations (
+            version BIGINT PRIMARY KEY,
+            description TEXT NOT NULL,
+            applied_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+            checksum TEXT
+        )",
+        table
+    )
+}
+
+// Hex of the file checksum `sqlx::migrate!` computes, taken before the table
+// name is filled in so it's the same for every table
+fn migration_checksum(migration: &sqlx::migrate::Migration) -> String {
+    migration.checksum.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn pending_migrations(migrator: &Migrator, table: &str, applied: &[i64]) -> Vec<PendingMigration> {
+    migrator
+        .iter()
+        .filter(|migration| !applied.contains(&migration.version))
+        .map(|migration| PendingMigration {
+            version: migration.version,
+            description: migration.description.to_string(),
+            sql: migration.sql.replace(TABLE_PLACEHOLDER, table),
+            record: format!(
+                "INSERT INTO {}_migrations (version, description, checksum) VALUES ({}, '{}', '{}')",
+                table,
+                migration.version,
+                migration.description.replace('\'', "''"),
+                migration_checksum(migration)
+            ),
+        })
+        .collect()
+}
+
+/// Apply the migrations `table` hasn't seen yet, returning how many ran.
+///
+/// Applied versions live in `<table>_migrations` rather than sqlx's single
+/// `_sqlx_migrations`, so several data tables can share a database. Each
+/// migration commits together with its bookkeeping row, so a failure leaves it
+/// pending for the next `init`. Like sqlx's migrator, this refuses to run when
+/// an applied migration's file has since been edited.
+async fn run_migrations<DB>(pool: &Pool<DB>, migrator: &Migrator, table: &str) -> Result<usize>
+where
+    DB: sqlx::Database,
+    for<'c> &'c Pool<DB>: Executor<'c, Database = DB>,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: sqlx::IntoArguments<'q, DB>,
+    i64: for<'r> sqlx::Decode<'r, DB> + sqlx::Type<DB>,
+    String: for<'r> sqlx::Decode<'r, DB> + sqlx::Type<DB>,
+    usize: sqlx::ColumnIndex<DB::Row>,
+{
+    pool.execute(migrations_table_sql(table).as_str())
+        .await
+        .context("Failed to create migrations table")?;
+
+    // Histories written before checksums were recorded lack the column
+    let mut connection = pool.acquire().await.context("Failed to acquire connection for migrations")?;
+    let checksum_query = format!("SELECT checksum FROM {}_migrations WHERE 1 = 0", table);
+    if connection.execute(checksum_query.as_str()).await.is_err() {
+        connection
+            .execute(format!("ALTER TABLE {}_migrations ADD COLUMN checksum TEXT", table).as_str())
+            .await
+            .context("Failed to add checksum column to migrations table")?;
+    }
+    drop(connection);
+
+    let applied: Vec<(i64, Option<String>)> =
+        sqlx::query_as(&format!("SELECT version, checksum FROM {}_migrations", table))
+            .fetch_all(pool)
+            .await
+            .context("Failed to read applied migrations")?;
+
+    for (version, recorded) in &applied {
+        let Some(migration) = migrator.iter().find(|migration| migration.version == *version) else {
+            continue;
+        };
+        let checksum = migration_checksum(migration);
+        match recorded {
+            Some(recorded) if *recorded != checksum => anyhow::bail!(
+                "Migration {} ({}) was changed after it was applied to '{}'",
+                version,
+                migration.description,
+                table
+            ),
+            Some(_) => {}
+            // Trust what's on disk for versions applied before checksums were kept
+            None => {
+                let backfill = format!(
+                    "UPDATE {}_migrations SET checksum = '{}' WHERE version = {}",
+                    table, checksum, version
+                );
+                pool.execute(backfill.as_str()).await.context("Failed to record migration checksum")?;
+            }
+        }
+    }
+
+    let applied: Vec<i64> = applied.into_iter().map(|(version, _)| version).collect();
+    let pending = pending_migrations(migrator, table, &applied);
+    for migration in &pending {
+        let mut transaction = pool.begin().await.context("Failed to begin migration transaction")?;
+        // Executed without bind parameters so files may hold several statements
+        transaction
+            .execute(migration.sql.as_str())
+            .await
+            .with_context(|| format!("Migration {} ({}) failed", migration.version, migration.description))?;
+        transaction.execute(migration.record.as_str()).await?;
+        transaction.commit().await.context("Failed to commit migration")?;
+
+        log::info!("Applied migration {} ({}) to '{}'", migration.version, migration.description, table);
+    }
+
+    Ok(pending.len())
+}
+
+// NUMERIC doesn't decode into f64, so price is cast on the way out
+pub(crate) const PG_SELECT_COLUMNS: &str =
+    "id, source, url, source_url, title, content, price::FLOAT8 AS price, image_url, author, timestamp, category, metadata";
+
+/// How `save_with_strategy` treats items whose id is already stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConflictStrategy {
+    /// Insert new rows and overwrite title/content/price/metadata/source_url of existing ones
+    #[default]
+    Upsert,
+    /// Insert new rows, leave existing ones untouched
+    InsertIgnore,
+    /// Only refresh existing rows, never insert
+    UpdateOnly,
+}
+
+/// A value bound to a placeholder of
+/// [`query_with_args`](DatabaseOutput::query_with_args)
+#[derive(Debug, Clone, PartialEq)]
+pub enum SqlArg {
+    Text(String),
+    Integer(i64),
+    Real(f64),
+    Null,
+}
+
+impl From<&str> for SqlArg {
+    fn from(value: &str) -> Self {
+        SqlArg::Text(value.to_string())
+    }
+}
+
+impl From<String> for SqlArg {
+    fn from(value: String) -> Self {
+        SqlArg::Text(value)
+    }
+}
+
+impl From<i64> for SqlArg {
+    fn from(value: i64) -> Self {
+        SqlArg::Integer(value)
+    }
+}
+
+impl From<f64> for SqlArg {
+    fn from(value: f64) -> Self {
+        SqlArg::Real(value)
+    }
+}
+
+impl<T: Into<SqlArg>> From<Option<T>> for SqlArg {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(SqlArg::Null, Into::into)
+    }
+}
 
 #[async_trait]
 pub trait DatabaseOutput {
+    /// Create the table or bring it up to date by running pending migrations
     async fn init(&self) -> Result<()>;
-    async fn save(&self, data: &[ScrapedData]) -> Result<usize>;
-    async fn query(&self, query: &str) -> Result<Vec<ScrapedData>>;
+
+    async fn save(&self, data: &[ScrapedData]) -> Result<usize> {
+        self.save_with_strategy(data, ConflictStrategy::Upsert).await
+    }
+
+    /// Save items, resolving id conflicts according to `strategy`; returns the number of rows written
+    async fn save_with_strategy(&self, data: &[ScrapedData], strategy: ConflictStrategy) -> Result<usize>;
+
+    /// Run raw SQL and map the rows back into items. This is an escape hatch:
+    /// the SQL runs exactly as given, so never build it from untrusted input
+    /// (use [`query_with_args`](Self::query_with_args) instead).
+    ///
+    /// Rows need the table's columns (`SELECT *` works on SQLite; on
+    /// PostgreSQL select `price::FLOAT8 AS price`, as NUMERIC doesn't decode).
+    async fn query(&self, query: &str) -> Result<Vec<ScrapedData>> {
+        self.query_with_args(query, &[]).await
+    }
+
+    /// [`query`](Self::query) with `args` bound to its placeholders (`$1`,
+    /// `$2`, .. on PostgreSQL, `?` on SQLite), so values are never spliced
+    /// into the SQL
+    async fn query_with_args(&self, query: &str, args: &[SqlArg]) -> Result<Vec<ScrapedData>>;
     async fn clear(&self) -> Result<()>;
 }
 
 pub struct PostgresOutput {
     pool: Pool<Postgres>,
     table_name: String,
+    batch_size: usize,
+    // Owned by the struct so `get_all_stream` can borrow it for the stream's lifetime
+    stream_query: String,
 }
 
 impl PostgresOutput {
@@ -26,45 +261,72 @@ impl PostgresOutput {
             .await
             .context("Failed to connect to PostgreSQL database")?;
 
+        let table_name = table_name.unwrap_or("scraped_data").to_string();
+        let stream_query = format!(
+            "SELECT {} FROM {} ORDER BY timestamp DESC",
+            PG_SELECT_COLUMNS, table_name
+        );
+
         Ok(Self {
             pool,
-            table_name: table_name.unwrap_or("scraped_data").to_string(),
+            table_name,
+            batch_size: batch_size_from_env(),
+            stream_query,
         })
     }
 
+    /// Override the number of rows written per transaction (`DB_BATCH_SIZE`)
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
     pub fn get_pool(&self) -> &Pool<Postgres> {
         &self.pool
     }
 
-    async fn create_table(&self) -> Result<()> {
-        let query = format!(
+    pub fn table_name(&self) -> &str {
+        &self.table_name
+    }
+
+    fn save_query(&self, strategy: ConflictStrategy) -> String {
+        let on_conflict = match strategy {
+            ConflictStrategy::Upsert => {
+                r#"ON CONFLICT (id) DO UPDATE SET
+                    title = EXCLUDED.title,
+                    content = EXCLUDED.content,
+                    price = EXCLUDED.price,
+                    metadata = EXCLUDED.metadata,
+                    source_url = EXCLUDED.source_url,
+                    updated_at = NOW()"#
+            }
+            ConflictStrategy::InsertIgnore => "ON CONFLICT (id) DO NOTHING",
+            ConflictStrategy::UpdateOnly => {
+                return format!(
+                    r#"
+                    UPDATE {} SET title = $2, content = $3, price = $4, metadata = $5, updated_at = NOW()
+                    WHERE id = $1
+                    "#,
+                    self.table_name
+                );
+            }
+        };
+
+        format!(
             r#"
-            CREATE TABLE IF NOT EXISTS {} (
-                id VARCHAR(255) PRIMARY KEY,
-                source VARCHAR(255) NOT NULL,
-                url TEXT NOT NULL,
-                title TEXT,
-                content TEXT,
-                price DECIMAL(10,2),
-                image_url TEXT,
-                author VARCHAR(255),
-                timestamp TIMESTAMPTZ NOT NULL,
-                category VARCHAR(255),
-                metadata JSONB,
-                created_at TIMESTAMPTZ DEFAULT NOW(),
-                updated_at TIMESTAMPTZ DEFAULT NOW()
-            )
+            INSERT INTO {} (id, source, url, title, content, price, image_url, author, timestamp, category, metadata, source_url)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            {}
             "#,
-            self.table_name
-        );
-
-        sqlx::query(&query)
-            .execute(&self.pool)
-            .await
-            .context("Failed to create database table")?;
-        Ok(())
+            self.table_name, on_conflict
+        )
     }
 
+
     /// Get all scraped data with optional limit and offset
     pub async fn get_all(&self, limit: Option<i64>, offset: Option<i64>) -> Result<Vec<ScrapedData>> {
         let limit = limit.unwrap_or(100);
@@ -72,13 +334,12 @@ impl PostgresOutput {
 
         let query = format!(
             r#"
-            SELECT id, source, url, title, content, price, image_url, author,
-                   timestamp, category, metadata
+            SELECT {}
             FROM {}
             ORDER BY timestamp DESC
             LIMIT $1 OFFSET $2
             "#,
-            self.table_name
+            PG_SELECT_COLUMNS, self.table_name
         );
 
         let rows = sqlx::query(&query)
@@ -88,105 +349,139 @@ impl PostgresOutput {
             .await
             .context("Failed to fetch data from database")?;
 
-        let mut results = Vec::new();
-        for row in rows {
-            let metadata_json: serde_json::Value = row.try_get("metadata").unwrap_or(serde_json::json!({}));
-            let metadata: HashMap<String, String> = serde_json::from_value(metadata_json).unwrap_or_default();
-
-            // PostgreSQL returns DateTime<Utc> directly for TIMESTAMPTZ
-            let timestamp: DateTime<Utc> = row.try_get("timestamp")?;
-
-            results.push(ScrapedData {
-                id: row.try_get("id")?,
-                source: row.try_get("source")?,
-                url: row.try_get("url")?,
-                title: row.try_get("title")?,
-                content: row.try_get("content")?,
-                price: row.try_get("price")?,
-                image_url: row.try_get("image_url")?,
-                author: row.try_get("author")?,
-                timestamp,
-                category: row.try_get("category")?,
-                metadata,
-            });
+        rows.iter().map(Self::row_to_data).collect()
+    }
+
+    /// The row with this id, if there is one
+    pub async fn get_by_id(&self, id: &str) -> Result<Option<ScrapedData>> {
+        let query = format!("SELECT {} FROM {} WHERE id = $1", PG_SELECT_COLUMNS, self.table_name);
+
+        let row = sqlx::query(&query)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .context("Failed to fetch item from database")?;
+
+        row.as_ref().map(Self::row_to_data).transpose()
+    }
+
+    /// Which of these ids have a row
+    pub async fn existing_ids(&self, ids: &[String]) -> Result<HashSet<String>> {
+        let query = format!("SELECT id FROM {} WHERE id = ANY($1)", self.table_name);
+
+        let found: Vec<String> = sqlx::query_scalar(&query)
+            .bind(ids)
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to look up item ids in database")?;
+
+        Ok(found.into_iter().collect())
+    }
+
+    /// Write only the fields set in `patch` to the row with this id, returning
+    /// the updated row, or `None` when no row has the id
+    pub async fn update_fields(&self, id: &str, patch: &ItemPatch) -> Result<Option<ScrapedData>> {
+        let text_fields = [
+            ("url", &patch.url),
+            ("title", &patch.title),
+            ("content", &patch.content),
+            ("image_url", &patch.image_url),
+            ("author", &patch.author),
+            ("category", &patch.category),
+        ];
+
+        // $1 is the id; each changed column gets the next placeholder
+        let mut assignments = Vec::new();
+        for (column, _) in text_fields.iter().filter(|(_, value)| value.is_some()) {
+            assignments.push(format!("{} = ${}", column, assignments.len() + 2));
         }
+        if patch.price.is_some() {
+            assignments.push(format!("price = ${}", assignments.len() + 2));
+        }
+        assignments.push("updated_at = NOW()".to_string());
+
+        let query = format!(
+            "UPDATE {} SET {} WHERE id = $1 RETURNING {}",
+            self.table_name,
+            assignments.join(", "),
+            PG_SELECT_COLUMNS
+        );
 
-        Ok(results)
+        let mut update = sqlx::query(&query).bind(id);
+        for value in text_fields.into_iter().filter_map(|(_, value)| value.as_ref()) {
+            update = update.bind(value);
+        }
+        if let Some(price) = patch.price {
+            update = update.bind(price);
+        }
+
+        let row = update
+            .fetch_optional(&self.pool)
+            .await
+            .context("Failed to update item in database")?;
+
+        row.as_ref().map(Self::row_to_data).transpose()
+    }
+
+    /// Stream every row, newest first, without buffering the result set
+    pub fn get_all_stream(&self) -> impl Stream<Item = Result<ScrapedData>> + Send + '_ {
+        sqlx::query(&self.stream_query)
+            .fetch(&self.pool)
+            .map(|row| {
+                let row = row.context("Failed to stream data from database")?;
+                Self::row_to_data(&row)
+            })
+    }
+
+    fn row_to_data(row: &PgRow) -> Result<ScrapedData> {
+        let metadata_json: serde_json::Value = row.try_get("metadata").unwrap_or(serde_json::json!({}));
+        let metadata: HashMap<String, String> = serde_json::from_value(metadata_json).unwrap_or_default();
+
+        // PostgreSQL returns DateTime<Utc> directly for TIMESTAMPTZ
+        let timestamp: DateTime<Utc> = row.try_get("timestamp")?;
+
+        Ok(ScrapedData {
+            id: row.try_get("id")?,
+            source: row.try_get("source")?,
+            url: row.try_get("url")?,
+            source_url: row.try_get("source_url")?,
+            title: row.try_get("title")?,
+            content: row.try_get("content")?,
+            price: row.try_get("price")?,
+            image_url: row.try_get("image_url")?,
+            author: row.try_get("author")?,
+            timestamp,
+            category: row.try_get("category")?,
+            metadata,
+        })
     }
 
     /// Search scraped data by query string
     pub async fn search(&self, query_str: &str, source_filter: Option<&str>, limit: Option<i64>) -> Result<Vec<ScrapedData>> {
         let limit = limit.unwrap_or(50);
 
-        let query = if let Some(_source) = source_filter {
-            format!(
-                r#"
-                SELECT id, source, url, title, content, price, image_url, author,
-                       timestamp, category, metadata
-                FROM {}
-                WHERE (title ILIKE $1 OR content ILIKE $1) AND source = $2
-                ORDER BY timestamp DESC
-                LIMIT $3
-                "#,
-                self.table_name
-            )
-        } else {
-            format!(
-                r#"
-                SELECT id, source, url, title, content, price, image_url, author,
-                       timestamp, category, metadata
-                FROM {}
-                WHERE title ILIKE $1 OR content ILIKE $1
-                ORDER BY timestamp DESC
-                LIMIT $2
-                "#,
-                self.table_name
-            )
-        };
+        let query = format!(
+            r#"
+            SELECT {}
+            FROM {}
+            WHERE (title ILIKE $1 OR content ILIKE $1) AND ($2::TEXT IS NULL OR source = $2)
+            ORDER BY timestamp DESC
+            LIMIT $3
+            "#,
+            PG_SELECT_COLUMNS, self.table_name
+        );
 
         let search_pattern = format!("%{}%", query_str);
 
-        let rows = if let Some(source) = source_filter {
-            sqlx::query(&query)
-                .bind(&search_pattern)
-                .bind(source)
-                .bind(limit)
-                .fetch_all(&self.pool)
-                .await
-                .context("Failed to search database with source filter")?
-        } else {
-            sqlx::query(&query)
-                .bind(&search_pattern)
-                .bind(limit)
-                .fetch_all(&self.pool)
-                .await
-                .context("Failed to search database")?
-        };
-
-        let mut results = Vec::new();
-        for row in rows {
-            let metadata_json: serde_json::Value = row.try_get("metadata").unwrap_or(serde_json::json!({}));
-            let metadata: HashMap<String, String> = serde_json::from_value(metadata_json).unwrap_or_default();
-
-            // PostgreSQL returns DateTime<Utc> directly for TIMESTAMPTZ
-            let timestamp: DateTime<Utc> = row.get("timestamp");
-
-            results.push(ScrapedData {
-                id: row.get("id"),
-                source: row.get("source"),
-                url: row.get("url"),
-                title: row.get("title"),
-                content: row.get("content"),
-                price: row.get("price"),
-                image_url: row.get("image_url"),
-                author: row.get("author"),
-                timestamp,
-                category: row.get("category"),
-                metadata,
-            });
-        }
+        let rows = sqlx::query(&query)
+            .bind(&search_pattern)
+            .bind(source_filter)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to search database")?;
 
-        Ok(results)
+        rows.iter().map(Self::row_to_data).collect()
     }
 
     /// Get count of all records
@@ -218,63 +513,291 @@ impl PostgresOutput {
 
         Ok(sources)
     }
+
+    /// Item count and newest timestamp per source
+    pub async fn get_source_summaries(&self) -> Result<Vec<SourceSummary>> {
+        let query = format!(
+            r#"
+            SELECT source, COUNT(*) AS count, MAX(timestamp) AS last_scraped
+            FROM {}
+            GROUP BY source
+            ORDER BY source
+            "#,
+            self.table_name
+        );
+        let rows = sqlx::query(&query)
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to summarize sources in database")?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(SourceSummary {
+                    source: row.try_get("source")?,
+                    count: row.try_get::<i64, _>("count")? as usize,
+                    last_scraped: row.try_get("last_scraped")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Distinct non-null categories with their item counts
+    pub async fn get_categories(&self) -> Result<Vec<CategoryCount>> {
+        let query = format!(
+            r#"
+            SELECT category, COUNT(*) AS count
+            FROM {}
+            WHERE category IS NOT NULL
+            GROUP BY category
+            ORDER BY category
+            "#,
+            self.table_name
+        );
+        let rows = sqlx::query(&query)
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to get categories from database")?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(CategoryCount {
+                    category: row.try_get("category")?,
+                    count: row.try_get::<i64, _>("count")? as usize,
+                })
+            })
+            .collect()
+    }
+
+    /// Item counts over the whole table, counted by the database
+    pub async fn get_stats(&self) -> Result<TableStats> {
+        let query = format!(
+            r#"
+            SELECT COUNT(*) AS total_items,
+                   COUNT(DISTINCT source) AS unique_sources,
+                   COUNT(content) AS items_with_content,
+                   COUNT(price) AS items_with_price,
+                   MAX(timestamp) AS newest
+            FROM {}
+            "#,
+            self.table_name
+        );
+        let row = sqlx::query(&query)
+            .fetch_one(&self.pool)
+            .await
+            .context("Failed to count items in database")?;
+
+        Ok(TableStats {
+            total_items: row.try_get::<i64, _>("total_items")? as usize,
+            unique_sources: row.try_get::<i64, _>("unique_sources")? as usize,
+            items_with_content: row.try_get::<i64, _>("items_with_content")? as usize,
+            items_with_price: row.try_get::<i64, _>("items_with_price")? as usize,
+            newest: row.try_get("newest")?,
+        })
+    }
+
+    /// [`ExtractionRate::summarize`] over every row, tallied by the database
+    /// from the outcomes recorded in each item's metadata
+    pub async fn get_extraction_rates(&self) -> Result<Vec<ExtractionRate>> {
+        let query = format!(
+            r#"
+            SELECT source, outcome.key AS field,
+                   COUNT(*) FILTER (WHERE outcome.value = 'found') AS found,
+                   COUNT(*) AS total
+            FROM {}, jsonb_each_text((metadata->>$1)::jsonb) AS outcome
+            WHERE metadata->>$1 IS NOT NULL
+            GROUP BY source, outcome.key
+            ORDER BY source, outcome.key
+            "#,
+            self.table_name
+        );
+        let rows = sqlx::query(&query)
+            .bind(EXTRACTION_KEY)
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to tally extraction outcomes in database")?;
+
+        rows.iter()
+            .map(|row| {
+                let found = row.try_get::<i64, _>("found")? as usize;
+                let total = row.try_get::<i64, _>("total")? as usize;
+                Ok(ExtractionRate {
+                    source: row.try_get("source")?,
+                    field: row.try_get("field")?,
+                    found,
+                    total,
+                    rate: found as f64 / total as f64,
+                })
+            })
+            .collect()
+    }
+
+    /// Recompute every row's id from `source + url` (see [`ScrapedData::stable_id`]).
+    ///
+    /// Rows that map to the same stable id are collapsed, keeping the one with
+    /// the newest `timestamp`. The losers are deleted before the remaining ids
+    /// are rewritten through an old-to-new mapping, so the primary key never
+    /// holds two rows with the same id. Everything runs in one transaction.
+    pub async fn regenerate_ids(&self) -> Result<RegeneratedIds> {
+        let mut transaction = self.pool.begin()
+            .await
+            .context("Failed to begin PostgreSQL transaction")?;
+
+        let query = format!(
+            "SELECT id, source, url FROM {} ORDER BY timestamp DESC, updated_at DESC NULLS LAST, id",
+            self.table_name
+        );
+        let rows = sqlx::query(&query)
+            .fetch_all(&mut *transaction)
+            .await
+            .context("Failed to read ids from database")?;
+
+        let mut kept = HashSet::new();
+        let mut duplicates = Vec::new();
+        let (mut old_ids, mut new_ids) = (Vec::new(), Vec::new());
+        for row in &rows {
+            let id: String = row.try_get("id")?;
+            let source: String = row.try_get("source")?;
+            let url: String = row.try_get("url")?;
+            let stable_id = ScrapedData::stable_id(&source, &url);
+
+            if !kept.insert(stable_id.clone()) {
+                duplicates.push(id);
+            } else if id != stable_id {
+                old_ids.push(id);
+                new_ids.push(stable_id);
+            }
+        }
+
+        let query = format!("DELETE FROM {} WHERE id = ANY($1)", self.table_name);
+        let collapsed = sqlx::query(&query)
+            .bind(&duplicates)
+            .execute(&mut *transaction)
+            .await
+            .context("Failed to remove duplicate rows")?
+            .rows_affected() as usize;
+
+        let query = format!(
+            r#"
+            UPDATE {} AS t SET id = m.new_id, updated_at = NOW()
+            FROM UNNEST($1::TEXT[], $2::TEXT[]) AS m(old_id, new_id)
+            WHERE t.id = m.old_id
+            "#,
+            self.table_name
+        );
+        let updated = sqlx::query(&query)
+            .bind(&old_ids)
+            .bind(&new_ids)
+            .execute(&mut *transaction)
+            .await
+            .context("Failed to rewrite ids")?
+            .rows_affected() as usize;
+
+        transaction.commit()
+            .await
+            .context("Failed to commit PostgreSQL transaction")?;
+        log::info!(
+            "Regenerated ids in '{}': {} updated, {} duplicates collapsed",
+            self.table_name, updated, collapsed
+        );
+        Ok(RegeneratedIds { updated, collapsed })
+    }
+}
+
+/// Counts over a whole table, see [`PostgresOutput::get_stats`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableStats {
+    pub total_items: usize,
+    pub unique_sources: usize,
+    pub items_with_content: usize,
+    pub items_with_price: usize,
+    /// Newest item timestamp; `None` for an empty table
+    pub newest: Option<DateTime<Utc>>,
+}
+
+/// Outcome of [`PostgresOutput::regenerate_ids`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RegeneratedIds {
+    /// Rows whose id changed
+    pub updated: usize,
+    /// Duplicate rows removed because they mapped to an id already kept
+    pub collapsed: usize,
 }
 
 #[async_trait]
 impl DatabaseOutput for PostgresOutput {
     async fn init(&self) -> Result<()> {
-        self.create_table().await?;
-        log::info!("PostgreSQL table '{}' initialized", self.table_name);
+        let applied = run_migrations(&self.pool, &POSTGRES_MIGRATIONS, &self.table_name).await?;
+        log::info!("PostgreSQL table '{}' initialized ({} migrations applied)", self.table_name, applied);
         Ok(())
     }
 
-    async fn save(&self, data: &[ScrapedData]) -> Result<usize> {
+    async fn save_with_strategy(&self, data: &[ScrapedData], strategy: ConflictStrategy) -> Result<usize> {
         let mut count = 0;
-
-        for item in data {
-            let query = format!(
-                r#"
-                INSERT INTO {} (id, source, url, title, content, price, image_url, author, timestamp, category, metadata)
-                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
-                ON CONFLICT (id) DO UPDATE SET
-                    title = EXCLUDED.title,
-                    content = EXCLUDED.content,
-                    price = EXCLUDED.price,
-                    metadata = EXCLUDED.metadata,
-                    updated_at = NOW()
-                "#,
-                self.table_name
-            );
-
-            let metadata_json = serde_json::to_value(&item.metadata)?;
-
-            let result = sqlx::query(&query)
-                .bind(&item.id)
-                .bind(&item.source)
-                .bind(&item.url)
-                .bind(&item.title.as_deref())
-                .bind(&item.content.as_deref())
-                .bind(item.price)
-                .bind(&item.image_url.as_deref())
-                .bind(&item.author.as_deref())
-                .bind(&item.timestamp)
-                .bind(&item.category.as_deref())
-                .bind(&metadata_json)
-                .execute(&self.pool)
+        let query = self.save_query(strategy);
+        // One transaction per chunk: a failing item rolls back its chunk, while
+        // chunks committed before it stay written
+        for chunk in data.chunks(self.batch_size) {
+            let mut transaction = self.pool.begin()
                 .await
-                .context(format!("Failed to save item with id: {}", item.id))?;
-
-            count += result.rows_affected() as usize;
+                .context("Failed to begin PostgreSQL transaction")?;
+
+            for item in chunk {
+                let metadata_json = serde_json::to_value(&item.metadata)?;
+
+                let statement = match strategy {
+                    ConflictStrategy::UpdateOnly => sqlx::query(&query)
+                        .bind(&item.id)
+                        .bind(item.title.as_deref())
+                        .bind(item.content.as_deref())
+                        .bind(item.price)
+                        .bind(&metadata_json),
+                    _ => sqlx::query(&query)
+                        .bind(&item.id)
+                        .bind(&item.source)
+                        .bind(&item.url)
+                        .bind(item.title.as_deref())
+                        .bind(item.content.as_deref())
+                        .bind(item.price)
+                        .bind(item.image_url.as_deref())
+                        .bind(item.author.as_deref())
+                        .bind(item.timestamp)
+                        .bind(item.category.as_deref())
+                        .bind(&metadata_json)
+                        .bind(item.source_url.as_deref()),
+                };
+
+                let result = statement
+                    .execute(&mut *transaction)
+                    .await
+                    .context(format!("Failed to save item with id: {}", item.id))?;
+
+                count += result.rows_affected() as usize;
+            }
+
+            transaction.commit()
+                .await
+                .context("Failed to commit PostgreSQL transaction")?;
         }
-
-        log::info!("Saved {} items to PostgreSQL", count);
+        log::info!("Saved {} items to PostgreSQL ({:?})", count, strategy);
         Ok(count)
     }
 
-    async fn query(&self, _query: &str) -> Result<Vec<ScrapedData>> {
-        // TODO: Implement proper query with FromRow derive for ScrapedData
-        log::warn!("Query method not yet implemented for PostgreSQL");
-        Ok(Vec::new())
+    async fn query_with_args(&self, query: &str, args: &[SqlArg]) -> Result<Vec<ScrapedData>> {
+        let mut statement = sqlx::query(query);
+        for arg in args {
+            statement = match arg {
+                SqlArg::Text(value) => statement.bind(value.as_str()),
+                SqlArg::Integer(value) => statement.bind(*value),
+                SqlArg::Real(value) => statement.bind(*value),
+                SqlArg::Null => statement.bind(None::<String>),
+            };
+        }
+
+        let rows = statement
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to run query against PostgreSQL")?;
+        rows.iter().map(Self::row_to_data).collect()
     }
 
     async fn clear(&self) -> Result<()> {
@@ -291,6 +814,7 @@ impl DatabaseOutput for PostgresOutput {
 pub struct SqliteOutput {
     pool: SqlitePool,
     table_name: String,
+    batch_size: usize,
 }
 
 impl SqliteOutput {
@@ -302,99 +826,161 @@ impl SqliteOutput {
         Ok(Self {
             pool,
             table_name: table_name.unwrap_or("scraped_data").to_string(),
+            batch_size: batch_size_from_env(),
         })
     }
 
-    async fn create_table(&self) -> Result<()> {
-        let query = format!(
+    /// Override the number of rows written per transaction (`DB_BATCH_SIZE`)
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    pub fn get_pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    pub fn table_name(&self) -> &str {
+        &self.table_name
+    }
+
+    fn row_to_data(row: &SqliteRow) -> Result<ScrapedData> {
+        // Metadata is stored as JSON text
+        let metadata_json: Option<String> = row.try_get("metadata").unwrap_or_default();
+        let metadata: HashMap<String, String> = metadata_json
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+
+        Ok(ScrapedData {
+            id: row.try_get("id")?,
+            source: row.try_get("source")?,
+            url: row.try_get("url")?,
+            source_url: row.try_get("source_url")?,
+            title: row.try_get("title")?,
+            content: row.try_get("content")?,
+            price: row.try_get("price")?,
+            image_url: row.try_get("image_url")?,
+            author: row.try_get("author")?,
+            // Written as RFC 3339 text, which decodes straight into DateTime<Utc>
+            timestamp: row.try_get("timestamp")?,
+            category: row.try_get("category")?,
+            metadata,
+        })
+    }
+
+    fn save_query(&self, strategy: ConflictStrategy) -> String {
+        let on_conflict = match strategy {
+            ConflictStrategy::Upsert => {
+                r#"ON CONFLICT(id) DO UPDATE SET
+                    title = excluded.title,
+                    content = excluded.content,
+                    price = excluded.price,
+                    metadata = excluded.metadata,
+                    source_url = excluded.source_url,
+                    updated_at = CURRENT_TIMESTAMP"#
+            }
+            ConflictStrategy::InsertIgnore => "ON CONFLICT(id) DO NOTHING",
+            ConflictStrategy::UpdateOnly => {
+                return format!(
+                    r#"
+                    UPDATE {} SET title = ?, content = ?, price = ?, metadata = ?, updated_at = CURRENT_TIMESTAMP
+                    WHERE id = ?
+                    "#,
+                    self.table_name
+                );
+            }
+        };
+
+        format!(
             r#"
-            CREATE TABLE IF NOT EXISTS {} (
-                id TEXT PRIMARY KEY,
-                source TEXT NOT NULL,
-                url TEXT NOT NULL,
-                title TEXT,
-                content TEXT,
-                price REAL,
-                image_url TEXT,
-                author TEXT,
-                timestamp DATETIME NOT NULL,
-                category TEXT,
-                metadata TEXT,
-                created_at DATETIME DEFAULT CURRENT_TIMESTAMP
-            )
+            INSERT INTO {} (id, source, url, title, content, price, image_url, author, timestamp, category, metadata, source_url)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            {}
             "#,
-            self.table_name
-        );
-
-        sqlx::query(&query)
-            .execute(&self.pool)
-            .await
-            .context("Failed to create SQLite table")?;
-        Ok(())
+            self.table_name, on_conflict
+        )
     }
+
 }
 
 #[async_trait]
 impl DatabaseOutput for SqliteOutput {
     async fn init(&self) -> Result<()> {
-        self.create_table().await?;
-        log::info!("SQLite table '{}' initialized", self.table_name);
+        let applied = run_migrations(&self.pool, &SQLITE_MIGRATIONS, &self.table_name).await?;
+        log::info!("SQLite table '{}' initialized ({} migrations applied)", self.table_name, applied);
         Ok(())
     }
 
-    async fn save(&self, data: &[ScrapedData]) -> Result<usize> {
+    async fn save_with_strategy(&self, data: &[ScrapedData], strategy: ConflictStrategy) -> Result<usize> {
         let mut count = 0;
-        let mut transaction = self.pool.begin()
-            .await
-            .context("Failed to begin SQLite transaction")?;
-
-        for item in data {
-            let query = format!(
-                r#"
-                INSERT INTO {} (id, source, url, title, content, price, image_url, author, timestamp, category, metadata)
-                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-                ON CONFLICT(id) DO UPDATE SET
-                    title = excluded.title,
-                    content = excluded.content,
-                    price = excluded.price,
-                    metadata = excluded.metadata,
-                    updated_at = CURRENT_TIMESTAMP
-                "#,
-                self.table_name
-            );
-
-            let metadata_json = serde_json::to_string(&item.metadata)?;
-
-            let result = sqlx::query(&query)
-                .bind(&item.id)
-                .bind(&item.source)
-                .bind(&item.url)
-                .bind(&item.title.as_deref())
-                .bind(&item.content.as_deref())
-                .bind(item.price)
-                .bind(&item.image_url.as_deref())
-                .bind(&item.author.as_deref())
-                .bind(item.timestamp.to_rfc3339())
-                .bind(&item.category.as_deref())
-                .bind(&metadata_json)
-                .execute(&mut *transaction)
+        let query = self.save_query(strategy);
+        // Chunked into transactions the same way as the PostgreSQL save
+        for chunk in data.chunks(self.batch_size) {
+            let mut transaction = self.pool.begin()
                 .await
-                .context(format!("Failed to save item to SQLite: {}", item.id))?;
-
-            count += result.rows_affected() as usize;
+                .context("Failed to begin SQLite transaction")?;
+
+            for item in chunk {
+                let metadata_json = serde_json::to_string(&item.metadata)?;
+
+                let statement = match strategy {
+                    ConflictStrategy::UpdateOnly => sqlx::query(&query)
+                        .bind(item.title.as_deref())
+                        .bind(item.content.as_deref())
+                        .bind(item.price)
+                        .bind(&metadata_json)
+                        .bind(&item.id),
+                    _ => sqlx::query(&query)
+                        .bind(&item.id)
+                        .bind(&item.source)
+                        .bind(&item.url)
+                        .bind(item.title.as_deref())
+                        .bind(item.content.as_deref())
+                        .bind(item.price)
+                        .bind(item.image_url.as_deref())
+                        .bind(item.author.as_deref())
+                        .bind(item.timestamp.to_rfc3339())
+                        .bind(item.category.as_deref())
+                        .bind(&metadata_json)
+                        .bind(item.source_url.as_deref()),
+                };
+
+                let result = statement
+                    .execute(&mut *transaction)
+                    .await
+                    .context(format!("Failed to save item to SQLite: {}", item.id))?;
+
+                count += result.rows_affected() as usize;
+            }
+
+            transaction.commit()
+                .await
+                .context("Failed to commit SQLite transaction")?;
         }
-
-        transaction.commit()
-            .await
-            .context("Failed to commit SQLite transaction")?;
-        log::info!("Saved {} items to SQLite", count);
+        log::info!("Saved {} items to SQLite ({:?})", count, strategy);
         Ok(count)
     }
 
-    async fn query(&self, _query: &str) -> Result<Vec<ScrapedData>> {
-        // TODO: Implement proper query with FromRow derive for ScrapedData
-        log::warn!("Query method not yet implemented for SQLite");
-        Ok(Vec::new())
+    async fn query_with_args(&self, query: &str, args: &[SqlArg]) -> Result<Vec<ScrapedData>> {
+        let mut statement = sqlx::query(query);
+        for arg in args {
+            statement = match arg {
+                SqlArg::Text(value) => statement.bind(value.as_str()),
+                SqlArg::Integer(value) => statement.bind(*value),
+                SqlArg::Real(value) => statement.bind(*value),
+                SqlArg::Null => statement.bind(None::<String>),
+            };
+        }
+
+        let rows = statement
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to run query against SQLite")?;
+        rows.iter().map(Self::row_to_data).collect()
     }
 
     async fn clear(&self) -> Result<()> {
@@ -406,4 +992,4 @@ impl DatabaseOutput for SqliteOutput {
         log::info!("Cleared SQLite table '{}'", self.table_name);
         Ok(())
     }
-}
\ No newline at end of file
+}
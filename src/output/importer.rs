@@ -0,0 +1,186 @@
+//! Loading items back from JSON and JSON Lines files
+//!
+//! Reads what `JsonOutput` exports (enveloped or bare arrays), one item per
+//! line (JSON Lines), and the AI normalizer's output, so data can be moved
+//! between instances or replayed into a database.
+
+use crate::{
+    ai::normalizer::NormalizedData,
+    core::models::ScrapedData,
+    output::database::DatabaseOutput,
+    processors::validator::Validator,
+    utils::error::ScraperError,
+};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Written by the AI pipeline after every run, alongside a timestamped copy
+pub const FINAL_NORMALIZED_FILE: &str = "final.json";
+
+/// How the pipeline stamps its output files, e.g. `final_20240131_154500.json`
+const FILE_TIMESTAMP_FORMAT: &str = "%Y%m%d_%H%M%S";
+
+/// A record accepted by the importer: a scraped item as exported, or an item
+/// from the AI normalization pipeline
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ImportRecord {
+    Scraped(ScrapedData),
+    Normalized(NormalizedData),
+}
+
+/// A record that was skipped, and why
+#[derive(Debug, Clone, PartialEq)]
+pub struct RejectedRecord {
+    /// Line number for JSON Lines, 1-based position for arrays
+    pub position: usize,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ImportReport {
+    pub items: Vec<ScrapedData>,
+    pub rejected: Vec<RejectedRecord>,
+}
+
+/// Reads files of [`ScrapedData`] or [`NormalizedData`] records, validating
+/// each one. The format is detected from the content: a JSON array, an
+/// export envelope (`{"schema_version": .., "items": [..]}`), a single object,
+/// or JSON Lines. Records of both kinds may be mixed in one file.
+pub struct Importer {
+    validator: Validator,
+}
+
+impl Default for Importer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Importer {
+    pub fn new() -> Self {
+        Self {
+            validator: Validator::new(),
+        }
+    }
+
+    /// Parse and validate records; malformed or invalid ones are reported, not fatal
+    pub fn parse(&self, input: &str) -> ImportReport {
+        let mut report = ImportReport::default();
+
+        match serde_json::from_str::<Value>(input) {
+            Ok(Value::Array(records)) => self.accept_all(&mut report, records),
+            Ok(Value::Object(mut object)) if object.get("items").is_some_and(Value::is_array) => {
+                let Some(Value::Array(records)) = object.remove("items") else {
+                    unreachable!("checked to be an array");
+                };
+                self.accept_all(&mut report, records)
+            }
+            Ok(record) => self.accept(&mut report, 1, record),
+            // Anything that isn't one JSON document is read as JSON Lines
+            Err(_) => {
+                for (index, line) in input.lines().enumerate() {
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    match serde_json::from_str(line) {
+                        Ok(record) => self.accept(&mut report, index + 1, record),
+                        Err(e) => report.rejected.push(RejectedRecord {
+                            position: index + 1,
+                            reason: format!("malformed JSON: {}", e),
+                        }),
+                    }
+                }
+            }
+        }
+
+        log::info!("Imported {} items, rejected {}", report.items.len(), report.rejected.len());
+        report
+    }
+
+    pub async fn read_file<P: AsRef<Path>>(&self, path: P) -> Result<ImportReport> {
+        let path = path.as_ref();
+        let input = tokio::fs::read_to_string(path)
+            .await
+            .map_err(ScraperError::IoError)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Ok(self.parse(&input))
+    }
+
+    /// Read a file and save its valid records to `db`
+    pub async fn import_file<P: AsRef<Path>>(&self, path: P, db: &(impl DatabaseOutput + Sync)) -> Result<ImportReport> {
+        let report = self.read_file(&path).await?;
+        let saved = db.save(&report.items).await?;
+        log::info!("Saved {} items from {}", saved, path.as_ref().display());
+        Ok(report)
+    }
+
+    fn accept_all(&self, report: &mut ImportReport, records: Vec<Value>) {
+        for (index, record) in records.into_iter().enumerate() {
+            self.accept(report, index + 1, record);
+        }
+    }
+
+    fn accept(&self, report: &mut ImportReport, position: usize, record: Value) {
+        let item = match serde_json::from_value(record) {
+            Ok(ImportRecord::Scraped(item)) => item,
+            Ok(ImportRecord::Normalized(item)) => item.into_scraped_data(),
+            Err(_) => {
+                report.rejected.push(RejectedRecord {
+                    position,
+                    reason: "not a scraped or normalized item".to_string(),
+                });
+                return;
+            }
+        };
+
+        match self.validator.check(&item) {
+            Ok(()) => report.items.push(item),
+            Err(reason) => report.rejected.push(RejectedRecord { position, reason }),
+        }
+    }
+}
+
+/// The normalized-data file to load from `dir`: `final.json` when present,
+/// otherwise the JSON file whose name carries the newest timestamp (as in
+/// `final_20240131_154500.json`). Returns `None` when there is neither.
+pub async fn latest_normalized_file<P: AsRef<Path>>(dir: P) -> Result<Option<PathBuf>> {
+    let dir = dir.as_ref();
+    let final_path = dir.join(FINAL_NORMALIZED_FILE);
+    if tokio::fs::metadata(&final_path).await.is_ok() {
+        return Ok(Some(final_path));
+    }
+
+    let mut entries = match tokio::fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => {
+            return Err(ScraperError::IoError(e)).with_context(|| format!("Failed to list {}", dir.display()));
+        }
+    };
+
+    let mut latest: Option<(chrono::NaiveDateTime, PathBuf)> = None;
+    while let Some(entry) = entries.next_entry().await.map_err(ScraperError::IoError)? {
+        let path = entry.path();
+        let Some(timestamp) = file_timestamp(&path) else {
+            continue;
+        };
+        if latest.as_ref().is_none_or(|(newest, _)| timestamp > *newest) {
+            latest = Some((timestamp, path));
+        }
+    }
+
+    Ok(latest.map(|(_, path)| path))
+}
+
+/// The timestamp at the end of a `.json` file's name, if it has one
+fn file_timestamp(path: &Path) -> Option<chrono::NaiveDateTime> {
+    if path.extension()? != "json" {
+        return None;
+    }
+    let stem = path.file_stem()?.to_str()?;
+    let stamp = stem.get(stem.len().checked_sub(15)?..)?;
+    chrono::NaiveDateTime::parse_from_str(stamp, FILE_TIMESTAMP_FORMAT).ok()
+}
@@ -1,10 +1,71 @@
 use crate::core::models::ScrapedData;
-use anyhow::Result;
-use serde_json;
-use std::fs::File;
+use crate::output::atomic::write_atomically;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::ser::{Error as _, SerializeSeq};
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::ser::{CompactFormatter, Formatter, PrettyFormatter};
+use std::borrow::Borrow;
+use std::cell::{Cell, RefCell};
 use std::io::Write;
 use std::path::Path;
 
+/// Layout version written into every JSON export. Version 1 was a bare array
+/// of items; version 2 wraps them in an [`ExportEnvelope`].
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// `{ "schema_version": 2, "generated_at": "...", "items": [...] }`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportEnvelope<T> {
+    pub schema_version: u32,
+    /// Unknown for version 1 exports, which carried no metadata
+    pub generated_at: Option<DateTime<Utc>>,
+    pub items: T,
+}
+
+impl<T> ExportEnvelope<T> {
+    /// Wrap items in an envelope stamped with the current version and time
+    pub fn new(items: T) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            generated_at: Some(Utc::now()),
+            items,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum VersionedExport<T> {
+    Enveloped(ExportEnvelope<Vec<T>>),
+    Bare(Vec<T>),
+}
+
+impl<T: DeserializeOwned> ExportEnvelope<Vec<T>> {
+    /// Parse an export of any version. Bare arrays are read as version 1;
+    /// newer versions are read on a best-effort basis, ignoring unknown fields.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let envelope = match serde_json::from_str(json).context("Failed to parse JSON export")? {
+            VersionedExport::Enveloped(envelope) => envelope,
+            VersionedExport::Bare(items) => Self {
+                schema_version: 1,
+                generated_at: None,
+                items,
+            },
+        };
+
+        if envelope.schema_version > SCHEMA_VERSION {
+            log::warn!(
+                "JSON export has schema version {}, newer than supported version {}; reading known fields only",
+                envelope.schema_version,
+                SCHEMA_VERSION
+            );
+        }
+        Ok(envelope)
+    }
+}
+
 pub struct JsonOutput;
 
 impl JsonOutput {
@@ -13,31 +74,92 @@ impl JsonOutput {
     }
 
     pub async fn export<P: AsRef<Path>>(&self, data: &[ScrapedData], path: P) -> Result<()> {
-        let json = serde_json::to_string_pretty(data)?;
-        
-        // Create directory if it doesn't exist
-        if let Some(parent) = path.as_ref().parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-
-        let mut file = File::create(path)?;
-        file.write_all(json.as_bytes())?;
-        
+        self.export_stream(data, path, true).await?;
         log::info!("Exported {} items to JSON", data.len());
         Ok(())
     }
 
     pub async fn export_minified<P: AsRef<Path>>(&self, data: &[ScrapedData], path: P) -> Result<()> {
-        let json = serde_json::to_string(data)?;
-        
-        if let Some(parent) = path.as_ref().parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-
-        let mut file = File::create(path)?;
-        file.write_all(json.as_bytes())?;
-        
+        self.export_stream(data, path, false).await?;
         log::info!("Exported {} items to minified JSON", data.len());
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// Write items into the export envelope one element at a time, so memory
+    /// stays flat however many items the iterator yields. Returns the number written.
+    pub async fn export_stream<I, P>(&self, items: I, path: P, pretty: bool) -> Result<usize>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<ScrapedData>,
+        P: AsRef<Path>,
+    {
+        let mut count = 0;
+        write_atomically(path, |file| {
+            count = write_envelope(file, items, pretty)?;
+            Ok(())
+        })?;
+
+        log::debug!("Streamed {} items to JSON", count);
+        Ok(count)
+    }
+
+    /// Read an export written by any version of `JsonOutput`
+    pub async fn import<P: AsRef<Path>>(&self, path: P) -> Result<ExportEnvelope<Vec<ScrapedData>>> {
+        let json = tokio::fs::read_to_string(path.as_ref())
+            .await
+            .with_context(|| format!("Failed to read {}", path.as_ref().display()))?;
+        ExportEnvelope::from_json(&json)
+    }
+}
+
+/// The envelope `export_stream` writes, to any writer; returns the number of items
+pub(crate) fn write_envelope<W, I>(writer: W, items: I, pretty: bool) -> Result<usize>
+where
+    W: Write,
+    I: IntoIterator,
+    I::Item: Borrow<ScrapedData>,
+{
+    let envelope = ExportEnvelope::new(StreamedItems {
+        items: RefCell::new(Some(items.into_iter())),
+        count: Cell::new(0),
+    });
+    if pretty {
+        write_with(writer, PrettyFormatter::new(), &envelope)?;
+    } else {
+        write_with(writer, CompactFormatter, &envelope)?;
+    }
+    Ok(envelope.items.count.get())
+}
+
+fn write_with<W: Write, F: Formatter, T: Serialize>(writer: W, formatter: F, value: &T) -> Result<()> {
+    let mut serializer = serde_json::Serializer::with_formatter(writer, formatter);
+    value.serialize(&mut serializer)?;
+    Ok(())
+}
+
+// Serializes as a JSON array by draining the iterator, so items are never collected up front
+struct StreamedItems<I> {
+    items: RefCell<Option<I>>,
+    count: Cell<usize>,
+}
+
+impl<I> Serialize for StreamedItems<I>
+where
+    I: Iterator,
+    I::Item: Borrow<ScrapedData>,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let items = self
+            .items
+            .borrow_mut()
+            .take()
+            .ok_or_else(|| S::Error::custom("streamed items can only be written once"))?;
+
+        let mut seq = serializer.serialize_seq(None)?;
+        for item in items {
+            seq.serialize_element(item.borrow())?;
+            self.count.set(self.count.get() + 1);
+        }
+        seq.end()
+    }
+}
@@ -2,8 +2,19 @@ pub mod json;
 pub mod csv;
 pub mod database;
 pub mod api;
+pub mod atomic;
+pub mod bundle;
+pub mod importer;
+pub mod sink;
+pub mod store;
+pub mod webhook;
 
-pub use json::JsonOutput;
+pub use json::{ExportEnvelope, JsonOutput};
 pub use csv::CsvOutput;
-pub use database::{DatabaseOutput, PostgresOutput, SqliteOutput};
-pub use api::ApiServer;
+pub use database::{ConflictStrategy, DatabaseOutput, PostgresOutput, RegeneratedIds, SqlArg, SqliteOutput};
+pub use api::{ApiError, ApiServer};
+pub use bundle::{BundleMetadata, BundleOutput};
+pub use sink::{FileFormat, FileOutput, Output};
+pub use store::{IndexedStore, ItemStore};
+pub use importer::{latest_normalized_file, ImportReport, Importer, RejectedRecord};
+pub use webhook::WebhookNotifier;
@@ -0,0 +1,126 @@
+//! One interface over every place scraped items can be written
+//!
+//! [`Output`] lets callers hold a list of destinations, e.g. a JSON file, a
+//! CSV file and a database, and write a dataset to each in turn. File
+//! exports take their path per call, so [`FileOutput`] pairs a format with
+//! the path it writes to; both databases are outputs as they are, and so is
+//! an `Arc` of any output, for handles shared with e.g. the API server.
+
+use crate::core::models::ScrapedData;
+use crate::output::{
+    bundle::BundleOutput,
+    csv::CsvOutput,
+    database::{DatabaseOutput, PostgresOutput, SqliteOutput},
+    json::JsonOutput,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+#[async_trait]
+pub trait Output: Send + Sync {
+    /// Where the items go, for logs and reports, e.g. `JSON output/items.json`
+    fn describe(&self) -> String;
+
+    /// Write the items; returns how many were written
+    async fn write(&self, data: &[ScrapedData]) -> Result<usize>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    Json,
+    Csv,
+    /// Zip of JSON, CSV, metadata and a README (see [`BundleOutput`])
+    Bundle,
+}
+
+/// A file export written to a fixed path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileOutput {
+    format: FileFormat,
+    path: PathBuf,
+}
+
+impl FileOutput {
+    pub fn new(format: FileFormat, path: impl Into<PathBuf>) -> Self {
+        Self {
+            format,
+            path: path.into(),
+        }
+    }
+
+    pub fn json(path: impl Into<PathBuf>) -> Self {
+        Self::new(FileFormat::Json, path)
+    }
+
+    pub fn csv(path: impl Into<PathBuf>) -> Self {
+        Self::new(FileFormat::Csv, path)
+    }
+
+    pub fn bundle(path: impl Into<PathBuf>) -> Self {
+        Self::new(FileFormat::Bundle, path)
+    }
+
+    pub fn format(&self) -> FileFormat {
+        self.format
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[async_trait]
+impl Output for FileOutput {
+    fn describe(&self) -> String {
+        let format = match self.format {
+            FileFormat::Json => "JSON",
+            FileFormat::Csv => "CSV",
+            FileFormat::Bundle => "Zip bundle",
+        };
+        format!("{} {}", format, self.path.display())
+    }
+
+    async fn write(&self, data: &[ScrapedData]) -> Result<usize> {
+        match self.format {
+            FileFormat::Json => JsonOutput::new().export(data, &self.path).await?,
+            FileFormat::Csv => CsvOutput::new().export(data, &self.path).await?,
+            FileFormat::Bundle => BundleOutput::new().export(data, &self.path).await?,
+        }
+        Ok(data.len())
+    }
+}
+
+#[async_trait]
+impl<T: Output + ?Sized> Output for Arc<T> {
+    fn describe(&self) -> String {
+        (**self).describe()
+    }
+
+    async fn write(&self, data: &[ScrapedData]) -> Result<usize> {
+        (**self).write(data).await
+    }
+}
+
+#[async_trait]
+impl Output for PostgresOutput {
+    fn describe(&self) -> String {
+        format!("PostgreSQL table {}", self.table_name())
+    }
+
+    async fn write(&self, data: &[ScrapedData]) -> Result<usize> {
+        self.save(data).await
+    }
+}
+
+#[async_trait]
+impl Output for SqliteOutput {
+    fn describe(&self) -> String {
+        format!("SQLite table {}", self.table_name())
+    }
+
+    async fn write(&self, data: &[ScrapedData]) -> Result<usize> {
+        self.save(data).await
+    }
+}
@@ -0,0 +1,264 @@
+//! In-memory item store behind the API
+//!
+//! [`IndexedStore`] keeps items in insertion order with a hash index by id and
+//! sorted indexes by source and category, so lookups don't scan every item
+//! and single items can be added or replaced without rebuilding the dataset.
+//! With a database holding the full dataset, the store can be capped so a
+//! long-running server only keeps the newest items in memory.
+
+use crate::core::models::ScrapedData;
+use crate::output::api::SharedData;
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// A capped store compacts once it is this fraction of its cap over it
+const EVICTION_SLACK_DIVISOR: usize = 16;
+
+/// Items served by the API. Ids are unique: adding an item whose id is
+/// already stored replaces it in place. A store may drop items to bound its
+/// memory, see [`IndexedStore::with_max_items`].
+pub trait ItemStore: Send + Sync {
+    /// Every item, in the order first added
+    fn items(&self) -> &[ScrapedData];
+
+    fn get(&self, id: &str) -> Option<&ScrapedData>;
+
+    /// Items from exactly this source
+    fn by_source(&self, source: &str) -> Vec<&ScrapedData>;
+
+    /// Items in this category, ignoring case
+    fn by_category(&self, category: &str) -> Vec<&ScrapedData>;
+
+    /// Distinct source names, sorted
+    fn sources(&self) -> Vec<&str>;
+
+    /// Items whose source and category contain the given text (ignoring
+    /// case), in insertion order; `None` doesn't filter on that field
+    fn select(&self, source: Option<&str>, category: Option<&str>) -> Vec<&ScrapedData>;
+
+    /// Add the item, or replace the stored one with its id; true when it was new
+    fn upsert(&mut self, item: ScrapedData) -> bool;
+
+    fn remove(&mut self, id: &str) -> Option<ScrapedData>;
+
+    /// Swap the whole dataset; of items sharing an id the last one is kept
+    fn replace_all(&mut self, items: Vec<ScrapedData>);
+
+    fn len(&self) -> usize {
+        self.items().len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.items().is_empty()
+    }
+
+    fn contains(&self, id: &str) -> bool {
+        self.get(id).is_some()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct IndexedStore {
+    items: Vec<ScrapedData>,
+    by_id: HashMap<String, usize>,
+    /// Positions in `items`, ascending
+    by_source: BTreeMap<String, Vec<usize>>,
+    /// Keyed by the lowercased category
+    by_category: BTreeMap<String, Vec<usize>>,
+    /// Oldest first; positions break timestamp ties in the order items were added
+    by_age: BTreeSet<(DateTime<Utc>, usize)>,
+    max_items: Option<usize>,
+}
+
+impl IndexedStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep about `max_items` items, evicting those with the oldest
+    /// `timestamp` first. Meant for when a database is the source of truth
+    /// and still has the evicted items.
+    ///
+    /// Evicting shifts items and so rebuilds the indexes; to keep that off
+    /// every insert, the store may grow a sixteenth past the cap before it
+    /// drops back to exactly `max_items` in one pass.
+    pub fn with_max_items(mut self, max_items: usize) -> Self {
+        self.max_items = Some(max_items);
+        self.evict_oldest();
+        self
+    }
+
+    pub fn max_items(&self) -> Option<usize> {
+        self.max_items
+    }
+
+    /// Wrap the store for sharing with the API server
+    pub fn into_shared(self) -> SharedData {
+        Arc::new(RwLock::new(self))
+    }
+
+    fn insert(&mut self, item: ScrapedData) -> bool {
+        match self.by_id.get(&item.id).copied() {
+            Some(position) => {
+                self.unindex_fields(position);
+                self.items[position] = item;
+                self.index(position);
+                false
+            }
+            None => {
+                self.items.push(item);
+                self.index(self.items.len() - 1);
+                true
+            }
+        }
+    }
+
+    fn rebuild(&mut self, items: Vec<ScrapedData>) {
+        self.items = Vec::with_capacity(items.len());
+        self.by_id.clear();
+        self.by_source.clear();
+        self.by_category.clear();
+        self.by_age.clear();
+        for item in items {
+            self.insert(item);
+        }
+    }
+
+    // Once the store is past its cap and slack, drop the oldest items
+    // (earliest added first on equal timestamps) down to the cap
+    fn evict_oldest(&mut self) {
+        let Some(max_items) = self.max_items else {
+            return;
+        };
+        if self.items.len() <= max_items + max_items / EVICTION_SLACK_DIVISOR {
+            return;
+        }
+
+        let excess = self.items.len() - max_items;
+        let evicted: HashSet<usize> = self.by_age.iter().take(excess).map(|&(_, position)| position).collect();
+
+        let items = std::mem::take(&mut self.items)
+            .into_iter()
+            .enumerate()
+            .filter(|(position, _)| !evicted.contains(position))
+            .map(|(_, item)| item)
+            .collect();
+        self.rebuild(items);
+        log::debug!("Evicted {} items from memory, keeping the newest {}", excess, max_items);
+    }
+
+    fn index(&mut self, position: usize) {
+        let item = &self.items[position];
+        self.by_id.insert(item.id.clone(), position);
+        insert_sorted(self.by_source.entry(item.source.clone()).or_default(), position);
+        self.by_age.insert((item.timestamp, position));
+        if let Some(category) = &item.category {
+            insert_sorted(self.by_category.entry(category.to_lowercase()).or_default(), position);
+        }
+    }
+
+    fn unindex_fields(&mut self, position: usize) {
+        let item = &self.items[position];
+        remove_position(&mut self.by_source, &item.source, position);
+        self.by_age.remove(&(item.timestamp, position));
+        if let Some(category) = &item.category {
+            remove_position(&mut self.by_category, &category.to_lowercase(), position);
+        }
+    }
+
+    fn resolve(&self, positions: impl IntoIterator<Item = usize>) -> Vec<&ScrapedData> {
+        positions.into_iter().map(|position| &self.items[position]).collect()
+    }
+
+    // Positions under every key containing `needle`
+    fn positions_matching(index: &BTreeMap<String, Vec<usize>>, needle: &str) -> BTreeSet<usize> {
+        let needle = needle.to_lowercase();
+        index
+            .iter()
+            .filter(|(key, _)| key.to_lowercase().contains(&needle))
+            .flat_map(|(_, positions)| positions.iter().copied())
+            .collect()
+    }
+}
+
+impl From<Vec<ScrapedData>> for IndexedStore {
+    fn from(items: Vec<ScrapedData>) -> Self {
+        let mut store = Self::new();
+        store.replace_all(items);
+        store
+    }
+}
+
+impl ItemStore for IndexedStore {
+    fn items(&self) -> &[ScrapedData] {
+        &self.items
+    }
+
+    fn get(&self, id: &str) -> Option<&ScrapedData> {
+        self.by_id.get(id).map(|&position| &self.items[position])
+    }
+
+    fn by_source(&self, source: &str) -> Vec<&ScrapedData> {
+        self.resolve(self.by_source.get(source).into_iter().flatten().copied())
+    }
+
+    fn by_category(&self, category: &str) -> Vec<&ScrapedData> {
+        self.resolve(self.by_category.get(&category.to_lowercase()).into_iter().flatten().copied())
+    }
+
+    fn sources(&self) -> Vec<&str> {
+        self.by_source.keys().map(String::as_str).collect()
+    }
+
+    fn select(&self, source: Option<&str>, category: Option<&str>) -> Vec<&ScrapedData> {
+        let sources = source.map(|needle| Self::positions_matching(&self.by_source, needle));
+        let categories = category.map(|needle| Self::positions_matching(&self.by_category, needle));
+
+        match (sources, categories) {
+            (None, None) => self.items.iter().collect(),
+            (Some(positions), None) | (None, Some(positions)) => self.resolve(positions),
+            (Some(sources), Some(categories)) => self.resolve(sources.intersection(&categories).copied()),
+        }
+    }
+
+    /// Past the cap (and its slack), this evicts the oldest items, possibly
+    /// including the new one
+    fn upsert(&mut self, item: ScrapedData) -> bool {
+        let added = self.insert(item);
+        self.evict_oldest();
+        added
+    }
+
+    /// Later items shift down a place, so this rebuilds the indexes
+    fn remove(&mut self, id: &str) -> Option<ScrapedData> {
+        let position = *self.by_id.get(id)?;
+        let removed = self.items.remove(position);
+        let items = std::mem::take(&mut self.items);
+        self.rebuild(items);
+        Some(removed)
+    }
+
+    fn replace_all(&mut self, items: Vec<ScrapedData>) {
+        self.rebuild(items);
+        self.evict_oldest();
+    }
+}
+
+fn insert_sorted(positions: &mut Vec<usize>, position: usize) {
+    if let Err(at) = positions.binary_search(&position) {
+        positions.insert(at, position);
+    }
+}
+
+fn remove_position(index: &mut BTreeMap<String, Vec<usize>>, key: &str, position: usize) {
+    if let Some(positions) = index.get_mut(key) {
+        if let Ok(at) = positions.binary_search(&position) {
+            positions.remove(at);
+        }
+        if positions.is_empty() {
+            index.remove(key);
+        }
+    }
+}
@@ -0,0 +1,68 @@
+//! Sends alert rule matches to an HTTP endpoint
+//!
+//! Matches are POSTed as one JSON document per run:
+//! `{"generated_at": "...", "matches": [{"rule": .., "item_id": .., ..}]}`.
+
+use crate::{processors::rules::RuleMatch, utils::error::ScraperError};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::time::Duration;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Serialize)]
+struct AlertPayload<'a> {
+    generated_at: DateTime<Utc>,
+    matches: &'a [RuleMatch],
+}
+
+pub struct WebhookNotifier {
+    client: reqwest::Client,
+    url: String,
+    timeout: Duration,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.to_string(),
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// POST the matches; nothing is sent when there are none. A non-success
+    /// response is an error.
+    pub async fn notify(&self, matches: &[RuleMatch]) -> Result<()> {
+        if matches.is_empty() {
+            return Ok(());
+        }
+
+        let response = self
+            .client
+            .post(&self.url)
+            .timeout(self.timeout)
+            .json(&AlertPayload {
+                generated_at: Utc::now(),
+                matches,
+            })
+            .send()
+            .await
+            .map_err(ScraperError::RequestError)
+            .with_context(|| format!("Failed to send alerts to {}", self.url))?;
+
+        if !response.status().is_success() {
+            return Err(ScraperError::HttpError(response.status()))
+                .with_context(|| format!("Alert webhook {} rejected the request", self.url));
+        }
+
+        log::info!("Sent {} alert matches to {}", matches.len(), self.url);
+        Ok(())
+    }
+}
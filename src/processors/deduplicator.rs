@@ -1,39 +1,137 @@
 use crate::core::models::ScrapedData;
 use anyhow::Result;
+use moka::policy::EvictionPolicy;
+use moka::sync::Cache;
+use serde::Serialize;
 use std::collections::HashSet;
+use std::time::Duration;
 
-pub struct Deduplicator;
+/// Content shorter than this isn't used to detect duplicates by default
+pub const DEFAULT_CONTENT_MIN_LEN: usize = 51;
+
+/// Metadata key holding a source's own id for an item (e.g. a Reddit post id)
+pub const SOURCE_ID_KEY: &str = "source_id";
+
+/// How many items a deduplication pass removed, by the key that matched.
+/// An item is counted once, under the first key checked: id, URL, title,
+/// then content.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct DedupStats {
+    /// Same native id from the same source
+    pub by_id: usize,
+    pub by_url: usize,
+    pub by_title: usize,
+    pub by_content: usize,
+    pub total_removed: usize,
+}
+
+impl DedupStats {
+    /// Add another pass's counts, e.g. from a second deduplicator
+    pub fn merge(&mut self, other: DedupStats) {
+        self.by_id += other.by_id;
+        self.by_url += other.by_url;
+        self.by_title += other.by_title;
+        self.by_content += other.by_content;
+        self.total_removed += other.total_removed;
+    }
+}
+
+/// Drops items whose URL, title or content was already seen in the batch.
+///
+/// Items carrying a native id in `metadata["source_id"]` are matched on that
+/// id (per source) instead: the same id under different URLs is a duplicate,
+/// while different ids are kept even if their titles collide.
+pub struct Deduplicator {
+    content_min_len: usize,
+    id_key: String,
+}
+
+impl Default for Deduplicator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl Deduplicator {
     pub fn new() -> Self {
-        Self
+        Self {
+            content_min_len: DEFAULT_CONTENT_MIN_LEN,
+            id_key: SOURCE_ID_KEY.to_string(),
+        }
+    }
+
+    /// Read native ids from this metadata key instead of `source_id`
+    pub fn with_id_key(mut self, key: &str) -> Self {
+        self.id_key = key.to_string();
+        self
+    }
+
+    /// Only treat content of at least `len` characters as identifying an item.
+    ///
+    /// Short snippets ("Read more", "In stock", a one-line teaser) are shared
+    /// by many distinct items, so keying on them would collapse unrelated
+    /// items into one. Use 0 to compare all content.
+    pub fn with_content_min_len(mut self, len: usize) -> Self {
+        self.content_min_len = len;
+        self
     }
 
     pub async fn deduplicate(&self, data: Vec<ScrapedData>) -> Result<Vec<ScrapedData>> {
+        let (deduplicated, _) = self.deduplicate_with_stats(data).await?;
+        Ok(deduplicated)
+    }
+
+    /// Like [`deduplicate`](Self::deduplicate), also saying which key caught
+    /// each removed item
+    pub async fn deduplicate_with_stats(&self, data: Vec<ScrapedData>) -> Result<(Vec<ScrapedData>, DedupStats)> {
+        let mut stats = DedupStats::default();
         let mut seen_urls = HashSet::new();
         let mut seen_titles = HashSet::new();
         let mut seen_contents = HashSet::new();
+        let mut seen_ids = HashSet::new();
         let mut deduplicated = Vec::new();
 
         for item in data {
+            let id_key = item
+                .metadata
+                .get(&self.id_key)
+                .map(|id| id.trim())
+                .filter(|id| !id.is_empty())
+                .map(|id| (item.source.clone(), id.to_string()));
             let url_key = item.url.to_lowercase();
             let title_key = item.title.as_ref().map(|t| t.to_lowercase());
-            let content_key = item.content.as_ref().map(|c| c.to_lowercase());
-
-            // Skip if we've seen this URL, title, or content before
-            if seen_urls.contains(&url_key) {
-                continue;
-            }
+            let content_key = item
+                .content
+                .as_ref()
+                .map(|c| c.to_lowercase())
+                .filter(|c| c.chars().count() >= self.content_min_len);
 
-            if let Some(ref title) = title_key {
-                if seen_titles.contains(title) {
-                    continue;
+            match id_key {
+                // A native id settles it; the heuristics below don't apply
+                Some(id) => {
+                    if !seen_ids.insert(id) {
+                        stats.by_id += 1;
+                        continue;
+                    }
                 }
-            }
+                None => {
+                    // Skip if we've seen this URL, title, or content before
+                    if seen_urls.contains(&url_key) {
+                        stats.by_url += 1;
+                        continue;
+                    }
+
+                    if let Some(ref title) = title_key
+                        && seen_titles.contains(title)
+                    {
+                        stats.by_title += 1;
+                        continue;
+                    }
 
-            if let Some(ref content) = content_key {
-                if content.len() > 50 && seen_contents.contains(content) { // Only check longer contents
-                    continue;
+                    if content_key.as_ref().is_some_and(|content| seen_contents.contains(content)) {
+                        stats.by_content += 1;
+                        continue;
+                    }
                 }
             }
 
@@ -42,15 +140,67 @@ impl Deduplicator {
                 seen_titles.insert(title);
             }
             if let Some(content) = content_key {
-                if content.len() > 50 {
-                    seen_contents.insert(content);
-                }
+                seen_contents.insert(content);
             }
 
             deduplicated.push(item);
         }
 
-        log::info!("Deduplication completed: {} unique items", deduplicated.len());
-        Ok(deduplicated)
+        stats.total_removed = stats.by_id + stats.by_url + stats.by_title + stats.by_content;
+        log::info!(
+            "Deduplication completed: {} unique items, {} removed ({} by id, {} by URL, {} by title, {} by content)",
+            deduplicated.len(),
+            stats.total_removed,
+            stats.by_id,
+            stats.by_url,
+            stats.by_title,
+            stats.by_content
+        );
+        Ok((deduplicated, stats))
+    }
+}
+
+/// Bounded memory of recently seen URLs for long-running scrapes.
+///
+/// Unlike [`Deduplicator`], which only dedupes within one batch, the window
+/// remembers URLs across batches. It holds at most `max_capacity` URLs, evicting
+/// the least recently seen first, and forgets a URL once `ttl` has passed, after
+/// which the item counts as new again.
+pub struct DedupWindow {
+    seen: Cache<String, ()>,
+}
+
+impl DedupWindow {
+    pub fn new(max_capacity: u64, ttl: Duration) -> Self {
+        let seen = Cache::builder()
+            .max_capacity(max_capacity)
+            .time_to_live(ttl)
+            .eviction_policy(EvictionPolicy::lru())
+            .build();
+
+        Self { seen }
+    }
+
+    /// Record the item's URL, returning whether it wasn't already in the window
+    pub fn insert(&self, item: &ScrapedData) -> bool {
+        self.seen.entry(item.url.to_lowercase()).or_insert(()).is_fresh()
+    }
+
+    /// Keep only the items not seen within the window, remembering them for next time
+    pub fn filter_new(&self, data: Vec<ScrapedData>) -> Vec<ScrapedData> {
+        let total = data.len();
+        let fresh: Vec<ScrapedData> = data.into_iter().filter(|item| self.insert(item)).collect();
+        log::debug!("Dedup window kept {} of {} items", fresh.len(), total);
+        fresh
+    }
+
+    /// Number of URLs currently remembered (applies pending evictions first)
+    pub fn len(&self) -> u64 {
+        self.seen.run_pending_tasks();
+        self.seen.entry_count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
     }
-}
\ No newline at end of file
+}
@@ -2,8 +2,10 @@ pub mod pipeline;
 pub mod validator;
 pub mod normalizer;
 pub mod deduplicator;
+pub mod rules;
 
-pub use pipeline::ProcessingPipeline;
+pub use pipeline::{PipelineConfig, PipelineReport, ProcessingPipeline};
 pub use validator::Validator;
-pub use normalizer::Normalizer;
-pub use deduplicator::Deduplicator;
+pub use normalizer::{MetadataFilter, Normalizer, CONTENT_LEN_KEY};
+pub use deduplicator::{DedupStats, DedupWindow, Deduplicator, DEFAULT_CONTENT_MIN_LEN, SOURCE_ID_KEY};
+pub use rules::{AlertsConfig, Comparison, Rule, RuleEngine, RuleMatch, RuleValue};
@@ -1,11 +1,84 @@
 use crate::core::models::ScrapedData;
 use anyhow::Result;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use url::Url;
 
-pub struct Normalizer;
+// Query parameters that only identify a campaign or click, never the page
+const TRACKING_PARAMS: &[&str] = &["fbclid", "gclid", "dclid", "msclkid", "yclid", "mc_cid", "mc_eid", "_ga", "igshid"];
+
+/// Metadata key recording the character count of content that was truncated
+pub const CONTENT_LEN_KEY: &str = "content_len";
+
+/// Source name under which a metadata filter applies to every source without its own
+pub const ALL_SOURCES: &str = "*";
+
+/// Which metadata keys to keep: only `allow`ed ones if an allowlist is set,
+/// minus any `deny`ed ones
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct MetadataFilter {
+    pub allow: Option<HashSet<String>>,
+    pub deny: HashSet<String>,
+}
+
+impl MetadataFilter {
+    /// Keep only these keys
+    pub fn allow<I: IntoIterator<Item = S>, S: Into<String>>(keys: I) -> Self {
+        Self {
+            allow: Some(keys.into_iter().map(Into::into).collect()),
+            deny: HashSet::new(),
+        }
+    }
+
+    /// Drop these keys, keeping everything else
+    pub fn deny<I: IntoIterator<Item = S>, S: Into<String>>(keys: I) -> Self {
+        Self {
+            allow: None,
+            deny: keys.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    pub fn apply(&self, metadata: &mut HashMap<String, String>) {
+        metadata.retain(|key, _| {
+            self.allow.as_ref().is_none_or(|allow| allow.contains(key)) && !self.deny.contains(key)
+        });
+    }
+}
+
+pub struct Normalizer {
+    metadata_filters: HashMap<String, MetadataFilter>,
+    max_content_len: Option<usize>,
+}
 
 impl Normalizer {
     pub fn new() -> Self {
-        Self
+        Self {
+            metadata_filters: HashMap::new(),
+            max_content_len: None,
+        }
+    }
+
+    /// Cut content longer than `len` characters down to its first `len`
+    /// followed by `…`, recording the original length in
+    /// `metadata["content_len"]`. Off by default.
+    pub fn with_max_content_len(mut self, len: usize) -> Self {
+        self.max_content_len = Some(len);
+        self
+    }
+
+    /// Filter the metadata of items from `source` (matched against
+    /// `ScrapedData::source`); use [`ALL_SOURCES`] for a fallback filter.
+    /// Runs last, so keys the normalizer adds itself such as `url_raw` and
+    /// `content_len` need allowing too.
+    pub fn with_metadata_filter(mut self, source: &str, filter: MetadataFilter) -> Self {
+        self.metadata_filters.insert(source.to_string(), filter);
+        self
+    }
+
+    pub fn with_metadata_filters(mut self, filters: HashMap<String, MetadataFilter>) -> Self {
+        self.metadata_filters.extend(filters);
+        self
     }
 
     pub async fn normalize(&self, data: Vec<ScrapedData>) -> Result<Vec<ScrapedData>> {
@@ -22,6 +95,17 @@ impl Normalizer {
                 item.content = Some(self.normalize_text(content));
             }
 
+            if let (Some(max_len), Some(content)) = (self.max_content_len, &mut item.content) {
+                let len = content.chars().count();
+                if len > max_len {
+                    let cut = content.char_indices().nth(max_len).map_or(content.len(), |(index, _)| index);
+                    content.truncate(cut);
+                    content.truncate(content.trim_end().len());
+                    content.push('…');
+                    item.metadata.insert(CONTENT_LEN_KEY.to_string(), len.to_string());
+                }
+            }
+
             // Normalize author
             if let Some(author) = &item.author {
                 item.author = Some(self.normalize_text(author));
@@ -37,6 +121,22 @@ impl Normalizer {
                 item.url = format!("https://{}", item.url);
             }
 
+            // Canonicalize so tracking params and fragments don't defeat dedup
+            if let Some(canonical) = canonicalize_url(&item.url)
+                && canonical != item.url
+            {
+                let raw = std::mem::replace(&mut item.url, canonical);
+                item.metadata.entry("url_raw".to_string()).or_insert(raw);
+            }
+
+            if let Some(filter) = self
+                .metadata_filters
+                .get(&item.source)
+                .or_else(|| self.metadata_filters.get(ALL_SOURCES))
+            {
+                filter.apply(&mut item.metadata);
+            }
+
             normalized.push(item);
         }
 
@@ -54,4 +154,36 @@ impl Normalizer {
             .collect::<Vec<&str>>()
             .join(" ")
     }
-}
\ No newline at end of file
+}
+
+/// Canonical form of a URL: lowercase host, no fragment, tracking parameters
+/// (`utm_*`, `fbclid`, ...) removed and the remaining query sorted.
+/// `None` if the URL can't be parsed.
+pub fn canonicalize_url(url: &str) -> Option<String> {
+    let mut url = Url::parse(url.trim()).ok()?;
+    url.set_fragment(None);
+
+    if let Some(host) = url.host_str().map(str::to_lowercase) {
+        url.set_host(Some(&host)).ok()?;
+    }
+
+    let mut pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(key, _)| !is_tracking_param(key))
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+    pairs.sort();
+
+    if pairs.is_empty() {
+        url.set_query(None);
+    } else {
+        url.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+
+    Some(url.to_string())
+}
+
+fn is_tracking_param(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    key.starts_with("utm_") || TRACKING_PARAMS.contains(&key.as_str())
+}
@@ -1,13 +1,59 @@
 use crate::{
     core::models::ScrapedData,
-    processors::{deduplicator::Deduplicator, normalizer::Normalizer, validator::Validator},
+    processors::{
+        deduplicator::{DedupStats, Deduplicator},
+        normalizer::{MetadataFilter, Normalizer},
+        validator::Validator,
+    },
 };
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Which pipeline stages run, read from the `[pipeline]` config section.
+/// Every stage is on unless switched off.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PipelineConfig {
+    pub validate: bool,
+    pub normalize: bool,
+    pub deduplicate: bool,
+    /// Metadata key filters by source name (`"*"` for all others), applied
+    /// while normalizing, e.g. `[pipeline.metadata."Books to Scrape"]`
+    pub metadata: HashMap<String, MetadataFilter>,
+    /// Truncate content longer than this many characters while normalizing
+    pub max_content_len: Option<usize>,
+}
+
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        Self {
+            validate: true,
+            normalize: true,
+            deduplicate: true,
+            metadata: HashMap::new(),
+            max_content_len: None,
+        }
+    }
+}
+
+/// What a pipeline run did to the batch
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PipelineReport {
+    pub input: usize,
+    /// Dropped by validation
+    pub invalid: usize,
+    /// Dropped by deduplication, by the key that matched
+    pub dedup: DedupStats,
+    pub output: usize,
+}
 
 pub struct ProcessingPipeline {
     validators: Vec<Validator>,
     normalizers: Vec<Normalizer>,
     deduplicators: Vec<Deduplicator>,
+    stages: PipelineConfig,
+    deterministic_order: bool,
 }
 
 impl ProcessingPipeline {
@@ -16,29 +62,103 @@ impl ProcessingPipeline {
             validators: vec![Validator::new()],
             normalizers: vec![Normalizer::new()],
             deduplicators: vec![Deduplicator::new()],
+            stages: PipelineConfig::default(),
+            deterministic_order: false,
+        }
+    }
+
+    /// The default pipeline with only the stages enabled in `config`, its
+    /// metadata filters and content length limit
+    pub fn from_config(config: &PipelineConfig) -> Self {
+        let mut normalizer = Normalizer::new().with_metadata_filters(config.metadata.clone());
+        if let Some(max_len) = config.max_content_len {
+            normalizer = normalizer.with_max_content_len(max_len);
         }
+
+        Self {
+            normalizers: vec![normalizer],
+            stages: config.clone(),
+            ..Self::new()
+        }
+    }
+
+    /// A pipeline that passes scraped data through untouched, for telling
+    /// whether items go missing in the scraper or in processing
+    pub fn raw() -> Self {
+        Self::from_config(&PipelineConfig {
+            validate: false,
+            normalize: false,
+            deduplicate: false,
+            ..PipelineConfig::default()
+        })
+    }
+
+    pub fn with_validation(mut self, enabled: bool) -> Self {
+        self.stages.validate = enabled;
+        self
+    }
+
+    pub fn with_normalization(mut self, enabled: bool) -> Self {
+        self.stages.normalize = enabled;
+        self
+    }
+
+    pub fn with_deduplication(mut self, enabled: bool) -> Self {
+        self.stages.deduplicate = enabled;
+        self
+    }
+
+    /// Sort the processed output by (source, timestamp, id) so results are stable
+    /// regardless of the order in which sources completed. Off by default.
+    pub fn with_deterministic_order(mut self, enabled: bool) -> Self {
+        self.deterministic_order = enabled;
+        self
     }
 
-    pub async fn process(&self, mut data: Vec<ScrapedData>) -> Result<Vec<ScrapedData>> {
+    pub async fn process(&self, data: Vec<ScrapedData>) -> Result<Vec<ScrapedData>> {
+        let (data, _) = self.process_with_report(data).await?;
+        Ok(data)
+    }
+
+    /// Process the batch and report how many items each stage removed
+    pub async fn process_with_report(&self, mut data: Vec<ScrapedData>) -> Result<(Vec<ScrapedData>, PipelineReport)> {
         log::info!("Processing {} items through pipeline", data.len());
+        let mut report = PipelineReport {
+            input: data.len(),
+            ..PipelineReport::default()
+        };
 
         // Validate
-        for validator in &self.validators {
-            data = validator.validate(data).await?;
+        if self.stages.validate {
+            for validator in &self.validators {
+                data = validator.validate(data).await?;
+            }
         }
+        report.invalid = report.input - data.len();
 
         // Normalize
-        for normalizer in &self.normalizers {
-            data = normalizer.normalize(data).await?;
+        if self.stages.normalize {
+            for normalizer in &self.normalizers {
+                data = normalizer.normalize(data).await?;
+            }
         }
 
         // Deduplicate
-        for deduplicator in &self.deduplicators {
-            data = deduplicator.deduplicate(data).await?;
+        if self.stages.deduplicate {
+            for deduplicator in &self.deduplicators {
+                let (deduplicated, stats) = deduplicator.deduplicate_with_stats(data).await?;
+                data = deduplicated;
+                report.dedup.merge(stats);
+            }
+        }
+
+        if self.deterministic_order {
+            sort_deterministic(&mut data);
         }
 
+        report.output = data.len();
         log::info!("Pipeline processing completed: {} items remaining", data.len());
-        Ok(data)
+        Ok((data, report))
     }
 
     pub fn add_validator(&mut self, validator: Validator) {
@@ -59,3 +179,13 @@ impl Default for ProcessingPipeline {
         Self::new()
     }
 }
+
+/// Sort items by (source, timestamp, id) for reproducible output
+pub fn sort_deterministic(data: &mut [ScrapedData]) {
+    data.sort_by(|a, b| {
+        a.source
+            .cmp(&b.source)
+            .then(a.timestamp.cmp(&b.timestamp))
+            .then_with(|| a.id.cmp(&b.id))
+    });
+}
@@ -0,0 +1,232 @@
+//! Alert rules evaluated over a scraped dataset
+//!
+//! A [`Rule`] compares one field of each item against a fixed value, e.g.
+//! "price below 15 in category Science", optionally scoped to a category or
+//! source. The [`RuleEngine`] runs every rule after a scrape and returns the
+//! matches, which a notifier such as
+//! [`WebhookNotifier`](crate::output::webhook::WebhookNotifier) sends on.
+
+use crate::{core::models::ScrapedData, utils::error::ScraperError};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// Prefix selecting a metadata key as the field, e.g. `metadata.discount_pct`
+const METADATA_PREFIX: &str = "metadata.";
+
+const FIELDS: &[&str] = &["price", "title", "content", "author", "category", "source", "url"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Comparison {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+    /// Case-insensitive substring match; text values only
+    Contains,
+}
+
+/// What a field is compared against. Numbers compare numerically, so the
+/// item's value must parse as one (e.g. a metadata value of `"20"`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RuleValue {
+    Number(f64),
+    Text(String),
+}
+
+impl From<f64> for RuleValue {
+    fn from(value: f64) -> Self {
+        RuleValue::Number(value)
+    }
+}
+
+impl From<&str> for RuleValue {
+    fn from(value: &str) -> Self {
+        RuleValue::Text(value.to_string())
+    }
+}
+
+/// One condition, read from `[[alerts.rules]]`:
+///
+/// ```toml
+/// [[alerts.rules]]
+/// name = "Cheap science books"
+/// field = "price"
+/// op = "lt"
+/// value = 15.0
+/// category = "Science"
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rule {
+    pub name: String,
+    /// `price`, `title`, `content`, `author`, `category`, `source`, `url` or
+    /// `metadata.<key>`
+    pub field: String,
+    pub op: Comparison,
+    pub value: RuleValue,
+    /// Only items in this category (case-insensitive)
+    #[serde(default)]
+    pub category: Option<String>,
+    /// Only items from this source
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+impl Rule {
+    pub fn new(name: &str, field: &str, op: Comparison, value: impl Into<RuleValue>) -> Self {
+        Self {
+            name: name.to_string(),
+            field: field.to_string(),
+            op,
+            value: value.into(),
+            category: None,
+            source: None,
+        }
+    }
+
+    pub fn in_category(mut self, category: &str) -> Self {
+        self.category = Some(category.to_string());
+        self
+    }
+
+    pub fn from_source(mut self, source: &str) -> Self {
+        self.source = Some(source.to_string());
+        self
+    }
+
+    fn check(&self) -> Result<()> {
+        if !FIELDS.contains(&self.field.as_str()) && !self.field.starts_with(METADATA_PREFIX) {
+            return Err(ScraperError::ConfigError(format!("rule '{}': unknown field '{}'", self.name, self.field)).into());
+        }
+        if self.op == Comparison::Contains && matches!(self.value, RuleValue::Number(_)) {
+            return Err(ScraperError::ConfigError(format!("rule '{}': 'contains' needs a text value", self.name)).into());
+        }
+        Ok(())
+    }
+
+    fn in_scope(&self, item: &ScrapedData) -> bool {
+        let category_ok = self.category.as_ref().is_none_or(|category| {
+            item.category.as_ref().is_some_and(|item_category| item_category.eq_ignore_ascii_case(category))
+        });
+        category_ok && self.source.as_ref().is_none_or(|source| item.source == *source)
+    }
+
+    /// The item's value for this rule's field, when it has one
+    fn field_value(&self, item: &ScrapedData) -> Option<String> {
+        match self.field.as_str() {
+            "price" => item.price.map(|price| price.to_string()),
+            "title" => item.title.clone(),
+            "content" => item.content.clone(),
+            "author" => item.author.clone(),
+            "category" => item.category.clone(),
+            "source" => Some(item.source.clone()),
+            "url" => Some(item.url.clone()),
+            field => item.metadata.get(field.strip_prefix(METADATA_PREFIX)?).cloned(),
+        }
+    }
+
+    /// The item's value when the item is in scope and satisfies the condition
+    pub fn matches(&self, item: &ScrapedData) -> Option<String> {
+        if !self.in_scope(item) {
+            return None;
+        }
+
+        let actual = self.field_value(item)?;
+        let ordering = match &self.value {
+            RuleValue::Number(expected) => actual.trim().parse::<f64>().ok()?.partial_cmp(expected)?,
+            RuleValue::Text(expected) if self.op == Comparison::Contains => {
+                return actual.to_lowercase().contains(&expected.to_lowercase()).then_some(actual);
+            }
+            RuleValue::Text(expected) => actual.as_str().cmp(expected.as_str()),
+        };
+
+        let satisfied = match self.op {
+            Comparison::Lt => ordering.is_lt(),
+            Comparison::Le => ordering.is_le(),
+            Comparison::Gt => ordering.is_gt(),
+            Comparison::Ge => ordering.is_ge(),
+            Comparison::Eq => ordering.is_eq(),
+            Comparison::Ne => ordering.is_ne(),
+            Comparison::Contains => false,
+        };
+        satisfied.then_some(actual)
+    }
+}
+
+/// The `[alerts]` config section
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct AlertsConfig {
+    pub rules: Vec<Rule>,
+    /// Where matches are POSTed after each run
+    pub webhook_url: Option<String>,
+}
+
+/// An item that triggered a rule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuleMatch {
+    pub rule: String,
+    pub item_id: String,
+    pub source: String,
+    pub url: String,
+    pub title: Option<String>,
+    pub category: Option<String>,
+    /// The item's value for the rule's field
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RuleEngine {
+    rules: Vec<Rule>,
+}
+
+impl RuleEngine {
+    /// Fails with a `ConfigError` naming the first rule with an unknown field
+    /// or a `contains` on a number
+    pub fn new(rules: Vec<Rule>) -> Result<Self> {
+        for rule in &rules {
+            rule.check()?;
+        }
+        Ok(Self { rules })
+    }
+
+    pub fn with_rule(mut self, rule: Rule) -> Result<Self> {
+        rule.check()?;
+        self.rules.push(rule);
+        Ok(self)
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// Every (rule, item) pair that matches, grouped by rule in the order the
+    /// rules were given
+    pub fn evaluate(&self, data: &[ScrapedData]) -> Vec<RuleMatch> {
+        let matches: Vec<RuleMatch> = self
+            .rules
+            .iter()
+            .flat_map(|rule| {
+                data.iter().filter_map(move |item| {
+                    rule.matches(item).map(|value| RuleMatch {
+                        rule: rule.name.clone(),
+                        item_id: item.id.clone(),
+                        source: item.source.clone(),
+                        url: item.url.clone(),
+                        title: item.title.clone(),
+                        category: item.category.clone(),
+                        value,
+                    })
+                })
+            })
+            .collect();
+
+        if !matches.is_empty() {
+            log::info!("{} alert rule matches over {} items", matches.len(), data.len());
+        }
+        matches
+    }
+}
@@ -18,36 +18,48 @@ impl Validator {
         Ok(validated)
     }
 
-    fn is_valid_item(&self, item: &ScrapedData) -> bool {
+    /// Why an item would be dropped by validation, or `Ok` if it passes
+    pub fn check(&self, item: &ScrapedData) -> std::result::Result<(), String> {
+        // Items without an id can't be stored or deduplicated reliably
+        if item.id.trim().is_empty() {
+            return Err("empty id".to_string());
+        }
+
         // Check if item has at least a title or content
         if item.title.is_none() && item.content.is_none() {
-            log::debug!("Item invalid: missing both title and content");
-            return false;
+            return Err("missing both title and content".to_string());
         }
 
         // Check if URL is valid
         if !self.is_valid_url(&item.url) {
-            log::debug!("Item invalid: invalid URL {}", item.url);
-            return false;
+            return Err(format!("invalid URL {}", item.url));
         }
 
         // Check if price is reasonable if present
         if let Some(price) = item.price {
             if price < 0.0 || price > 1_000_000.0 {
-                log::debug!("Item invalid: unreasonable price {}", price);
-                return false;
+                return Err(format!("unreasonable price {}", price));
             }
         }
 
         // Check content length if present
         if let Some(content) = &item.content {
             if content.trim().len() < 3 {
-                log::debug!("Item invalid: content too short");
-                return false;
+                return Err("content too short".to_string());
             }
         }
 
-        true
+        Ok(())
+    }
+
+    fn is_valid_item(&self, item: &ScrapedData) -> bool {
+        match self.check(item) {
+            Ok(()) => true,
+            Err(reason) => {
+                log::debug!("Item invalid: {}", reason);
+                false
+            }
+        }
     }
 
     fn is_valid_url(&self, url: &str) -> bool {
@@ -0,0 +1,200 @@
+//! End-to-end check of a deployment
+//!
+//! Scrapes a catalog page bundled with the binary, runs the items through the
+//! default pipeline, writes every export format, reads them back and
+//! optionally stores them in an in-memory SQLite database, checking the item
+//! count at each step. No network access is needed, so it runs the same in
+//! CI and on a freshly deployed host (`rust-scraper-pro selftest`).
+
+use crate::{
+    output::{csv::CsvOutput, database::DatabaseOutput, importer::Importer, json::JsonOutput},
+    processors::pipeline::ProcessingPipeline,
+    sources::{source::Source, EcommerceSource},
+    SqliteOutput,
+};
+use anyhow::{Context, Result};
+use std::fmt;
+use std::path::PathBuf;
+
+/// Items the bundled catalog yields
+pub const EXPECTED_ITEMS: usize = 5;
+
+const CATALOG_URL: &str = "https://selftest.invalid/catalogue";
+
+const CATALOG_HTML: &str = r#"<html>
+<body>
+  <article class="product_pod">
+    <h3><a href="rust-in-action.html" title="Rust in Action">Rust in Action</a></h3>
+    <p class="price_color">£39.99</p>
+    <p class="instock availability">In stock</p>
+  </article>
+  <article class="product_pod">
+    <h3><a href="zero-to-production.html" title="Zero To Production">Zero To Production</a></h3>
+    <p class="price_color">£32.50</p>
+    <p class="instock availability">In stock</p>
+  </article>
+  <article class="product_pod">
+    <h3><a href="programming-rust.html" title="Programming Rust">Programming Rust</a></h3>
+    <p class="price_color">£45.00</p>
+    <p class="instock availability">In stock</p>
+  </article>
+  <article class="product_pod">
+    <h3><a href="rust-for-rustaceans.html" title="Rust for Rustaceans">Rust for Rustaceans</a></h3>
+    <p class="price_color">£29.95</p>
+    <p class="instock availability">In stock</p>
+  </article>
+  <article class="product_pod">
+    <h3><a href="the-rust-book.html" title="The Rust Programming Language">The Rust Programming Language</a></h3>
+    <p class="price_color">£24.00</p>
+    <p class="instock availability">Out of stock</p>
+  </article>
+</body>
+</html>"#;
+
+/// Outcome of one step of the self-test
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelftestCheck {
+    pub name: &'static str,
+    pub passed: bool,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SelftestReport {
+    pub checks: Vec<SelftestCheck>,
+    /// Where the exports were written; kept so a failure can be inspected
+    pub output_dir: PathBuf,
+}
+
+impl SelftestReport {
+    pub fn passed(&self) -> bool {
+        !self.checks.is_empty() && self.checks.iter().all(|check| check.passed)
+    }
+
+    fn record(&mut self, name: &'static str, result: Result<String>) -> bool {
+        let (passed, detail) = match result {
+            Ok(detail) => (true, detail),
+            Err(e) => (false, format!("{:#}", e)),
+        };
+        self.checks.push(SelftestCheck { name, passed, detail });
+        passed
+    }
+}
+
+impl fmt::Display for SelftestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            writeln!(f, "[{}] {}: {}", if check.passed { "PASS" } else { "FAIL" }, check.name, check.detail)?;
+        }
+        write!(f, "Self-test {}", if self.passed() { "passed" } else { "FAILED" })
+    }
+}
+
+pub struct Selftest {
+    output_dir: PathBuf,
+    sqlite: bool,
+}
+
+impl Default for Selftest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Selftest {
+    /// Writes exports under a fresh directory in the system temp dir
+    pub fn new() -> Self {
+        Self {
+            output_dir: std::env::temp_dir().join(format!("rust-scraper-pro-selftest-{}", uuid::Uuid::new_v4().simple())),
+            sqlite: true,
+        }
+    }
+
+    pub fn with_output_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.output_dir = dir.into();
+        self
+    }
+
+    /// Also save the items to an in-memory SQLite database (on by default)
+    pub fn with_sqlite(mut self, enabled: bool) -> Self {
+        self.sqlite = enabled;
+        self
+    }
+
+    /// Run every step, stopping at the first failure; the report says which
+    /// steps passed rather than returning an error
+    pub async fn run(&self) -> SelftestReport {
+        let mut report = SelftestReport {
+            checks: Vec::new(),
+            output_dir: self.output_dir.clone(),
+        };
+
+        let source = EcommerceSource::new(CATALOG_URL).with_name("Selftest Catalog");
+        let scraped = match source.scrape(CATALOG_HTML).await {
+            Ok(items) => items,
+            Err(e) => {
+                report.record("scrape", Err(e));
+                return report;
+            }
+        };
+        if !report.record("scrape", expect_count("scraped", scraped.len())) {
+            return report;
+        }
+
+        let items = match ProcessingPipeline::new().process(scraped).await {
+            Ok(items) => items,
+            Err(e) => {
+                report.record("pipeline", Err(e));
+                return report;
+            }
+        };
+        if !report.record("pipeline", expect_count("processed", items.len())) {
+            return report;
+        }
+
+        let json_path = self.output_dir.join("selftest.json");
+        let json = async {
+            JsonOutput::new().export(&items, &json_path).await?;
+            let read_back = Importer::new().read_file(&json_path).await?;
+            expect_count(&format!("read back from {}", json_path.display()), read_back.items.len())
+        };
+        if !report.record("json export", json.await) {
+            return report;
+        }
+
+        let csv_path = self.output_dir.join("selftest.csv");
+        let csv = async {
+            CsvOutput::new().export(&items, &csv_path).await?;
+            let mut reader = csv::Reader::from_path(&csv_path)
+                .with_context(|| format!("Failed to open {}", csv_path.display()))?;
+            let rows = reader.records().collect::<std::result::Result<Vec<_>, _>>()?;
+            expect_count(&format!("in {}", csv_path.display()), rows.len())
+        };
+        if !report.record("csv export", csv.await) {
+            return report;
+        }
+
+        if self.sqlite {
+            let sqlite = async {
+                let db = SqliteOutput::new("sqlite::memory:", None).await?;
+                db.init().await?;
+                db.save(&items).await?;
+                let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM scraped_data")
+                    .fetch_one(db.get_pool())
+                    .await?;
+                expect_count("in in-memory SQLite", stored as usize)
+            };
+            report.record("sqlite", sqlite.await);
+        }
+
+        report
+    }
+}
+
+fn expect_count(what: &str, actual: usize) -> Result<String> {
+    if actual == EXPECTED_ITEMS {
+        Ok(format!("{} items {}", actual, what))
+    } else {
+        anyhow::bail!("expected {} items {}, got {}", EXPECTED_ITEMS, what, actual)
+    }
+}
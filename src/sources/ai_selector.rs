@@ -0,0 +1,284 @@
+use crate::{
+    ai::DetectedSelectors,
+    core::models::ScrapedData,
+    sources::{
+        source::{apply_category_override, item_limit, AiSelectorSource, Source},
+        table::parse_number,
+    },
+    utils::time,
+};
+use anyhow::Result;
+use scraper::{ElementRef, Html, Selector};
+use serde::Serialize;
+
+/// Detections below this confidence are not trusted unless configured otherwise
+pub const DEFAULT_MIN_CONFIDENCE: f32 = 0.5;
+
+/// Result of [`AiSelectorSource::check_selectors`]: for every field with an
+/// expected value, what each candidate selector extracted from the page
+#[derive(Debug, Clone, Serialize)]
+pub struct SelectorReport {
+    pub fields: Vec<FieldReport>,
+}
+
+impl SelectorReport {
+    /// Whether every field's expected value was found by one of its candidates
+    pub fn passed(&self) -> bool {
+        self.fields.iter().all(FieldReport::matched)
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &FieldReport> {
+        self.fields.iter().filter(|field| !field.matched())
+    }
+
+    pub fn field(&self, field: &str) -> Option<&FieldReport> {
+        self.fields.iter().find(|report| report.field == field)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldReport {
+    pub field: String,
+    pub expected: String,
+    /// In the order the extractor tries them
+    pub candidates: Vec<CandidateCheck>,
+}
+
+impl FieldReport {
+    pub fn matched(&self) -> bool {
+        self.candidates.iter().any(|candidate| candidate.matched)
+    }
+
+    /// The candidate a scrape would take the field from, if it matches anything
+    pub fn used(&self) -> Option<&CandidateCheck> {
+        self.candidates.iter().find(|candidate| !candidate.values.is_empty())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CandidateCheck {
+    pub selector: String,
+    /// Values extracted by this selector, one per item on the page
+    pub values: Vec<String>,
+    /// Whether one of the values equals the expected value
+    pub matched: bool,
+}
+
+impl AiSelectorSource {
+    /// Scrape with selectors from the [`SelectorAssistant`](crate::ai::SelectorAssistant)
+    pub fn new(base_url: &str, selectors: DetectedSelectors) -> Self {
+        Self {
+            name: "AI Selector Source".to_string(),
+            base_url: base_url.to_string(),
+            category: None,
+            selectors: Box::new(selectors),
+            min_confidence: DEFAULT_MIN_CONFIDENCE,
+            max_items: None,
+        }
+    }
+
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    /// Label every item with `category`
+    pub fn with_category(mut self, category: &str) -> Self {
+        self.category = Some(category.to_string());
+        self
+    }
+
+    /// Below this confidence the detected selectors are ignored and common
+    /// selectors guessed from each page are used instead
+    pub fn with_min_confidence(mut self, min_confidence: f32) -> Self {
+        self.min_confidence = min_confidence;
+        self
+    }
+
+    /// Take at most `max_items` items from each page
+    pub fn with_max_items(mut self, max_items: usize) -> Self {
+        self.max_items = Some(max_items);
+        self
+    }
+
+    fn extract(&self, element: ElementRef, selectors: &DetectedSelectors, strategy: &str) -> Option<ScrapedData> {
+        let mut data = ScrapedData::new(self.name().to_string(), self.base_url().to_string());
+        let mut used = Vec::new();
+        // Candidates are tried in order; the first that matches inside this item wins
+        let mut find = |field: &str, candidates: &[String]| {
+            let (selector, found) = candidates.iter().find_map(|candidate| {
+                let found = element.select(&Selector::parse(candidate).ok()?).next()?;
+                Some((candidate, found))
+            })?;
+            used.push((field.to_string(), selector.clone()));
+            Some(found)
+        };
+
+        data.title = find("title", &selectors.title).map(text).filter(|title| !title.is_empty());
+        let link = find("link", &selectors.link).and_then(|link| link.value().attr("href"));
+        if data.title.is_none() && link.is_none() {
+            return None;
+        }
+        if let Some(href) = link {
+            data.url = self.resolve_url(href);
+        }
+
+        if let Some(price) = find("price", &selectors.price).map(text) {
+            data.price = parse_number(&price);
+            data.metadata.insert("price_text".to_string(), price);
+        }
+        data.image_url = find("image", &selectors.image)
+            .and_then(|image| image.value().attr("src"))
+            .map(|src| self.resolve_url(src));
+        data.content = find("description", &selectors.description).map(text);
+        data.author = find("author", &selectors.author).map(text);
+        data.category = find("category", &selectors.category).map(text);
+
+        if let Some(date) = find("date", &selectors.date) {
+            let date = date.value().attr("datetime").map(str::to_string).unwrap_or_else(|| text(date));
+            if let Some(published) = time::parse_datetime(&date) {
+                data.timestamp = published;
+            }
+            data.metadata.insert("publish_date".to_string(), date);
+        }
+
+        // Provenance, so low-quality results can be traced back to their selectors
+        data.metadata.insert("selector_strategy".to_string(), strategy.to_string());
+        data.metadata.insert("selector_confidence".to_string(), format!("{:.2}", selectors.confidence));
+        for (field, selector) in used {
+            data.metadata.insert(format!("selector_{}", field), selector);
+        }
+
+        Some(data)
+    }
+
+    /// Test the detected selectors against a page whose content is known,
+    /// e.g. while tuning selectors for a new site.
+    ///
+    /// `expected` pairs a field name (`title`, `price`, `link`, `image`,
+    /// `description`, `author`, `category` or `date`) with a value that one of
+    /// the page's items should have. Each candidate selector for the field is
+    /// run over every item and reported with the values it produced. Prices are
+    /// compared as numbers and links and images as resolved URLs; the other
+    /// fields must match the element's text exactly (whitespace collapsed).
+    ///
+    /// The detected selectors are checked whatever their confidence.
+    pub fn check_selectors(&self, html: &str, expected: &[(&str, &str)]) -> SelectorReport {
+        let document = Html::parse_document(html);
+        let items = self.containers(&document, &self.selectors);
+
+        let fields = expected
+            .iter()
+            .map(|&(field, expected)| {
+                let candidates = self
+                    .selectors
+                    .candidates(field)
+                    .iter()
+                    .map(|selector| {
+                        let values: Vec<String> = match Selector::parse(selector) {
+                            Ok(parsed) => items
+                                .iter()
+                                .filter_map(|item| item.select(&parsed).next())
+                                .filter_map(|element| self.field_value(field, element))
+                                .collect(),
+                            Err(_) => Vec::new(),
+                        };
+                        let matched = values.iter().any(|value| same_value(field, value, expected));
+                        CandidateCheck {
+                            selector: selector.clone(),
+                            values,
+                            matched,
+                        }
+                    })
+                    .collect();
+
+                FieldReport {
+                    field: field.to_string(),
+                    expected: expected.to_string(),
+                    candidates,
+                }
+            })
+            .collect();
+
+        SelectorReport { fields }
+    }
+
+    /// Elements holding one item each: those matched by the first container
+    /// candidate found in the page, or the whole page
+    fn containers<'a>(&self, document: &'a Html, selectors: &DetectedSelectors) -> Vec<ElementRef<'a>> {
+        selectors
+            .container
+            .iter()
+            .filter_map(|candidate| Selector::parse(candidate).ok())
+            .map(|container| document.select(&container).collect::<Vec<_>>())
+            .find(|elements| !elements.is_empty())
+            .unwrap_or_else(|| vec![document.root_element()])
+    }
+
+    /// A field's value as the scrape would read it from the matched element
+    fn field_value(&self, field: &str, element: ElementRef) -> Option<String> {
+        match field {
+            "link" => element.value().attr("href").map(|href| self.resolve_url(href)),
+            "image" => element.value().attr("src").map(|src| self.resolve_url(src)),
+            "date" => Some(element.value().attr("datetime").map(str::to_string).unwrap_or_else(|| text(element))),
+            _ => Some(text(element)),
+        }
+    }
+
+    fn resolve_url(&self, href: &str) -> String {
+        url::Url::parse(self.base_url())
+            .and_then(|base| base.join(href))
+            .map(|url| url.to_string())
+            .unwrap_or_else(|_| href.to_string())
+    }
+}
+
+fn same_value(field: &str, value: &str, expected: &str) -> bool {
+    if field == "price"
+        && let (Some(value), Some(expected)) = (parse_number(value), parse_number(expected))
+    {
+        return (value - expected).abs() < 0.005;
+    }
+    value == expected.trim()
+}
+
+fn text(element: ElementRef) -> String {
+    element.text().collect::<Vec<_>>().join(" ").split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[async_trait::async_trait]
+impl Source for AiSelectorSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    async fn scrape(&self, html: &str) -> Result<Vec<ScrapedData>> {
+        let (selectors, strategy) = if self.selectors.confidence >= self.min_confidence {
+            ((*self.selectors).clone(), "ai")
+        } else {
+            log::warn!(
+                "Selector confidence {:.2} for {} is below {:.2}; using heuristic selectors",
+                self.selectors.confidence,
+                self.selectors.domain,
+                self.min_confidence
+            );
+            (DetectedSelectors::heuristic(&self.selectors.domain, html), "heuristic")
+        };
+
+        let document = Html::parse_document(html);
+        let mut results: Vec<ScrapedData> = self
+            .containers(&document, &selectors)
+            .into_iter()
+            .filter_map(|element| self.extract(element, &selectors, strategy))
+            .take(item_limit(self.max_items))
+            .collect();
+
+        apply_category_override(&mut results, self.category.as_deref());
+        log::info!("Scraped {} items from {} using {} selectors", results.len(), self.name(), strategy);
+        Ok(results)
+    }
+}
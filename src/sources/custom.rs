@@ -1,7 +1,7 @@
 use crate::{
     core::models::ScrapedData,
     core::scraper::ScraperEngine,
-    sources::source::{CustomSource, Source},
+    sources::source::{apply_category_override, item_limit, CustomSource, Source},
 };
 use anyhow::Result;
 use serde::Deserialize;
@@ -32,6 +32,9 @@ impl CustomSource {
             name: name.to_string(),
             base_url: base_url.to_string(),
             selectors: Vec::new(),
+            category: None,
+            max_items: None,
+            pre_clean: false,
         }
     }
 
@@ -40,11 +43,33 @@ impl CustomSource {
         self
     }
 
+    /// Label every item with `category`
+    pub fn with_category(mut self, category: &str) -> Self {
+        self.category = Some(category.to_string());
+        self
+    }
+
+    /// Take at most `max_items` items from each page
+    pub fn with_max_items(mut self, max_items: usize) -> Self {
+        self.max_items = Some(max_items);
+        self
+    }
+
+    /// Strip `<script>`, `<style>`, `<noscript>` and comments from pages
+    /// before scraping, so inline code doesn't end up in the content
+    pub fn with_pre_clean(mut self, enabled: bool) -> Self {
+        self.pre_clean = enabled;
+        self
+    }
+
     pub fn from_config(config: CustomConfig) -> Self {
         Self {
             name: config.name,
             base_url: config.base_url,
             selectors: Vec::new(), // Would map from config
+            category: None,
+            max_items: None,
+            pre_clean: false,
         }
     }
 }
@@ -59,16 +84,24 @@ impl Source for CustomSource {
         &self.base_url
     }
 
+    fn pre_clean(&self) -> bool {
+        self.pre_clean
+    }
+
     async fn scrape(&self, html: &str) -> Result<Vec<ScrapedData>> {
         let document = ScraperEngine::parse_html(html);
         let mut results = Vec::new();
+        let limit = item_limit(self.max_items);
 
         // Use custom selectors if provided, otherwise use generic approach
         if !self.selectors.is_empty() {
             // Custom selector logic would go here
-            for selector in &self.selectors {
+            'selectors: for selector in &self.selectors {
                 if let Ok(elements) = ScraperEngine::select_element(&document, selector) {
                     for element in elements {
+                        if results.len() >= limit {
+                            break 'selectors;
+                        }
                         let data = ScrapedData::new(self.name().to_string(), self.base_url().to_string())
                             .with_content(element);
                         results.push(data);
@@ -79,9 +112,12 @@ impl Source for CustomSource {
             // Generic scraping approach
             let generic_selectors = vec!["article", "div", "section", "main"];
             
-            for selector in generic_selectors {
+            'generic: for selector in generic_selectors {
                 if let Ok(elements) = ScraperEngine::select_element(&document, selector) {
                     for element in elements {
+                        if results.len() >= limit {
+                            break 'generic;
+                        }
                         if element.len() > 10 { // Basic content length filter
                             let data = ScrapedData::new(self.name().to_string(), self.base_url().to_string())
                                 .with_content(element);
@@ -92,6 +128,7 @@ impl Source for CustomSource {
             }
         }
 
+        apply_category_override(&mut results, self.category.as_deref());
         log::info!("Scraped {} items from custom source {}", results.len(), self.name());
         Ok(results)
     }
@@ -1,16 +1,92 @@
 use crate::{
     core::models::ScrapedData,
-    sources::source::{EcommerceSource, Source},
+    sources::source::{apply_category_override, item_limit, record_unparsed, EcommerceSource, Source, UnparsedElement},
+    utils::error::ScraperError,
 };
 use anyhow::Result;
 use regex::Regex;
 use lazy_static::lazy_static;
+use scraper::{ElementRef, Selector};
+use serde::Deserialize;
+use std::path::PathBuf;
+use url::Url;
+
+/// The pager's "next" link on books.toscrape.com and many shop themes
+pub const DEFAULT_NEXT_PAGE_SELECTOR: &str = "li.next a";
+
+lazy_static! {
+    static ref PRICE_REGEX: Regex = Regex::new(r#"([\$£€])?\s*(\d+\.?\d*)"#).unwrap();
+}
+
+/// Where to find prices within a product, since sale markup varies between shops
+#[derive(Debug, Clone, Deserialize)]
+pub struct PriceSelectors {
+    /// Element holding the price when there's no separate sale price
+    pub price: String,
+    /// Discounted price shown next to a struck-through original
+    pub sale_price: Option<String>,
+    /// Struck-through price from before the discount
+    pub original_price: Option<String>,
+}
+
+impl Default for PriceSelectors {
+    fn default() -> Self {
+        Self {
+            price: "p.price_color".to_string(),
+            sale_price: Some("ins, .sale-price, .price-sale".to_string()),
+            original_price: Some("del, s, .original-price, .price-old, .was-price".to_string()),
+        }
+    }
+}
+
+struct PriceMatch {
+    amount: f64,
+    symbol: Option<String>,
+}
+
+/// Amounts in `text`, in order. Those with a currency symbol win: bare numbers
+/// are only taken when there are none, so "£40.00 (23% off)" isn't read as 23.
+fn price_matches(text: &str) -> Vec<PriceMatch> {
+    let matches: Vec<PriceMatch> = PRICE_REGEX
+        .captures_iter(text)
+        .filter_map(|captures| {
+            Some(PriceMatch {
+                amount: captures.get(2)?.as_str().parse().ok()?,
+                symbol: captures.get(1).map(|symbol| symbol.as_str().to_string()),
+            })
+        })
+        .collect();
+
+    if matches.iter().any(|m| m.symbol.is_some()) {
+        matches.into_iter().filter(|m| m.symbol.is_some()).collect()
+    } else {
+        matches
+    }
+}
+
+fn parse_selector(selector: &str) -> Result<Selector> {
+    Selector::parse(selector).map_err(|e| ScraperError::SelectorError(e.to_string()).into())
+}
+
+fn first_text(product: &ElementRef, selector: Option<&Selector>) -> Option<String> {
+    product
+        .select(selector?)
+        .next()
+        .map(|element| element.text().collect::<String>())
+}
 
 impl EcommerceSource {
     pub fn new(base_url: &str) -> Self {
         Self {
             name: "Ecommerce Source".to_string(),
             base_url: base_url.to_string(),
+            category: None,
+            default_currency: "GBP".to_string(),
+            price_selectors: PriceSelectors::default(),
+            unparsed_log: None,
+            max_items: None,
+            max_pages: None,
+            next_page_selector: DEFAULT_NEXT_PAGE_SELECTOR.to_string(),
         }
     }
 
@@ -18,6 +94,59 @@ impl EcommerceSource {
         self.name = name.to_string();
         self
     }
+
+    /// Label every product with `category`
+    pub fn with_category(mut self, category: &str) -> Self {
+        self.category = Some(category.to_string());
+        self
+    }
+
+    pub fn with_price_selectors(mut self, selectors: PriceSelectors) -> Self {
+        self.price_selectors = selectors;
+        self
+    }
+
+    /// Also append products without a title, with their HTML and the page they
+    /// were found on, to a JSON Lines file; they are still returned as usual
+    pub fn with_unparsed_log(mut self, path: impl Into<PathBuf>) -> Self {
+        self.unparsed_log = Some(path.into());
+        self
+    }
+
+    /// Currency code recorded for prices shown without a recognised symbol
+    pub fn with_default_currency(mut self, currency: &str) -> Self {
+        self.default_currency = currency.to_string();
+        self
+    }
+
+    /// Take at most `max_items` products from each page
+    pub fn with_max_items(mut self, max_items: usize) -> Self {
+        self.max_items = Some(max_items);
+        self
+    }
+
+    /// Have [`ScraperEngine::scrape_source`](crate::core::scraper::ScraperEngine::scrape_source)
+    /// follow the listing's next-page links, fetching up to `max_pages` pages
+    pub fn with_max_pages(mut self, max_pages: usize) -> Self {
+        self.max_pages = Some(max_pages);
+        self
+    }
+
+    /// Link to the next page, `li.next a` by default
+    pub fn with_next_page_selector(mut self, selector: &str) -> Self {
+        self.next_page_selector = selector.to_string();
+        self
+    }
+}
+
+/// ISO 4217 code for a currency symbol found in a price
+pub fn currency_for_symbol(symbol: &str) -> Option<&'static str> {
+    match symbol {
+        "$" => Some("USD"),
+        "£" => Some("GBP"),
+        "€" => Some("EUR"),
+        _ => None,
+    }
 }
 
 #[async_trait::async_trait]
@@ -30,25 +159,47 @@ impl Source for EcommerceSource {
         &self.base_url
     }
 
+    fn next_page(&self, html: &str, page_url: &str) -> Option<String> {
+        let selector = Selector::parse(&self.next_page_selector)
+            .map_err(|e| log::warn!("Invalid next-page selector '{}': {}", self.next_page_selector, e))
+            .ok()?;
+        let href = scraper::Html::parse_document(html)
+            .select(&selector)
+            .find_map(|link| link.value().attr("href").map(str::to_string))?;
+        Url::parse(page_url).and_then(|page| page.join(&href)).ok().map(String::from)
+    }
+
+    fn max_pages(&self) -> usize {
+        self.max_pages.unwrap_or(1)
+    }
+
     async fn scrape(&self, html: &str) -> Result<Vec<ScrapedData>> {
-        use scraper::{Html, Selector};
+        self.scrape_at(html, &self.base_url).await
+    }
+
+    async fn scrape_at(&self, html: &str, page_url: &str) -> Result<Vec<ScrapedData>> {
+        use scraper::Html;
 
         let document = Html::parse_document(html);
         let mut results = Vec::new();
-
-        lazy_static! {
-            static ref PRICE_REGEX: Regex = Regex::new(r#"[\$£€](\d+\.?\d*)"#).unwrap();
-        }
+        let mut unparsed = Vec::new();
 
         // Selectors optimized for books.toscrape.com
         let product_selector = Selector::parse("article.product_pod").unwrap();
         let title_selector = Selector::parse("h3 a").unwrap();
-        let price_selector = Selector::parse("p.price_color").unwrap();
+        let price_selector = parse_selector(&self.price_selectors.price)?;
+        let sale_price_selector = self.price_selectors.sale_price.as_deref().map(parse_selector).transpose()?;
+        let original_price_selector = self.price_selectors.original_price.as_deref().map(parse_selector).transpose()?;
         let image_selector = Selector::parse("div.image_container img").unwrap();
         let availability_selector = Selector::parse("p.availability").unwrap();
         let rating_selector = Selector::parse("p.star-rating").unwrap();
 
+        let limit = item_limit(self.max_items);
         for product in document.select(&product_selector) {
+            if results.len() >= limit {
+                break;
+            }
+
             let mut data = ScrapedData::new(self.name().to_string(), self.base_url().to_string());
 
             // Extract product title and URL
@@ -71,16 +222,45 @@ impl Source for EcommerceSource {
                 }
             }
 
-            // Extract price
-            if let Some(price_elem) = product.select(&price_selector).next() {
-                let price_text = price_elem.text().collect::<String>();
-                if let Some(captures) = PRICE_REGEX.captures(&price_text) {
-                    if let Some(price_match) = captures.get(1) {
-                        if let Ok(price) = price_match.as_str().parse::<f64>() {
-                            data.price = Some(price);
-                            data.metadata.insert("price_text".to_string(), price_text.clone());
-                            data.metadata.insert("currency".to_string(), "GBP".to_string());
-                        }
+            // Logged for debugging only; the product is still kept
+            if data.title.is_none() && self.unparsed_log.is_some() {
+                unparsed.push(UnparsedElement {
+                    source: self.name().to_string(),
+                    page_url: page_url.to_string(),
+                    reason: "missing title".to_string(),
+                    html: product.html(),
+                });
+            }
+
+            // Extract price, preferring a sale price over the regular one
+            let original_price = first_text(&product, original_price_selector.as_ref())
+                .and_then(|text| price_matches(&text).into_iter().next());
+            let price_text = first_text(&product, sale_price_selector.as_ref())
+                .or_else(|| first_text(&product, Some(&price_selector)));
+
+            if let Some(price_text) = price_text {
+                // The regular price element may also contain the struck-through original
+                let matches = price_matches(&price_text);
+                let current = matches
+                    .iter()
+                    .find(|m| original_price.as_ref().is_none_or(|original| m.amount != original.amount))
+                    .or(matches.first());
+
+                if let Some(current) = current {
+                    let currency = current
+                        .symbol
+                        .as_deref()
+                        .and_then(currency_for_symbol)
+                        .unwrap_or(&self.default_currency);
+
+                    data.price = Some(current.amount);
+                    data.metadata.insert("price_text".to_string(), price_text.trim().to_string());
+                    data.metadata.insert("currency".to_string(), currency.to_string());
+
+                    if let Some(original) = original_price.filter(|original| original.amount > current.amount) {
+                        let discount = (original.amount - current.amount) / original.amount * 100.0;
+                        data.metadata.insert("original_price".to_string(), format!("{:.2}", original.amount));
+                        data.metadata.insert("discount_pct".to_string(), format!("{:.0}", discount));
                     }
                 }
             }
@@ -116,7 +296,7 @@ impl Source for EcommerceSource {
                 }
             }
 
-            // Set category from source name if it contains category info
+            // Infer category from the source name; `with_category` overrides this below
             if self.name().to_lowercase().contains("science") {
                 data.category = Some("Science".to_string());
             } else {
@@ -126,6 +306,11 @@ impl Source for EcommerceSource {
             results.push(data);
         }
 
+        if let Some(path) = &self.unparsed_log {
+            record_unparsed(path, &unparsed);
+        }
+
+        apply_category_override(&mut results, self.category.as_deref());
         log::info!("Scraped {} products from {}", results.len(), self.name());
         Ok(results)
     }
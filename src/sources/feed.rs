@@ -0,0 +1,122 @@
+use crate::{
+    core::models::ScrapedData,
+    core::scraper::ScraperEngine,
+    sources::source::{apply_category_override, item_limit, FeedSource, Source},
+};
+use anyhow::{Context, Result};
+use url::Url;
+
+impl FeedSource {
+    pub fn new(feed_url: &str) -> Self {
+        Self {
+            name: "Feed Source".to_string(),
+            base_url: feed_url.to_string(),
+            category: None,
+            max_items: None,
+        }
+    }
+
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    /// Label every entry with `category`
+    pub fn with_category(mut self, category: &str) -> Self {
+        self.category = Some(category.to_string());
+        self
+    }
+
+    /// Take at most `max_items` entries from the feed
+    pub fn with_max_items(mut self, max_items: usize) -> Self {
+        self.max_items = Some(max_items);
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl Source for FeedSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    async fn scrape(&self, html: &str) -> Result<Vec<ScrapedData>> {
+        let mut results = parse_feed(html, self.name(), self.base_url())?;
+        results.truncate(item_limit(self.max_items));
+        apply_category_override(&mut results, self.category.as_deref());
+        log::info!("Parsed {} feed entries from {}", results.len(), self.name());
+        Ok(results)
+    }
+
+    fn accepts_content_type(&self, mime: &str) -> bool {
+        // Misconfigured servers serve feeds as HTML too
+        is_feed_content_type(mime) || mime == "text/html"
+    }
+}
+
+/// MIME types feeds are served with, counting generic XML
+pub fn is_feed_content_type(mime: &str) -> bool {
+    matches!(mime, "application/rss+xml" | "application/atom+xml" | "application/xml" | "text/xml")
+}
+
+/// Quick check for whether a fetched body is an RSS/Atom document rather than HTML
+pub fn looks_like_feed(body: &str) -> bool {
+    let head: String = body.trim_start().chars().take(512).collect::<String>().to_lowercase();
+    head.contains("<rss") || head.contains("<feed") || head.contains("<rdf:rdf")
+}
+
+/// Find the feed advertised by a page via `<link rel="alternate" type="application/rss+xml">`
+pub fn discover_feed_url(html: &str, page_url: &str) -> Option<String> {
+    let document = ScraperEngine::parse_html(html);
+    let selector = r#"link[rel="alternate"][type="application/rss+xml"], link[rel="alternate"][type="application/atom+xml"]"#;
+    let href = ScraperEngine::select_attribute(&document, selector, "href").ok()?.into_iter().next()?;
+
+    match Url::parse(page_url).and_then(|base| base.join(&href)) {
+        Ok(url) => Some(url.to_string()),
+        Err(_) => Some(href),
+    }
+}
+
+/// Parse RSS/Atom entries into `ScrapedData`
+pub fn parse_feed(body: &str, source_name: &str, feed_url: &str) -> Result<Vec<ScrapedData>> {
+    let feed = feed_rs::parser::parse(body.as_bytes()).context("Failed to parse RSS/Atom feed")?;
+
+    let results = feed
+        .entries
+        .into_iter()
+        .map(|entry| {
+            let link = entry
+                .links
+                .iter()
+                .find(|link| link.rel.as_deref().is_none_or(|rel| rel == "alternate"))
+                .or_else(|| entry.links.first())
+                .map(|link| link.href.clone())
+                .unwrap_or_else(|| feed_url.to_string());
+
+            let mut data = ScrapedData::new(source_name.to_string(), link);
+            data.title = entry.title.map(|title| title.content);
+            data.content = entry
+                .summary
+                .map(|summary| summary.content)
+                .or_else(|| entry.content.and_then(|content| content.body));
+            data.author = entry.authors.first().map(|person| person.name.clone());
+
+            if let Some(published) = entry.published.or(entry.updated) {
+                data.timestamp = published;
+            }
+            if let Some(category) = entry.categories.first() {
+                data.category = Some(category.label.clone().unwrap_or_else(|| category.term.clone()));
+            }
+
+            data.metadata.insert("feed_entry_id".to_string(), entry.id);
+            data.metadata.insert("feed_url".to_string(), feed_url.to_string());
+            data
+        })
+        .collect();
+
+    Ok(results)
+}
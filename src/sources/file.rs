@@ -0,0 +1,143 @@
+//! Offline sources for pages saved to disk
+//!
+//! Lets a parser be developed or a bug reproduced against saved copies of
+//! pages instead of the live site.
+
+use crate::{
+    core::models::ScrapedData,
+    sources::source::Source,
+    utils::{error::ScraperError, html::strip_noise},
+};
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+/// Runs an inner source's parser over an HTML file instead of a fetched page.
+///
+/// Items are built exactly as if the page had been fetched from the inner
+/// source's base URL, so relative links resolve against the live site.
+pub struct FileSource<S> {
+    path: PathBuf,
+    inner: S,
+}
+
+impl<S: Source> FileSource<S> {
+    pub fn new(path: impl Into<PathBuf>, inner: S) -> Self {
+        Self {
+            path: path.into(),
+            inner,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Read the saved page and parse it with the inner source
+    pub async fn scrape(&self) -> Result<Vec<ScrapedData>> {
+        scrape_file(&self.path, &self.inner).await
+    }
+}
+
+/// Runs an inner source's parser over every `.html` file in a directory,
+/// e.g. to reprocess an archive of saved pages after fixing selectors.
+///
+/// Files are read in name order and subdirectories are not descended into.
+/// A file that can't be read or parsed is logged and skipped.
+pub struct DirectorySource<S> {
+    dir: PathBuf,
+    inner: S,
+    pattern: Option<Regex>,
+}
+
+impl<S: Source> DirectorySource<S> {
+    pub fn new(dir: impl Into<PathBuf>, inner: S) -> Self {
+        Self {
+            dir: dir.into(),
+            inner,
+            pattern: None,
+        }
+    }
+
+    /// Only read files whose name matches this glob (`*` and `?` wildcards),
+    /// e.g. `"2024-*.html"`
+    pub fn with_glob(mut self, glob: &str) -> Self {
+        self.pattern = Some(glob_to_regex(glob));
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// The saved pages this source will read, in the order they are scraped
+    pub fn files(&self) -> Result<Vec<PathBuf>> {
+        let entries = std::fs::read_dir(&self.dir)
+            .map_err(ScraperError::IoError)
+            .with_context(|| format!("Failed to read directory {}", self.dir.display()))?;
+
+        let mut files = Vec::new();
+        for entry in entries {
+            let path = entry.map_err(ScraperError::IoError)?.path();
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let is_html = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("html"));
+            if path.is_file() && is_html && self.pattern.as_ref().is_none_or(|pattern| pattern.is_match(name)) {
+                files.push(path);
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+
+    /// Scrape every matching file and combine the results
+    pub async fn scrape(&self) -> Result<Vec<ScrapedData>> {
+        let files = self.files()?;
+        let mut results = Vec::new();
+        for path in &files {
+            match scrape_file(path, &self.inner).await {
+                Ok(items) => results.extend(items),
+                Err(e) => log::warn!("Skipping {}: {:#}", path.display(), e),
+            }
+        }
+
+        log::info!("Scraped {} items from {} files in {}", results.len(), files.len(), self.dir.display());
+        Ok(results)
+    }
+}
+
+async fn scrape_file(path: &Path, inner: &impl Source) -> Result<Vec<ScrapedData>> {
+    let html = tokio::fs::read_to_string(path)
+        .await
+        .map_err(ScraperError::IoError)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+
+    let results = if inner.pre_clean() {
+        inner.scrape(&strip_noise(&html)).await?
+    } else {
+        inner.scrape(&html).await?
+    };
+    log::info!("Scraped {} items from {} with {}", results.len(), path.display(), inner.name());
+    Ok(results)
+}
+
+fn glob_to_regex(glob: &str) -> Regex {
+    let pattern: String = glob
+        .chars()
+        .map(|c| match c {
+            '*' => ".*".to_string(),
+            '?' => ".".to_string(),
+            c => regex::escape(&c.to_string()),
+        })
+        .collect();
+    Regex::new(&format!("^{}$", pattern)).expect("escaped glob is a valid regex")
+}
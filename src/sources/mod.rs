@@ -3,5 +3,12 @@ pub mod news;
 pub mod ecommerce;
 pub mod social;
 pub mod custom;
+pub mod feed;
+pub mod table;
+pub mod file;
+pub mod ai_selector;
+pub mod readability;
 
-pub use source::{Source, SourceType, NewsSource, EcommerceSource, SocialSource, CustomSource};
+pub use source::{ConfiguredSource, RequestDefaults, Source, SourceType, NewsSource, EcommerceSource, SocialSource, CustomSource, FeedSource, TableSource, AiSelectorSource, UnparsedElement};
+pub use ai_selector::{CandidateCheck, FieldReport, SelectorReport};
+pub use file::{DirectorySource, FileSource};
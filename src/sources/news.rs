@@ -1,7 +1,11 @@
 use crate::{
     core::models::ScrapedData,
     core::scraper::ScraperEngine,
-    sources::source::{NewsSource, Source},
+    sources::{
+        feed, readability,
+        source::{apply_category_override, item_limit, NewsSource, Source},
+    },
+    utils::time,
 };
 use anyhow::Result;
 
@@ -10,6 +14,8 @@ impl NewsSource {
         Self {
             name: "News Source".to_string(),
             base_url: base_url.to_string(),
+            category: None,
+            max_items: None,
         }
     }
 
@@ -17,6 +23,18 @@ impl NewsSource {
         self.name = name.to_string();
         self
     }
+
+    /// Label every article with `category`
+    pub fn with_category(mut self, category: &str) -> Self {
+        self.category = Some(category.to_string());
+        self
+    }
+
+    /// Take at most `max_items` articles from each page
+    pub fn with_max_items(mut self, max_items: usize) -> Self {
+        self.max_items = Some(max_items);
+        self
+    }
 }
 
 #[async_trait::async_trait]
@@ -30,6 +48,19 @@ impl Source for NewsSource {
     }
 
     async fn scrape(&self, html: &str) -> Result<Vec<ScrapedData>> {
+        // Feeds are far more reliable than generic selectors, so prefer them when given one
+        if feed::looks_like_feed(html) {
+            let mut results = feed::parse_feed(html, self.name(), self.base_url())?;
+            results.truncate(item_limit(self.max_items));
+            apply_category_override(&mut results, self.category.as_deref());
+            log::info!("Parsed {} news articles from feed {}", results.len(), self.name());
+            return Ok(results);
+        }
+
+        if let Some(feed_url) = feed::discover_feed_url(html, self.base_url()) {
+            log::info!("{} advertises a feed at {}; consider using FeedSource", self.name(), feed_url);
+        }
+
         let document = ScraperEngine::parse_html(html);
         let mut results = Vec::new();
 
@@ -42,7 +73,15 @@ impl Source for NewsSource {
 
         let articles = ScraperEngine::select_element(&document, article_selector)?;
 
-        for _article in articles {
+        // On a single article's page its readable body beats the first <p>;
+        // a listing keeps the selector-based teaser
+        let main_text = if articles.len() == 1 {
+            readability::extract_main_text(&document)
+        } else {
+            None
+        };
+
+        for _article in articles.into_iter().take(item_limit(self.max_items)) {
             let mut data = ScrapedData::new(self.name().to_string(), self.base_url().to_string());
             
             // Extract title
@@ -53,7 +92,9 @@ impl Source for NewsSource {
             }
 
             // Extract content
-            if let Ok(contents) = ScraperEngine::select_element(&document, content_selector) {
+            if let Some(text) = &main_text {
+                data.content = Some(text.clone());
+            } else if let Ok(contents) = ScraperEngine::select_element(&document, content_selector) {
                 if let Some(content) = contents.get(0) {
                     data.content = Some(content.clone());
                 }
@@ -66,9 +107,13 @@ impl Source for NewsSource {
                 }
             }
 
-            // Extract date if available
+            // Extract date if available, keeping the raw text alongside the parsed timestamp
             if let Ok(dates) = ScraperEngine::select_element(&document, date_selector) {
                 if let Some(date) = dates.get(0) {
+                    match time::parse_datetime(date) {
+                        Some(published) => data.timestamp = published,
+                        None => log::debug!("Could not parse publish date '{}', using scrape time", date),
+                    }
                     data.metadata.insert("publish_date".to_string(), date.clone());
                 }
             }
@@ -76,7 +121,13 @@ impl Source for NewsSource {
             results.push(data);
         }
 
+        apply_category_override(&mut results, self.category.as_deref());
         log::info!("Scraped {} news articles from {}", results.len(), self.name());
         Ok(results)
     }
+
+    /// Feeds as well as HTML, since `scrape` parses a feed when given one
+    fn accepts_content_type(&self, mime: &str) -> bool {
+        matches!(mime, "text/html" | "application/xhtml+xml") || feed::is_feed_content_type(mime)
+    }
 }
\ No newline at end of file
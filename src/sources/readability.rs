@@ -0,0 +1,133 @@
+//! Readability-style extraction of an article's main text
+//!
+//! A trimmed-down port of the Readability heuristic. Every paragraph scores
+//! points for its parent and grandparent (more for long, comma-rich text),
+//! paragraphs inside navigation, sidebars, comments and the like are skipped,
+//! and the best-scoring container, discounted by how much of its text is
+//! links, is taken as the article body.
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use scraper::{ElementRef, Html, Selector};
+use std::collections::HashMap;
+
+/// Shorter paragraphs (captions, bylines, buttons) don't vote for a container
+const MIN_PARAGRAPH_CHARS: usize = 25;
+
+/// Less text than this means the page probably isn't an article
+const MIN_ARTICLE_CHARS: usize = 140;
+
+lazy_static! {
+    static ref PARAGRAPHS: Selector = Selector::parse("p, pre").unwrap();
+    static ref LINKS: Selector = Selector::parse("a").unwrap();
+    // Class or id of containers whose paragraphs are never the article...
+    static ref UNLIKELY: Regex = Regex::new(
+        r"(?i)ad-|ad_|banner|breadcrumb|comment|community|disqus|footer|menu|nav|popup|promo|related|share|sidebar|social|sponsor|widget"
+    )
+    .unwrap();
+    // ...unless it also looks like this
+    static ref MAYBE: Regex = Regex::new(r"(?i)article|body|column|content|main|story").unwrap();
+    static ref POSITIVE: Regex = Regex::new(r"(?i)article|blog|body|content|entry|main|page|post|story|text").unwrap();
+    static ref NEGATIVE: Regex = Regex::new(
+        r"(?i)banner|comment|contact|foot|masthead|meta|promo|related|share|sidebar|sponsor|tags|widget"
+    )
+    .unwrap();
+}
+
+/// The page's main text with paragraphs separated by a blank line, or
+/// `None` when no container holds enough prose to be an article
+pub fn extract_main_text(document: &Html) -> Option<String> {
+    let mut scores = HashMap::new();
+    for paragraph in document.select(&PARAGRAPHS) {
+        if in_boilerplate(paragraph, None) {
+            continue;
+        }
+        let text = normalized_text(paragraph);
+        let length = text.chars().count();
+        if length < MIN_PARAGRAPH_CHARS {
+            continue;
+        }
+
+        let score = 1.0 + text.matches(',').count() as f64 + (length / 100).min(3) as f64;
+        let mut containers = paragraph.ancestors().filter_map(ElementRef::wrap);
+        if let Some(parent) = containers.next() {
+            *scores.entry(parent.id()).or_insert_with(|| initial_score(parent)) += score;
+        }
+        if let Some(grandparent) = containers.next() {
+            *scores.entry(grandparent.id()).or_insert_with(|| initial_score(grandparent)) += score / 2.0;
+        }
+    }
+
+    let (best, _) = scores
+        .into_iter()
+        .filter_map(|(id, score)| {
+            let container = document.tree.get(id).and_then(ElementRef::wrap)?;
+            Some((container, score * (1.0 - link_density(container))))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))?;
+
+    let text = best
+        .select(&PARAGRAPHS)
+        .filter(|paragraph| !in_boilerplate(*paragraph, Some(best)))
+        .map(normalized_text)
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    (text.chars().count() >= MIN_ARTICLE_CHARS).then_some(text)
+}
+
+fn normalized_text(element: ElementRef) -> String {
+    element.text().flat_map(str::split_whitespace).collect::<Vec<_>>().join(" ")
+}
+
+fn class_and_id(element: ElementRef) -> String {
+    let value = element.value();
+    format!("{} {}", value.attr("class").unwrap_or_default(), value.id().unwrap_or_default())
+}
+
+/// Whether the element sits inside navigation, a sidebar, comments and so on,
+/// looking no further up than `within`
+fn in_boilerplate(element: ElementRef, within: Option<ElementRef>) -> bool {
+    element
+        .ancestors()
+        .filter_map(ElementRef::wrap)
+        .take_while(|ancestor| within.is_none_or(|within| ancestor.id() != within.id()))
+        .any(|ancestor| match ancestor.value().name() {
+            "nav" | "aside" | "footer" | "header" | "form" => true,
+            "body" | "html" => false,
+            _ => {
+                let names = class_and_id(ancestor);
+                UNLIKELY.is_match(&names) && !MAYBE.is_match(&names)
+            }
+        })
+}
+
+fn initial_score(container: ElementRef) -> f64 {
+    let tag = match container.value().name() {
+        "article" | "main" => 10.0,
+        "div" => 5.0,
+        "section" | "blockquote" | "pre" | "td" => 3.0,
+        "ol" | "ul" | "dl" | "li" | "form" => -3.0,
+        _ => 0.0,
+    };
+
+    let names = class_and_id(container);
+    let mut class = 0.0;
+    if POSITIVE.is_match(&names) {
+        class += 25.0;
+    }
+    if NEGATIVE.is_match(&names) {
+        class -= 25.0;
+    }
+    tag + class
+}
+
+/// Share of the element's text that is inside links
+fn link_density(element: ElementRef) -> f64 {
+    let total: usize = element.text().map(str::len).sum();
+    if total == 0 {
+        return 0.0;
+    }
+    let linked: usize = element.select(&LINKS).flat_map(|link| link.text()).map(str::len).sum();
+    linked as f64 / total as f64
+}
@@ -1,10 +1,10 @@
 use crate::{
     core::models::ScrapedData,
     core::scraper::ScraperEngine,
-    sources::source::{SocialSource, Source},
+    sources::source::{apply_category_override, item_limit, SocialSource, Source},
+    utils::time,
 };
 use anyhow::Result;
-use chrono::{DateTime, Utc};
 use regex::Regex;
 use lazy_static::lazy_static;
 use serde::Deserialize;
@@ -28,6 +28,8 @@ impl SocialSource {
         Self {
             name: "Social Media Source".to_string(),
             base_url: base_url.to_string(),
+            category: None,
+            max_items: None,
         }
     }
 
@@ -35,6 +37,8 @@ impl SocialSource {
         Self {
             name: "Twitter".to_string(),
             base_url: "https://twitter.com".to_string(),
+            category: None,
+            max_items: None,
         }
     }
 
@@ -42,6 +46,8 @@ impl SocialSource {
         Self {
             name: "Reddit".to_string(),
             base_url: "https://reddit.com".to_string(),
+            category: None,
+            max_items: None,
         }
     }
 
@@ -49,6 +55,18 @@ impl SocialSource {
         self.name = name.to_string();
         self
     }
+
+    /// Label every post with `category`
+    pub fn with_category(mut self, category: &str) -> Self {
+        self.category = Some(category.to_string());
+        self
+    }
+
+    /// Take at most `max_items` posts from each page
+    pub fn with_max_items(mut self, max_items: usize) -> Self {
+        self.max_items = Some(max_items);
+        self
+    }
 }
 
 #[async_trait::async_trait]
@@ -72,6 +90,8 @@ impl Source for SocialSource {
             _ => self.scrape_generic_social(&document, &mut results)?,
         }
 
+        results.truncate(item_limit(self.max_items));
+        apply_category_override(&mut results, self.category.as_deref());
         log::info!("Scraped {} social posts from {}", results.len(), self.name());
         Ok(results)
     }
@@ -121,8 +141,9 @@ impl SocialSource {
             // Extract timestamp
             if let Ok(timestamps) = ScraperEngine::select_element(document, timestamp_selector) {
                 if let Some(timestamp) = timestamps.get(0) {
-                    if let Ok(parsed_time) = DateTime::parse_from_rfc3339(timestamp) {
-                        data.timestamp = parsed_time.with_timezone(&Utc);
+                    // Timelines often show relative times ("2h ago") rather than ISO dates
+                    if let Some(parsed_time) = time::parse_datetime(timestamp) {
+                        data.timestamp = parsed_time;
                     }
                 }
             }
@@ -1,12 +1,174 @@
 use async_trait::async_trait;
+use crate::ai::DetectedSelectors;
 use crate::core::models::ScrapedData;
+use crate::sources::ecommerce::PriceSelectors;
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
+/// Extracts items from the pages the engine fetches for it.
+///
+/// The bundled sources' `with_max_items(n)` caps what each page yields:
+/// extraction stops once a page has produced `n` items, which is handy for
+/// trying selectors on a large listing. The cap applies per page, not to a
+/// whole paginated scrape.
+///
+/// Their `with_category` labels every item with a fixed category. It is set
+/// after extraction, so it wins over one guessed from the source name or read
+/// from the page; without it the items keep their own.
 #[async_trait]
 pub trait Source: Send + Sync {
     fn name(&self) -> &str;
     fn base_url(&self) -> &str;
     async fn scrape(&self, html: &str) -> Result<Vec<ScrapedData>>;
+
+    /// [`scrape`](Self::scrape) for a page fetched from `page_url`, which the
+    /// engine passes for every page it follows; sources that report where an
+    /// element was found override this
+    async fn scrape_at(&self, html: &str, _page_url: &str) -> Result<Vec<ScrapedData>> {
+        self.scrape(html).await
+    }
+
+    /// Whether a response with this MIME type (lowercase, without parameters)
+    /// can be handed to `scrape`; HTML only unless the source says otherwise
+    fn accepts_content_type(&self, mime: &str) -> bool {
+        matches!(mime, "text/html" | "application/xhtml+xml")
+    }
+
+    /// Extra headers and cookies the engine sends with this source's requests
+    fn request_defaults(&self) -> Option<&RequestDefaults> {
+        None
+    }
+
+    /// Whether the engine strips scripts, styles and comments from the page
+    /// before calling `scrape`; see [`strip_noise`](crate::utils::html::strip_noise)
+    fn pre_clean(&self) -> bool {
+        false
+    }
+
+    /// Absolute URL of the page after `page_url`, when the page links to one;
+    /// relative links resolve against `page_url`
+    fn next_page(&self, _html: &str, _page_url: &str) -> Option<String> {
+        None
+    }
+
+    /// Most pages [`ScraperEngine::scrape_source`](crate::core::scraper::ScraperEngine::scrape_source)
+    /// fetches by following [`next_page`](Self::next_page) links
+    fn max_pages(&self) -> usize {
+        1
+    }
+}
+
+/// Headers and cookies sent with every request for one source, e.g. an
+/// `Accept-Language` for a localized site or a consent cookie
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(default)]
+pub struct RequestDefaults {
+    pub headers: BTreeMap<String, String>,
+    pub cookies: BTreeMap<String, String>,
+}
+
+impl RequestDefaults {
+    pub fn is_empty(&self) -> bool {
+        self.headers.is_empty() && self.cookies.is_empty()
+    }
+
+    /// The cookies as one `Cookie` header value, `None` when there are none
+    pub fn cookie_header(&self) -> Option<String> {
+        if self.cookies.is_empty() {
+            return None;
+        }
+        Some(
+            self.cookies
+                .iter()
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect::<Vec<_>>()
+                .join("; "),
+        )
+    }
+
+    /// Fails with a `ConfigError` on a header name or value, or a cookie,
+    /// that can't be sent
+    pub fn check(&self) -> Result<()> {
+        use crate::utils::error::ScraperError;
+        use reqwest::header::{HeaderName, HeaderValue};
+
+        for (name, value) in &self.headers {
+            HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| ScraperError::ConfigError(format!("invalid header name '{}'", name)))?;
+            HeaderValue::from_str(value)
+                .map_err(|_| ScraperError::ConfigError(format!("invalid value for header '{}'", name)))?;
+        }
+        if let Some(cookies) = self.cookie_header() {
+            HeaderValue::from_str(&cookies).map_err(|_| ScraperError::ConfigError("invalid cookie value".to_string()))?;
+        }
+        Ok(())
+    }
+}
+
+/// A source with request defaults attached, e.g. as loaded from
+/// `config/sources.toml`; scrapes exactly like the source it wraps
+pub struct ConfiguredSource<S> {
+    pub inner: S,
+    pub request: RequestDefaults,
+    /// Pre-clean pages even when the inner source doesn't ask for it
+    pub pre_clean: bool,
+}
+
+impl<S: Source> ConfiguredSource<S> {
+    pub fn new(inner: S, request: RequestDefaults) -> Self {
+        Self {
+            inner,
+            request,
+            pre_clean: false,
+        }
+    }
+
+    pub fn with_pre_clean(mut self, enabled: bool) -> Self {
+        self.pre_clean = enabled;
+        self
+    }
+}
+
+#[async_trait]
+impl<S: Source> Source for ConfiguredSource<S> {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn base_url(&self) -> &str {
+        self.inner.base_url()
+    }
+
+    async fn scrape(&self, html: &str) -> Result<Vec<ScrapedData>> {
+        self.inner.scrape(html).await
+    }
+
+    async fn scrape_at(&self, html: &str, page_url: &str) -> Result<Vec<ScrapedData>> {
+        self.inner.scrape_at(html, page_url).await
+    }
+
+    fn accepts_content_type(&self, mime: &str) -> bool {
+        self.inner.accepts_content_type(mime)
+    }
+
+    fn request_defaults(&self) -> Option<&RequestDefaults> {
+        Some(&self.request).filter(|request| !request.is_empty())
+    }
+
+    fn pre_clean(&self) -> bool {
+        self.pre_clean || self.inner.pre_clean()
+    }
+
+    fn next_page(&self, html: &str, page_url: &str) -> Option<String> {
+        self.inner.next_page(html, page_url)
+    }
+
+    fn max_pages(&self) -> usize {
+        self.inner.max_pages()
+    }
 }
 
 pub enum SourceType {
@@ -14,6 +176,9 @@ pub enum SourceType {
     Ecommerce(EcommerceSource),
     Social(SocialSource),
     Custom(CustomSource),
+    Feed(FeedSource),
+    Table(TableSource),
+    AiSelector(AiSelectorSource),
 }
 
 #[async_trait]
@@ -24,6 +189,9 @@ impl Source for SourceType {
             SourceType::Ecommerce(source) => source.name(),
             SourceType::Social(source) => source.name(),
             SourceType::Custom(source) => source.name(),
+            SourceType::Feed(source) => source.name(),
+            SourceType::Table(source) => source.name(),
+            SourceType::AiSelector(source) => source.name(),
         }
     }
 
@@ -33,6 +201,9 @@ impl Source for SourceType {
             SourceType::Ecommerce(source) => source.base_url(),
             SourceType::Social(source) => source.base_url(),
             SourceType::Custom(source) => source.base_url(),
+            SourceType::Feed(source) => source.base_url(),
+            SourceType::Table(source) => source.base_url(),
+            SourceType::AiSelector(source) => source.base_url(),
         }
     }
 
@@ -42,28 +213,198 @@ impl Source for SourceType {
             SourceType::Ecommerce(source) => source.scrape(html).await,
             SourceType::Social(source) => source.scrape(html).await,
             SourceType::Custom(source) => source.scrape(html).await,
+            SourceType::Feed(source) => source.scrape(html).await,
+            SourceType::Table(source) => source.scrape(html).await,
+            SourceType::AiSelector(source) => source.scrape(html).await,
+        }
+    }
+
+    async fn scrape_at(&self, html: &str, page_url: &str) -> Result<Vec<ScrapedData>> {
+        match self {
+            SourceType::News(source) => source.scrape_at(html, page_url).await,
+            SourceType::Ecommerce(source) => source.scrape_at(html, page_url).await,
+            SourceType::Social(source) => source.scrape_at(html, page_url).await,
+            SourceType::Custom(source) => source.scrape_at(html, page_url).await,
+            SourceType::Feed(source) => source.scrape_at(html, page_url).await,
+            SourceType::Table(source) => source.scrape_at(html, page_url).await,
+            SourceType::AiSelector(source) => source.scrape_at(html, page_url).await,
+        }
+    }
+
+    fn accepts_content_type(&self, mime: &str) -> bool {
+        match self {
+            SourceType::News(source) => source.accepts_content_type(mime),
+            SourceType::Ecommerce(source) => source.accepts_content_type(mime),
+            SourceType::Social(source) => source.accepts_content_type(mime),
+            SourceType::Custom(source) => source.accepts_content_type(mime),
+            SourceType::Feed(source) => source.accepts_content_type(mime),
+            SourceType::Table(source) => source.accepts_content_type(mime),
+            SourceType::AiSelector(source) => source.accepts_content_type(mime),
+        }
+    }
+
+    fn pre_clean(&self) -> bool {
+        match self {
+            SourceType::News(source) => source.pre_clean(),
+            SourceType::Ecommerce(source) => source.pre_clean(),
+            SourceType::Social(source) => source.pre_clean(),
+            SourceType::Custom(source) => source.pre_clean(),
+            SourceType::Feed(source) => source.pre_clean(),
+            SourceType::Table(source) => source.pre_clean(),
+            SourceType::AiSelector(source) => source.pre_clean(),
+        }
+    }
+
+    fn next_page(&self, html: &str, page_url: &str) -> Option<String> {
+        match self {
+            SourceType::News(source) => source.next_page(html, page_url),
+            SourceType::Ecommerce(source) => source.next_page(html, page_url),
+            SourceType::Social(source) => source.next_page(html, page_url),
+            SourceType::Custom(source) => source.next_page(html, page_url),
+            SourceType::Feed(source) => source.next_page(html, page_url),
+            SourceType::Table(source) => source.next_page(html, page_url),
+            SourceType::AiSelector(source) => source.next_page(html, page_url),
+        }
+    }
+
+    fn max_pages(&self) -> usize {
+        match self {
+            SourceType::News(source) => source.max_pages(),
+            SourceType::Ecommerce(source) => source.max_pages(),
+            SourceType::Social(source) => source.max_pages(),
+            SourceType::Custom(source) => source.max_pages(),
+            SourceType::Feed(source) => source.max_pages(),
+            SourceType::Table(source) => source.max_pages(),
+            SourceType::AiSelector(source) => source.max_pages(),
+        }
+    }
+}
+
+/// Most items a source may return: its `max_items` cap, or no limit
+pub(crate) fn item_limit(max_items: Option<usize>) -> usize {
+    max_items.unwrap_or(usize::MAX)
+}
+
+/// Tag every item with a fixed category, overriding whatever the source inferred
+pub(crate) fn apply_category_override(results: &mut [ScrapedData], category: Option<&str>) {
+    if let Some(category) = category {
+        for item in results.iter_mut() {
+            item.category = Some(category.to_string());
         }
     }
 }
 
+/// A container element that matched a source's selector but didn't yield a
+/// usable item, kept so selector gaps can be diagnosed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnparsedElement {
+    pub source: String,
+    pub page_url: String,
+    /// Why no item was produced, e.g. "missing title"
+    pub reason: String,
+    /// Outer HTML of the element
+    pub html: String,
+}
+
+/// Append unparsed elements to a JSON Lines debug file. Failures are only
+/// logged, since the debug output must never break a scrape.
+pub(crate) fn record_unparsed(path: &Path, elements: &[UnparsedElement]) {
+    if elements.is_empty() {
+        return;
+    }
+
+    let write = || -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+        for element in elements {
+            writeln!(file, "{}", serde_json::to_string(element)?)?;
+        }
+        Ok(())
+    };
+
+    match write() {
+        Ok(()) => log::warn!(
+            "{} elements from {} could not be parsed; see {}",
+            elements.len(),
+            elements[0].source,
+            path.display()
+        ),
+        Err(e) => log::warn!("Failed to record unparsed elements to {}: {}", path.display(), e),
+    }
+}
+
 // These will be implemented in their respective modules
 pub struct NewsSource {
     pub name: String,
     pub base_url: String,
+    pub category: Option<String>,
+    /// Stop after this many articles per page
+    pub max_items: Option<usize>,
 }
 
 pub struct EcommerceSource {
     pub name: String,
     pub base_url: String,
+    pub category: Option<String>,
+    pub default_currency: String,
+    pub price_selectors: PriceSelectors,
+    /// JSON Lines file receiving products that had no title
+    pub unparsed_log: Option<PathBuf>,
+    /// Stop after this many products per page
+    pub max_items: Option<usize>,
+    /// Follow "next" links up to this many pages in total; one page when unset
+    pub max_pages: Option<usize>,
+    /// Link to the following page of the listing
+    pub next_page_selector: String,
 }
 
 pub struct SocialSource {
     pub name: String,
     pub base_url: String,
+    pub category: Option<String>,
+    /// Stop after this many posts per page
+    pub max_items: Option<usize>,
 }
 
 pub struct CustomSource {
     pub name: String,
     pub base_url: String,
     pub selectors: Vec<String>,
-}
\ No newline at end of file
+    pub category: Option<String>,
+    /// Stop after this many items per page
+    pub max_items: Option<usize>,
+    /// Strip scripts, styles and comments before the generic selectors run
+    pub pre_clean: bool,
+}
+
+pub struct FeedSource {
+    pub name: String,
+    pub base_url: String,
+    pub category: Option<String>,
+    /// Stop after this many entries per feed
+    pub max_items: Option<usize>,
+}
+
+pub struct TableSource {
+    pub name: String,
+    pub base_url: String,
+    pub category: Option<String>,
+    pub table_selector: String,
+    /// Lowercased column header -> `ScrapedData` field name
+    pub columns: std::collections::HashMap<String, String>,
+    /// Stop after this many rows per page
+    pub max_items: Option<usize>,
+}
+
+pub struct AiSelectorSource {
+    pub name: String,
+    pub base_url: String,
+    pub category: Option<String>,
+    pub selectors: Box<DetectedSelectors>,
+    /// Detections less confident than this are replaced by heuristic selectors
+    pub min_confidence: f32,
+    /// Stop after this many items per page
+    pub max_items: Option<usize>,
+}
@@ -0,0 +1,212 @@
+use crate::{
+    core::models::ScrapedData,
+    sources::source::{apply_category_override, item_limit, Source, TableSource},
+    utils::error::ScraperError,
+};
+use anyhow::Result;
+use lazy_static::lazy_static;
+use regex::Regex;
+use scraper::{ElementRef, Html, Selector};
+use std::collections::HashMap;
+
+lazy_static! {
+    static ref NUMBER_REGEX: Regex = Regex::new(r"\d+(?:\.\d+)?").unwrap();
+}
+
+impl TableSource {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            name: "Table Source".to_string(),
+            base_url: base_url.to_string(),
+            category: None,
+            table_selector: "table".to_string(),
+            columns: HashMap::new(),
+            max_items: None,
+        }
+    }
+
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    /// Label every row with `category`
+    pub fn with_category(mut self, category: &str) -> Self {
+        self.category = Some(category.to_string());
+        self
+    }
+
+    /// Which table(s) on the page to read; defaults to every `<table>`
+    pub fn with_table_selector(mut self, selector: &str) -> Self {
+        self.table_selector = selector.to_string();
+        self
+    }
+
+    /// Map a column header (matched case-insensitively) to a `ScrapedData` field:
+    /// `title`, `content`, `price`, `author`, `url`, `image_url` or `category`.
+    /// Unmapped columns end up in metadata under their header text.
+    pub fn with_column(mut self, header: &str, field: &str) -> Self {
+        self.columns.insert(header.trim().to_lowercase(), field.to_string());
+        self
+    }
+
+    /// Take at most `max_items` rows from each page
+    pub fn with_max_items(mut self, max_items: usize) -> Self {
+        self.max_items = Some(max_items);
+        self
+    }
+
+    // Data rows of one table, stopping once `limit` rows are read
+    fn table_rows(&self, table: ElementRef, limit: usize) -> Vec<ScrapedData> {
+        let row_selector = Selector::parse("tr").unwrap();
+        let head_row_selector = Selector::parse("thead tr").unwrap();
+
+        let rows: Vec<ElementRef> = table.select(&row_selector).collect();
+        // Header row: the first row of <thead>, or a leading row made of <th> cells
+        let header_row = table.select(&head_row_selector).next().or_else(|| {
+            rows.first()
+                .copied()
+                .filter(|row| row.children().filter_map(ElementRef::wrap).all(|cell| cell.value().name() == "th"))
+        });
+
+        let mut headers = header_row.map(expand_cells).unwrap_or_default();
+        disambiguate(&mut headers);
+
+        let mut results = Vec::new();
+        for row in rows {
+            if results.len() >= limit {
+                break;
+            }
+
+            let in_head = row
+                .ancestors()
+                .any(|node| node.value().as_element().is_some_and(|element| element.name() == "thead"));
+            if in_head || Some(row.id()) == header_row.map(|header| header.id()) {
+                continue;
+            }
+
+            let cells = expand_cells(row);
+            if cells.iter().all(|cell| cell.is_empty()) {
+                continue;
+            }
+
+            let mut data = ScrapedData::new(self.name().to_string(), self.base_url().to_string());
+            if let Some(href) = first_link(row) {
+                data.url = self.resolve_url(&href);
+            }
+
+            for (index, value) in cells.into_iter().enumerate() {
+                let header = headers
+                    .get(index)
+                    .cloned()
+                    .unwrap_or_else(|| format!("column_{}", index + 1));
+                if value.is_empty() {
+                    continue;
+                }
+
+                match self.columns.get(&header.to_lowercase()).map(String::as_str) {
+                    Some("title") => data.title = Some(value),
+                    Some("content") => data.content = Some(value),
+                    Some("author") => data.author = Some(value),
+                    Some("category") => data.category = Some(value),
+                    Some("image_url") => data.image_url = Some(self.resolve_url(&value)),
+                    Some("url") => data.url = self.resolve_url(&value),
+                    Some("price") => {
+                        data.price = parse_number(&value);
+                        data.metadata.insert("price_text".to_string(), value);
+                    }
+                    _ => {
+                        data.metadata.insert(header, value);
+                    }
+                }
+            }
+
+            results.push(data);
+        }
+
+        results
+    }
+
+    fn resolve_url(&self, href: &str) -> String {
+        url::Url::parse(self.base_url())
+            .and_then(|base| base.join(href))
+            .map(|url| url.to_string())
+            .unwrap_or_else(|_| href.to_string())
+    }
+}
+
+// Cell texts of a row, repeating a cell across every column its colspan covers
+fn expand_cells(row: ElementRef) -> Vec<String> {
+    let mut cells = Vec::new();
+    for cell in row.children().filter_map(ElementRef::wrap) {
+        if !matches!(cell.value().name(), "td" | "th") {
+            continue;
+        }
+
+        let text = cell.text().collect::<Vec<_>>().join(" ");
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let span = cell
+            .value()
+            .attr("colspan")
+            .and_then(|span| span.trim().parse::<usize>().ok())
+            .unwrap_or(1)
+            .clamp(1, 100);
+        cells.extend(std::iter::repeat_n(text, span));
+    }
+    cells
+}
+
+// A header spanning several columns yields "Price", "Price 2", ... so metadata keys stay unique
+fn disambiguate(headers: &mut [String]) {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for header in headers.iter_mut() {
+        let count = seen.entry(header.clone()).or_insert(0);
+        *count += 1;
+        if *count > 1 {
+            *header = format!("{} {}", header, count);
+        }
+    }
+}
+
+fn first_link(row: ElementRef) -> Option<String> {
+    let link_selector = Selector::parse("a[href]").unwrap();
+    row.select(&link_selector)
+        .next()
+        .and_then(|link| link.value().attr("href"))
+        .map(str::to_string)
+}
+
+pub(crate) fn parse_number(text: &str) -> Option<f64> {
+    let text = text.replace(',', "");
+    NUMBER_REGEX.find(&text)?.as_str().parse().ok()
+}
+
+#[async_trait::async_trait]
+impl Source for TableSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    async fn scrape(&self, html: &str) -> Result<Vec<ScrapedData>> {
+        let document = Html::parse_document(html);
+        let table_selector =
+            Selector::parse(&self.table_selector).map_err(|e| ScraperError::SelectorError(e.to_string()))?;
+
+        let limit = item_limit(self.max_items);
+        let mut results: Vec<ScrapedData> = Vec::new();
+        for table in document.select(&table_selector) {
+            if results.len() >= limit {
+                break;
+            }
+            results.extend(self.table_rows(table, limit - results.len()));
+        }
+
+        apply_category_override(&mut results, self.category.as_deref());
+        log::info!("Scraped {} table rows from {}", results.len(), self.name());
+        Ok(results)
+    }
+}
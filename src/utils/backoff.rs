@@ -0,0 +1,100 @@
+//! Randomized delays with an injectable source of randomness
+//!
+//! Production code uses entropy-seeded jitter, while tests pass a fixed seed
+//! (or no jitter at all) so timing assertions are exact.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Source of randomness for delays and rotation choices
+pub struct Jitter {
+    ratio: f64,
+    rng: Option<Mutex<StdRng>>,
+}
+
+impl Jitter {
+    /// Leave delays untouched and always pick the first candidate
+    pub fn none() -> Self {
+        Self { ratio: 0.0, rng: None }
+    }
+
+    /// Scale delays by a random factor within `1 ± ratio`
+    pub fn random(ratio: f64) -> Self {
+        Self::with_rng(ratio, StdRng::from_entropy())
+    }
+
+    /// Like [`Jitter::random`], but reproducible for a given seed
+    pub fn seeded(ratio: f64, seed: u64) -> Self {
+        Self::with_rng(ratio, StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(ratio: f64, rng: StdRng) -> Self {
+        Self {
+            ratio: ratio.clamp(0.0, 1.0),
+            rng: Some(Mutex::new(rng)),
+        }
+    }
+
+    pub fn ratio(&self) -> f64 {
+        self.ratio
+    }
+
+    pub fn apply(&self, delay: Duration) -> Duration {
+        match &self.rng {
+            Some(rng) if self.ratio > 0.0 => {
+                let factor = rng.lock().unwrap().gen_range(1.0 - self.ratio..=1.0 + self.ratio);
+                delay.mul_f64(factor)
+            }
+            _ => delay,
+        }
+    }
+
+    /// Pick one of several candidates, e.g. a user agent or proxy to rotate to
+    pub fn choose<'a, T>(&self, candidates: &'a [T]) -> Option<&'a T> {
+        match &self.rng {
+            Some(rng) if !candidates.is_empty() => {
+                let index = rng.lock().unwrap().gen_range(0..candidates.len());
+                candidates.get(index)
+            }
+            _ => candidates.first(),
+        }
+    }
+}
+
+impl Default for Jitter {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+/// Exponential backoff for retries: `base * 2^attempt`, capped at `max`, then jittered
+pub struct Backoff {
+    base: Duration,
+    max: Duration,
+    jitter: Jitter,
+}
+
+impl Backoff {
+    /// Retries spread by ±50% so clients that failed together don't retry together
+    pub fn new(base: Duration, max: Duration) -> Self {
+        Self {
+            base,
+            max,
+            jitter: Jitter::random(0.5),
+        }
+    }
+
+    pub fn with_jitter(mut self, jitter: Jitter) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Delay before retry number `attempt` (0 for the first retry)
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let multiplier = 2u32.checked_pow(attempt).unwrap_or(u32::MAX);
+        let delay = self.base.saturating_mul(multiplier).min(self.max);
+        self.jitter.apply(delay).min(self.max)
+    }
+}
@@ -1,6 +1,7 @@
 use anyhow::Result;
 use moka::sync::Cache;
 use std::hash::Hash;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Duration;
 use serde::{Serialize, de::DeserializeOwned};
 use std::path::Path;
@@ -9,6 +10,8 @@ use tokio::fs;
 pub struct CacheSystem<K, V> {
     memory_cache: Option<Cache<K, V>>,
     cache_dir: Option<String>,
+    hits: AtomicU64,
+    misses: AtomicU64,
 }
 
 impl<K, V> CacheSystem<K, V>
@@ -25,6 +28,8 @@ where
         Self {
             memory_cache: Some(cache),
             cache_dir: None,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
         }
     }
 
@@ -32,6 +37,8 @@ where
         Self {
             memory_cache: None,
             cache_dir: Some(cache_dir.to_string()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
         }
     }
 
@@ -44,6 +51,8 @@ where
         Self {
             memory_cache: Some(cache),
             cache_dir: Some(cache_dir.to_string()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
         }
     }
 
@@ -56,6 +65,7 @@ where
         if let Some(cache) = &self.memory_cache {
             if let Some(value) = cache.get(key) {
                 log::debug!("Cache hit (memory) for key: {}", key.to_string());
+                self.hits.fetch_add(1, Ordering::Relaxed);
                 return Some(value);
             }
         }
@@ -69,11 +79,13 @@ where
                     
                     // Note: Not repopulating memory cache to avoid type issues
                     
+                    self.hits.fetch_add(1, Ordering::Relaxed);
                     return Some(value);
                 }
             }
         }
 
+        self.misses.fetch_add(1, Ordering::Relaxed);
         None
     }
 
@@ -159,21 +171,39 @@ where
     }
 
     pub fn stats(&self) -> CacheStats {
-        if let Some(cache) = &self.memory_cache {
-            CacheStats {
-                entry_count: cache.entry_count(),
-                hit_rate: 0.0, // Stats not available in sync cache
-                miss_rate: 0.0,
-            }
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        let lookups = hits + misses;
+        let (hit_rate, miss_rate) = if lookups == 0 {
+            (0.0, 0.0)
         } else {
-            CacheStats::default()
+            (hits as f64 / lookups as f64, misses as f64 / lookups as f64)
+        };
+
+        let entry_count = match &self.memory_cache {
+            Some(cache) => {
+                // moka counts lazily; flush pending inserts/evictions first
+                cache.run_pending_tasks();
+                cache.entry_count()
+            }
+            None => 0,
+        };
+
+        CacheStats {
+            entry_count,
+            hits,
+            misses,
+            hit_rate,
+            miss_rate,
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, serde::Deserialize, utoipa::ToSchema)]
 pub struct CacheStats {
     pub entry_count: u64,
+    pub hits: u64,
+    pub misses: u64,
     pub hit_rate: f64,
     pub miss_rate: f64,
 }
@@ -182,6 +212,8 @@ impl Default for CacheStats {
     fn default() -> Self {
         Self {
             entry_count: 0,
+            hits: 0,
+            misses: 0,
             hit_rate: 0.0,
             miss_rate: 0.0,
         }
@@ -8,6 +8,15 @@ pub enum ScraperError {
     #[error("HTTP error: {0}")]
     HttpError(reqwest::StatusCode),
     
+    #[error("Request blocked by server: {0}")]
+    Blocked(reqwest::StatusCode),
+    
+    #[error("Rate limited by server (retry after: {retry_after:?}s)")]
+    RateLimited { retry_after: Option<u64> },
+    
+    #[error("Unsupported content type: {0}")]
+    UnsupportedContentType(String),
+    
     #[error("Selector parsing error: {0}")]
     SelectorError(String),
     
@@ -33,6 +42,31 @@ pub enum ScraperError {
     ValidationError(String),
 }
 
+impl ScraperError {
+    /// Map a non-success HTTP status to the most specific error variant
+    pub fn from_status(status: reqwest::StatusCode, retry_after: Option<u64>) -> Self {
+        match status {
+            reqwest::StatusCode::TOO_MANY_REQUESTS => ScraperError::RateLimited { retry_after },
+            reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => {
+                ScraperError::Blocked(status)
+            }
+            _ => ScraperError::HttpError(status),
+        }
+    }
+
+    /// A copy of errors describing the server's answer (status codes, content
+    /// type), which are the same for everyone asking; `None` for the rest
+    pub(crate) fn response_error_copy(&self) -> Option<Self> {
+        match self {
+            ScraperError::HttpError(status) => Some(ScraperError::HttpError(*status)),
+            ScraperError::Blocked(status) => Some(ScraperError::Blocked(*status)),
+            ScraperError::RateLimited { retry_after } => Some(ScraperError::RateLimited { retry_after: *retry_after }),
+            ScraperError::UnsupportedContentType(mime) => Some(ScraperError::UnsupportedContentType(mime.clone())),
+            _ => None,
+        }
+    }
+}
+
 impl From<toml::de::Error> for ScraperError {
     fn from(err: toml::de::Error) -> Self {
         ScraperError::ConfigError(err.to_string())
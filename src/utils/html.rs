@@ -0,0 +1,21 @@
+//! Cleanup applied to fetched HTML before a source parses it
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::borrow::Cow;
+
+lazy_static! {
+    // Elements whose content is never page text, plus comments. Non-greedy so
+    // each match stops at the nearest closing tag.
+    static ref NOISE_REGEX: Regex = Regex::new(
+        r"(?is)<script\b[^>]*>.*?</script\s*>|<style\b[^>]*>.*?</style\s*>|<noscript\b[^>]*>.*?</noscript\s*>|<!--.*?-->"
+    )
+    .unwrap();
+}
+
+/// The HTML without `<script>`, `<style>` and `<noscript>` elements or
+/// comments, so generic selectors only see the page's visible content.
+/// Borrowed when there was nothing to remove.
+pub fn strip_noise(html: &str) -> Cow<'_, str> {
+    NOISE_REGEX.replace_all(html, "")
+}
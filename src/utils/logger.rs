@@ -1,28 +1,67 @@
 use anyhow::Result;
 use log::LevelFilter;
+use tracing_subscriber::EnvFilter;
 
+// Logs go through tracing-subscriber, which also picks up `log` records, so
+// lines emitted while handling an API request carry that request's span
+// (including its `X-Request-Id`)
 pub fn setup_logger() -> Result<()> {
-    env_logger::Builder::new()
-        .filter_level(LevelFilter::Info)
-        .format_timestamp_secs()
-        .format_module_path(false)
-        .format_target(false)
-        .init();
-        
-    Ok(())
+    setup_logger_with_level(LevelFilter::Info)
 }
 
 pub fn setup_logger_with_level(level: LevelFilter) -> Result<()> {
-    env_logger::Builder::new()
-        .filter_level(level)
-        .format_timestamp_secs()
-        .format_module_path(false)
-        .format_target(false)
-        .init();
-        
+    let level = match level {
+        LevelFilter::Off => tracing_subscriber::filter::LevelFilter::OFF,
+        LevelFilter::Error => tracing_subscriber::filter::LevelFilter::ERROR,
+        LevelFilter::Warn => tracing_subscriber::filter::LevelFilter::WARN,
+        LevelFilter::Info => tracing_subscriber::filter::LevelFilter::INFO,
+        LevelFilter::Debug => tracing_subscriber::filter::LevelFilter::DEBUG,
+        LevelFilter::Trace => tracing_subscriber::filter::LevelFilter::TRACE,
+    };
+
+    tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_target(false)
+        .try_init()
+        .map_err(|e| anyhow::anyhow!("Failed to initialize logger: {}", e))?;
+
     Ok(())
 }
 
+/// Like [`setup_logger`], with per-target levels, e.g. `&[("sqlx", LevelFilter::Warn)]`.
+///
+/// Directives from `RUST_LOG` (`info,rust_scraper_pro::core::scraper=debug`)
+/// are applied on top, so the environment can still override them.
+pub fn setup_logger_with_targets(targets: &[(&str, LevelFilter)]) -> Result<()> {
+    let filter = targets_filter(targets, std::env::var("RUST_LOG").ok().as_deref())?;
+
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_target(false)
+        .try_init()
+        .map_err(|e| anyhow::anyhow!("Failed to initialize logger: {}", e))?;
+
+    Ok(())
+}
+
+/// Filter used by [`setup_logger_with_targets`]: `info` by default, then the
+/// per-target levels, then any `RUST_LOG`-style directives
+pub fn targets_filter(targets: &[(&str, LevelFilter)], env_directives: Option<&str>) -> Result<EnvFilter> {
+    let mut filter = EnvFilter::new("info");
+    for (target, level) in targets {
+        let directive = format!("{}={}", target, level.as_str().to_lowercase());
+        filter = filter.add_directive(directive.parse()?);
+    }
+
+    for directive in env_directives.unwrap_or_default().split(',').map(str::trim) {
+        if !directive.is_empty() {
+            filter = filter.add_directive(directive.parse()?);
+        }
+    }
+
+    Ok(filter)
+}
+
 pub fn setup_test_logger() -> Result<()> {
     env_logger::Builder::new()
         .filter_level(LevelFilter::Debug)
@@ -30,4 +69,4 @@ pub fn setup_test_logger() -> Result<()> {
         .try_init()?;
         
     Ok(())
-}
\ No newline at end of file
+}
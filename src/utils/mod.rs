@@ -1,10 +1,22 @@
+pub mod backoff;
 pub mod cache;
 pub mod error;
+pub mod html;
 pub mod logger;
 pub mod rate_limiter;
+pub mod raw_html;
+#[cfg(feature = "redis")]
+pub mod redis_cache;
+pub mod robots;
 pub mod time;
 
+pub use backoff::{Backoff, Jitter};
 pub use cache::HtmlCache;
 pub use error::ScraperError;
-pub use logger::{setup_logger, setup_logger_with_level, setup_test_logger};
-pub use rate_limiter::RateLimiter;
+pub use html::strip_noise;
+pub use logger::{setup_logger, setup_logger_with_level, setup_logger_with_targets, setup_test_logger};
+pub use rate_limiter::{AdaptiveDelayState, HostDelayStats, HostLimiter, PolitenessController, RateLimiter};
+pub use raw_html::{RawHtmlStore, RAW_HTML_PATH_KEY};
+#[cfg(feature = "redis")]
+pub use redis_cache::{RedisCache, RedisHtmlCache};
+pub use robots::RobotsRules;
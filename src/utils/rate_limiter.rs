@@ -1,4 +1,12 @@
+use crate::ai::adaptive_delay::{AdaptiveDelayConfig, AdaptiveDelayController, AdaptiveDelayStats};
+use crate::utils::{backoff::Jitter, error::ScraperError};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use tokio::time::sleep;
 
 pub struct RateLimiter {
@@ -31,4 +39,279 @@ impl RateLimiter {
     pub fn get_interval(&self) -> Duration {
         self.interval
     }
-}
\ No newline at end of file
+}
+
+/// Caps the number of simultaneous requests to any single host
+pub struct HostLimiter {
+    max_per_host: usize,
+    semaphores: Mutex<HashMap<String, Arc<Semaphore>>>,
+}
+
+impl HostLimiter {
+    pub fn new(max_per_host: usize) -> Self {
+        Self {
+            max_per_host: max_per_host.max(1),
+            semaphores: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Wait for a free slot for the URL's host; the slot is released when the permit is dropped
+    pub async fn acquire(&self, url: &str) -> OwnedSemaphorePermit {
+        let host = host_key(url);
+
+        let semaphore = self
+            .semaphores
+            .lock()
+            .unwrap()
+            .entry(host)
+            .or_insert_with(|| Arc::new(Semaphore::new(self.max_per_host)))
+            .clone();
+
+        semaphore.acquire_owned().await.expect("host semaphore is never closed")
+    }
+
+    pub fn max_per_host(&self) -> usize {
+        self.max_per_host
+    }
+}
+
+/// Per-host delay between requests, taking the largest of three signals:
+/// the configured interval, the host's robots.txt `Crawl-delay`, and an
+/// adaptive delay derived from the host's recent response times.
+pub struct PolitenessController {
+    base_delay: Duration,
+    adaptive_config: Option<AdaptiveDelayConfig>,
+    jitter: Jitter,
+    hosts: Mutex<HashMap<String, HostPoliteness>>,
+}
+
+/// Adaptive-delay samples per host, saved between runs so a scheduled scrape
+/// starts with the delays the previous run settled on
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AdaptiveDelayState {
+    /// Recent response times in milliseconds, oldest first, keyed by host
+    pub hosts: BTreeMap<String, Vec<u64>>,
+}
+
+/// Delay state of one host, as reported by [`PolitenessController::host_stats`]
+#[derive(Debug, Clone)]
+pub struct HostDelayStats {
+    pub host: String,
+    /// Delay currently enforced between requests
+    pub delay: Duration,
+    pub crawl_delay: Option<Duration>,
+    /// Response-time statistics, when adaptive delays are enabled
+    pub adaptive: Option<AdaptiveDelayStats>,
+    /// Factor the adaptive delay is raised by because of recent failures
+    pub error_escalation: f64,
+}
+
+#[derive(Default)]
+struct HostPoliteness {
+    crawl_delay: Option<Duration>,
+    adaptive: Option<AdaptiveDelayController>,
+    next_slot: Option<Instant>,
+}
+
+impl PolitenessController {
+    pub fn new(base_delay_ms: u64) -> Self {
+        Self {
+            base_delay: Duration::from_millis(base_delay_ms),
+            adaptive_config: None,
+            jitter: Jitter::none(),
+            hosts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Slow down for hosts that respond slowly, using one adaptive controller per host
+    pub fn with_adaptive(mut self, config: AdaptiveDelayConfig) -> Self {
+        self.adaptive_config = Some(config);
+        self
+    }
+
+    /// Randomize the spacing between requests so they don't arrive in lockstep
+    pub fn with_jitter(mut self, jitter: Jitter) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Record the `Crawl-delay` a host asked for in its robots.txt
+    pub fn set_crawl_delay(&self, url: &str, delay: Duration) {
+        self.hosts.lock().unwrap().entry(host_key(url)).or_default().crawl_delay = Some(delay);
+    }
+
+    /// Feed a response time into the host's adaptive delay (no-op unless adaptive is enabled)
+    pub fn record_response_time(&self, url: &str, duration: Duration) {
+        let Some(config) = &self.adaptive_config else {
+            return;
+        };
+
+        let mut hosts = self.hosts.lock().unwrap();
+        hosts
+            .entry(host_key(url))
+            .or_default()
+            .adaptive
+            .get_or_insert_with(|| AdaptiveDelayController::new(config.clone()))
+            .record_response_time(duration);
+    }
+
+    /// Record whether a request to the URL's host succeeded, so repeated
+    /// failures escalate its adaptive delay (no-op unless adaptive is enabled)
+    pub fn record_outcome(&self, url: &str, success: bool) {
+        let Some(config) = &self.adaptive_config else {
+            return;
+        };
+
+        let mut hosts = self.hosts.lock().unwrap();
+        let adaptive = hosts
+            .entry(host_key(url))
+            .or_default()
+            .adaptive
+            .get_or_insert_with(|| AdaptiveDelayController::new(config.clone()));
+        if success {
+            adaptive.record_success();
+        } else {
+            adaptive.record_failure();
+        }
+    }
+
+    /// Delay state of every host seen so far, sorted by host
+    pub fn host_stats(&self) -> Vec<HostDelayStats> {
+        let hosts = self.hosts.lock().unwrap();
+        let mut stats: Vec<HostDelayStats> = hosts
+            .iter()
+            .map(|(host, politeness)| HostDelayStats {
+                host: host.clone(),
+                delay: Self::delay_for(self.base_delay, Some(politeness)),
+                crawl_delay: politeness.crawl_delay,
+                adaptive: politeness.adaptive.as_ref().map(AdaptiveDelayController::get_stats),
+                error_escalation: politeness.adaptive.as_ref().map_or(1.0, AdaptiveDelayController::error_escalation),
+            })
+            .collect();
+        stats.sort_by(|a, b| a.host.cmp(&b.host));
+        stats
+    }
+
+    /// The response-time samples of every host seen so far
+    pub fn adaptive_state(&self) -> AdaptiveDelayState {
+        let hosts = self.hosts.lock().unwrap();
+        let hosts = hosts
+            .iter()
+            .filter_map(|(host, politeness)| {
+                let samples = politeness.adaptive.as_ref()?.samples();
+                let millis = samples.iter().map(|sample| sample.as_millis() as u64).collect();
+                Some((host.clone(), millis))
+            })
+            .collect();
+        AdaptiveDelayState { hosts }
+    }
+
+    /// Seed each host's adaptive delay with saved samples, replacing what was
+    /// recorded; ignored unless adaptive delays are enabled
+    pub fn restore_adaptive_state(&self, state: &AdaptiveDelayState) {
+        let Some(config) = &self.adaptive_config else {
+            log::warn!("Ignoring saved adaptive delays: adaptive mode is not enabled");
+            return;
+        };
+
+        let mut hosts = self.hosts.lock().unwrap();
+        for (host, samples) in &state.hosts {
+            hosts
+                .entry(host.clone())
+                .or_default()
+                .adaptive
+                .get_or_insert_with(|| AdaptiveDelayController::new(config.clone()))
+                .restore_samples(samples.iter().map(|&ms| Duration::from_millis(ms)));
+        }
+    }
+
+    /// Write [`adaptive_state`](Self::adaptive_state) to a JSON file
+    pub async fn save_adaptive_state<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(ScraperError::IoError)?;
+        }
+
+        let state = self.adaptive_state();
+        let json = serde_json::to_string_pretty(&state).map_err(ScraperError::SerializationError)?;
+        tokio::fs::write(path, json)
+            .await
+            .map_err(ScraperError::IoError)
+            .with_context(|| format!("Failed to write adaptive delays to {}", path.display()))?;
+
+        log::debug!("Saved adaptive delays for {} hosts to {}", state.hosts.len(), path.display());
+        Ok(())
+    }
+
+    /// Restore samples saved by [`save_adaptive_state`](Self::save_adaptive_state),
+    /// returning how many hosts were loaded. A missing file (first run) loads nothing.
+    pub async fn load_adaptive_state<P: AsRef<Path>>(&self, path: P) -> Result<usize> {
+        let path = path.as_ref();
+        let content = match tokio::fs::read_to_string(path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => {
+                return Err(ScraperError::IoError(e))
+                    .with_context(|| format!("Failed to read adaptive delays from {}", path.display()));
+            }
+        };
+        let state: AdaptiveDelayState = serde_json::from_str(&content)
+            .map_err(ScraperError::SerializationError)
+            .with_context(|| format!("Failed to parse adaptive delays in {}", path.display()))?;
+
+        self.restore_adaptive_state(&state);
+        Ok(state.hosts.len())
+    }
+
+    /// Delay currently enforced between requests to the URL's host
+    pub fn effective_delay(&self, url: &str) -> Duration {
+        let hosts = self.hosts.lock().unwrap();
+        Self::delay_for(self.base_delay, hosts.get(&host_key(url)))
+    }
+
+    fn delay_for(base_delay: Duration, host: Option<&HostPoliteness>) -> Duration {
+        let Some(host) = host else {
+            return base_delay;
+        };
+
+        let adaptive = host.adaptive.as_ref().map(|adaptive| adaptive.calculate_delay());
+        [Some(base_delay), host.crawl_delay, adaptive]
+            .into_iter()
+            .flatten()
+            .max()
+            .unwrap_or(base_delay)
+    }
+
+    /// Wait for the URL's host to become available, returning how long we slept.
+    ///
+    /// Slots are reserved under the lock, so concurrent callers targeting the same
+    /// host are spaced out rather than released together.
+    pub async fn wait(&self, url: &str) -> Duration {
+        let now = Instant::now();
+        let slot = {
+            let mut hosts = self.hosts.lock().unwrap();
+            let host = hosts.entry(host_key(url)).or_default();
+            let slot = host.next_slot.map_or(now, |next| next.max(now));
+            host.next_slot = Some(slot + self.jitter.apply(Self::delay_for(self.base_delay, Some(host))));
+            slot
+        };
+
+        let wait_time = slot - now;
+        if !wait_time.is_zero() {
+            log::debug!("Politeness delay for {}: waiting {}ms", url, wait_time.as_millis());
+            tokio::time::sleep_until(slot.into()).await;
+        }
+        wait_time
+    }
+}
+
+// Host (with explicit port) used to group requests; unparsable URLs share one bucket
+pub(crate) fn host_key(url: &str) -> String {
+    url::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(|h| match u.port() {
+            Some(port) => format!("{}:{}", h, port),
+            None => h.to_string(),
+        }))
+        .unwrap_or_default()
+}
@@ -0,0 +1,84 @@
+//! Archive of fetched pages, so a fixed parser can be re-run against the
+//! HTML that items were originally scraped from
+
+use crate::{core::models::ScrapedData, utils::error::ScraperError};
+use anyhow::{Context, Result};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Metadata key holding the path of the stored page an item was scraped from
+pub const RAW_HTML_PATH_KEY: &str = "raw_html_path";
+
+/// Stores pages gzip-compressed in a directory, one file per distinct page.
+///
+/// Files are named after a hash of the HTML, so fetching an unchanged page
+/// again reuses its file while every version of a changing page is kept.
+#[derive(Debug, Clone)]
+pub struct RawHtmlStore {
+    dir: PathBuf,
+}
+
+impl RawHtmlStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Store a page and return the path it was written to
+    pub async fn save(&self, html: &str) -> Result<PathBuf> {
+        let path = self.dir.join(format!("{:x}.html.gz", md5::compute(html)));
+        if tokio::fs::try_exists(&path).await.unwrap_or(false) {
+            return Ok(path);
+        }
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(html.as_bytes()).map_err(ScraperError::IoError)?;
+        let compressed = encoder.finish().map_err(ScraperError::IoError)?;
+
+        tokio::fs::create_dir_all(&self.dir)
+            .await
+            .map_err(ScraperError::IoError)
+            .with_context(|| format!("Failed to create {}", self.dir.display()))?;
+
+        // Written under a temporary name first so a reader never sees a partial file
+        let temp = self.dir.join(format!(".{}.tmp", uuid::Uuid::new_v4()));
+        tokio::fs::write(&temp, compressed)
+            .await
+            .map_err(ScraperError::IoError)
+            .with_context(|| format!("Failed to write {}", temp.display()))?;
+        if let Err(e) = tokio::fs::rename(&temp, &path).await {
+            let _ = tokio::fs::remove_file(&temp).await;
+            return Err(anyhow::Error::new(ScraperError::IoError(e)).context(format!("Failed to write {}", path.display())));
+        }
+
+        Ok(path)
+    }
+
+    /// Read back a page written by [`save`](Self::save)
+    pub async fn load(path: impl AsRef<Path>) -> Result<String> {
+        let path = path.as_ref();
+        let compressed = tokio::fs::read(path)
+            .await
+            .map_err(ScraperError::IoError)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+
+        let mut html = String::new();
+        GzDecoder::new(compressed.as_slice())
+            .read_to_string(&mut html)
+            .map_err(ScraperError::IoError)
+            .with_context(|| format!("Failed to decompress {}", path.display()))?;
+        Ok(html)
+    }
+
+    /// The page an item was scraped from, if it was stored
+    pub async fn load_for(item: &ScrapedData) -> Result<Option<String>> {
+        match item.metadata.get(RAW_HTML_PATH_KEY) {
+            Some(path) => Self::load(path).await.map(Some),
+            None => Ok(None),
+        }
+    }
+}
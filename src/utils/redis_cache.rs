@@ -0,0 +1,228 @@
+//! Cache shared by several scraper instances through Redis
+
+use crate::utils::{cache::CacheStats, error::ScraperError};
+use anyhow::{Context, Result};
+use futures::StreamExt;
+use redis::aio::MultiplexedConnection;
+use redis::{AsyncCommands, Client, RedisResult, SetExpiry, SetOptions};
+use serde::{de::DeserializeOwned, Serialize};
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+/// Prefix for every key written, so `clear` only removes this cache's entries
+pub const DEFAULT_KEY_PREFIX: &str = "rust-scraper-pro:";
+
+const SCAN_BATCH: usize = 500;
+
+/// Same `get`/`set`/`remove`/`clear` surface as
+/// [`CacheSystem`](crate::utils::cache::CacheSystem), backed by Redis so
+/// every scraper pointed at the same server sees the same entries.
+///
+/// Values are stored as JSON and expire through Redis's own TTL. A lookup
+/// that fails because Redis is unreachable counts as a miss.
+pub struct RedisCache<K, V> {
+    client: Client,
+    prefix: String,
+    ttl: Option<Duration>,
+    connection: Mutex<Option<MultiplexedConnection>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    _entries: PhantomData<fn(K) -> V>,
+}
+
+impl<K, V> RedisCache<K, V>
+where
+    K: ToString,
+    V: Serialize + DeserializeOwned,
+{
+    /// Connect lazily to `redis://[:password@]host[:port][/db]`; entries expire
+    /// after `ttl_seconds` (0 keeps them until evicted by Redis)
+    pub fn new(redis_url: &str, ttl_seconds: u64) -> Result<Self> {
+        let client = Client::open(redis_url)
+            .map_err(|e| ScraperError::ConfigError(format!("Invalid Redis URL {}: {}", redis_url, e)))?;
+
+        Ok(Self {
+            client,
+            prefix: DEFAULT_KEY_PREFIX.to_string(),
+            ttl: (ttl_seconds > 0).then(|| Duration::from_secs(ttl_seconds)),
+            connection: Mutex::new(None),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            _entries: PhantomData,
+        })
+    }
+
+    /// Namespace keys differently, e.g. to keep two crawls' caches apart on one server
+    pub fn with_key_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    /// Expire entries after this long; overrides the TTL given to `new`
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    pub async fn get(&self, key: &K) -> Option<V> {
+        let found = match self.lookup(&self.key(key)).await {
+            Ok(Some(value)) => serde_json::from_str(&value).ok(),
+            Ok(None) => None,
+            Err(e) => {
+                log::warn!("Redis cache lookup failed for {}: {:#}", key.to_string(), e);
+                None
+            }
+        };
+
+        match found {
+            Some(value) => {
+                log::debug!("Cache hit (redis) for key: {}", key.to_string());
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Some(value)
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    pub async fn set(&self, key: K, value: V) -> Result<()> {
+        let serialized = serde_json::to_string(&value).map_err(ScraperError::SerializationError)?;
+        let key = self.key(&key);
+        let mut connection = self.connection().await?;
+        let stored: RedisResult<()> = match self.ttl {
+            Some(ttl) => {
+                let millis = u64::try_from(ttl.as_millis()).unwrap_or(u64::MAX).max(1);
+                let options = SetOptions::default().with_expiration(SetExpiry::PX(millis));
+                connection.set_options(&key, &serialized, options).await
+            }
+            None => connection.set(&key, &serialized).await,
+        };
+        self.check(stored, "SET")?;
+
+        log::debug!("Cache set for key: {}", key);
+        Ok(())
+    }
+
+    pub async fn remove(&self, key: &K) -> Result<()> {
+        let mut connection = self.connection().await?;
+        let removed: RedisResult<()> = connection.del(self.key(key)).await;
+        self.check(removed, "DEL")?;
+        log::debug!("Cache removed for key: {}", key.to_string());
+        Ok(())
+    }
+
+    /// Delete every entry under this cache's key prefix, leaving other keys alone
+    pub async fn clear(&self) -> Result<()> {
+        let pattern = format!("{}*", escape_glob(&self.prefix));
+        let mut connection = self.connection().await?;
+        let scanned = redis::cmd("SCAN")
+            .cursor_arg(0)
+            .arg("MATCH")
+            .arg(&pattern)
+            .arg("COUNT")
+            .arg(SCAN_BATCH)
+            .clone()
+            .iter_async::<String>(&mut connection)
+            .await;
+        let keys: Vec<String> = self.check(scanned, "SCAN")?.collect().await;
+
+        for batch in keys.chunks(SCAN_BATCH) {
+            let removed: RedisResult<()> = connection.del(batch).await;
+            self.check(removed, "DEL")?;
+        }
+
+        log::info!("Cache cleared");
+        Ok(())
+    }
+
+    /// Lookups made through this instance; Redis doesn't report a per-prefix
+    /// entry count, so `entry_count` is always 0
+    pub fn stats(&self) -> CacheStats {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        let lookups = hits + misses;
+        let (hit_rate, miss_rate) = if lookups == 0 {
+            (0.0, 0.0)
+        } else {
+            (hits as f64 / lookups as f64, misses as f64 / lookups as f64)
+        };
+
+        CacheStats {
+            entry_count: 0,
+            hits,
+            misses,
+            hit_rate,
+            miss_rate,
+        }
+    }
+
+    fn key(&self, key: &K) -> String {
+        format!("{}{}", self.prefix, key.to_string())
+    }
+
+    async fn lookup(&self, key: &str) -> Result<Option<String>> {
+        let mut connection = self.connection().await?;
+        let value = connection.get(key).await;
+        self.check(value, "GET")
+    }
+
+    /// The shared connection, opened on first use and again after it drops
+    async fn connection(&self) -> Result<MultiplexedConnection> {
+        let mut connection = self.connection.lock().await;
+        if let Some(connection) = connection.as_ref() {
+            return Ok(connection.clone());
+        }
+
+        let opened = self
+            .client
+            .get_multiplexed_async_connection()
+            .await
+            .with_context(|| format!("Failed to connect to Redis at {}", self.client.get_connection_info().addr))?;
+        *connection = Some(opened.clone());
+        Ok(opened)
+    }
+
+    /// Surface a command's error, forgetting the connection if it can't be reused
+    fn check<T>(&self, result: RedisResult<T>, command: &str) -> Result<T> {
+        result.map_err(|e| {
+            if e.is_unrecoverable_error()
+                && let Ok(mut connection) = self.connection.try_lock()
+            {
+                *connection = None;
+            }
+            ScraperError::CacheError(format!("Redis {}: {}", command, e)).into()
+        })
+    }
+}
+
+/// Redis-backed counterpart of [`HtmlCache`](crate::utils::cache::HtmlCache)
+pub type RedisHtmlCache = RedisCache<String, String>;
+
+impl RedisHtmlCache {
+    pub async fn get_html(&self, url: &str) -> Option<String> {
+        self.get(&url.to_string()).await
+    }
+
+    pub async fn set_html(&self, url: &str, html: &str) -> Result<()> {
+        self.set(url.to_string(), html.to_string()).await
+    }
+
+    pub async fn remove_html(&self, url: &str) -> Result<()> {
+        self.remove(&url.to_string()).await
+    }
+}
+
+/// Escape Redis glob metacharacters so a prefix matches literally
+fn escape_glob(prefix: &str) -> String {
+    prefix
+        .chars()
+        .flat_map(|c| match c {
+            '*' | '?' | '[' | ']' | '\\' => vec!['\\', c],
+            c => vec![c],
+        })
+        .collect()
+}
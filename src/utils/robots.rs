@@ -0,0 +1,160 @@
+//! robots.txt parsing
+//!
+//! Supports the parts of the format crawlers agree on: `User-agent` groups,
+//! `Allow`/`Disallow` with `*` wildcards and a `$` end anchor (the longest
+//! matching rule wins, `Allow` on a tie) and `Crawl-delay`.
+
+use std::time::Duration;
+use url::Url;
+
+#[derive(Debug, Clone, PartialEq)]
+struct RobotsRule {
+    allow: bool,
+    pattern: String,
+}
+
+#[derive(Debug, Default)]
+struct Group {
+    agents: Vec<String>,
+    rules: Vec<RobotsRule>,
+    crawl_delay: Option<Duration>,
+}
+
+/// The rules of one host's robots.txt that apply to our user agent
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RobotsRules {
+    rules: Vec<RobotsRule>,
+    crawl_delay: Option<Duration>,
+}
+
+impl RobotsRules {
+    /// No restrictions, e.g. for a host without a robots.txt
+    pub fn allow_all() -> Self {
+        Self::default()
+    }
+
+    /// Use the group whose `User-agent` is the longest token found in
+    /// `user_agent` (case-insensitive), falling back to the `*` group
+    pub fn parse(content: &str, user_agent: &str) -> Self {
+        let user_agent = user_agent.to_ascii_lowercase();
+        let mut groups: Vec<Group> = Vec::new();
+        // Consecutive User-agent lines share the rules that follow them
+        let mut reading_agents = false;
+
+        for line in content.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+
+            match key.trim().to_ascii_lowercase().as_str() {
+                "user-agent" => {
+                    if !reading_agents {
+                        groups.push(Group::default());
+                        reading_agents = true;
+                    }
+                    if let Some(group) = groups.last_mut() {
+                        group.agents.push(value.to_ascii_lowercase());
+                    }
+                }
+                key @ ("allow" | "disallow") => {
+                    reading_agents = false;
+                    // An empty Disallow allows everything, same as no rule
+                    if let Some(group) = groups.last_mut()
+                        && !value.is_empty()
+                    {
+                        group.rules.push(RobotsRule {
+                            allow: key == "allow",
+                            pattern: value.to_string(),
+                        });
+                    }
+                }
+                "crawl-delay" => {
+                    reading_agents = false;
+                    if let Some(group) = groups.last_mut() {
+                        group.crawl_delay = value
+                            .parse::<f64>()
+                            .ok()
+                            .filter(|seconds| seconds.is_finite() && *seconds >= 0.0)
+                            .map(Duration::from_secs_f64);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let named = groups
+            .iter()
+            .filter_map(|group| {
+                group
+                    .agents
+                    .iter()
+                    .filter(|agent| *agent != "*" && user_agent.contains(agent.as_str()))
+                    .map(|agent| agent.len())
+                    .max()
+                    .map(|specificity| (specificity, group))
+            })
+            .max_by_key(|(specificity, _)| *specificity)
+            .map(|(_, group)| group);
+
+        match named.or_else(|| groups.iter().find(|group| group.agents.iter().any(|agent| agent == "*"))) {
+            Some(group) => Self {
+                rules: group.rules.clone(),
+                crawl_delay: group.crawl_delay,
+            },
+            None => Self::allow_all(),
+        }
+    }
+
+    pub fn crawl_delay(&self) -> Option<Duration> {
+        self.crawl_delay
+    }
+
+    /// Whether the URL's path and query may be fetched
+    pub fn is_allowed(&self, url: &Url) -> bool {
+        let mut path = url.path().to_string();
+        if let Some(query) = url.query() {
+            path.push('?');
+            path.push_str(query);
+        }
+        if path == "/robots.txt" {
+            return true;
+        }
+
+        self.rules
+            .iter()
+            .filter(|rule| pattern_matches(&rule.pattern, &path))
+            .max_by_key(|rule| (rule.pattern.len(), rule.allow))
+            .is_none_or(|rule| rule.allow)
+    }
+}
+
+/// Prefix match where `*` matches any run of characters and a trailing `$`
+/// anchors the pattern at the end of the path
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+
+    let mut parts = pattern.split('*');
+    let Some(mut rest) = path.strip_prefix(parts.next().unwrap_or_default()) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    if parts.is_empty() {
+        return !anchored || rest.is_empty();
+    }
+
+    for (i, part) in parts.iter().enumerate() {
+        if anchored && i == parts.len() - 1 {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(position) => rest = &rest[position + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
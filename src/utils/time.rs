@@ -1,6 +1,6 @@
 //! Time utilities for safe timestamp handling across the application
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, Utc};
 
 /// Parse a timestamp string or return current UTC time
 pub fn parse_or_now(timestamp_str: &str) -> DateTime<Utc> {
@@ -22,6 +22,106 @@ pub fn parse_optional_or_now(timestamp: Option<&str>) -> DateTime<Utc> {
         .unwrap_or_else(|| Utc::now())
 }
 
+/// Absolute date/time formats commonly seen on news and e-commerce pages
+const DATETIME_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%d %H:%M",
+    "%B %d, %Y %H:%M",
+    "%b %d, %Y %H:%M",
+];
+
+const DATE_FORMATS: &[&str] = &[
+    "%Y-%m-%d",
+    "%B %d, %Y",
+    "%b %d, %Y",
+    "%b. %d, %Y",
+    "%d %B %Y",
+    "%d %b %Y",
+    "%d/%m/%Y",
+];
+
+/// Parse a human or machine formatted publish date.
+///
+/// Supports RFC 3339, RFC 822/2822, common written forms such as
+/// "Jan 2, 2024" and relative phrases like "3 hours ago". Dates without a
+/// timezone are treated as UTC. Returns `None` when nothing matches.
+pub fn parse_datetime(text: &str) -> Option<DateTime<Utc>> {
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+
+    if let Ok(dt) = DateTime::parse_from_rfc3339(text) {
+        return Some(dt.with_timezone(&Utc));
+    }
+
+    if let Ok(dt) = DateTime::parse_from_rfc2822(text) {
+        return Some(dt.with_timezone(&Utc));
+    }
+
+    for format in DATETIME_FORMATS {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(text, format) {
+            return Some(dt.and_utc());
+        }
+    }
+
+    for format in DATE_FORMATS {
+        if let Ok(date) = NaiveDate::parse_from_str(text, format) {
+            return date.and_hms_opt(0, 0, 0).map(|dt| dt.and_utc());
+        }
+    }
+
+    parse_relative_time(text, Utc::now())
+}
+
+/// Parse a relative timestamp such as "5 minutes ago", "an hour ago",
+/// "yesterday", "3d ago" or "just now" against a fixed reference time.
+///
+/// Months and years are approximated as 30 and 365 days.
+pub fn parse_relative_time(text: &str, reference: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let lower = text.trim().to_lowercase();
+
+    match lower.as_str() {
+        "just now" | "now" | "moments ago" | "a moment ago" | "today" => return Some(reference),
+        "yesterday" => return Some(reference - Duration::days(1)),
+        "last week" => return Some(reference - Duration::weeks(1)),
+        "last month" => return Some(reference - Duration::days(30)),
+        "last year" => return Some(reference - Duration::days(365)),
+        _ => {}
+    }
+
+    let rest = lower.strip_suffix("ago")?.trim();
+
+    // Split "2 hours", "an hour" or compact forms like "2h"/"15min"
+    let (amount, unit) = match rest.split_once(char::is_whitespace) {
+        Some((amount, unit)) => (amount.to_string(), unit.trim().to_string()),
+        None => {
+            let split = rest.find(|c: char| !c.is_ascii_digit())?;
+            (rest[..split].to_string(), rest[split..].to_string())
+        }
+    };
+
+    let amount: i64 = match amount.as_str() {
+        "a" | "an" | "one" => 1,
+        n => n.parse().ok()?,
+    };
+
+    // Scraped text can hold any number, so amounts too large for a date are rejected
+    let duration = match unit.trim_end_matches('s') {
+        "second" | "sec" | "" => Duration::try_seconds(amount),
+        "minute" | "min" | "m" => Duration::try_minutes(amount),
+        "hour" | "hr" | "h" => Duration::try_hours(amount),
+        "day" | "d" => Duration::try_days(amount),
+        "week" | "w" => Duration::try_weeks(amount),
+        "month" | "mo" => amount.checked_mul(30).and_then(Duration::try_days),
+        "year" | "yr" | "y" => amount.checked_mul(365).and_then(Duration::try_days),
+        _ => return None,
+    }?;
+
+    reference.checked_sub_signed(duration)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -52,4 +152,63 @@ mod tests {
         let result = parse_optional_or_now(None);
         assert!(result.timestamp() > 0);
     }
+
+    #[test]
+    fn test_parse_datetime_formats() {
+        let expected = "2024-01-02T10:30:00+00:00";
+        assert_eq!(parse_datetime("2024-01-02T10:30:00Z").unwrap().to_rfc3339(), expected);
+        assert_eq!(parse_datetime("Tue, 02 Jan 2024 10:30:00 GMT").unwrap().to_rfc3339(), expected);
+        assert_eq!(parse_datetime("2024-01-02 10:30:00").unwrap().to_rfc3339(), expected);
+
+        let midnight = "2024-01-02T00:00:00+00:00";
+        assert_eq!(parse_datetime("Jan 2, 2024").unwrap().to_rfc3339(), midnight);
+        assert_eq!(parse_datetime("January 2, 2024").unwrap().to_rfc3339(), midnight);
+        assert_eq!(parse_datetime("2 January 2024").unwrap().to_rfc3339(), midnight);
+        assert_eq!(parse_datetime(" 2024-01-02 ").unwrap().to_rfc3339(), midnight);
+
+        assert!(parse_datetime("not a date").is_none());
+        assert!(parse_datetime("").is_none());
+    }
+
+    #[test]
+    fn test_parse_datetime_relative() {
+        let parsed = parse_datetime("3 hours ago").unwrap();
+        let expected = Utc::now() - Duration::hours(3);
+        assert!((parsed - expected).num_seconds().abs() < 5);
+    }
+
+    #[test]
+    fn test_parse_relative_time() {
+        let reference = parse_or_now("2024-06-15T12:00:00Z");
+        let parse = |text: &str| parse_relative_time(text, reference).map(|dt| dt.to_rfc3339());
+
+        assert_eq!(parse("just now"), Some("2024-06-15T12:00:00+00:00".to_string()));
+        assert_eq!(parse("30 seconds ago"), Some("2024-06-15T11:59:30+00:00".to_string()));
+        assert_eq!(parse("5 minutes ago"), Some("2024-06-15T11:55:00+00:00".to_string()));
+        assert_eq!(parse("a minute ago"), Some("2024-06-15T11:59:00+00:00".to_string()));
+        assert_eq!(parse("2 hours ago"), Some("2024-06-15T10:00:00+00:00".to_string()));
+        assert_eq!(parse("an hour ago"), Some("2024-06-15T11:00:00+00:00".to_string()));
+        assert_eq!(parse("Yesterday"), Some("2024-06-14T12:00:00+00:00".to_string()));
+        assert_eq!(parse("3 days ago"), Some("2024-06-12T12:00:00+00:00".to_string()));
+        assert_eq!(parse("2 weeks ago"), Some("2024-06-01T12:00:00+00:00".to_string()));
+        assert_eq!(parse("1 month ago"), Some("2024-05-16T12:00:00+00:00".to_string()));
+        assert_eq!(parse("1 year ago"), Some("2023-06-16T12:00:00+00:00".to_string()));
+        assert_eq!(parse("3h ago"), Some("2024-06-15T09:00:00+00:00".to_string()));
+        assert_eq!(parse("15min ago"), Some("2024-06-15T11:45:00+00:00".to_string()));
+
+        assert_eq!(parse("3 fortnights ago"), None);
+        assert_eq!(parse("tomorrow"), None);
+        assert_eq!(parse("in 2 hours"), None);
+    }
+
+    #[test]
+    fn test_parse_relative_time_rejects_huge_amounts() {
+        let reference = parse_or_now("2024-06-15T12:00:00Z");
+
+        assert_eq!(parse_relative_time("99999999999 days ago", reference), None);
+        assert_eq!(parse_relative_time("9999999999999999 years ago", reference), None);
+        assert_eq!(parse_relative_time("9223372036854775807 months ago", reference), None);
+        assert_eq!(parse_relative_time("9223372036854775807 seconds ago", reference), None);
+        assert!(parse_datetime("99999999999 days ago").is_none());
+    }
 }
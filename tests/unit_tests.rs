@@ -2,16 +2,56 @@
 mod tests {
     use super::*;
     use rust_scraper_pro::{
-        core::models::ScrapedData,
-        processors::{normalizer::Normalizer, validator::Validator, deduplicator::Deduplicator},
-        utils::logger::setup_test_logger,
+        ai::{DataNormalizer, DeepSeekClient, DetectedSelectors, SelectorAssistant, HEURISTIC_CONFIDENCE},
+        core::config::Config,
+        core::crawler::{CrawlConfig, CrawlState, ScrapeProgress, StopReason},
+        core::models::{CategoryCount, ExtractionRate, MergePolicy, ScrapedData, ScrapingConfig, SourceSummary},
+        core::pagination::PaginationConfig,
+        core::scraper::{ScraperEngine, UrlOutcome},
+        output::{
+            api::{ApiServer, AppendResponse, SharedData},
+            sink::{FileOutput, Output},
+            store::{IndexedStore, ItemStore},
+            database::{ConflictStrategy, DatabaseOutput, PostgresOutput, RegeneratedIds, SqlArg, SqliteOutput},
+        },
+        processors::{normalizer::{canonicalize_url, MetadataFilter, Normalizer}, validator::Validator, deduplicator::{DedupWindow, Deduplicator}, pipeline::{PipelineConfig, ProcessingPipeline}},
+        sources::{ecommerce::PriceSelectors, feed, AiSelectorSource, DirectorySource, EcommerceSource, FeedSource, FileSource, NewsSource, Source, SourceType, TableSource, UnparsedElement},
+        utils::{
+            backoff::{Backoff, Jitter},
+            cache::{CacheStats, HtmlCache},
+            error::ScraperError,
+            logger::{setup_test_logger, targets_filter},
+            rate_limiter::{HostLimiter, PolitenessController},
+            raw_html::{RawHtmlStore, RAW_HTML_PATH_KEY},
+            robots::RobotsRules,
+        },
     };
     use chrono::Utc;
+    use std::sync::Arc;
 
     fn setup() {
         let _ = setup_test_logger();
     }
 
+    fn test_engine() -> ScraperEngine {
+        test_engine_with_rate_limit(0)
+    }
+
+    fn test_engine_with_rate_limit(rate_limit_ms: u64) -> ScraperEngine {
+        let config = Config {
+            scraping: ScrapingConfig {
+                rate_limit_ms,
+                max_retries: 0,
+                follow_robots_txt: false,
+                ..ScrapingConfig::default()
+            },
+            sources: Vec::new(),
+            pipeline: PipelineConfig::default(),
+            alerts: Default::default(),
+        };
+        ScraperEngine::new(config, ProcessingPipeline::new(), None)
+    }
+
     #[tokio::test]
     async fn test_normalizer() {
         setup();
@@ -74,6 +114,28 @@ mod tests {
         assert_eq!(deduplicated.len(), 2);
     }
 
+    #[tokio::test]
+    async fn test_dedup_window_forgets_old_entries() {
+        setup();
+
+        let item = |path: &str| ScrapedData::new("source".to_string(), format!("https://example.com/{}", path));
+
+        // Capacity: the least recently seen URL is evicted first
+        let window = DedupWindow::new(2, std::time::Duration::from_secs(60));
+        let fresh = window.filter_new(vec![item("a"), item("b"), item("A"), item("c")]);
+        assert_eq!(fresh.len(), 3, "same URL in different case is a duplicate");
+        assert_eq!(window.len(), 2);
+        assert!(window.insert(&item("a")), "evicted URL counts as new again");
+        assert!(!window.insert(&item("c")));
+
+        // TTL: entries expire even while there's room
+        let window = DedupWindow::new(100, std::time::Duration::from_millis(50));
+        assert!(window.insert(&item("a")));
+        assert!(!window.insert(&item("a")));
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        assert!(window.insert(&item("a")));
+    }
+
     #[tokio::test]
     async fn test_scraped_data_creation() {
         setup();
@@ -106,4 +168,4672 @@ mod tests {
         assert_eq!(data.metadata.get("key2"), Some(&"value2".to_string()));
         assert_eq!(data.metadata.len(), 2);
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_merge_fills_fields_and_unions_metadata() {
+        setup();
+
+        let mut listing = ScrapedData::new("shop".to_string(), "https://shop.example.com/widget".to_string())
+            .with_title("Widget".to_string())
+            .with_price(9.99);
+        listing.add_metadata("currency".to_string(), "GBP".to_string());
+        listing.add_metadata("rating".to_string(), "Four".to_string());
+
+        let mut detail = ScrapedData::new("shop".to_string(), "https://shop.example.com/widget".to_string())
+            .with_title("Widget (Deluxe Edition)".to_string())
+            .with_content("A very fine widget".to_string());
+        detail.add_metadata("rating".to_string(), "Five".to_string());
+        detail.add_metadata("weight".to_string(), "2kg".to_string());
+
+        let original_id = listing.id.clone();
+        let mut merged = listing.clone();
+        merged.merge(&detail);
+
+        assert_eq!(merged.id, original_id);
+        assert_eq!(merged.title.as_deref(), Some("Widget"), "existing values win by default");
+        assert_eq!(merged.content.as_deref(), Some("A very fine widget"), "empty fields are filled");
+        assert_eq!(merged.price, Some(9.99), "missing values never erase existing ones");
+        assert_eq!(merged.metadata.len(), 3);
+        assert_eq!(merged.metadata["rating"], "Four");
+        assert_eq!(merged.metadata["weight"], "2kg");
+
+        let mut preferred = listing.clone();
+        preferred.merge_with(&detail, MergePolicy::PreferOther);
+        assert_eq!(preferred.title.as_deref(), Some("Widget (Deluxe Edition)"));
+        assert_eq!(preferred.price, Some(9.99));
+        assert_eq!(preferred.metadata["rating"], "Five");
+        assert_eq!(preferred.metadata["currency"], "GBP");
+    }
+
+    #[tokio::test]
+    async fn test_blocked_response_downcasts_to_scraper_error() {
+        setup();
+
+        let mut server = mockito::Server::new_async().await;
+        let _forbidden = server.mock("GET", "/").with_status(403).create_async().await;
+
+        let mut engine = test_engine();
+        let err = engine
+            .scrape_source(NewsSource::new(&server.url()))
+            .await
+            .unwrap_err();
+
+        match err.downcast_ref::<ScraperError>() {
+            Some(ScraperError::Blocked(status)) => assert_eq!(status.as_u16(), 403),
+            other => panic!("expected ScraperError::Blocked, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_response_carries_retry_after() {
+        setup();
+
+        let mut server = mockito::Server::new_async().await;
+        let _limited = server
+            .mock("GET", "/")
+            .with_status(429)
+            .with_header("retry-after", "30")
+            .create_async()
+            .await;
+
+        let mut engine = test_engine();
+        let err = engine
+            .scrape_source(NewsSource::new(&server.url()))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref::<ScraperError>(),
+            Some(ScraperError::RateLimited { retry_after: Some(30) })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_non_html_response_is_rejected() {
+        setup();
+
+        let mut server = mockito::Server::new_async().await;
+        let _json = server
+            .mock("GET", "/")
+            .with_header("content-type", "application/json; charset=utf-8")
+            .with_body(r#"{"articles": []}"#)
+            .create_async()
+            .await;
+
+        let mut engine = test_engine();
+        let err = engine.scrape_source(NewsSource::new(&server.url())).await.unwrap_err();
+        match err.downcast_ref::<ScraperError>() {
+            Some(ScraperError::UnsupportedContentType(mime)) => assert_eq!(mime, "application/json"),
+            other => panic!("expected ScraperError::UnsupportedContentType, got {:?}", other),
+        }
+
+        // Feeds are accepted under their XML content types
+        let _feed = server
+            .mock("GET", "/feed.xml")
+            .with_header("content-type", "application/rss+xml")
+            .with_body(r#"<?xml version="1.0"?><rss version="2.0"><channel><title>T</title>
+                <item><title>Entry</title><link>https://example.com/e</link></item></channel></rss>"#)
+            .create_async()
+            .await;
+        let items = engine
+            .scrape_source(FeedSource::new(&format!("{}/feed.xml", server.url())))
+            .await
+            .unwrap();
+        assert_eq!(items.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_news_source_accepts_feed_content_types() {
+        setup();
+
+        let mut server = mockito::Server::new_async().await;
+        let _feed = server
+            .mock("GET", "/")
+            .with_header("content-type", "application/rss+xml; charset=utf-8")
+            .with_body(r#"<?xml version="1.0"?><rss version="2.0"><channel><title>T</title>
+                <item><title>Entry</title><link>https://example.com/e</link></item></channel></rss>"#)
+            .create_async()
+            .await;
+
+        let mut engine = test_engine();
+        let items = engine.scrape_source(NewsSource::new(&server.url())).await.unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].title.as_deref(), Some("Entry"));
+    }
+
+    /// Mock DeepSeek endpoint that echoes the submitted items back as normalized data,
+    /// answering earlier batches more slowly so completions arrive out of order.
+    async fn mock_deepseek_echo(server: &mut mockito::ServerGuard) -> mockito::Mock {
+        server
+            .mock("POST", "/chat/completions")
+            .with_header("content-type", "application/json")
+            .with_body_from_request(|request| {
+                let body: serde_json::Value = serde_json::from_slice(request.body().unwrap()).unwrap();
+                let prompt = body["messages"][1]["content"].as_str().unwrap();
+                let items_json = prompt.split_once("\n\n").unwrap().1;
+                let items: Vec<serde_json::Value> = serde_json::from_str(items_json).unwrap();
+
+                let first_index: u64 = items[0]["id"].as_str().unwrap().trim_start_matches("item-").parse().unwrap();
+                std::thread::sleep(std::time::Duration::from_millis(60u64.saturating_sub(first_index * 10)));
+
+                let normalized: Vec<serde_json::Value> = items
+                    .iter()
+                    .map(|item| serde_json::json!({
+                        "id": item["id"],
+                        "title": item["title"],
+                        "price_usd": item["price"],
+                        "image": null,
+                        "category": null,
+                        "source": item["source"],
+                        "timestamp": item["timestamp"],
+                    }))
+                    .collect();
+
+                serde_json::json!({
+                    "id": "mock",
+                    "choices": [{
+                        "message": { "role": "assistant", "content": serde_json::to_string(&normalized).unwrap() },
+                        "finish_reason": "stop"
+                    }],
+                    "usage": { "prompt_tokens": 1, "completion_tokens": 1, "total_tokens": 2 }
+                })
+                .to_string()
+                .into_bytes()
+            })
+            .create_async()
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_normalization_preserves_order() {
+        setup();
+
+        let mut server = mockito::Server::new_async().await;
+        let _mock = mock_deepseek_echo(&mut server).await;
+
+        let client = DeepSeekClient::with_config("test-key".to_string(), None)
+            .unwrap()
+            .with_base_url(&server.url());
+        let normalizer = DataNormalizer::new(client)
+            .with_batch_size(2)
+            .with_concurrency(3)
+            .with_batch_delay(std::time::Duration::ZERO);
+
+        let data: Vec<ScrapedData> = (0..7)
+            .map(|i| {
+                let mut item = ScrapedData::new("test".to_string(), format!("https://example.com/{}", i))
+                    .with_title(format!("Item {}", i));
+                item.id = format!("item-{}", i);
+                item
+            })
+            .collect();
+
+        let (normalized, stats) = normalizer.normalize_all(data).await.unwrap();
+
+        let ids: Vec<&str> = normalized.iter().map(|item| item.id.as_str()).collect();
+        assert_eq!(ids, vec!["item-0", "item-1", "item-2", "item-3", "item-4", "item-5", "item-6"]);
+        assert_eq!(stats.total_output, 7);
+    }
+
+    #[tokio::test]
+    async fn test_deterministic_order_is_stable_across_runs() {
+        setup();
+
+        let timestamp = Utc::now();
+        let fixtures: Vec<ScrapedData> = [("Beta", "b1"), ("Alpha", "a2"), ("Beta", "b0"), ("Alpha", "a1")]
+            .iter()
+            .map(|(source, slug)| {
+                let mut item = ScrapedData::new(source.to_string(), format!("https://example.com/{}", slug))
+                    .with_title(format!("Title {}", slug));
+                item.id = slug.to_string();
+                item.timestamp = timestamp;
+                item
+            })
+            .collect();
+
+        let pipeline = ProcessingPipeline::new().with_deterministic_order(true);
+
+        let first = pipeline.process(fixtures.clone()).await.unwrap();
+        let mut reversed = fixtures;
+        reversed.reverse();
+        let second = pipeline.process(reversed).await.unwrap();
+
+        let first_ids: Vec<&str> = first.iter().map(|item| item.id.as_str()).collect();
+        let second_ids: Vec<&str> = second.iter().map(|item| item.id.as_str()).collect();
+        assert_eq!(first_ids, vec!["a1", "a2", "b0", "b1"]);
+        assert_eq!(first_ids, second_ids);
+    }
+
+    fn article_page(title: &str, links: &[&str]) -> String {
+        let anchors: String = links.iter().map(|href| format!("<a href=\"{}\">link</a>", href)).collect();
+        format!("<html><body><article><h2>{}</h2><p>Body of {}</p></article>{}</body></html>", title, title, anchors)
+    }
+
+    #[tokio::test]
+    async fn test_crawl_resumes_from_checkpoint_after_interruption() {
+        setup();
+
+        let mut server = mockito::Server::new_async().await;
+        let _root = server.mock("GET", "/").with_body(article_page("Root", &["/a", "/b"])).create_async().await;
+        let _a = server.mock("GET", "/a").with_body(article_page("A", &["/c"])).create_async().await;
+        let _b = server.mock("GET", "/b").with_body(article_page("B", &[])).create_async().await;
+        let broken_c = server.mock("GET", "/c").with_status(500).create_async().await;
+
+        let checkpoint = std::env::temp_dir().join(format!("crawl-{}.json", uuid::Uuid::new_v4()));
+        let config = CrawlConfig {
+            checkpoint_path: Some(checkpoint.clone()),
+            checkpoint_every: 1,
+            ..CrawlConfig::default()
+        };
+        let source = NewsSource::new(&server.url());
+
+        // First run dies on /c, leaving a checkpoint behind
+        let mut engine = test_engine();
+        assert!(engine.crawl(&source, &config).await.is_err());
+
+        let saved = CrawlState::load(&checkpoint).await.unwrap();
+        assert_eq!(saved.pages_fetched, 3);
+        assert_eq!(saved.results.len(), 3);
+        assert_eq!(saved.frontier.front().unwrap().url, format!("{}/c", server.url()));
+
+        // The page recovers and a fresh engine resumes where the crash happened
+        broken_c.remove_async().await;
+        let _c = server.mock("GET", "/c").with_body(article_page("C", &[])).create_async().await;
+
+        let mut engine = test_engine();
+        let finished = engine.resume_from(saved, &source, &config).await.unwrap();
+
+        assert!(finished.is_complete());
+        assert_eq!(finished.pages_fetched, 4);
+        assert_eq!(finished.results.len(), 4);
+
+        let _ = std::fs::remove_file(&checkpoint);
+    }
+
+    #[tokio::test]
+    async fn test_crawl_stops_at_item_cap() {
+        setup();
+
+        let mut server = mockito::Server::new_async().await;
+        let page = "<html><body><article><h2>One</h2></article><article><h2>Two</h2></article>\
+                    <article><h2>Three</h2></article><a href=\"/next\">next</a></body></html>";
+        let _root = server.mock("GET", "/").with_body(page).create_async().await;
+        let next = server.mock("GET", "/next").with_body(page).expect(0).create_async().await;
+
+        let config = CrawlConfig {
+            max_items: Some(2),
+            ..CrawlConfig::default()
+        };
+
+        let mut engine = test_engine();
+        let state = engine.crawl(&NewsSource::new(&server.url()), &config).await.unwrap();
+
+        assert_eq!(state.results.len(), 2);
+        assert_eq!(state.stop_reason, Some(StopReason::MaxItems));
+        next.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_crawl_stops_at_time_budget() {
+        setup();
+
+        let mut server = mockito::Server::new_async().await;
+        let _root = server.mock("GET", "/").with_body(article_page("Root", &["/a", "/b", "/c"])).create_async().await;
+        let _a = server.mock("GET", "/a").with_body(article_page("A", &[])).create_async().await;
+        let _b = server.mock("GET", "/b").with_body(article_page("B", &[])).create_async().await;
+        let _c = server.mock("GET", "/c").with_body(article_page("C", &[])).create_async().await;
+
+        // Requests are spaced 100ms apart, so only two fit into a 150ms budget
+        let config = CrawlConfig {
+            max_duration: Some(std::time::Duration::from_millis(150)),
+            ..CrawlConfig::default()
+        };
+
+        let mut engine = test_engine_with_rate_limit(100);
+        let state = engine.crawl(&NewsSource::new(&server.url()), &config).await.unwrap();
+
+        assert_eq!(state.pages_fetched, 2);
+        assert_eq!(state.stop_reason, Some(StopReason::MaxDuration));
+        assert!(!state.is_complete());
+    }
+
+    #[tokio::test]
+    async fn test_host_limiter_caps_simultaneous_requests_per_host() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        setup();
+
+        let limiter = Arc::new(HostLimiter::new(2));
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let tasks: Vec<_> = (0..10)
+            .map(|i| {
+                let (limiter, in_flight, peak) = (limiter.clone(), in_flight.clone(), peak.clone());
+                tokio::spawn(async move {
+                    let _permit = limiter.acquire(&format!("http://mock.test/page/{}", i)).await;
+                    let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+
+        // A different host is not held up by the busy one
+        let other = tokio::time::timeout(
+            std::time::Duration::from_millis(10),
+            limiter.acquire("http://other.test/"),
+        )
+        .await;
+        assert!(other.is_ok());
+
+        for task in tasks {
+            task.await.unwrap();
+        }
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_crawl_visits_every_page_once() {
+        setup();
+
+        let mut server = mockito::Server::new_async().await;
+        let _root = server.mock("GET", "/").with_body(article_page("Root", &["/a", "/b", "/c"])).create_async().await;
+        let a = server.mock("GET", "/a").with_body(article_page("A", &["/b"])).expect(1).create_async().await;
+        let b = server.mock("GET", "/b").with_body(article_page("B", &["/a"])).expect(1).create_async().await;
+        let c = server.mock("GET", "/c").with_body(article_page("C", &[])).expect(1).create_async().await;
+
+        let config = CrawlConfig {
+            concurrency: 4,
+            max_concurrent_per_host: 2,
+            ..CrawlConfig::default()
+        };
+
+        let mut engine = test_engine();
+        let state = engine.crawl(&NewsSource::new(&server.url()), &config).await.unwrap();
+
+        assert_eq!(state.pages_fetched, 4);
+        assert_eq!(state.stop_reason, Some(StopReason::Completed));
+        a.assert_async().await;
+        b.assert_async().await;
+        c.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_crawl_reports_progress_per_page() {
+        setup();
+
+        let mut server = mockito::Server::new_async().await;
+        let _root = server.mock("GET", "/").with_body(article_page("Root", &["/a", "/b"])).create_async().await;
+        let _a = server.mock("GET", "/a").with_body(article_page("A", &[])).create_async().await;
+        let _b = server.mock("GET", "/b").with_body(article_page("B", &[])).create_async().await;
+
+        let reports = Arc::new(std::sync::Mutex::new(Vec::<ScrapeProgress>::new()));
+        let sink = reports.clone();
+        let mut engine = test_engine().with_progress(move |progress| sink.lock().unwrap().push(progress));
+        let state = engine.crawl(&NewsSource::new(&server.url()), &CrawlConfig::default()).await.unwrap();
+
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), 3, "one report per fetched page");
+        assert_eq!(reports.iter().map(|p| p.pages_fetched).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(reports[0].current_url, server.url());
+        assert_eq!(reports[2].items_collected, state.results.len());
+    }
+
+    #[tokio::test]
+    async fn test_scrape_pages_follows_page_param_until_exhausted() {
+        use mockito::Matcher;
+
+        setup();
+
+        let mut server = mockito::Server::new_async().await;
+        let mut pages = Vec::new();
+        for page in 1..=3 {
+            let body = article_page(&format!("Story {}", page), &[]);
+            pages.push(
+                server
+                    .mock("GET", "/news")
+                    .match_query(Matcher::AllOf(vec![
+                        Matcher::UrlEncoded("section".into(), "world".into()),
+                        Matcher::UrlEncoded("page".into(), page.to_string()),
+                    ]))
+                    .with_body(body)
+                    .expect(1)
+                    .create_async()
+                    .await,
+            );
+        }
+        // Past the end the site keeps serving its last page
+        let past_end = server
+            .mock("GET", "/news")
+            .match_query(Matcher::UrlEncoded("page".into(), "4".into()))
+            .with_body(article_page("Story 3", &[]))
+            .expect(1)
+            .create_async()
+            .await;
+        let beyond = server
+            .mock("GET", "/news")
+            .match_query(Matcher::UrlEncoded("page".into(), "5".into()))
+            .expect(0)
+            .create_async()
+            .await;
+
+        let source = NewsSource::new(&format!("{}/news?section=world&page=1", server.url()));
+        let mut engine = test_engine();
+        let items = engine.scrape_pages(&source, &PaginationConfig::default()).await.unwrap();
+
+        let titles: Vec<_> = items.iter().map(|item| item.title.as_deref().unwrap()).collect();
+        assert_eq!(titles, vec!["Story 1", "Story 2", "Story 3"]);
+        for page in pages {
+            page.assert_async().await;
+        }
+        past_end.assert_async().await;
+        beyond.assert_async().await;
+
+        // Path placeholders take precedence over the query parameter
+        let config = PaginationConfig::default();
+        assert_eq!(config.page_url("https://example.com/news/{page}/", 2), "https://example.com/news/2/");
+    }
+
+    #[tokio::test]
+    async fn test_listing_then_detail_pages_are_merged() {
+        setup();
+
+        let product = |href: &str, title: &str, price: &str| {
+            format!(
+                r#"<article class="product_pod"><h3><a href="{}" title="{}">{}</a></h3><p class="price_color">{}</p></article>"#,
+                href, title, title, price
+            )
+        };
+        let listing_html = format!(
+            "<html><body>{}{}{}</body></html>",
+            product("widget.html", "Widget", "£10.00"),
+            product("gadget.html", "Gadget", "£20.00"),
+            product("broken.html", "Broken", "£30.00"),
+        );
+
+        let mut server = mockito::Server::new_async().await;
+        let _listing = server.mock("GET", "/shop").with_body(listing_html).create_async().await;
+        let widget = server
+            .mock("GET", "/shop/widget.html")
+            .with_body("<html><body><article><h2>Widget</h2><p>Hand-made widget</p><span class=\"author\">Acme</span></article></body></html>")
+            .expect(1)
+            .create_async()
+            .await;
+        let _gadget = server
+            .mock("GET", "/shop/gadget.html")
+            .with_body("<html><body><article><h2>Gadget</h2><p>Gadget specs</p></article></body></html>")
+            .create_async()
+            .await;
+        let _broken = server.mock("GET", "/shop/broken.html").with_status(500).create_async().await;
+
+        let listing = EcommerceSource::new(&format!("{}/shop", server.url()));
+        let detail = NewsSource::new(&server.url());
+        let mut engine = test_engine();
+        let items = engine.scrape_listing_then_details(&listing, &detail).await.unwrap();
+
+        assert_eq!(items.len(), 3);
+        let widget_item = &items[0];
+        assert_eq!(widget_item.url, format!("{}/shop/widget.html", server.url()));
+        assert_eq!(widget_item.price, Some(10.0), "listing fields are kept");
+        assert_eq!(widget_item.content.as_deref(), Some("Hand-made widget"));
+        assert_eq!(widget_item.author.as_deref(), Some("Acme"));
+        assert_eq!(items[1].content.as_deref(), Some("Gadget specs"));
+
+        // A failed detail page leaves the listing item untouched
+        assert_eq!(items[2].title.as_deref(), Some("Broken"));
+        assert_eq!(items[2].content, None);
+        widget.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_feed_source_parses_rss() {
+        setup();
+
+        let rss = std::fs::read_to_string("tests/fixtures/rss.xml").unwrap();
+        let source = FeedSource::new("https://news.example.com/feed.xml").with_name("Example News");
+        let items = source.scrape(&rss).await.unwrap();
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].title.as_deref(), Some("Rust 2024 edition released"));
+        assert_eq!(items[0].url, "https://news.example.com/rust-2024");
+        assert_eq!(items[0].content.as_deref(), Some("The new edition stabilizes let chains."));
+        assert_eq!(items[0].category.as_deref(), Some("Technology"));
+        assert_eq!(items[0].timestamp.to_rfc3339(), "2024-01-02T10:30:00+00:00");
+        assert_eq!(items[0].source, "Example News");
+    }
+
+    #[tokio::test]
+    async fn test_feed_source_parses_atom() {
+        setup();
+
+        let atom = std::fs::read_to_string("tests/fixtures/atom.xml").unwrap();
+        let items = FeedSource::new("https://blog.example.com/atom.xml").scrape(&atom).await.unwrap();
+
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].title.as_deref(), Some("Scraping politely"));
+        assert_eq!(items[0].url, "https://blog.example.com/scraping-politely");
+        assert_eq!(items[0].author.as_deref(), Some("Sam Writer"));
+        assert_eq!(items[0].content.as_deref(), Some("Respect robots.txt and rate limits."));
+        assert_eq!(items[0].timestamp.to_rfc3339(), "2024-03-04T09:15:00+00:00");
+    }
+
+    #[tokio::test]
+    async fn test_news_source_detects_feeds() {
+        setup();
+
+        let rss = std::fs::read_to_string("tests/fixtures/rss.xml").unwrap();
+        let items = NewsSource::new("https://news.example.com/feed.xml").scrape(&rss).await.unwrap();
+        assert_eq!(items.len(), 2);
+
+        let page = r#"<html><head><link rel="alternate" type="application/rss+xml" href="/feed.xml"></head></html>"#;
+        assert_eq!(
+            feed::discover_feed_url(page, "https://news.example.com/world/"),
+            Some("https://news.example.com/feed.xml".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_news_source_parses_publish_date() {
+        setup();
+
+        let html = r#"<html><body><article><h2>Budget passes</h2><p>Parliament voted today.</p>
+            <span class="date">Jan 2, 2024</span></article></body></html>"#;
+        let items = NewsSource::new("https://news.example.com").scrape(html).await.unwrap();
+
+        assert_eq!(items[0].timestamp.to_rfc3339(), "2024-01-02T00:00:00+00:00");
+        assert_eq!(items[0].metadata.get("publish_date"), Some(&"Jan 2, 2024".to_string()));
+
+        // Unparseable dates keep the raw text and fall back to the scrape time
+        let html = html.replace("Jan 2, 2024", "sometime last spring");
+        let before = Utc::now();
+        let items = NewsSource::new("https://news.example.com").scrape(&html).await.unwrap();
+        assert!(items[0].timestamp >= before);
+        assert_eq!(items[0].metadata.get("publish_date"), Some(&"sometime last spring".to_string()));
+    }
+
+    fn product_page(price: &str) -> String {
+        format!(
+            r#"<html><body><article class="product_pod">
+            <h3><a href="book.html" title="Book">Book</a></h3>
+            <p class="price_color">{}</p></article></body></html>"#,
+            price
+        )
+    }
+
+    #[tokio::test]
+    async fn test_ecommerce_currency_follows_price_symbol() {
+        setup();
+
+        for (price, currency) in [("$12.50", "USD"), ("£12.50", "GBP"), ("€12.50", "EUR")] {
+            let items = EcommerceSource::new("https://shop.example.com").scrape(&product_page(price)).await.unwrap();
+            assert_eq!(items[0].price, Some(12.5), "{}", price);
+            assert_eq!(items[0].metadata.get("currency").map(String::as_str), Some(currency), "{}", price);
+        }
+
+        // Prices without a symbol fall back to the configured default
+        let items = EcommerceSource::new("https://shop.example.com")
+            .with_default_currency("SEK")
+            .scrape(&product_page("12.50"))
+            .await
+            .unwrap();
+        assert_eq!(items[0].metadata.get("currency").map(String::as_str), Some("SEK"));
+
+        // Percentages and counts next to the price aren't candidate prices
+        for price in ["£40.00 (23% off)", "(23% off) £40.00", "<del>£52.00</del> (23% off) £40.00", "£40.00 (22 available)"] {
+            let items = EcommerceSource::new("https://shop.example.com").scrape(&product_page(price)).await.unwrap();
+            assert_eq!(items[0].price, Some(40.0), "{}", price);
+            assert_eq!(items[0].metadata.get("currency").map(String::as_str), Some("GBP"), "{}", price);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ecommerce_sale_and_original_prices() {
+        setup();
+
+        let html = std::fs::read_to_string("tests/fixtures/sale_products.html").unwrap();
+        let items = EcommerceSource::new("https://shop.example.com").scrape(&html).await.unwrap();
+        let meta = |item: &ScrapedData, key: &str| item.metadata.get(key).cloned();
+
+        assert_eq!(items[0].price, Some(30.0));
+        assert_eq!(meta(&items[0], "original_price").as_deref(), Some("40.00"));
+        assert_eq!(meta(&items[0], "discount_pct").as_deref(), Some("25"));
+
+        // Original struck through inside the regular price element
+        assert_eq!(items[1].price, Some(15.0));
+        assert_eq!(meta(&items[1], "original_price").as_deref(), Some("20.00"));
+        assert_eq!(meta(&items[1], "currency").as_deref(), Some("EUR"));
+
+        assert_eq!(items[2].price, Some(12.99));
+        assert_eq!(meta(&items[2], "original_price"), None);
+        assert_eq!(meta(&items[2], "discount_pct"), None);
+
+        // Shop-specific markup needs its own selectors
+        assert_eq!(items[3].price, None);
+        let items = EcommerceSource::new("https://shop.example.com")
+            .with_price_selectors(PriceSelectors {
+                price: "span.now".to_string(),
+                sale_price: None,
+                original_price: Some("span.before".to_string()),
+            })
+            .scrape(&html)
+            .await
+            .unwrap();
+        assert_eq!(items[3].price, Some(8.0));
+        assert_eq!(meta(&items[3], "discount_pct").as_deref(), Some("20"));
+    }
+
+    #[tokio::test]
+    async fn test_table_source_maps_columns() {
+        setup();
+
+        let html = std::fs::read_to_string("tests/fixtures/price_table.html").unwrap();
+        let items = TableSource::new("https://shop.example.com/prices")
+            .with_table_selector("table#prices")
+            .with_column("Product", "title")
+            .with_column("vendor", "author")
+            .with_column("Price", "price")
+            .scrape(&html)
+            .await
+            .unwrap();
+
+        // The empty spacer row is skipped
+        let titles: Vec<_> = items.iter().map(|item| item.title.as_deref().unwrap()).collect();
+        assert_eq!(titles, vec!["Widget", "Gadget", "Doohickey"]);
+
+        let widget = &items[0];
+        assert_eq!(widget.url, "https://shop.example.com/items/widget");
+        assert_eq!(widget.author.as_deref(), Some("Acme"));
+        assert_eq!(widget.price, Some(1249.5));
+        // The second column under the spanning "Price" header stays in metadata
+        assert_eq!(widget.metadata.get("Price 2").map(String::as_str), Some("£1,099.00"));
+        assert_eq!(widget.metadata.get("Stock").map(String::as_str), Some("12"));
+
+        // A spanning cell fills every column it covers
+        let gadget = &items[1];
+        assert_eq!(gadget.price, Some(15.0));
+        assert_eq!(gadget.metadata.get("Price 2").map(String::as_str), Some("£15.00"));
+        assert_eq!(gadget.url, "https://shop.example.com/prices");
+
+        assert!(!items[2].metadata.contains_key("Price 2"));
+        assert_eq!(items[2].metadata.get("Stock").map(String::as_str), Some("140"));
+    }
+
+    #[tokio::test]
+    async fn test_category_override_takes_precedence() {
+        setup();
+
+        let html = r#"<html><body><article class="product_pod">
+            <h3><a href="book.html" title="A Brief History of Time">A Brief History...</a></h3>
+            <p class="price_color">£20.00</p></article></body></html>"#;
+
+        let inferred = EcommerceSource::new("https://books.example.com").scrape(html).await.unwrap();
+        assert_eq!(inferred[0].category.as_deref(), Some("Books"));
+
+        let overridden = EcommerceSource::new("https://books.example.com")
+            .with_category("Science Books")
+            .scrape(html)
+            .await
+            .unwrap();
+        assert_eq!(overridden[0].category.as_deref(), Some("Science Books"));
+
+        let rss = std::fs::read_to_string("tests/fixtures/rss.xml").unwrap();
+        let feed_items = FeedSource::new("https://news.example.com/feed.xml")
+            .with_category("Headlines")
+            .scrape(&rss)
+            .await
+            .unwrap();
+        assert!(feed_items.iter().all(|item| item.category.as_deref() == Some("Headlines")));
+    }
+
+    fn api_item(id: &str, title: &str) -> ScrapedData {
+        let mut item = ScrapedData::new("api".to_string(), format!("https://example.com/{}", id))
+            .with_title(title.to_string());
+        item.id = id.to_string();
+        item
+    }
+
+    async fn api_request(
+        app: axum::Router,
+        request: axum::http::Request<axum::body::Body>,
+    ) -> (axum::http::StatusCode, axum::http::HeaderMap, Vec<u8>) {
+        use tower::ServiceExt;
+
+        let response = app.oneshot(request).await.unwrap();
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, headers, body.to_vec())
+    }
+
+    fn json_request(method: &str, uri: &str, body: &impl serde::Serialize) -> axum::http::Request<axum::body::Body> {
+        axum::http::Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(axum::body::Body::from(serde_json::to_vec(body).unwrap()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_append_items_skips_existing_ids() {
+        setup();
+
+        let data: SharedData = IndexedStore::from(vec![api_item("a", "Existing")]).into_shared();
+        let app = ApiServer::new(data.clone(), None, None).create_app();
+
+        let batch = vec![api_item("a", "Duplicate"), api_item("b", "New"), api_item("c", "Also new"), api_item("b", "Repeat")];
+        let (status, _, body) = api_request(app, json_request("POST", "/api/items", &batch)).await;
+
+        assert_eq!(status, axum::http::StatusCode::OK);
+        let response: AppendResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!((response.inserted, response.skipped, response.items_count), (2, 2, 3));
+        assert_eq!(response.dropped, 0);
+
+        let stored = data.read().await;
+        assert_eq!(stored.items()[0].title.as_deref(), Some("Existing"));
+        assert_eq!(stored.items().iter().map(|item| item.id.as_str()).collect::<Vec<_>>(), vec!["a", "b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn test_posted_items_are_validated() {
+        setup();
+
+        let mut bad_url = api_item("bad-url", "Bad URL");
+        bad_url.url = "not-a-url".to_string();
+        let empty_id = api_item("", "No id");
+        let no_text = ScrapedData::new("api".to_string(), "https://example.com/empty".to_string());
+        let batch = vec![api_item("ok-1", "Fine"), bad_url, empty_id, no_text, api_item("ok-2", "Also fine")];
+
+        // Append endpoint reports rejected items separately from duplicates
+        let data: SharedData = IndexedStore::from(Vec::new()).into_shared();
+        let app = ApiServer::new(data.clone(), None, None).create_app();
+        let (status, _, body) = api_request(app, json_request("POST", "/api/items", &batch)).await;
+        assert_eq!(status, axum::http::StatusCode::OK);
+
+        let response: AppendResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!((response.inserted, response.skipped, response.dropped), (2, 0, 3));
+        assert_eq!(data.read().await.len(), 2);
+
+        // Replace endpoint only stores the valid items
+        let data: SharedData = IndexedStore::from(Vec::new()).into_shared();
+        let app = ApiServer::new(data.clone(), None, None).create_app();
+        let (_, _, body) = api_request(app, json_request("POST", "/api/update", &batch)).await;
+
+        let response: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(response["items_count"], "2");
+        assert_eq!(response["dropped"], "3");
+        assert!(data.read().await.items().iter().all(|item| item.id.starts_with("ok-")));
+    }
+
+    #[tokio::test]
+    async fn test_openapi_spec_is_served() {
+        setup();
+
+        let data: SharedData = IndexedStore::from(Vec::new()).into_shared();
+        let app = ApiServer::new(data, None, None).create_app();
+        let request = axum::http::Request::get("/api/openapi.json").body(axum::body::Body::empty()).unwrap();
+        let (status, _, body) = api_request(app.clone(), request).await;
+        assert_eq!(status, axum::http::StatusCode::OK);
+
+        let spec: serde_json::Value = serde_json::from_slice(&body).expect("spec should be valid JSON");
+        assert!(spec["openapi"].as_str().unwrap().starts_with("3."));
+        for path in ["/api/data", "/api/search", "/api/items", "/api/update", "/api/export/csv"] {
+            assert!(spec["paths"].get(path).is_some(), "missing {} in spec", path);
+        }
+        assert!(spec["components"]["schemas"].get("ScrapedData").is_some());
+
+        let request = axum::http::Request::get("/api/docs/").body(axum::body::Body::empty()).unwrap();
+        let (status, _, _) = api_request(app, request).await;
+        assert_eq!(status, axum::http::StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_get_endpoints_honour_if_none_match() {
+        setup();
+
+        let data: SharedData = IndexedStore::from(vec![api_item("a", "First")]).into_shared();
+        let app = ApiServer::new(data, None, None).create_app();
+        let get = |uri: &str, etag: Option<&str>| {
+            let mut builder = axum::http::Request::get(uri);
+            if let Some(etag) = etag {
+                builder = builder.header("if-none-match", etag);
+            }
+            builder.body(axum::body::Body::empty()).unwrap()
+        };
+
+        for uri in ["/api/data", "/api/export/json"] {
+            let (status, headers, body) = api_request(app.clone(), get(uri, None)).await;
+            assert_eq!(status, axum::http::StatusCode::OK);
+            assert!(!body.is_empty());
+            let etag = headers["etag"].to_str().unwrap().to_string();
+            assert!(etag.starts_with("W/\""));
+
+            let (status, headers, body) = api_request(app.clone(), get(uri, Some(&etag))).await;
+            assert_eq!(status, axum::http::StatusCode::NOT_MODIFIED, "{}", uri);
+            assert_eq!(headers["etag"].to_str().unwrap(), etag);
+            assert!(body.is_empty());
+        }
+
+        // Filtered views are tagged separately from the unfiltered one
+        let (_, plain, _) = api_request(app.clone(), get("/api/data", None)).await;
+        let (_, filtered, _) = api_request(app.clone(), get("/api/data?source=api", None)).await;
+        assert_ne!(plain["etag"], filtered["etag"]);
+
+        // Writes invalidate the tag
+        api_request(app.clone(), json_request("POST", "/api/items", &vec![api_item("b", "Second")])).await;
+        let (status, _, _) = api_request(app, get("/api/data", Some(plain["etag"].to_str().unwrap()))).await;
+        assert_eq!(status, axum::http::StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_api_responses_are_compressed_when_accepted() {
+        setup();
+
+        let items: Vec<ScrapedData> = (0..20).map(|i| api_item(&format!("item-{}", i), "Compressible title")).collect();
+        let data: SharedData = IndexedStore::from(items).into_shared();
+        let app = ApiServer::new(data, None, None).create_app();
+        let get = |encoding: Option<&str>| {
+            let mut builder = axum::http::Request::get("/api/export/json");
+            if let Some(encoding) = encoding {
+                builder = builder.header("accept-encoding", encoding);
+            }
+            builder.body(axum::body::Body::empty()).unwrap()
+        };
+
+        let (status, headers, plain) = api_request(app.clone(), get(None)).await;
+        assert_eq!(status, axum::http::StatusCode::OK);
+        assert!(headers.get("content-encoding").is_none());
+
+        for encoding in ["gzip", "br"] {
+            let (status, headers, body) = api_request(app.clone(), get(Some(encoding))).await;
+            assert_eq!(status, axum::http::StatusCode::OK);
+            assert_eq!(headers["content-encoding"], encoding);
+            assert!(body.len() < plain.len());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cache_endpoints_report_and_clear() {
+        setup();
+
+        let cache = Arc::new(HtmlCache::new_html_cache(100, 3600));
+        cache.set_html("https://example.com/a", "<html>a</html>").await.unwrap();
+        cache.set_html("https://example.com/b", "<html>b</html>").await.unwrap();
+        assert!(cache.get_html("https://example.com/a").await.is_some());
+        assert!(cache.get_html("https://example.com/missing").await.is_none());
+
+        let data: SharedData = IndexedStore::from(Vec::new()).into_shared();
+        let app = ApiServer::new(data.clone(), None, None).with_cache(cache.clone()).create_app();
+        let get_stats = || axum::http::Request::get("/api/cache/stats").body(axum::body::Body::empty()).unwrap();
+
+        let (status, _, body) = api_request(app.clone(), get_stats()).await;
+        assert_eq!(status, axum::http::StatusCode::OK);
+        let stats: CacheStats = serde_json::from_slice(&body).unwrap();
+        assert_eq!((stats.entry_count, stats.hits, stats.misses), (2, 1, 1));
+        assert_eq!(stats.hit_rate, 0.5);
+
+        let clear = axum::http::Request::post("/api/cache/clear").body(axum::body::Body::empty()).unwrap();
+        let (status, _, body) = api_request(app.clone(), clear).await;
+        assert_eq!(status, axum::http::StatusCode::OK);
+        let response: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(response["cleared"], "2");
+
+        let (_, _, body) = api_request(app, get_stats()).await;
+        let stats: CacheStats = serde_json::from_slice(&body).unwrap();
+        assert_eq!(stats.entry_count, 0);
+        assert!(cache.get_html("https://example.com/a").await.is_none());
+
+        // Without a cache the endpoints say so instead of pretending it's empty
+        let app = ApiServer::new(data, None, None).create_app();
+        let (status, _, _) = api_request(app, get_stats()).await;
+        assert_eq!(status, axum::http::StatusCode::NOT_FOUND);
+    }
+
+    // Router over an empty dataset serving a throwaway frontend build
+    async fn frontend_app(files: &[(&str, &str)]) -> (axum::Router, std::path::PathBuf) {
+        let dir = std::env::temp_dir().join(format!("frontend-{}", uuid::Uuid::new_v4()));
+        for (name, content) in files {
+            let path = dir.join(name);
+            tokio::fs::create_dir_all(path.parent().unwrap()).await.unwrap();
+            tokio::fs::write(path, content).await.unwrap();
+        }
+
+        let data: SharedData = IndexedStore::from(Vec::new()).into_shared();
+        let app = ApiServer::new(data, None, None).with_frontend_dir(&dir).create_app();
+        (app, dir)
+    }
+
+    fn get_request(uri: &str) -> axum::http::Request<axum::body::Body> {
+        axum::http::Request::get(uri).body(axum::body::Body::empty()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_missing_assets_are_not_served_index_html() {
+        setup();
+
+        let (app, dir) = frontend_app(&[("index.html", "<html>app</html>")]).await;
+
+        for asset in ["/assets/index-abc123.js", "/assets/style.css", "/logo.png"] {
+            let (status, _, body) = api_request(app.clone(), get_request(asset)).await;
+            assert_eq!(status, axum::http::StatusCode::NOT_FOUND, "{}", asset);
+            assert_eq!(body, b"Asset not found");
+        }
+
+        // Route-like paths still fall back to index.html
+        let (status, _, body) = api_request(app, get_request("/dashboard/settings")).await;
+        assert_eq!(status, axum::http::StatusCode::OK);
+        assert_eq!(body, b"<html>app</html>");
+
+        tokio::fs::remove_dir_all(dir).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_frontend_rejects_path_traversal() {
+        setup();
+
+        let (app, dir) = frontend_app(&[("index.html", "<html>app</html>")]).await;
+        tokio::fs::write(dir.with_extension("secret"), "outside").await.unwrap();
+
+        let name = dir.file_name().unwrap().to_str().unwrap();
+        for attempt in [
+            "/../../etc/passwd".to_string(),
+            format!("/assets/../../{}.secret", name),
+            format!("/./../{}.secret", name),
+        ] {
+            let (status, _, body) = api_request(app.clone(), get_request(&attempt)).await;
+            assert_eq!(status, axum::http::StatusCode::FORBIDDEN, "{}", attempt);
+            assert_ne!(body, b"outside");
+        }
+
+        tokio::fs::remove_file(dir.with_extension("secret")).await.unwrap();
+        tokio::fs::remove_dir_all(dir).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_frontend_served_from_configured_dir() {
+        setup();
+
+        let (app, dir) = frontend_app(&[
+            ("index.html", "<html>app</html>"),
+            ("assets/app.js", "console.log('hi');"),
+        ])
+        .await;
+
+        let (status, headers, body) = api_request(app.clone(), get_request("/assets/app.js")).await;
+        assert_eq!(status, axum::http::StatusCode::OK);
+        assert_eq!(headers["content-type"], "text/javascript");
+        assert_eq!(body, b"console.log('hi');");
+
+        let (status, _, body) = api_request(app, get_request("/")).await;
+        assert_eq!(status, axum::http::StatusCode::OK);
+        assert_eq!(body, b"<html>app</html>");
+
+        tokio::fs::remove_dir_all(dir).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_frontend_serves_precompressed_assets() {
+        setup();
+
+        let (app, dir) = frontend_app(&[
+            ("index.html", "<html>app</html>"),
+            ("assets/app.js", "console.log('plain');"),
+            ("assets/app.js.br", "brotli-bytes"),
+            ("assets/app.js.gz", "gzip-bytes"),
+            ("assets/style.css", "body {}"),
+        ])
+        .await;
+        let get = |uri: &str, encoding: &str| {
+            axum::http::Request::get(uri)
+                .header("accept-encoding", encoding)
+                .body(axum::body::Body::empty())
+                .unwrap()
+        };
+
+        let (status, headers, body) = api_request(app.clone(), get("/assets/app.js", "gzip, deflate, br")).await;
+        assert_eq!(status, axum::http::StatusCode::OK);
+        assert_eq!(headers["content-encoding"], "br");
+        assert_eq!(headers["content-type"], "text/javascript");
+        assert_eq!(body, b"brotli-bytes");
+
+        let (_, headers, body) = api_request(app.clone(), get("/assets/app.js", "gzip, br;q=0")).await;
+        assert_eq!(headers["content-encoding"], "gzip");
+        assert_eq!(body, b"gzip-bytes");
+
+        let (_, headers, body) = api_request(app.clone(), get("/assets/app.js", "identity")).await;
+        assert!(headers.get("content-encoding").is_none());
+        assert_eq!(body, b"console.log('plain');");
+
+        // No precompressed variant on disk: the original is served as-is
+        let (_, headers, body) = api_request(app, get("/assets/style.css", "br")).await;
+        assert!(headers.get("content-encoding").is_none());
+        assert_eq!(body, b"body {}");
+
+        tokio::fs::remove_dir_all(dir).await.unwrap();
+    }
+
+    #[test]
+    fn test_politeness_uses_the_largest_delay_signal() {
+        use rust_scraper_pro::ai::{AdaptiveDelayConfig, DelayMode};
+        use std::time::Duration;
+
+        let adaptive = AdaptiveDelayConfig {
+            mode: DelayMode::Adaptive,
+            min_delay_ms: 0,
+            max_delay_ms: 10_000,
+            sample_size: 4,
+            multiplier: 1.0,
+            ..AdaptiveDelayConfig::default()
+        };
+        let politeness = PolitenessController::new(300).with_adaptive(adaptive);
+
+        // Configured interval dominates for a host we know nothing about
+        assert_eq!(politeness.effective_delay("https://fresh.example/"), Duration::from_millis(300));
+
+        // robots.txt Crawl-delay dominates, and only for its own host
+        politeness.set_crawl_delay("https://robots.example/", Duration::from_secs(2));
+        assert_eq!(politeness.effective_delay("https://robots.example/page"), Duration::from_secs(2));
+        assert_eq!(politeness.effective_delay("https://fresh.example/"), Duration::from_millis(300));
+
+        // A slow server pushes the adaptive delay above both
+        for _ in 0..4 {
+            politeness.record_response_time("https://slow.example/", Duration::from_millis(900));
+        }
+        politeness.set_crawl_delay("https://slow.example/", Duration::from_millis(500));
+        assert_eq!(politeness.effective_delay("https://slow.example/a"), Duration::from_millis(900));
+
+        // ...but a fast one never drops below the configured interval
+        for _ in 0..4 {
+            politeness.record_response_time("https://fast.example/", Duration::from_millis(10));
+        }
+        assert_eq!(politeness.effective_delay("https://fast.example/"), Duration::from_millis(300));
+    }
+
+    #[tokio::test]
+    async fn test_politeness_spaces_requests_per_host() {
+        use std::time::{Duration, Instant};
+
+        let politeness = PolitenessController::new(100);
+        politeness.set_crawl_delay("https://a.example/", Duration::from_millis(200));
+
+        let started = Instant::now();
+        assert!(politeness.wait("https://a.example/1").await.is_zero());
+        assert!(politeness.wait("https://b.example/1").await.is_zero(), "other hosts are not held up");
+        politeness.wait("https://a.example/2").await;
+        assert!(started.elapsed() >= Duration::from_millis(200));
+
+        // Concurrent waiters on one host are released one interval apart
+        let started = Instant::now();
+        futures::future::join(politeness.wait("https://b.example/2"), politeness.wait("https://b.example/3")).await;
+        assert!(started.elapsed() >= Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_stable_id_is_deterministic() {
+        let first = ScrapedData::new("shop".to_string(), "https://example.com/item/1".to_string());
+        let second = ScrapedData::new("shop".to_string(), "https://example.com/item/1".to_string());
+        assert_ne!(first.id, second.id);
+
+        let first = first.with_stable_id();
+        let second = second.with_stable_id();
+        assert_eq!(first.id, second.id);
+        assert!(uuid::Uuid::parse_str(&first.id).is_ok());
+
+        // Either half changing gives a different id
+        let other_url = ScrapedData::new("shop".to_string(), "https://example.com/item/2".to_string()).with_stable_id();
+        let other_source = ScrapedData::new("blog".to_string(), "https://example.com/item/1".to_string()).with_stable_id();
+        assert_ne!(first.id, other_url.id);
+        assert_ne!(first.id, other_source.id);
+    }
+
+    // Conflict scenario shared by the database tests: "a" is already stored, "b" is new
+    fn conflict_batch() -> (ScrapedData, Vec<ScrapedData>) {
+        let existing = api_item("a", "Old title");
+        let batch = vec![api_item("a", "New title"), api_item("b", "Fresh")];
+        (existing, batch)
+    }
+
+    // Expected (rows written, title of "a", whether "b" exists) for each strategy
+    const CONFLICT_EXPECTATIONS: [(ConflictStrategy, usize, &str, bool); 3] = [
+        (ConflictStrategy::Upsert, 2, "New title", true),
+        (ConflictStrategy::InsertIgnore, 1, "Old title", true),
+        (ConflictStrategy::UpdateOnly, 1, "New title", false),
+    ];
+
+    #[tokio::test]
+    async fn test_sqlite_conflict_strategies() {
+        setup();
+
+        for (strategy, written, title, inserted) in CONFLICT_EXPECTATIONS {
+            let path = std::env::temp_dir().join(format!("conflict-{}.db", uuid::Uuid::new_v4()));
+            let db = SqliteOutput::new(&format!("sqlite://{}?mode=rwc", path.display()), None).await.unwrap();
+            db.init().await.unwrap();
+
+            let (existing, batch) = conflict_batch();
+            db.save(&[existing]).await.unwrap();
+            assert_eq!(db.save_with_strategy(&batch, strategy).await.unwrap(), written, "{:?}", strategy);
+
+            let stored_title: String = sqlx::query_scalar("SELECT title FROM scraped_data WHERE id = 'a'")
+                .fetch_one(db.get_pool())
+                .await
+                .unwrap();
+            let b_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM scraped_data WHERE id = 'b'")
+                .fetch_one(db.get_pool())
+                .await
+                .unwrap();
+            assert_eq!(stored_title, title, "{:?}", strategy);
+            assert_eq!(b_count == 1, inserted, "{:?}", strategy);
+
+            db.get_pool().close().await;
+            let _ = tokio::fs::remove_file(path).await;
+        }
+    }
+
+    // Postgres tests only run when TEST_DATABASE_URL points at a scratch database
+    async fn test_postgres() -> Option<PostgresOutput> {
+        let url = std::env::var("TEST_DATABASE_URL").ok()?;
+        let table = format!("test_{}", uuid::Uuid::new_v4().simple());
+        let db = PostgresOutput::new(&url, Some(&table)).await.expect("TEST_DATABASE_URL should be reachable");
+        db.init().await.unwrap();
+        Some(db)
+    }
+
+    // Drops the data table along with its migration history
+    async fn drop_postgres_table(db: &PostgresOutput, table: &str) {
+        sqlx::query(&format!("DROP TABLE IF EXISTS {}, {}_migrations", table, table))
+            .execute(db.get_pool())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_postgres_conflict_strategies() {
+        setup();
+
+        for (strategy, written, title, inserted) in CONFLICT_EXPECTATIONS {
+            let Some(db) = test_postgres().await else {
+                return;
+            };
+
+            let (existing, batch) = conflict_batch();
+            db.save(&[existing]).await.unwrap();
+            assert_eq!(db.save_with_strategy(&batch, strategy).await.unwrap(), written, "{:?}", strategy);
+
+            let table = db.table_name();
+            let stored_title: String = sqlx::query_scalar(&format!("SELECT title FROM {} WHERE id = 'a'", table))
+                .fetch_one(db.get_pool())
+                .await
+                .unwrap();
+            let b_count: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {} WHERE id = 'b'", table))
+                .fetch_one(db.get_pool())
+                .await
+                .unwrap();
+            assert_eq!(stored_title, title, "{:?}", strategy);
+            assert_eq!(b_count == 1, inserted, "{:?}", strategy);
+
+            drop_postgres_table(&db, db.table_name()).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_postgres_save_is_all_or_nothing() {
+        setup();
+
+        let Some(db) = test_postgres().await else {
+            return;
+        };
+
+        // `author` is VARCHAR(255), so the middle item fails after the first was written
+        let mut too_long = api_item("bad", "Too long");
+        too_long.author = Some("x".repeat(300));
+        let batch = vec![api_item("first", "First"), too_long, api_item("last", "Last")];
+
+        assert!(db.save(&batch).await.is_err());
+
+        let count: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {}", db.table_name()))
+            .fetch_one(db.get_pool())
+            .await
+            .unwrap();
+        assert_eq!(count, 0, "a failed batch must not leave partial rows behind");
+
+        drop_postgres_table(&db, db.table_name()).await;
+    }
+
+    #[tokio::test]
+    async fn test_postgres_search() {
+        setup();
+
+        let Some(db) = test_postgres().await else {
+            return;
+        };
+
+        let mut priced = api_item("priced", "Priced widget");
+        priced.price = Some(12.5);
+        let mut other = api_item("other", "Other widget");
+        other.source = "other".to_string();
+        db.save(&[priced, other, api_item("gadget", "Gadget")]).await.unwrap();
+
+        let found = db.search("widget", None, None).await.unwrap();
+        let mut ids: Vec<_> = found.iter().map(|item| item.id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["other", "priced"]);
+        // price is NUMERIC in the table and must come back as f64
+        assert_eq!(found.iter().find(|item| item.id == "priced").unwrap().price, Some(12.5));
+
+        let found = db.search("WIDGET", Some("api"), Some(10)).await.unwrap();
+        assert_eq!(found.iter().map(|item| item.id.as_str()).collect::<Vec<_>>(), vec!["priced"]);
+        assert_eq!(db.search("widget", None, Some(1)).await.unwrap().len(), 1);
+
+        drop_postgres_table(&db, db.table_name()).await;
+    }
+
+    #[tokio::test]
+    async fn test_postgres_streams_large_result_sets() {
+        use futures::TryStreamExt;
+
+        setup();
+
+        let Some(db) = test_postgres().await else {
+            return;
+        };
+
+        let items: Vec<ScrapedData> = (0..3000)
+            .map(|i| {
+                let mut item = api_item(&format!("row-{}", i), &format!("Row {}", i));
+                item.price = Some(i as f64 / 4.0);
+                item
+            })
+            .collect();
+        assert_eq!(db.save(&items).await.unwrap(), 3000);
+
+        let streamed: Vec<ScrapedData> = db.get_all_stream().try_collect().await.unwrap();
+        assert_eq!(streamed.len(), 3000);
+        let ids: std::collections::HashSet<_> = streamed.iter().map(|item| item.id.as_str()).collect();
+        assert_eq!(ids.len(), 3000);
+        assert!(streamed.iter().any(|item| item.price == Some(749.75)));
+
+        // The CSV export streams from the database when one is configured
+        let table = db.table_name().to_string();
+        let db = Arc::new(db);
+        let data: SharedData = IndexedStore::from(Vec::new()).into_shared();
+        let app = ApiServer::new(data, Some(db.clone()), None).create_app();
+        let request = axum::http::Request::get("/api/export/csv").body(axum::body::Body::empty()).unwrap();
+        let (status, headers, body) = api_request(app, request).await;
+        assert_eq!(status, axum::http::StatusCode::OK);
+        assert_eq!(headers["content-type"], "text/csv");
+
+        let csv = String::from_utf8(body).unwrap();
+        assert!(csv.starts_with("id,source,url,title,content,price,author,timestamp,category\n"));
+        assert_eq!(csv.lines().count(), 3001);
+
+        drop_postgres_table(&db, &table).await;
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_migrations_upgrade_existing_table() {
+        setup();
+
+        let path = std::env::temp_dir().join(format!("migrate-{}.db", uuid::Uuid::new_v4()));
+        let db = SqliteOutput::new(&format!("sqlite://{}?mode=rwc", path.display()), None).await.unwrap();
+
+        // A table created by the old ad-hoc create_table: no updated_at, no migration history
+        sqlx::query(
+            "CREATE TABLE scraped_data (id TEXT PRIMARY KEY, source TEXT NOT NULL, url TEXT NOT NULL, title TEXT,
+             content TEXT, price REAL, image_url TEXT, author TEXT, timestamp DATETIME NOT NULL, category TEXT,
+             metadata TEXT, created_at DATETIME DEFAULT CURRENT_TIMESTAMP)",
+        )
+        .execute(db.get_pool())
+        .await
+        .unwrap();
+
+        db.init().await.unwrap();
+        let applied: Vec<i64> = sqlx::query_scalar("SELECT version FROM scraped_data_migrations ORDER BY version")
+            .fetch_all(db.get_pool())
+            .await
+            .unwrap();
+        assert_eq!(applied, vec![1, 2, 3]);
+
+        // Upserts touch updated_at, which used to fail on this schema
+        db.save(&[api_item("a", "First")]).await.unwrap();
+        db.save(&[api_item("a", "Second")]).await.unwrap();
+
+        // Running init again is a no-op
+        db.init().await.unwrap();
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM scraped_data_migrations")
+            .fetch_one(db.get_pool())
+            .await
+            .unwrap();
+        assert_eq!(count, 3);
+
+        // A history from before checksums were kept gets them filled in
+        sqlx::query("ALTER TABLE scraped_data_migrations DROP COLUMN checksum")
+            .execute(db.get_pool())
+            .await
+            .unwrap();
+        db.get_pool().close().await;
+        let db = SqliteOutput::new(&format!("sqlite://{}?mode=rwc", path.display()), None).await.unwrap();
+        db.init().await.unwrap();
+        let missing: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM scraped_data_migrations WHERE checksum IS NULL")
+            .fetch_one(db.get_pool())
+            .await
+            .unwrap();
+        assert_eq!(missing, 0);
+
+        // An applied migration whose file has changed since is refused
+        sqlx::query("UPDATE scraped_data_migrations SET checksum = 'edited' WHERE version = 2")
+            .execute(db.get_pool())
+            .await
+            .unwrap();
+        let err = db.init().await.unwrap_err();
+        assert!(format!("{:#}", err).contains("Migration 2"), "{:#}", err);
+
+        db.get_pool().close().await;
+        let _ = tokio::fs::remove_file(path).await;
+    }
+
+    #[tokio::test]
+    async fn test_postgres_migrations_are_tracked_per_table() {
+        setup();
+
+        let Some(db) = test_postgres().await else {
+            return;
+        };
+
+        let table = db.table_name().to_string();
+        let applied: Vec<i64> = sqlx::query_scalar(&format!("SELECT version FROM {}_migrations", table))
+            .fetch_all(db.get_pool())
+            .await
+            .unwrap();
+        assert_eq!(applied, vec![1, 2]);
+
+        db.init().await.unwrap();
+        db.save(&[api_item("a", "First")]).await.unwrap();
+        db.save(&[api_item("a", "Second")]).await.unwrap();
+
+        sqlx::query(&format!("UPDATE {}_migrations SET checksum = 'edited' WHERE version = 1", table))
+            .execute(db.get_pool())
+            .await
+            .unwrap();
+        assert!(db.init().await.is_err(), "an edited migration must not pass");
+
+        drop_postgres_table(&db, &table).await;
+    }
+
+    #[tokio::test]
+    async fn test_postgres_regenerate_ids() {
+        setup();
+
+        let Some(db) = test_postgres().await else {
+            return;
+        };
+
+        // Legacy random ids, two of which point at the same page
+        let mut older = ScrapedData::new("shop".to_string(), "https://example.com/a".to_string()).with_title("Old".to_string());
+        older.timestamp -= chrono::Duration::hours(1);
+        let newer = ScrapedData::new("shop".to_string(), "https://example.com/a".to_string()).with_title("New".to_string());
+        let other = ScrapedData::new("shop".to_string(), "https://example.com/b".to_string());
+        let already_stable = ScrapedData::new("blog".to_string(), "https://example.com/a".to_string()).with_stable_id();
+        db.save(&[older, newer, other, already_stable.clone()]).await.unwrap();
+
+        let report = db.regenerate_ids().await.unwrap();
+        assert_eq!(report.updated, 2);
+        assert_eq!(report.collapsed, 1);
+
+        let mut rows = db.get_all(None, None).await.unwrap();
+        rows.sort_by(|a, b| a.id.cmp(&b.id));
+        assert_eq!(rows.len(), 3);
+        for row in &rows {
+            assert_eq!(row.id, ScrapedData::stable_id(&row.source, &row.url));
+        }
+        let collapsed = rows.iter().find(|row| row.source == "shop" && row.url.ends_with("/a")).unwrap();
+        assert_eq!(collapsed.title.as_deref(), Some("New"));
+        assert!(rows.iter().any(|row| row.id == already_stable.id));
+
+        // A second pass finds nothing left to do
+        assert_eq!(db.regenerate_ids().await.unwrap(), RegeneratedIds::default());
+
+        drop_postgres_table(&db, db.table_name()).await;
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_save_commits_in_batches() {
+        setup();
+
+        let path = std::env::temp_dir().join(format!("batches-{}.db", uuid::Uuid::new_v4()));
+        let db = SqliteOutput::new(&format!("sqlite://{}?mode=rwc", path.display()), None)
+            .await
+            .unwrap()
+            .with_batch_size(2);
+        db.init().await.unwrap();
+
+        let items: Vec<_> = (0..5).map(|i| api_item(&format!("item-{}", i), "Title")).collect();
+        assert_eq!(db.save(&items).await.unwrap(), 5);
+
+        // Reject the last item: 5 items in batches of 2 leave it alone in the third batch
+        sqlx::query(
+            "CREATE TRIGGER reject_bad BEFORE INSERT ON scraped_data WHEN NEW.id = 'bad'
+             BEGIN SELECT RAISE(ABORT, 'rejected'); END",
+        )
+        .execute(db.get_pool())
+        .await
+        .unwrap();
+        db.clear().await.unwrap();
+
+        let mut items: Vec<_> = (0..4).map(|i| api_item(&format!("item-{}", i), "Title")).collect();
+        items.push(api_item("bad", "Title"));
+        assert!(db.save(&items).await.is_err());
+
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM scraped_data")
+            .fetch_one(db.get_pool())
+            .await
+            .unwrap();
+        assert_eq!(count, 4, "the two full batches before the failing one stay committed");
+
+        db.get_pool().close().await;
+        let _ = tokio::fs::remove_file(path).await;
+    }
+
+    fn sourced_item(source: &str, path: &str, hours_ago: i64) -> ScrapedData {
+        let mut item = ScrapedData::new(source.to_string(), format!("https://example.com/{}", path))
+            .with_title(path.to_string());
+        item.timestamp = Utc::now() - chrono::Duration::hours(hours_ago);
+        item
+    }
+
+    #[tokio::test]
+    async fn test_source_summary_endpoint() {
+        setup();
+
+        let items = vec![
+            sourced_item("news", "a", 3),
+            sourced_item("shop", "b", 5),
+            sourced_item("news", "c", 1),
+            sourced_item("news", "d", 2),
+        ];
+        let newest_news = items[2].timestamp;
+        let data: SharedData = IndexedStore::from(items).into_shared();
+        let app = ApiServer::new(data, None, None).create_app();
+
+        let (status, _, body) = api_request(app, get_request("/api/sources/summary")).await;
+        assert_eq!(status, axum::http::StatusCode::OK);
+
+        let summaries: Vec<SourceSummary> = serde_json::from_slice(&body).unwrap();
+        let counts: Vec<_> = summaries.iter().map(|s| (s.source.as_str(), s.count)).collect();
+        assert_eq!(counts, vec![("news", 3), ("shop", 1)]);
+        assert_eq!(summaries[0].last_scraped, newest_news);
+    }
+
+    #[tokio::test]
+    async fn test_postgres_source_summaries() {
+        setup();
+
+        let Some(db) = test_postgres().await else {
+            return;
+        };
+
+        let items = vec![sourced_item("news", "a", 3), sourced_item("news", "c", 1), sourced_item("shop", "b", 5)];
+        db.save(&items).await.unwrap();
+
+        // Postgres keeps microseconds, so compare timestamps at that precision
+        let micros = |summaries: Vec<SourceSummary>| {
+            summaries
+                .into_iter()
+                .map(|s| (s.source, s.count, s.last_scraped.timestamp_micros()))
+                .collect::<Vec<_>>()
+        };
+        let summaries = db.get_source_summaries().await.unwrap();
+        assert_eq!(micros(summaries), micros(SourceSummary::summarize(&items)));
+
+        drop_postgres_table(&db, db.table_name()).await;
+    }
+
+    fn categorized_items() -> Vec<ScrapedData> {
+        [Some("books"), Some("games"), None, Some("books"), Some("toys"), Some("books"), None]
+            .into_iter()
+            .enumerate()
+            .map(|(i, category)| {
+                let mut item = sourced_item("shop", &i.to_string(), 0);
+                item.category = category.map(str::to_string);
+                item
+            })
+            .collect()
+    }
+
+    fn category_counts(counts: &[CategoryCount]) -> Vec<(&str, usize)> {
+        counts.iter().map(|c| (c.category.as_str(), c.count)).collect()
+    }
+
+    #[tokio::test]
+    async fn test_categories_endpoint() {
+        setup();
+
+        let data: SharedData = IndexedStore::from(categorized_items()).into_shared();
+        let app = ApiServer::new(data, None, None).create_app();
+
+        let (status, _, body) = api_request(app, get_request("/api/categories")).await;
+        assert_eq!(status, axum::http::StatusCode::OK);
+
+        let categories: Vec<CategoryCount> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(category_counts(&categories), vec![("books", 3), ("games", 1), ("toys", 1)]);
+    }
+
+    #[tokio::test]
+    async fn test_postgres_categories() {
+        setup();
+
+        let Some(db) = test_postgres().await else {
+            return;
+        };
+
+        db.save(&categorized_items()).await.unwrap();
+        let categories = db.get_categories().await.unwrap();
+        assert_eq!(category_counts(&categories), vec![("books", 3), ("games", 1), ("toys", 1)]);
+
+        drop_postgres_table(&db, db.table_name()).await;
+    }
+
+    #[tokio::test]
+    async fn test_responses_carry_request_id() {
+        setup();
+
+        let data: SharedData = IndexedStore::from(Vec::new()).into_shared();
+        let app = ApiServer::new(data, None, None).create_app();
+
+        let (_, headers, _) = api_request(app.clone(), get_request("/api/health")).await;
+        let first = headers.get("x-request-id").expect("response should carry X-Request-Id").to_str().unwrap().to_string();
+        assert!(uuid::Uuid::parse_str(&first).is_ok());
+
+        let (_, headers, _) = api_request(app.clone(), get_request("/api/health")).await;
+        assert_ne!(headers["x-request-id"], first.as_str(), "each request gets its own id");
+
+        // An id supplied by the caller is kept, so it can be traced across services
+        let request = axum::http::Request::get("/api/health")
+            .header("x-request-id", "upstream-123")
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let (_, headers, _) = api_request(app, request).await;
+        assert_eq!(headers["x-request-id"], "upstream-123");
+    }
+
+    // Captures formatted tracing output so filters can be checked
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn logs_with_filter(filter: tracing_subscriber::EnvFilter) -> String {
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_env_filter(filter)
+            .with_writer(move || writer.clone())
+            .finish();
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(target: "sqlx::query", "sqlx info");
+            tracing::warn!(target: "sqlx::query", "sqlx warn");
+            tracing::debug!(target: "rust_scraper_pro::core::scraper", "scraper debug");
+            tracing::debug!(target: "rust_scraper_pro::output::api", "api debug");
+        });
+
+        String::from_utf8(logs.0.lock().unwrap().clone()).unwrap()
+    }
+
+    #[test]
+    fn test_logger_target_filters() {
+        let targets = [("sqlx", log::LevelFilter::Warn), ("rust_scraper_pro::core::scraper", log::LevelFilter::Debug)];
+
+        let logs = logs_with_filter(targets_filter(&targets, None).unwrap());
+        assert!(!logs.contains("sqlx info"));
+        assert!(logs.contains("sqlx warn"));
+        assert!(logs.contains("scraper debug"));
+        assert!(!logs.contains("api debug"), "other targets stay at the default info level");
+
+        // RUST_LOG-style directives override the configured targets
+        let logs = logs_with_filter(targets_filter(&targets, Some("sqlx=info, rust_scraper_pro::output=debug")).unwrap());
+        assert!(logs.contains("sqlx info"));
+        assert!(logs.contains("api debug"));
+
+        assert!(targets_filter(&targets, Some("sqlx=loud")).is_err());
+    }
+
+    #[test]
+    fn test_seeded_backoff_is_exact() {
+        use std::time::Duration;
+
+        let delays = |backoff: Backoff| (0..6).map(|attempt| backoff.delay(attempt).as_millis()).collect::<Vec<_>>();
+        let backoff = || Backoff::new(Duration::from_millis(100), Duration::from_secs(2));
+
+        assert_eq!(delays(backoff().with_jitter(Jitter::none())), vec![100, 200, 400, 800, 1600, 2000]);
+        // Same seed, same jittered schedule; the cap still holds after jitter
+        let seeded = delays(backoff().with_jitter(Jitter::seeded(0.5, 42)));
+        assert_eq!(seeded, vec![102, 208, 454, 724, 854, 1829]);
+        assert_eq!(seeded, delays(backoff().with_jitter(Jitter::seeded(0.5, 42))));
+
+        // Rotation choices are reproducible too, and fixed without jitter
+        let agents = ["a", "b", "c", "d"];
+        let picks = |jitter: Jitter| (0..8).map(|_| *jitter.choose(&agents).unwrap()).collect::<String>();
+        assert_eq!(picks(Jitter::seeded(0.0, 7)), picks(Jitter::seeded(0.0, 7)));
+        assert_eq!(picks(Jitter::none()), "aaaaaaaa");
+    }
+
+    #[tokio::test]
+    async fn test_atomic_export_leaves_no_partial_file() {
+        use rust_scraper_pro::output::{atomic::write_atomically, CsvOutput, JsonOutput};
+        use std::io::Write;
+
+        let dir = std::env::temp_dir().join(format!("atomic-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("items.json");
+        let items = vec![api_item("1", "First"), api_item("2", "Second")];
+        JsonOutput::new().export(&items, &path).await.unwrap();
+        let original = std::fs::read_to_string(&path).unwrap();
+
+        // A writer failing halfway through must not clobber the previous export
+        let result = write_atomically(&path, |file| {
+            file.write_all(b"[{\"id\": \"partial")?;
+            anyhow::bail!("disk full")
+        });
+        assert!(result.is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), original);
+
+        CsvOutput::new().export(&items, dir.join("items.csv")).await.unwrap();
+        let csv = std::fs::read_to_string(dir.join("items.csv")).unwrap();
+        assert_eq!(csv.lines().count(), 3);
+
+        let mut files: Vec<String> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        files.sort();
+        assert_eq!(files, vec!["items.csv", "items.json"], "no temp files are left behind");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_streaming_json_export() {
+        use rust_scraper_pro::output::JsonOutput;
+
+        let dir = std::env::temp_dir().join(format!("stream-{}", uuid::Uuid::new_v4()));
+        let output = JsonOutput::new();
+
+        // Items are generated lazily, never collected into a Vec before writing
+        let items = (0..50_000).map(|i| {
+            let mut item = api_item(&i.to_string(), &format!("Item {}", i));
+            item.metadata.insert("quote".to_string(), "say \"hi\",\n]".to_string());
+            item
+        });
+        let path = dir.join("large.json");
+        assert_eq!(output.export_stream(items, &path, false).await.unwrap(), 50_000);
+
+        let parsed = output.import(&path).await.unwrap().items;
+        assert_eq!(parsed.len(), 50_000);
+        assert_eq!(parsed[49_999].title.as_deref(), Some("Item 49999"));
+        assert_eq!(parsed[0].metadata["quote"], "say \"hi\",\n]");
+
+        let small = vec![api_item("1", "First"), api_item("2", "Second")];
+        output.export_stream(&small, dir.join("pretty.json"), true).await.unwrap();
+        let pretty = std::fs::read_to_string(dir.join("pretty.json")).unwrap();
+        assert!(pretty.contains("\n  \"items\": [\n    {\n      \"id\": \"1\""));
+        assert_eq!(output.import(dir.join("pretty.json")).await.unwrap().items.len(), 2);
+
+        output.export_stream(Vec::<ScrapedData>::new(), dir.join("empty.json"), false).await.unwrap();
+        assert!(std::fs::read_to_string(dir.join("empty.json")).unwrap().ends_with("\"items\":[]}"));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_json_export_envelope_round_trip() {
+        use rust_scraper_pro::output::{json::SCHEMA_VERSION, ExportEnvelope, JsonOutput};
+
+        let dir = std::env::temp_dir().join(format!("envelope-{}", uuid::Uuid::new_v4()));
+        let output = JsonOutput::new();
+        let items = vec![api_item("1", "First"), api_item("2", "Second")];
+
+        let before = Utc::now();
+        output.export_minified(&items, dir.join("data.json")).await.unwrap();
+        let raw: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(dir.join("data.json")).unwrap()).unwrap();
+        assert_eq!(raw["schema_version"], SCHEMA_VERSION);
+        assert_eq!(raw["items"][1]["title"], "Second");
+
+        let export = output.import(dir.join("data.json")).await.unwrap();
+        assert_eq!(export.schema_version, SCHEMA_VERSION);
+        assert!(export.generated_at.unwrap() >= before);
+        assert_eq!(export.items.iter().map(|item| item.id.as_str()).collect::<Vec<_>>(), vec!["1", "2"]);
+
+        // Version 1 exports were a bare array
+        let legacy = serde_json::to_string(&items).unwrap();
+        let export = ExportEnvelope::<Vec<ScrapedData>>::from_json(&legacy).unwrap();
+        assert_eq!(export.schema_version, 1);
+        assert!(export.generated_at.is_none());
+        assert_eq!(export.items.len(), 2);
+
+        // Newer versions still load, ignoring fields this version doesn't know
+        let future = serde_json::json!({
+            "schema_version": SCHEMA_VERSION + 1,
+            "generated_at": "2030-01-01T00:00:00Z",
+            "source_count": 3,
+            "items": [{ "id": "x", "title": "Later" }],
+        });
+        let export = ExportEnvelope::<Vec<serde_json::Value>>::from_json(&future.to_string()).unwrap();
+        assert_eq!(export.schema_version, SCHEMA_VERSION + 1);
+        assert_eq!(export.items[0]["title"], "Later");
+
+        assert!(ExportEnvelope::<Vec<ScrapedData>>::from_json("{\"items\": 3}").is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_stages_can_be_disabled() {
+        setup();
+
+        let duplicate = || ScrapedData::new("source".to_string(), "https://example.com/1".to_string())
+            .with_title("  Same   Title ".to_string());
+        let untitled = ScrapedData::new("source".to_string(), "https://example.com/2".to_string());
+        let data = vec![duplicate(), duplicate(), untitled];
+
+        let processed = ProcessingPipeline::new().process(data.clone()).await.unwrap();
+        assert_eq!(processed.len(), 1);
+
+        let kept = ProcessingPipeline::new().with_deduplication(false).process(data.clone()).await.unwrap();
+        assert_eq!(kept.len(), 2, "duplicates survive with dedup off");
+        assert_eq!(kept[0].title.as_deref(), Some("Same Title"));
+
+        let raw = ProcessingPipeline::raw().process(data.clone()).await.unwrap();
+        assert_eq!(raw.len(), 3);
+        assert_eq!(raw[0].title.as_deref(), Some("  Same   Title "));
+
+        let config: Config = toml::from_str(r#"
+            sources = []
+            [scraping]
+            rate_limit_ms = 0
+            timeout_seconds = 30
+            max_retries = 0
+            user_agent = "test"
+            follow_robots_txt = false
+            [pipeline]
+            deduplicate = false
+        "#).unwrap();
+        assert!(config.pipeline.validate && config.pipeline.normalize);
+        let from_config = ProcessingPipeline::from_config(&config.pipeline).process(data).await.unwrap();
+        assert_eq!(from_config.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_file_source_parses_saved_page() {
+        setup();
+
+        let table = || TableSource::new("https://shop.example.com/prices")
+            .with_table_selector("table#prices")
+            .with_column("Product", "title")
+            .with_column("Price", "price");
+        let source = FileSource::new("tests/fixtures/price_table.html", table());
+        let items = source.scrape().await.unwrap();
+
+        let titles: Vec<_> = items.iter().map(|item| item.title.as_deref().unwrap()).collect();
+        assert_eq!(titles, vec!["Widget", "Gadget", "Doohickey"]);
+        // Links resolve against the inner source's live URL, not the file path
+        assert_eq!(items[0].url, "https://shop.example.com/items/widget");
+        assert_eq!(items[0].price, Some(1249.5));
+
+        let err = FileSource::new("tests/fixtures/missing.html", table()).scrape().await.unwrap_err();
+        assert!(matches!(err.downcast_ref::<ScraperError>(), Some(ScraperError::IoError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_directory_source_reads_saved_pages() {
+        setup();
+
+        let dir = std::env::temp_dir().join(format!("pages-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        let page = |rows: &[&str]| {
+            let rows: String = rows.iter().map(|title| format!("<tr><td>{}</td><td>£1.00</td></tr>", title)).collect();
+            format!("<html><body><table><tr><th>Product</th><th>Price</th></tr>{}</table></body></html>", rows)
+        };
+        std::fs::write(dir.join("2024-02.html"), page(&["Gadget", "Gizmo"])).unwrap();
+        std::fs::write(dir.join("2024-01.html"), page(&["Widget"])).unwrap();
+        std::fs::write(dir.join("2023-12.html"), page(&["Old"])).unwrap();
+        std::fs::write(dir.join("notes.txt"), page(&["Not a page"])).unwrap();
+        std::fs::write(dir.join("nested/2024-03.html"), page(&["Nested"])).unwrap();
+
+        let table = || TableSource::new("https://shop.example.com/").with_column("Product", "title");
+        let titles = |items: Vec<ScrapedData>| items.into_iter().map(|item| item.title.unwrap()).collect::<Vec<_>>();
+
+        // Only top-level .html files, in name order
+        let all = DirectorySource::new(&dir, table()).scrape().await.unwrap();
+        assert_eq!(titles(all), vec!["Old", "Widget", "Gadget", "Gizmo"]);
+
+        let filtered = DirectorySource::new(&dir, table()).with_glob("2024-*.html");
+        assert_eq!(filtered.files().unwrap().len(), 2);
+        assert_eq!(titles(filtered.scrape().await.unwrap()), vec!["Widget", "Gadget", "Gizmo"]);
+        assert!(DirectorySource::new(&dir, table()).with_glob("202?-1*.html").scrape().await.unwrap().len() == 1);
+
+        assert!(DirectorySource::new(dir.join("missing"), table()).scrape().await.is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_untitled_products_are_reported() {
+        setup();
+
+        let html = r#"<html><body>
+            <article class="product_pod"><h3><a href="a.html" title="Alpha">Alpha</a></h3><p class="price_color">£1.00</p></article>
+            <article class="product_pod"><h3>No link here</h3><p class="price_color">£2.00</p></article>
+            <article class="product_pod"><h3><a href="b.html" title="Beta">Beta</a></h3></article>
+            <article class="product_pod" id="empty"></article>
+        </body></html>"#;
+
+        // Without a debug log the untitled products come through for the validator to drop
+        let items = EcommerceSource::new("https://shop.example.com").scrape(html).await.unwrap();
+        assert_eq!(items.len(), 4);
+
+        let log = std::env::temp_dir().join(format!("unparsed-{}", uuid::Uuid::new_v4())).join("unparsed.jsonl");
+        let source = EcommerceSource::new("https://shop.example.com").with_unparsed_log(&log);
+        let items = source.scrape(html).await.unwrap();
+        let titles: Vec<_> = items.iter().map(|item| item.title.as_deref()).collect();
+        assert_eq!(titles, vec![Some("Alpha"), None, Some("Beta"), None]);
+
+        let unparsed: Vec<UnparsedElement> = std::fs::read_to_string(&log)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(unparsed.len(), 2);
+        assert!(unparsed.iter().all(|element| element.reason == "missing title"));
+        assert!(unparsed[0].html.contains("No link here"));
+        assert!(unparsed[1].html.contains(r#"id="empty""#));
+        assert_eq!(unparsed[0].page_url, "https://shop.example.com");
+
+        // Later scrapes append rather than overwrite
+        source.scrape(html).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&log).unwrap().lines().count(), 4);
+
+        // Pages reached through next links are logged under their own URL
+        let mut server = mockito::Server::new_async().await;
+        let next = r#"<ul class="pager"><li class="next"><a href="page-2.html">next</a></li></ul>"#;
+        server
+            .mock("GET", "/")
+            .with_header("content-type", "text/html")
+            .with_body(format!("<html><body>{}{}</body></html>", r#"<article class="product_pod"><h3><a href="a.html" title="Alpha">Alpha</a></h3></article>"#, next))
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/page-2.html")
+            .with_header("content-type", "text/html")
+            .with_body(r#"<html><body><article class="product_pod"><h3>Untitled</h3></article></body></html>"#)
+            .create_async()
+            .await;
+        std::fs::remove_file(&log).unwrap();
+        let source = EcommerceSource::new(&server.url()).with_max_pages(2).with_unparsed_log(&log);
+        let items = test_engine().scrape_source(source).await.unwrap();
+        assert_eq!(items.len(), 2);
+        let unparsed: UnparsedElement = serde_json::from_str(std::fs::read_to_string(&log).unwrap().trim()).unwrap();
+        assert_eq!(unparsed.page_url, format!("{}/page-2.html", server.url()));
+
+        std::fs::remove_dir_all(log.parent().unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_normalizer_canonicalizes_urls() {
+        setup();
+
+        assert_eq!(
+            canonicalize_url("https://Shop.Example.COM/Item?utm_source=news&b=2&fbclid=abc&a=1#reviews").as_deref(),
+            Some("https://shop.example.com/Item?a=1&b=2")
+        );
+        assert_eq!(
+            canonicalize_url("https://example.com/p?UTM_Medium=email&gclid=x").as_deref(),
+            Some("https://example.com/p")
+        );
+        assert_eq!(canonicalize_url("not a url"), None);
+
+        let tracked = ScrapedData::new("test".to_string(), "https://Example.com/a?utm_campaign=spring&id=7#top".to_string())
+            .with_title("A".to_string());
+        let clean = ScrapedData::new("test".to_string(), "https://example.com/a?id=7".to_string())
+            .with_title("A".to_string());
+
+        let normalized = Normalizer::new().normalize(vec![tracked, clean]).await.unwrap();
+        assert_eq!(normalized[0].url, "https://example.com/a?id=7");
+        assert_eq!(
+            normalized[0].metadata.get("url_raw").map(String::as_str),
+            Some("https://Example.com/a?utm_campaign=spring&id=7#top")
+        );
+        // Already-canonical URLs are left alone and get no raw copy
+        assert_eq!(normalized[1].url, "https://example.com/a?id=7");
+        assert!(!normalized[1].metadata.contains_key("url_raw"));
+
+        // Both now dedupe to one item
+        let deduplicated = Deduplicator::new().deduplicate(normalized).await.unwrap();
+        assert_eq!(deduplicated.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_metadata_filters_per_source() {
+        setup();
+
+        let item = |source: &str| {
+            let mut item = ScrapedData::new(source.to_string(), format!("https://{}.example.com/1", source))
+                .with_title(format!("Item from {}", source));
+            for key in ["rating", "availability", "price_text", "tracking_blob"] {
+                item.metadata.insert(key.to_string(), "x".to_string());
+            }
+            item
+        };
+        let keys = |item: &ScrapedData| {
+            let mut keys: Vec<_> = item.metadata.keys().cloned().collect();
+            keys.sort();
+            keys
+        };
+
+        let normalizer = Normalizer::new()
+            .with_metadata_filter("books", MetadataFilter::allow(["rating", "availability"]))
+            .with_metadata_filter("*", MetadataFilter::deny(["tracking_blob"]));
+        let items = normalizer.normalize(vec![item("books"), item("news")]).await.unwrap();
+        assert_eq!(keys(&items[0]), vec!["availability", "rating"]);
+        assert_eq!(keys(&items[1]), vec!["availability", "price_text", "rating"]);
+
+        // Unfiltered without any configuration
+        let items = Normalizer::new().normalize(vec![item("books")]).await.unwrap();
+        assert_eq!(items[0].metadata.len(), 4);
+
+        let config: PipelineConfig = toml::from_str(r#"
+            [metadata.books]
+            allow = ["rating", "price_text"]
+            deny = ["price_text"]
+        "#).unwrap();
+        let items = ProcessingPipeline::from_config(&config).process(vec![item("books"), item("news")]).await.unwrap();
+        let books = items.iter().find(|item| item.source == "books").unwrap();
+        assert_eq!(keys(books), vec!["rating"]);
+        let news = items.iter().find(|item| item.source == "news").unwrap();
+        assert_eq!(news.metadata.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_scrape_urls_reports_each_url() {
+        setup();
+
+        let mut server = mockito::Server::new_async().await;
+        let mut mocks = Vec::new();
+        for name in ["a", "b", "d"] {
+            mocks.push(
+                server
+                    .mock("GET", format!("/{}", name).as_str())
+                    .with_header("content-type", "text/html")
+                    .with_body(article_page(&format!("Story {}", name), &[]))
+                    .expect(1)
+                    .create_async()
+                    .await,
+            );
+        }
+        let _broken = server.mock("GET", "/c").with_status(500).create_async().await;
+
+        let urls: Vec<String> = ["a", "b", "c", "d"].iter().map(|name| format!("{}/{}", server.url(), name)).collect();
+        let mut engine = test_engine();
+        let batch = engine
+            .scrape_urls(urls.clone(), NewsSource::new(&server.url()), 3)
+            .await
+            .unwrap();
+
+        let titles: Vec<_> = batch.items.iter().map(|item| item.title.as_deref().unwrap()).collect();
+        assert_eq!(titles, vec!["Story a", "Story b", "Story d"]);
+        assert_eq!(batch.succeeded(), 3);
+        assert_eq!(batch.outcomes.iter().map(|outcome| outcome.url.clone()).collect::<Vec<_>>(), urls);
+        assert_eq!(batch.outcomes[0], UrlOutcome { url: urls[0].clone(), result: Ok(1) });
+
+        let failures: Vec<_> = batch.failures().collect();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].url, urls[2]);
+        assert!(failures[0].result.as_ref().unwrap_err().contains("500"));
+
+        for mock in mocks {
+            mock.assert_async().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_source_url_records_the_scraped_page() {
+        setup();
+
+        let mut server = mockito::Server::new_async().await;
+        let _listing = server
+            .mock("GET", "/catalogue/page-2.html")
+            .with_header("content-type", "text/html")
+            .with_body(product_page("£5.00"))
+            .create_async()
+            .await;
+
+        let page_url = format!("{}/catalogue/page-2.html", server.url());
+        let mut engine = test_engine();
+        let items = engine.scrape_source(EcommerceSource::new(&page_url)).await.unwrap();
+        assert_eq!(items[0].url, format!("{}/book.html", page_url));
+        assert_eq!(items[0].source_url.as_deref(), Some(page_url.as_str()));
+
+        // Persisted alongside the item, and optional for exports that predate it
+        let path = std::env::temp_dir().join(format!("source-url-{}.db", uuid::Uuid::new_v4()));
+        let db = SqliteOutput::new(&format!("sqlite://{}?mode=rwc", path.display()), None).await.unwrap();
+        db.init().await.unwrap();
+        db.save(&items).await.unwrap();
+        let stored: Option<String> = sqlx::query_scalar("SELECT source_url FROM scraped_data")
+            .fetch_one(db.get_pool())
+            .await
+            .unwrap();
+        assert_eq!(stored, Some(page_url.clone()));
+        db.get_pool().close().await;
+        let _ = tokio::fs::remove_file(path).await;
+
+        let legacy: ScrapedData = serde_json::from_value(serde_json::json!({
+            "id": "1", "source": "s", "url": "https://example.com", "title": null, "content": null,
+            "price": null, "image_url": null, "author": null, "timestamp": "2024-01-01T00:00:00Z",
+            "metadata": {}, "category": null,
+        }))
+        .unwrap();
+        assert_eq!(legacy.source_url, None);
+
+        if let Some(db) = test_postgres().await {
+            db.save(&items).await.unwrap();
+            let rows = db.get_all(None, None).await.unwrap();
+            assert_eq!(rows[0].source_url.as_deref(), Some(page_url.as_str()));
+            let table = db.table_name().to_string();
+            drop_postgres_table(&db, &table).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_deepseek_timeout_and_request_limit() {
+        use std::time::Duration;
+        use rust_scraper_pro::ai::DeepSeekMessage;
+
+        setup();
+
+        let message = || vec![DeepSeekMessage { role: "user".to_string(), content: "hi".to_string() }];
+        let client = DeepSeekClient::with_config("test-key".to_string(), None).unwrap();
+        let request = client.completion_request(message()).build().unwrap();
+        assert_eq!(request.timeout(), Some(&Duration::from_secs(60)));
+
+        let client = client.with_timeout(Duration::from_millis(1500));
+        let request = client.completion_request(message()).build().unwrap();
+        assert_eq!(request.timeout(), Some(&Duration::from_millis(1500)));
+
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/chat/completions")
+            .with_body(
+                serde_json::json!({
+                    "id": "mock",
+                    "choices": [{ "message": { "role": "assistant", "content": "OK" }, "finish_reason": "stop" }],
+                    "usage": { "prompt_tokens": 1, "completion_tokens": 1, "total_tokens": 2 }
+                })
+                .to_string(),
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        // With every permit of the shared limiter taken, calls wait instead of going out
+        let limiter = Arc::new(tokio::sync::Semaphore::new(0));
+        let client = DeepSeekClient::with_config("test-key".to_string(), None)
+            .unwrap()
+            .with_base_url(&server.url())
+            .with_request_limiter(limiter.clone());
+        assert!(tokio::time::timeout(Duration::from_millis(200), client.ask("hi")).await.is_err());
+
+        limiter.add_permits(1);
+        assert_eq!(client.ask("hi").await.unwrap(), "OK");
+        assert_eq!(limiter.available_permits(), 1, "the permit is returned after the call");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_selector_assistant_falls_back_to_heuristics() {
+        setup();
+
+        let html = r#"<html><body>
+            <article><h2><a href="/p/1">Lamp</a></h2><span class="price">$20</span>
+            <img src="/lamp.jpg"><time datetime="2024-05-01">May 1</time><p>A lamp.</p></article>
+        </body></html>"#;
+
+        let mut server = mockito::Server::new_async().await;
+        let _down = server.mock("POST", "/chat/completions").with_status(503).create_async().await;
+        let dir = std::env::temp_dir().join(format!("selectors-{}", uuid::Uuid::new_v4()));
+        let client = DeepSeekClient::with_config("test-key".to_string(), None)
+            .unwrap()
+            .with_base_url(&server.url());
+        let assistant = SelectorAssistant::with_selectors_dir(client, dir.clone());
+
+        let selectors = assistant.get_or_detect_selectors("shop.example.com", html).await.unwrap();
+        assert_eq!(selectors.confidence, HEURISTIC_CONFIDENCE);
+        assert_eq!(selectors.metadata.get("strategy").map(String::as_str), Some("heuristic"));
+        assert_eq!(selectors.container, vec!["article"]);
+        assert_eq!(selectors.title, vec!["h2"]);
+        assert_eq!(selectors.price, vec![".price"]);
+        assert_eq!(selectors.image, vec!["article img", "img"]);
+        assert_eq!(selectors.date, vec!["time"]);
+        assert_eq!(selectors.link, vec!["article a[href]", "h2 a[href]", "a[href]"]);
+        assert!(selectors.author.is_empty(), "nothing resembling an author on the page");
+        // Guesses aren't cached, so the AI gets another chance next time
+        assert!(!assistant.has_selectors("shop.example.com").await);
+
+        // Works with no AI configured at all
+        let offline = SelectorAssistant::heuristic_only(dir);
+        let selectors = offline.get_or_detect_selectors("shop.example.com", html).await.unwrap();
+        assert_eq!(selectors.title, vec!["h2"]);
+    }
+
+    #[tokio::test]
+    async fn test_low_confidence_selectors_fall_back_to_heuristics() {
+        setup();
+
+        let html = r#"<html><body><h1>Catalogue</h1>
+            <article><h2><a href="/p/1">Lamp</a></h2><span class="price">£20.00</span></article>
+            <article><h2><a href="/p/2">Desk</a></h2><span class="price">£1,250.00</span></article>
+        </body></html>"#;
+        let detected = |confidence: f32| DetectedSelectors {
+            domain: "shop.example.com".to_string(),
+            container: vec!["article".to_string()],
+            title: vec!["h2 a".to_string()],
+            price: vec!["span.cost".to_string()],
+            image: vec![],
+            category: vec![],
+            description: vec![],
+            author: vec![],
+            date: vec![],
+            link: vec!["h2 a".to_string()],
+            metadata: Default::default(),
+            confidence,
+            generated_at: Utc::now().to_rfc3339(),
+        };
+        let meta = |item: &ScrapedData, key: &str| item.metadata.get(key).cloned();
+
+        // Confident detection is used as is, including its wrong price selector
+        let items = AiSelectorSource::new("https://shop.example.com/", detected(0.9)).scrape(html).await.unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].title.as_deref(), Some("Lamp"));
+        assert_eq!(items[0].url, "https://shop.example.com/p/1");
+        assert_eq!(items[0].price, None);
+        assert_eq!(meta(&items[0], "selector_strategy").as_deref(), Some("ai"));
+        assert_eq!(meta(&items[0], "selector_confidence").as_deref(), Some("0.90"));
+        assert_eq!(meta(&items[0], "selector_title").as_deref(), Some("h2 a"));
+        assert_eq!(meta(&items[0], "selector_price"), None, "unmatched fields are skipped");
+
+        // Below the threshold the page's own structure is guessed instead
+        let source = AiSelectorSource::new("https://shop.example.com/", detected(0.3)).with_min_confidence(0.6);
+        let items = source.scrape(html).await.unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[1].title.as_deref(), Some("Desk"));
+        assert_eq!(items[1].price, Some(1250.0));
+        assert_eq!(meta(&items[1], "selector_strategy").as_deref(), Some("heuristic"));
+        assert_eq!(meta(&items[1], "selector_confidence").as_deref(), Some("0.20"));
+        assert_eq!(meta(&items[1], "selector_title").as_deref(), Some("h2"));
+        assert_eq!(meta(&items[1], "selector_price").as_deref(), Some(".price"));
+    }
+
+    #[tokio::test]
+    async fn test_selector_candidates_fall_back_in_order() {
+        setup();
+
+        // Cached selector files from before candidate lists still load
+        let legacy: DetectedSelectors = serde_json::from_str(
+            r#"{"domain": "shop.example.com", "title": "h1.name", "price": null, "confidence": 0.9, "generated_at": "2024-01-01T00:00:00Z"}"#,
+        )
+        .unwrap();
+        assert_eq!(legacy.title, vec!["h1.name"]);
+        assert!(legacy.price.is_empty() && legacy.container.is_empty());
+
+        let selectors: DetectedSelectors = serde_json::from_value(serde_json::json!({
+            "domain": "shop.example.com",
+            "container": ["li.product", ".card"],
+            "title": [".product-name", "h3"],
+            "price": [".price-now", ".price"],
+            "link": ["a.product-link", "h3 a"],
+            "confidence": 0.9,
+            "generated_at": "2024-01-01T00:00:00Z"
+        }))
+        .unwrap();
+        let html = r#"<html><body>
+            <div class="card"><h3><a href="/p/1">Lamp</a></h3><span class="price">£20.00</span></div>
+            <div class="card"><h3><a href="/p/2">Desk</a></h3><span class="price-now">£95.00</span><span class="price">£120.00</span></div>
+        </body></html>"#;
+
+        let items = AiSelectorSource::new("https://shop.example.com/", selectors).scrape(html).await.unwrap();
+        assert_eq!(items.len(), 2, "second container candidate splits the page");
+        let meta = |item: &ScrapedData, key: &str| item.metadata.get(key).cloned();
+
+        assert_eq!(items[0].title.as_deref(), Some("Lamp"));
+        assert_eq!(items[0].url, "https://shop.example.com/p/1");
+        assert_eq!(items[0].price, Some(20.0));
+        assert_eq!(meta(&items[0], "selector_title").as_deref(), Some("h3"));
+        assert_eq!(meta(&items[0], "selector_link").as_deref(), Some("h3 a"));
+        assert_eq!(meta(&items[0], "selector_price").as_deref(), Some(".price"));
+
+        // Candidates are tried per item, so the preferred one wins where present
+        assert_eq!(items[1].price, Some(95.0));
+        assert_eq!(meta(&items[1], "selector_price").as_deref(), Some(".price-now"));
+    }
+
+    #[test]
+    fn test_check_selectors_reports_per_field_matches() {
+        setup();
+
+        let html = std::fs::read_to_string("tests/fixtures/product_page.html").unwrap();
+        let selectors: DetectedSelectors = serde_json::from_value(serde_json::json!({
+            "domain": "furniture.example.com",
+            "container": [".product-detail"],
+            "title": ["title", "h1.product-title"],
+            "price": ["p.price", "p.price.now"],
+            "link": ["a.permalink"],
+            "image": ["img.missing"],
+            "confidence": 0.9,
+            "generated_at": "2024-01-01T00:00:00Z"
+        }))
+        .unwrap();
+        let source = AiSelectorSource::new("https://furniture.example.com/desks/", selectors);
+
+        let report = source.check_selectors(
+            &html,
+            &[
+                ("title", "Oak Writing Desk"),
+                ("price", "149.99"),
+                ("link", "https://furniture.example.com/p/oak-writing-desk"),
+                ("image", "https://furniture.example.com/img/oak-desk.jpg"),
+                ("author", "Anyone"),
+            ],
+        );
+        assert!(!report.passed());
+
+        // <title> is outside the container, so only the second candidate finds anything
+        let title = report.field("title").unwrap();
+        assert!(title.matched());
+        assert!(!title.candidates[0].matched && title.candidates[0].values.is_empty());
+        assert!(title.candidates[1].matched);
+        assert_eq!(title.used().unwrap().selector, "h1.product-title");
+
+        // The preferred price selector picks up the old price first; the report shows it
+        let price = report.field("price").unwrap();
+        assert!(price.matched());
+        assert_eq!(price.candidates[0].values, vec!["£180.00"]);
+        assert!(!price.candidates[0].matched);
+        assert_eq!(price.used().unwrap().selector, "p.price", "a scrape would read the wrong price");
+        assert!(price.candidates[1].matched);
+
+        assert!(report.field("link").unwrap().matched());
+
+        let failures: Vec<&str> = report.failures().map(|field| field.field.as_str()).collect();
+        assert_eq!(failures, vec!["image", "author"]);
+        assert!(report.field("author").unwrap().candidates.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_captured_response_headers_are_stored_in_metadata() {
+        setup();
+
+        let mut server = mockito::Server::new_async().await;
+        let _page = server
+            .mock("GET", "/shop")
+            .with_header("content-type", "text/html; charset=utf-8")
+            .with_header("server", "nginx/1.25")
+            .with_header("cache-control", "max-age=60")
+            .with_body(product_page("£5.00"))
+            .create_async()
+            .await;
+
+        let config = Config {
+            scraping: ScrapingConfig {
+                rate_limit_ms: 0,
+                follow_robots_txt: false,
+                capture_headers: vec!["Server".to_string(), "Cache-Control".to_string(), "X-Missing".to_string()],
+                ..ScrapingConfig::default()
+            },
+            sources: Vec::new(),
+            pipeline: PipelineConfig::default(),
+            alerts: Default::default(),
+        };
+        let mut engine = ScraperEngine::new(config, ProcessingPipeline::new(), None);
+        let items = engine
+            .scrape_source(EcommerceSource::new(&format!("{}/shop", server.url())))
+            .await
+            .unwrap();
+
+        assert!(!items.is_empty());
+        let metadata = &items[0].metadata;
+        assert_eq!(metadata.get("http_server").map(String::as_str), Some("nginx/1.25"));
+        assert_eq!(metadata.get("http_cache_control").map(String::as_str), Some("max-age=60"));
+        assert!(!metadata.contains_key("http_x_missing"));
+        assert!(!metadata.contains_key("http_content_type"), "only configured headers are captured");
+
+        // Nothing is captured unless configured
+        let items = test_engine()
+            .scrape_source(EcommerceSource::new(&format!("{}/shop", server.url())))
+            .await
+            .unwrap();
+        assert!(!items[0].metadata.keys().any(|key| key.starts_with("http_")));
+    }
+
+    #[tokio::test]
+    async fn test_raw_html_is_archived_for_reprocessing() {
+        setup();
+
+        let html = product_page("£5.00");
+        let mut server = mockito::Server::new_async().await;
+        let _page = server
+            .mock("GET", "/shop")
+            .with_header("content-type", "text/html")
+            .with_body(&html)
+            .create_async()
+            .await;
+
+        let dir = std::env::temp_dir().join(format!("raw-html-{}", uuid::Uuid::new_v4()));
+        let mut engine = test_engine().with_raw_html_store(RawHtmlStore::new(&dir));
+        let items = engine
+            .scrape_source(EcommerceSource::new(&format!("{}/shop", server.url())))
+            .await
+            .unwrap();
+
+        let path = items[0].metadata.get(RAW_HTML_PATH_KEY).expect("archived page path").clone();
+        assert!(path.ends_with(".html.gz"));
+        let stored = std::fs::read(&path).unwrap();
+        assert_eq!(&stored[..2], &[0x1f, 0x8b], "stored gzip-compressed");
+        assert_eq!(RawHtmlStore::load(&path).await.unwrap(), html);
+        assert_eq!(RawHtmlStore::load_for(&items[0]).await.unwrap().as_deref(), Some(html.as_str()));
+
+        // The archived page can be run through a parser again
+        let reparsed = EcommerceSource::new(&format!("{}/shop", server.url())).scrape(&html).await.unwrap();
+        assert_eq!(reparsed[0].title, items[0].title);
+
+        // An unchanged page is stored once
+        let again = engine
+            .scrape_source(EcommerceSource::new(&format!("{}/shop", server.url())))
+            .await
+            .unwrap();
+        assert_eq!(again[0].metadata.get(RAW_HTML_PATH_KEY), Some(&path));
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        // Nothing is archived without a store
+        let items = test_engine()
+            .scrape_source(EcommerceSource::new(&format!("{}/shop", server.url())))
+            .await
+            .unwrap();
+        assert!(!items[0].metadata.contains_key(RAW_HTML_PATH_KEY));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Minimal in-process Redis speaking just the commands `RedisCache` sends
+    #[cfg(feature = "redis")]
+    async fn mock_redis() -> String {
+        use std::collections::HashMap;
+        use std::time::{Duration, Instant};
+        use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+
+        type Store = Arc<tokio::sync::Mutex<HashMap<String, (String, Option<Instant>)>>>;
+        let store: Store = Default::default();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("redis://{}", listener.local_addr().unwrap());
+
+        tokio::spawn(async move {
+            loop {
+                let (socket, _) = listener.accept().await.unwrap();
+                let store = store.clone();
+                tokio::spawn(async move {
+                    let mut socket = BufReader::new(socket);
+                    loop {
+                        let mut line = String::new();
+                        if socket.read_line(&mut line).await.unwrap_or(0) == 0 {
+                            return;
+                        }
+                        let count: usize = line.trim()[1..].parse().unwrap();
+                        let mut args = Vec::new();
+                        for _ in 0..count {
+                            line.clear();
+                            socket.read_line(&mut line).await.unwrap();
+                            let mut arg = vec![0; line.trim()[1..].parse::<usize>().unwrap() + 2];
+                            socket.read_exact(&mut arg).await.unwrap();
+                            args.push(String::from_utf8(arg[..arg.len() - 2].to_vec()).unwrap());
+                        }
+
+                        let mut store = store.lock().await;
+                        store.retain(|_, (_, expires)| expires.is_none_or(|at| at > Instant::now()));
+                        let reply = match args[0].as_str() {
+                            "GET" => match store.get(&args[1]) {
+                                Some((value, _)) => format!("${}\r\n{}\r\n", value.len(), value),
+                                None => "$-1\r\n".to_string(),
+                            },
+                            "SET" => {
+                                let expires = (args.len() == 5)
+                                    .then(|| Instant::now() + Duration::from_millis(args[4].parse().unwrap()));
+                                store.insert(args[1].clone(), (args[2].clone(), expires));
+                                "+OK\r\n".to_string()
+                            }
+                            "DEL" => {
+                                let removed = args[1..].iter().filter(|key| store.remove(*key).is_some()).count();
+                                format!(":{}\r\n", removed)
+                            }
+                            "SCAN" => {
+                                let prefix = args[3].trim_end_matches('*');
+                                let keys: Vec<&String> = store.keys().filter(|key| key.starts_with(prefix)).collect();
+                                let mut reply = format!("*2\r\n$1\r\n0\r\n*{}\r\n", keys.len());
+                                for key in keys {
+                                    reply.push_str(&format!("${}\r\n{}\r\n", key.len(), key));
+                                }
+                                reply
+                            }
+                            command => format!("-ERR unknown command '{}'\r\n", command),
+                        };
+                        socket.get_mut().write_all(reply.as_bytes()).await.unwrap();
+                    }
+                });
+            }
+        });
+        url
+    }
+
+    #[cfg(feature = "redis")]
+    #[tokio::test]
+    async fn test_redis_cache_is_shared_and_expires() {
+        use rust_scraper_pro::utils::redis_cache::{RedisCache, RedisHtmlCache};
+        use std::time::Duration;
+        setup();
+
+        let url = mock_redis().await;
+        let first = RedisHtmlCache::new(&url, 3600).unwrap();
+        let second = RedisHtmlCache::new(&url, 3600).unwrap();
+
+        first.set_html("https://example.com/a", "<html>a</html>").await.unwrap();
+        assert_eq!(second.get_html("https://example.com/a").await.as_deref(), Some("<html>a</html>"));
+        assert_eq!(second.get_html("https://example.com/missing").await, None);
+        assert_eq!((second.stats().hits, second.stats().misses), (1, 1));
+
+        second.remove_html("https://example.com/a").await.unwrap();
+        assert_eq!(first.get_html("https://example.com/a").await, None);
+
+        // Entries expire through the TTL sent to Redis
+        let short: RedisCache<String, Vec<u32>> = RedisCache::new(&url, 0).unwrap().with_ttl(Duration::from_millis(50));
+        short.set("numbers".to_string(), vec![1, 2, 3]).await.unwrap();
+        assert_eq!(short.get(&"numbers".to_string()).await, Some(vec![1, 2, 3]));
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        assert_eq!(short.get(&"numbers".to_string()).await, None);
+
+        // Clearing only touches this cache's prefix
+        let other = RedisHtmlCache::new(&url, 3600).unwrap().with_key_prefix("other:");
+        other.set_html("https://example.com/b", "kept").await.unwrap();
+        first.set_html("https://example.com/c", "cleared").await.unwrap();
+        first.clear().await.unwrap();
+        assert_eq!(first.get_html("https://example.com/c").await, None);
+        assert_eq!(other.get_html("https://example.com/b").await.as_deref(), Some("kept"));
+
+        // An unreachable server degrades to misses for lookups
+        let down = RedisHtmlCache::new("redis://127.0.0.1:1", 60).unwrap();
+        assert_eq!(down.get_html("https://example.com/a").await, None);
+        assert!(down.set_html("https://example.com/a", "x").await.is_err());
+        assert!(RedisHtmlCache::new("http://127.0.0.1", 60).is_err());
+
+        // A page fetched by one engine is served to another from the shared cache
+        let mut server = mockito::Server::new_async().await;
+        let page = server
+            .mock("GET", "/shop")
+            .with_header("content-type", "text/html")
+            .with_body(product_page("£5.00"))
+            .expect(1)
+            .create_async()
+            .await;
+        let shop = format!("{}/shop", server.url());
+        for _ in 0..2 {
+            let shared = Arc::new(RedisHtmlCache::new(&url, 3600).unwrap());
+            let mut engine = test_engine().with_shared_cache(shared);
+            let items = engine.scrape_source(EcommerceSource::new(&shop)).await.unwrap();
+            assert_eq!(items[0].price, Some(5.0));
+        }
+        page.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_fetches_of_one_url_share_a_request() {
+        setup();
+
+        let mut server = mockito::Server::new_async().await;
+        let page = server
+            .mock("GET", "/shop")
+            .with_header("content-type", "text/html")
+            .with_body(product_page("£5.00"))
+            .expect(1)
+            .create_async()
+            .await;
+        let missing = server.mock("GET", "/gone").with_status(404).expect(1).create_async().await;
+
+        let mut engine = test_engine();
+        let shop = format!("{}/shop", server.url());
+        let batch = engine
+            .scrape_urls(vec![shop; 10], EcommerceSource::new(&server.url()), 10)
+            .await
+            .unwrap();
+        assert_eq!(batch.succeeded(), 10);
+        assert!(batch.items.iter().all(|item| item.price == Some(5.0)));
+        page.assert_async().await;
+
+        // Status errors are shared too, and keep their type
+        let gone = format!("{}/gone", server.url());
+        let batch = engine
+            .scrape_urls(vec![gone; 10], EcommerceSource::new(&server.url()), 10)
+            .await
+            .unwrap();
+        assert_eq!(batch.failures().count(), 10);
+        assert!(batch.failures().all(|outcome| outcome.result.as_ref().unwrap_err().contains("404")));
+        missing.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_fetches_only_share_matching_requests() {
+        use mockito::Matcher;
+        use rust_scraper_pro::sources::source::{ConfiguredSource, RequestDefaults};
+        setup();
+
+        // Slow responses keep both fetches in flight at once
+        let slow_body = |body: String| {
+            move |_: &mockito::Request| {
+                std::thread::sleep(std::time::Duration::from_millis(200));
+                body.clone().into_bytes()
+            }
+        };
+        let mut server = mockito::Server::new_async().await;
+        let consented = server
+            .mock("GET", "/shop")
+            .match_header("cookie", "consent=yes")
+            .with_header("content-type", "text/html")
+            .with_body_from_request(slow_body(product_page("£5.00")))
+            .expect(1)
+            .create_async()
+            .await;
+        let anonymous = server
+            .mock("GET", "/shop")
+            .match_header("cookie", Matcher::Missing)
+            .with_header("content-type", "text/html")
+            .with_body_from_request(slow_body(product_page("£9.00")))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mut engine = test_engine();
+        let shop = format!("{}/shop", server.url());
+        let consent = RequestDefaults {
+            cookies: [("consent".to_string(), "yes".to_string())].into(),
+            ..RequestDefaults::default()
+        };
+        let sources = vec![
+            ConfiguredSource::new(EcommerceSource::new(&shop), consent),
+            ConfiguredSource::new(EcommerceSource::new(&shop), RequestDefaults::default()),
+        ];
+        let items = engine.scrape_sources(sources, 2).await.unwrap();
+        let prices: Vec<_> = items.iter().map(|item| item.price).collect();
+        assert_eq!(prices, vec![Some(5.0), Some(9.0)]);
+        consented.assert_async().await;
+        anonymous.assert_async().await;
+
+        // A source that can parse what the leader's source refused fetches it itself
+        let feed = server
+            .mock("GET", "/feed")
+            .with_header("content-type", "application/rss+xml")
+            .with_body_from_request(slow_body(
+                r#"<?xml version="1.0"?><rss version="2.0"><channel><title>T</title>
+                <item><title>Entry</title><link>https://example.com/e</link></item></channel></rss>"#
+                    .to_string(),
+            ))
+            .expect(2)
+            .create_async()
+            .await;
+        let feed_url = format!("{}/feed", server.url());
+        let sources = vec![
+            SourceType::Ecommerce(EcommerceSource::new(&feed_url)),
+            SourceType::News(NewsSource::new(&feed_url)),
+        ];
+        let items = engine.scrape_sources(sources, 2).await.unwrap();
+        let titles: Vec<_> = items.iter().filter_map(|item| item.title.as_deref()).collect();
+        assert_eq!(titles, vec!["Entry"]);
+        feed.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_cached_pages_are_kept_apart_by_request_defaults() {
+        use mockito::Matcher;
+        use rust_scraper_pro::sources::source::{ConfiguredSource, RequestDefaults};
+        setup();
+
+        let mut server = mockito::Server::new_async().await;
+        let consented = server
+            .mock("GET", "/shop")
+            .match_header("cookie", "consent=yes")
+            .with_header("content-type", "text/html")
+            .with_body(product_page("£5.00"))
+            .expect(1)
+            .create_async()
+            .await;
+        let anonymous = server
+            .mock("GET", "/shop")
+            .match_header("cookie", Matcher::Missing)
+            .with_header("content-type", "text/html")
+            .with_body(product_page("£9.00"))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let cache = Arc::new(HtmlCache::new_html_cache(100, 3600));
+        let config = Config {
+            scraping: ScrapingConfig { rate_limit_ms: 0, follow_robots_txt: false, ..ScrapingConfig::default() },
+            sources: Vec::new(),
+            pipeline: PipelineConfig::default(),
+            alerts: Default::default(),
+        };
+        let mut engine = ScraperEngine::new(config, ProcessingPipeline::new(), Some(cache));
+        let shop = format!("{}/shop", server.url());
+        let consent = RequestDefaults {
+            cookies: [("consent".to_string(), "yes".to_string())].into(),
+            ..RequestDefaults::default()
+        };
+        let price = |items: Vec<ScrapedData>| items[0].price;
+
+        // Each variant is fetched once, then served from its own cache entry
+        for _ in 0..2 {
+            let with_consent = ConfiguredSource::new(EcommerceSource::new(&shop), consent.clone());
+            assert_eq!(price(engine.scrape_source(with_consent).await.unwrap()), Some(5.0));
+            let plain = EcommerceSource::new(&shop);
+            assert_eq!(price(engine.scrape_source(plain).await.unwrap()), Some(9.0));
+        }
+        consented.assert_async().await;
+        anonymous.assert_async().await;
+    }
+
+    /// One item per page, titled with the page body; pages mentioning "slow" take a while to parse.
+    /// Follows `?page=N` to `?page=N+1` for up to `max_pages` pages.
+    struct SlowSource {
+        base_url: String,
+        delay: std::time::Duration,
+        max_pages: usize,
+    }
+
+    #[async_trait::async_trait]
+    impl Source for SlowSource {
+        fn name(&self) -> &str {
+            "Slow Source"
+        }
+
+        fn base_url(&self) -> &str {
+            &self.base_url
+        }
+
+        async fn scrape(&self, html: &str) -> anyhow::Result<Vec<ScrapedData>> {
+            if html.contains("slow") {
+                tokio::time::sleep(self.delay).await;
+            }
+            let mut item = ScrapedData::new("Slow Source".to_string(), self.base_url.clone());
+            item.title = Some(html.trim().to_string());
+            Ok(vec![item])
+        }
+
+        fn next_page(&self, _html: &str, page_url: &str) -> Option<String> {
+            let (base, page) = page_url.rsplit_once("page=")?;
+            Some(format!("{}page={}", base, page.parse::<usize>().ok()? + 1))
+        }
+
+        fn max_pages(&self) -> usize {
+            self.max_pages
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scrape_budget_cancels_slow_sources() {
+        use mockito::Matcher;
+        use std::time::Duration;
+        setup();
+
+        let mut server = mockito::Server::new_async().await;
+        let mut pages = Vec::new();
+        for (page, body) in [(1, "page one"), (2, "page two"), (3, "slow page three")] {
+            pages.push(
+                server
+                    .mock("GET", "/list")
+                    .match_query(Matcher::UrlEncoded("page".into(), page.to_string()))
+                    .with_header("content-type", "text/html")
+                    .with_body(body)
+                    .create_async()
+                    .await,
+            );
+        }
+        let slow = |url: String| SlowSource {
+            base_url: url,
+            delay: Duration::from_secs(5),
+            max_pages: 1,
+        };
+
+        let mut engine = test_engine();
+        let budget = Duration::from_millis(300);
+
+        let scrape = engine
+            .scrape_source_within(slow(format!("{}/list?page=3", server.url())), budget)
+            .await
+            .unwrap();
+        assert!(scrape.timed_out);
+        assert!(scrape.items.is_empty());
+
+        let scrape = engine
+            .scrape_source_within(slow(format!("{}/list?page=1", server.url())), budget)
+            .await
+            .unwrap();
+        assert!(!scrape.timed_out);
+        assert_eq!(scrape.items[0].title.as_deref(), Some("page one"));
+
+        // Pages followed before the budget ran out are kept
+        let following = SlowSource {
+            max_pages: 3,
+            ..slow(format!("{}/list?page=1", server.url()))
+        };
+        let scrape = engine.scrape_source_within(following, budget).await.unwrap();
+        assert!(scrape.timed_out);
+        let titles: Vec<_> = scrape.items.iter().filter_map(|item| item.title.as_deref()).collect();
+        assert_eq!(titles, vec!["page one", "page two"]);
+
+        // Likewise for numbered pages
+        let config = PaginationConfig {
+            max_pages: 3,
+            ..PaginationConfig::default()
+        };
+        let started = std::time::Instant::now();
+        let scrape = engine
+            .scrape_pages_within(&slow(format!("{}/list", server.url())), &config, budget)
+            .await
+            .unwrap();
+        assert!(started.elapsed() < Duration::from_secs(2), "cancelled, not waited out");
+        assert!(scrape.timed_out);
+        let titles: Vec<_> = scrape.items.iter().filter_map(|item| item.title.as_deref()).collect();
+        assert_eq!(titles, vec!["page one", "page two"]);
+
+        // Errors still surface as errors
+        assert!(engine
+            .scrape_source_within(slow(format!("{}/missing", server.url())), budget)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_extraction_outcomes_are_recorded_per_field() {
+        setup();
+
+        let mut server = mockito::Server::new_async().await;
+        let _page = server
+            .mock("GET", "/shop")
+            .with_header("content-type", "text/html")
+            .with_body(product_page("£5.00"))
+            .create_async()
+            .await;
+
+        let mut engine = test_engine();
+        let items = engine
+            .scrape_source(EcommerceSource::new(&format!("{}/shop", server.url())))
+            .await
+            .unwrap();
+        let outcomes = items[0].extraction().expect("outcomes recorded at scrape time");
+        assert_eq!(outcomes.get("title"), Some(&true));
+        assert_eq!(outcomes.get("price"), Some(&true));
+        assert_eq!(outcomes.get("author"), Some(&false));
+        assert_eq!(outcomes.len(), 6);
+
+        // Rates per source and field, skipping items scraped without the engine
+        let mut with_price = ScrapedData::new("shop".to_string(), "https://example.com/a".to_string()).with_price(1.0);
+        with_price.record_extraction();
+        let mut rest = Vec::new();
+        for path in ["b", "c", "d"] {
+            let mut item = ScrapedData::new("shop".to_string(), format!("https://example.com/{}", path));
+            item.record_extraction();
+            rest.push(item);
+        }
+        let unrecorded = ScrapedData::new("shop".to_string(), "https://example.com/e".to_string()).with_price(2.0);
+        let data = [vec![with_price, unrecorded], rest].concat();
+
+        let rates = ExtractionRate::summarize(&data);
+        let price = rates.iter().find(|rate| rate.field == "price").unwrap();
+        assert_eq!((price.found, price.total, price.rate), (1, 4, 0.25));
+        assert_eq!(rates.len(), 6, "one entry per field for the single source");
+
+        let app = ApiServer::new(IndexedStore::from(data).into_shared(), None, None).create_app();
+        let (status, _, body) = api_request(app, get_request("/api/extraction")).await;
+        assert_eq!(status, axum::http::StatusCode::OK);
+        let served: Vec<ExtractionRate> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(served, rates);
+    }
+
+    #[tokio::test]
+    async fn test_importer_reads_mixed_json_lines_and_arrays() {
+        use rust_scraper_pro::output::{Importer, JsonOutput};
+        setup();
+
+        let scraped = ScrapedData::new("shop".to_string(), "https://shop.example.com/lamp".to_string())
+            .with_title("Lamp".to_string())
+            .with_price(20.0);
+        let mut no_url = scraped.clone();
+        no_url.id = "no-url".to_string();
+        no_url.url = "lamp.html".to_string();
+        let normalized = serde_json::json!({
+            "id": "norm-1",
+            "title": "Desk",
+            "price_usd": 120.5,
+            "image": "https://shop.example.com/desk.jpg",
+            "category": "Furniture",
+            "source": "books",
+            "timestamp": "2024-05-01T10:00:00Z",
+            "metadata": {"url": "https://shop.example.com/desk"}
+        });
+        let lines = [
+            serde_json::to_string(&scraped).unwrap(),
+            normalized.to_string(),
+            String::new(),
+            "{not json".to_string(),
+            serde_json::to_string(&no_url).unwrap(),
+            r#"{"unrelated": true}"#.to_string(),
+        ];
+
+        let dir = std::env::temp_dir().join(format!("import-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let jsonl = dir.join("items.jsonl");
+        std::fs::write(&jsonl, lines.join("\n")).unwrap();
+
+        let importer = Importer::new();
+        let report = importer.read_file(&jsonl).await.unwrap();
+        let ids: Vec<&str> = report.items.iter().map(|item| item.id.as_str()).collect();
+        assert_eq!(ids, vec![scraped.id.as_str(), "norm-1"]);
+
+        let desk = &report.items[1];
+        assert_eq!(desk.url, "https://shop.example.com/desk");
+        assert_eq!(desk.title.as_deref(), Some("Desk"));
+        assert_eq!(desk.price, Some(120.5));
+        assert_eq!(desk.timestamp.to_rfc3339(), "2024-05-01T10:00:00+00:00");
+
+        let rejected: Vec<(usize, &str)> = report
+            .rejected
+            .iter()
+            .map(|rejected| (rejected.position, rejected.reason.as_str()))
+            .collect();
+        assert_eq!(rejected.len(), 3);
+        assert_eq!(rejected[0].0, 4);
+        assert!(rejected[0].1.starts_with("malformed JSON"));
+        assert_eq!(rejected[1], (5, "invalid URL lamp.html"));
+        assert_eq!(rejected[2], (6, "not a scraped or normalized item"));
+
+        // Exports (enveloped) and bare arrays read the same way
+        let exported = dir.join("export.json");
+        JsonOutput::new().export(&report.items, &exported).await.unwrap();
+        assert_eq!(importer.read_file(&exported).await.unwrap().items.len(), 2);
+        let bare = serde_json::to_string(&vec![normalized.clone(), serde_json::json!(42)]).unwrap();
+        let report = importer.parse(&bare);
+        assert_eq!((report.items.len(), report.rejected[0].position), (1, 2));
+
+        // And straight into a database
+        let path = dir.join("import.db");
+        let db = SqliteOutput::new(&format!("sqlite://{}?mode=rwc", path.display()), None).await.unwrap();
+        db.init().await.unwrap();
+        importer.import_file(&jsonl, &db).await.unwrap();
+        let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM scraped_data")
+            .fetch_one(db.get_pool())
+            .await
+            .unwrap();
+        assert_eq!(stored, 2);
+        db.get_pool().close().await;
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_normalized_data_converts_without_losing_items() {
+        use rust_scraper_pro::ai::{NormalizedData, URL_PLACEHOLDER_KEY};
+        setup();
+
+        let parse = |json: serde_json::Value| serde_json::from_value::<NormalizedData>(json).unwrap().into_scraped_data();
+
+        let full = parse(serde_json::json!({
+            "id": "a1",
+            "title": "A Light in the Attic",
+            "price_usd": 65.7,
+            "image": "https://books.example.com/attic.jpg",
+            "category": "Poetry",
+            "source": "Books to Scrape - Main",
+            "timestamp": "2025-01-02T03:04:05Z",
+            "metadata": {"url": "https://books.example.com/attic.html", "rating": "Three"}
+        }));
+        assert_eq!(full.id, "a1");
+        assert_eq!(full.url, "https://books.example.com/attic.html");
+        assert_eq!(full.title.as_deref(), Some("A Light in the Attic"));
+        assert_eq!(full.price, Some(65.7));
+        assert_eq!(full.image_url.as_deref(), Some("https://books.example.com/attic.jpg"));
+        assert_eq!(full.category.as_deref(), Some("Poetry"));
+        assert_eq!(full.timestamp.to_rfc3339(), "2025-01-02T03:04:05+00:00");
+        assert_eq!(full.metadata.get("rating").map(String::as_str), Some("Three"));
+        assert!(!full.metadata.contains_key(URL_PLACEHOLDER_KEY));
+
+        // No URL in metadata: the image stands in
+        let imaged = parse(serde_json::json!({
+            "id": "b2", "title": "Tipping the Velvet", "image": "https://books.example.com/velvet.jpg",
+            "source": "Books", "timestamp": "2025-01-02T03:04:05Z"
+        }));
+        assert_eq!(imaged.url, "https://books.example.com/velvet.jpg");
+        assert_eq!(imaged.price, None);
+
+        // Only the required fields: kept, with a placeholder URL that still validates
+        let before = Utc::now();
+        let bare = parse(serde_json::json!({"id": "c 3", "title": "Soumission", "source": "Books to Scrape - Main"}));
+        assert_eq!(bare.url, "https://books-to-scrape-main.invalid/items/c+3");
+        assert_eq!(bare.metadata.get(URL_PLACEHOLDER_KEY).map(String::as_str), Some("true"));
+        assert_eq!((bare.price, bare.image_url.as_deref(), bare.category.as_deref()), (None, None, None));
+        assert!(bare.timestamp >= before, "missing timestamp falls back to now");
+        assert!(Validator::new().check(&bare).is_ok());
+
+        let untitled = parse(serde_json::json!({"id": "d4", "title": "  ", "source": "Books"}));
+        assert_eq!(untitled.title, None);
+    }
+
+    #[tokio::test]
+    async fn test_latest_normalized_file_picks_newest_run() {
+        use rust_scraper_pro::output::latest_normalized_file;
+
+        let dir = std::env::temp_dir().join(format!("normalized-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        for name in [
+            "final_20240101_120000.json",
+            "final_20240315_080000.json",
+            "final_20240301_235959.json",
+            "final_20991231_000000.json.bak",
+            "notes.json",
+        ] {
+            tokio::fs::write(dir.join(name), "[]").await.unwrap();
+        }
+
+        let latest = latest_normalized_file(&dir).await.unwrap();
+        assert_eq!(latest, Some(dir.join("final_20240315_080000.json")));
+
+        // final.json always wins when the pipeline wrote one
+        tokio::fs::write(dir.join("final.json"), "[]").await.unwrap();
+        let latest = latest_normalized_file(&dir).await.unwrap();
+        assert_eq!(latest, Some(dir.join("final.json")));
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+        assert_eq!(latest_normalized_file(&dir).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_write_file_ensuring_dirs_creates_nested_directories() {
+        use rust_scraper_pro::output::atomic::write_file_ensuring_dirs;
+
+        let root = std::env::temp_dir().join(format!("ensure-dirs-{}", uuid::Uuid::new_v4()));
+        let path = root.join("data").join("normalized").join("final.json");
+        assert!(!root.exists());
+
+        write_file_ensuring_dirs(&path, "[]").await.unwrap();
+        assert_eq!(tokio::fs::read_to_string(&path).await.unwrap(), "[]");
+
+        // Existing directories are fine, and the file is replaced
+        write_file_ensuring_dirs(&path, "[{}]").await.unwrap();
+        assert_eq!(tokio::fs::read_to_string(&path).await.unwrap(), "[{}]");
+
+        tokio::fs::remove_dir_all(&root).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_crawl_follows_at_most_max_links_per_page() {
+        setup();
+
+        let mut server = mockito::Server::new_async().await;
+        // 100 links, the listing's own pages after 50 links elsewhere on the site
+        let links: Vec<String> = (0..50)
+            .map(|i| format!("/elsewhere/{}", i))
+            .chain((0..50).map(|i| format!("/list/{}", i)))
+            .collect();
+        let links: Vec<&str> = links.iter().map(String::as_str).collect();
+        let _root = server.mock("GET", "/list/").with_body(article_page("Root", &links)).create_async().await;
+        let followed = server
+            .mock("GET", mockito::Matcher::Regex(r"^/list/\d+$".to_string()))
+            .with_body(article_page("Child", &[]))
+            .expect(10)
+            .create_async()
+            .await;
+        let elsewhere = server
+            .mock("GET", mockito::Matcher::Regex("^/elsewhere/".to_string()))
+            .expect(0)
+            .create_async()
+            .await;
+
+        let config = CrawlConfig {
+            max_depth: 1,
+            max_links_per_page: Some(10),
+            concurrency: 4,
+            ..CrawlConfig::default()
+        };
+        let mut engine = test_engine();
+        let state = engine.crawl(&NewsSource::new(&format!("{}/list/", server.url())), &config).await.unwrap();
+
+        assert_eq!(state.pages_fetched, 11);
+        assert!(state.visited.contains(&format!("{}/list/9", server.url())));
+        assert!(!state.visited.contains(&format!("{}/list/10", server.url())));
+        followed.assert_async().await;
+        elsewhere.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_crawl_applies_include_and_exclude_patterns() {
+        setup();
+
+        let mut server = mockito::Server::new_async().await;
+        let root_links = ["/product/1", "/product/2?add-to-cart=2", "/cart", "/about"];
+        let _root = server.mock("GET", "/").with_body(article_page("Root", &root_links)).create_async().await;
+        let product = server
+            .mock("GET", "/product/1")
+            .with_body(article_page("Product", &[]))
+            .expect(1)
+            .create_async()
+            .await;
+        let skipped = server
+            .mock("GET", mockito::Matcher::Regex("^/(cart|about|product/2)".to_string()))
+            .expect(0)
+            .create_async()
+            .await;
+
+        let config = CrawlConfig {
+            include_patterns: vec!["/product/".to_string(), "/cart".to_string()],
+            exclude_patterns: vec!["/cart".to_string(), r"[?&]add-to-cart=".to_string()],
+            ..CrawlConfig::default()
+        };
+        let mut engine = test_engine();
+        let state = engine.crawl(&NewsSource::new(&server.url()), &config).await.unwrap();
+
+        assert_eq!(state.pages_fetched, 2);
+        assert!(state.visited.contains(&format!("{}/product/1", server.url())));
+        product.assert_async().await;
+        skipped.assert_async().await;
+
+        let invalid = CrawlConfig {
+            include_patterns: vec!["(".to_string()],
+            ..CrawlConfig::default()
+        };
+        assert!(engine.crawl(&NewsSource::new(&server.url()), &invalid).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_crawl_dedupes_pages_by_canonical_link() {
+        setup();
+
+        let mut server = mockito::Server::new_async().await;
+        let canonical = format!("{}/story", server.url());
+        let story = "<html><head><link rel=\"canonical\" href=\"/story\"></head>\
+                     <body><article><h2>Story</h2></article></body></html>";
+        let _root = server
+            .mock("GET", "/")
+            .with_body("<html><body><a href=\"/story?utm_source=feed\">a</a><a href=\"/amp/story\">b</a></body></html>")
+            .create_async()
+            .await;
+        let _tracked = server.mock("GET", "/story?utm_source=feed").with_body(story).create_async().await;
+        let _amp = server.mock("GET", "/amp/story").with_body(story).create_async().await;
+
+        let mut engine = test_engine();
+        let state = engine.crawl(&NewsSource::new(&server.url()), &CrawlConfig::default()).await.unwrap();
+
+        let stories: Vec<_> = state.results.iter().filter(|item| item.title.as_deref() == Some("Story")).collect();
+        assert_eq!(stories.len(), 1);
+        assert_eq!(stories[0].url, canonical);
+        assert_eq!(state.pages_fetched, 2);
+        assert!(state.visited.contains(&canonical));
+    }
+
+    #[test]
+    fn test_random_delay_mode_stays_within_bounds() {
+        use rust_scraper_pro::ai::{AdaptiveDelayConfig, AdaptiveDelayController, DelayMode};
+
+        let controller = AdaptiveDelayController::new(AdaptiveDelayConfig {
+            mode: DelayMode::Random,
+            min_delay_ms: 100,
+            max_delay_ms: 400,
+            ..AdaptiveDelayConfig::default()
+        });
+
+        let delays: Vec<u128> = (0..1000).map(|_| controller.calculate_delay().as_millis()).collect();
+        assert!(delays.iter().all(|delay| (100..=400).contains(delay)));
+
+        // Spread across the range rather than stuck at one end
+        assert!(delays.iter().any(|&delay| delay < 200));
+        assert!(delays.iter().any(|&delay| delay > 300));
+    }
+
+    #[tokio::test]
+    async fn test_adaptive_delay_state_round_trips_through_disk() {
+        use rust_scraper_pro::ai::{AdaptiveDelayConfig, DelayMode};
+        use std::time::Duration;
+
+        let adaptive = AdaptiveDelayConfig {
+            mode: DelayMode::Adaptive,
+            min_delay_ms: 0,
+            max_delay_ms: 10_000,
+            sample_size: 3,
+            multiplier: 1.0,
+            ..AdaptiveDelayConfig::default()
+        };
+        let path = std::env::temp_dir().join(format!("adaptive-{}", uuid::Uuid::new_v4())).join("delays.json");
+
+        let first_run = PolitenessController::new(100).with_adaptive(adaptive.clone());
+        for ms in [100, 200, 600, 900, 1200] {
+            first_run.record_response_time("https://slow.example/", Duration::from_millis(ms));
+        }
+        first_run.record_response_time("https://fast.example/", Duration::from_millis(50));
+        first_run.save_adaptive_state(&path).await.unwrap();
+
+        // Only the sample window is kept, oldest first
+        let state = first_run.adaptive_state();
+        assert_eq!(state.hosts["slow.example"], vec![600, 900, 1200]);
+
+        let next_run = PolitenessController::new(100).with_adaptive(adaptive);
+        assert_eq!(next_run.effective_delay("https://slow.example/"), Duration::from_millis(100));
+        assert_eq!(next_run.load_adaptive_state(&path).await.unwrap(), 2);
+
+        assert_eq!(next_run.adaptive_state(), state);
+        assert_eq!(next_run.effective_delay("https://slow.example/"), Duration::from_millis(900));
+
+        // A first run has nothing to load
+        let missing = path.with_file_name("missing.json");
+        assert_eq!(next_run.load_adaptive_state(&missing).await.unwrap(), 0);
+
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_adaptive_delay_escalates_on_errors_and_decays_on_success() {
+        use rust_scraper_pro::ai::{AdaptiveDelayConfig, AdaptiveDelayController, DelayMode};
+        use std::time::Duration;
+
+        let controller = AdaptiveDelayController::new(AdaptiveDelayConfig {
+            mode: DelayMode::Fixed,
+            min_delay_ms: 100,
+            max_delay_ms: 100,
+            sample_size: 10,
+            error_rate_threshold: 0.3,
+            error_backoff: 2.0,
+            max_error_escalation: 8.0,
+            ..AdaptiveDelayConfig::default()
+        });
+        for _ in 0..7 {
+            controller.record_success();
+        }
+        assert_eq!(controller.calculate_delay(), Duration::from_millis(100));
+
+        // Failures up to the threshold (3 of the last 10) don't escalate yet
+        controller.record_failure();
+        controller.record_failure();
+        controller.record_failure();
+        assert_eq!(controller.calculate_delay(), Duration::from_millis(100));
+
+        // Past it, each failure doubles the delay until the cap
+        let mut delays = Vec::new();
+        for _ in 0..5 {
+            controller.record_failure();
+            delays.push(controller.calculate_delay().as_millis());
+        }
+        assert_eq!(delays, vec![200, 400, 800, 800, 800]);
+
+        // Successes ease it back down to the normal delay
+        controller.record_success();
+        assert_eq!(controller.calculate_delay(), Duration::from_millis(400));
+        for _ in 0..3 {
+            controller.record_success();
+        }
+        assert_eq!(controller.calculate_delay(), Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn test_engine_stats_snapshot_and_endpoint() {
+        use rust_scraper_pro::ai::AdaptiveDelayConfig;
+        use rust_scraper_pro::core::stats::{EngineStats, HostHealth};
+
+        setup();
+
+        let mut server = mockito::Server::new_async().await;
+        let _ok = server.mock("GET", "/news").with_body(article_page("Story", &[])).create_async().await;
+        let _down = server.mock("GET", "/down").with_status(503).create_async().await;
+
+        let cache = Arc::new(HtmlCache::new_html_cache(100, 3600));
+        let config = Config {
+            scraping: ScrapingConfig { rate_limit_ms: 0, ..ScrapingConfig::default() },
+            sources: Vec::new(),
+            pipeline: PipelineConfig::default(),
+            alerts: Default::default(),
+        };
+        let adaptive = AdaptiveDelayConfig {
+            min_delay_ms: 0,
+            error_rate_threshold: 0.0,
+            ..AdaptiveDelayConfig::default()
+        };
+        let mut engine = ScraperEngine::new(config, ProcessingPipeline::new(), Some(cache))
+            .with_politeness(PolitenessController::new(0).with_adaptive(adaptive));
+        let monitor = engine.monitor();
+
+        let news = format!("{}/news", server.url());
+        engine.scrape_source(NewsSource::new(&news).with_name("News")).await.unwrap();
+        engine.scrape_source(NewsSource::new(&news).with_name("News")).await.unwrap();
+        let down = NewsSource::new(&format!("{}/down", server.url())).with_name("Down");
+        assert!(engine.scrape_source(down).await.is_err());
+
+        let stats = engine.stats();
+        // The second scrape of /news came from the cache
+        let cache_stats = stats.cache.as_ref().unwrap();
+        assert_eq!((cache_stats.hits, cache_stats.entry_count), (1, 1));
+        assert_eq!((stats.total_requests, stats.total_errors), (2, 1));
+
+        assert_eq!(stats.hosts.len(), 1);
+        let host = &stats.hosts[0];
+        assert_eq!(host.host, server.host_with_port());
+        assert_eq!((host.requests, host.errors), (2, 1));
+        assert!(host.last_request.is_some());
+        assert_eq!(host.health, HostHealth::Throttled);
+        assert!(host.error_escalation > 1.0);
+
+        let sources: Vec<_> = stats.sources.iter().map(|s| (s.source.as_str(), s.pages, s.errors)).collect();
+        assert_eq!(sources, vec![("Down", 1, 1), ("News", 2, 0)]);
+        assert!(stats.sources.iter().all(|source| source.last_scraped.is_some()));
+
+        // The API serves the same snapshot
+        let data: SharedData = IndexedStore::from(Vec::new()).into_shared();
+        let app = ApiServer::new(data.clone(), None, None).with_engine_monitor(monitor).create_app();
+        let (status, _, body) = api_request(app, get_request("/api/engine-stats")).await;
+        assert_eq!(status, axum::http::StatusCode::OK);
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        for key in ["generated_at", "cache", "hosts", "sources", "total_requests", "total_errors"] {
+            assert!(json.get(key).is_some(), "missing {}", key);
+        }
+        assert_eq!(json["hosts"][0]["health"], "throttled");
+        let served: EngineStats = serde_json::from_value(json).unwrap();
+        assert_eq!(served.hosts, stats.hosts);
+
+        let app = ApiServer::new(data, None, None).create_app();
+        let (status, _, _) = api_request(app, get_request("/api/engine-stats")).await;
+        assert_eq!(status, axum::http::StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_selftest_passes_end_to_end() {
+        use rust_scraper_pro::selftest::{Selftest, EXPECTED_ITEMS};
+
+        setup();
+
+        let dir = std::env::temp_dir().join(format!("selftest-{}", uuid::Uuid::new_v4()));
+        let report = Selftest::new().with_output_dir(&dir).run().await;
+
+        assert!(report.passed(), "{}", report);
+        let steps: Vec<_> = report.checks.iter().map(|check| check.name).collect();
+        assert_eq!(steps, vec!["scrape", "pipeline", "json export", "csv export", "sqlite"]);
+        assert!(report.checks[0].detail.starts_with(&EXPECTED_ITEMS.to_string()));
+        assert!(dir.join("selftest.json").exists() && dir.join("selftest.csv").exists());
+        assert!(report.to_string().ends_with("Self-test passed"));
+
+        // An unwritable output directory fails the export step and stops there
+        let blocker = dir.join("not-a-dir");
+        tokio::fs::write(&blocker, "").await.unwrap();
+        let report = Selftest::new().with_output_dir(&blocker).with_sqlite(false).run().await;
+        assert!(!report.passed());
+        let last = report.checks.last().unwrap();
+        assert_eq!((last.name, last.passed), ("json export", false));
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_deduplicator_content_min_len() {
+        setup();
+
+        let item = |path: &str, title: &str, content: &str| {
+            ScrapedData::new("source".to_string(), format!("https://example.com/{}", path))
+                .with_title(title.to_string())
+                .with_content(content.to_string())
+        };
+        let short = "In stock";
+        let long = "A long product description shared word for word by two listings";
+        let batch = || {
+            vec![
+                item("a", "Desk", short),
+                item("b", "Chair", short),
+                item("c", "Lamp", long),
+                item("d", "Lamp (copy)", long),
+            ]
+        };
+
+        // By default short snippets are ignored, long content is a dedup key
+        let titles = |items: Vec<ScrapedData>| items.into_iter().filter_map(|i| i.title).collect::<Vec<_>>();
+        let kept = Deduplicator::new().deduplicate(batch()).await.unwrap();
+        assert_eq!(titles(kept), vec!["Desk", "Chair", "Lamp"]);
+
+        // Lowering the threshold makes the short snippet a key too
+        let kept = Deduplicator::new().with_content_min_len(short.len()).deduplicate(batch()).await.unwrap();
+        assert_eq!(titles(kept), vec!["Desk", "Lamp"]);
+
+        // Raising it above the long content stops content dedup entirely
+        let kept = Deduplicator::new().with_content_min_len(long.len() + 1).deduplicate(batch()).await.unwrap();
+        assert_eq!(titles(kept).len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_deduplicator_prefers_source_ids() {
+        setup();
+
+        let post = |url: &str, title: &str, id: Option<&str>| {
+            let mut item = ScrapedData::new("Reddit".to_string(), url.to_string()).with_title(title.to_string());
+            if let Some(id) = id {
+                item.metadata.insert("source_id".to_string(), id.to_string());
+            }
+            item
+        };
+        let batch = vec![
+            post("https://reddit.com/r/rust/comments/abc123/hello", "Hello", Some("abc123")),
+            // Same post through another URL and an edited title
+            post("https://old.reddit.com/r/rust/comments/abc123", "Hello (edited)", Some("abc123")),
+            // A different post that happens to share the title
+            post("https://reddit.com/r/rust/comments/def456/hello", "Hello", Some("def456")),
+            // No id: falls back to URL/title matching
+            post("https://example.com/a", "Hello", None),
+        ];
+
+        let kept = Deduplicator::new().deduplicate(batch.clone()).await.unwrap();
+        let urls: Vec<_> = kept.iter().map(|item| item.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            vec!["https://reddit.com/r/rust/comments/abc123/hello", "https://reddit.com/r/rust/comments/def456/hello"]
+        );
+
+        // The id can live under another key
+        let renamed: Vec<_> = batch
+            .into_iter()
+            .map(|mut item| {
+                if let Some(id) = item.metadata.remove("source_id") {
+                    item.metadata.insert("post_id".to_string(), id);
+                }
+                item
+            })
+            .collect();
+        let kept = Deduplicator::new().with_id_key("post_id").deduplicate(renamed).await.unwrap();
+        assert_eq!(kept.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_export_bundle_zip_contains_every_export() {
+        use rust_scraper_pro::output::bundle::{BundleMetadata, BUNDLE_CSV, BUNDLE_JSON, BUNDLE_METADATA, BUNDLE_README};
+        use std::io::Read;
+
+        setup();
+
+        let mut other = api_item("c", "Other source");
+        other.source = "Other".to_string();
+        let data: SharedData = IndexedStore::from(vec![api_item("a", "First"), api_item("b", "Second"), other]).into_shared();
+        let app = ApiServer::new(data, None, None).create_app();
+
+        let (status, headers, body) = api_request(app, get_request("/api/export/bundle")).await;
+        assert_eq!(status, axum::http::StatusCode::OK);
+        assert_eq!(headers[axum::http::header::CONTENT_TYPE], "application/zip");
+
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(body)).unwrap();
+        let mut names: Vec<&str> = archive.file_names().collect();
+        names.sort();
+        assert_eq!(names, vec![BUNDLE_README, BUNDLE_CSV, BUNDLE_JSON, BUNDLE_METADATA]);
+
+        let mut metadata = String::new();
+        archive.by_name(BUNDLE_METADATA).unwrap().read_to_string(&mut metadata).unwrap();
+        let metadata: BundleMetadata = serde_json::from_str(&metadata).unwrap();
+        assert_eq!(metadata.item_count, 3);
+        assert_eq!(metadata.sources.values().sum::<usize>(), 3);
+        assert_eq!(metadata.sources["Other"], 1);
+
+        let mut json = String::new();
+        archive.by_name(BUNDLE_JSON).unwrap().read_to_string(&mut json).unwrap();
+        assert_eq!(rust_scraper_pro::output::importer::Importer::new().parse(&json).items.len(), 3);
+
+        let mut csv = String::new();
+        archive.by_name(BUNDLE_CSV).unwrap().read_to_string(&mut csv).unwrap();
+        assert_eq!(csv.lines().count(), 4, "header plus one row per item");
+    }
+
+    #[tokio::test]
+    async fn test_max_items_stops_sources_after_n_matches() {
+        use rust_scraper_pro::sources::{source::Source, SocialSource, TableSource};
+
+        setup();
+
+        let products: String = (1..=20)
+            .map(|i| format!(r#"<article class="product_pod"><h3><a href="book-{i}.html" title="Book {i}">Book {i}</a></h3><p class="price_color">£{i}.00</p></article>"#))
+            .collect();
+        let listing = format!("<html><body>{}</body></html>", products);
+
+        let source = EcommerceSource::new("https://shop.example.com").with_max_items(3);
+        let items = source.scrape(&listing).await.unwrap();
+        assert_eq!(items.len(), 3);
+        let titles: Vec<_> = items.iter().map(|item| item.title.as_deref().unwrap()).collect();
+        assert_eq!(titles, vec!["Book 1", "Book 2", "Book 3"]);
+
+        assert_eq!(EcommerceSource::new("https://shop.example.com").scrape(&listing).await.unwrap().len(), 20);
+
+        // The cap spans every matching table on the page
+        let rows: String = (1..=4).map(|i| format!("<tr><td>Row {i}</td></tr>")).collect();
+        let tables = format!("<table><tr><th>Name</th></tr>{rows}</table><table><tr><th>Name</th></tr>{rows}</table>");
+        let source = TableSource::new("https://example.com").with_column("name", "title").with_max_items(6);
+        assert_eq!(source.scrape(&tables).await.unwrap().len(), 6);
+
+        let posts: String = (1..=5).map(|i| format!(r#"<div class="post">Post {i}</div>"#)).collect();
+        let source = SocialSource::new("https://social.example.com").with_max_items(2);
+        assert_eq!(source.scrape(&format!("<html><body>{posts}</body></html>")).await.unwrap().len(), 2);
+
+        let entries: String = (1..=5).map(|i| format!("<item><title>Entry {i}</title><link>https://example.com/{i}</link></item>")).collect();
+        let feed = format!(r#"<?xml version="1.0"?><rss version="2.0"><channel><title>T</title>{entries}</channel></rss>"#);
+        let source = FeedSource::new("https://example.com/feed.xml").with_max_items(4);
+        assert_eq!(source.scrape(&feed).await.unwrap().len(), 4);
+    }
+
+    fn priced_item(title: &str, category: &str, price: f64) -> ScrapedData {
+        let mut item = ScrapedData::new("Books".to_string(), format!("https://books.example.com/{}", title.to_lowercase().replace(' ', "-")))
+            .with_title(title.to_string());
+        item.category = Some(category.to_string());
+        item.price = Some(price);
+        item
+    }
+
+    #[test]
+    fn test_price_below_rule_matches_only_scoped_items() {
+        use rust_scraper_pro::processors::rules::{Comparison, Rule, RuleEngine};
+
+        setup();
+
+        let data = vec![
+            priced_item("Cosmos", "Science", 12.5),
+            priced_item("Brief History", "Science", 15.0),
+            priced_item("Selfish Gene", "Science", 22.0),
+            priced_item("Cheap Novel", "Fiction", 3.0),
+        ];
+
+        let engine = RuleEngine::new(vec![Rule::new("Cheap science", "price", Comparison::Lt, 15.0).in_category("science")]).unwrap();
+        let matches = engine.evaluate(&data);
+
+        assert_eq!(matches.len(), 1, "15.0 is not below 15, and fiction is out of scope");
+        assert_eq!(matches[0].rule, "Cheap science");
+        assert_eq!(matches[0].title.as_deref(), Some("Cosmos"));
+        assert_eq!(matches[0].value, "12.5");
+
+        // Rules read from config, including metadata fields and source scoping
+        let config: rust_scraper_pro::processors::rules::AlertsConfig = toml::from_str(
+            r#"
+            [[rules]]
+            name = "Deep discount"
+            field = "metadata.discount_pct"
+            op = "ge"
+            value = 50
+            source = "Books"
+            "#,
+        )
+        .unwrap();
+        let mut discounted = priced_item("Half Off", "Fiction", 5.0);
+        discounted.metadata.insert("discount_pct".to_string(), "50".to_string());
+        let matches = RuleEngine::new(config.rules).unwrap().evaluate(&[discounted, priced_item("Full Price", "Fiction", 10.0)]);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].title.as_deref(), Some("Half Off"));
+
+        assert!(RuleEngine::new(vec![Rule::new("Typo", "prcie", Comparison::Lt, 1.0)]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_webhook_notifier_posts_rule_matches() {
+        use rust_scraper_pro::output::webhook::WebhookNotifier;
+        use rust_scraper_pro::processors::rules::{Comparison, Rule, RuleEngine};
+
+        setup();
+
+        let mut server = mockito::Server::new_async().await;
+        let hook = server
+            .mock("POST", "/alerts")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "matches": [{"rule": "Cheap", "title": "Cosmos", "value": "12.5"}]
+            })))
+            .with_status(204)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let engine = RuleEngine::new(vec![Rule::new("Cheap", "price", Comparison::Le, 12.5)]).unwrap();
+        let matches = engine.evaluate(&[priced_item("Cosmos", "Science", 12.5), priced_item("Dune", "Fiction", 20.0)]);
+
+        let notifier = WebhookNotifier::new(&format!("{}/alerts", server.url()));
+        notifier.notify(&matches).await.unwrap();
+        // No matches, no request
+        notifier.notify(&[]).await.unwrap();
+        hook.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_dedup_stats_break_down_removals_by_key() {
+        use rust_scraper_pro::processors::DedupStats;
+
+        setup();
+
+        let item = |url: &str, title: &str| ScrapedData::new("test".to_string(), url.to_string()).with_title(title.to_string());
+        let batch = vec![
+            item("https://example.com/a", "Alpha"),
+            item("https://example.com/b", "Beta"),
+            item("https://EXAMPLE.com/a", "Alpha reposted"),
+            item("https://example.com/c", "beta"),
+            item("https://example.com/d", "Delta"),
+        ];
+
+        let (unique, stats) = Deduplicator::new().deduplicate_with_stats(batch.clone()).await.unwrap();
+        assert_eq!(unique.len(), 3);
+        assert_eq!(
+            stats,
+            DedupStats {
+                by_id: 0,
+                by_url: 1,
+                by_title: 1,
+                by_content: 0,
+                total_removed: 2,
+            }
+        );
+
+        // The pipeline reports the same breakdown
+        let (processed, report) = ProcessingPipeline::new().with_validation(false).process_with_report(batch).await.unwrap();
+        assert_eq!(processed.len(), 3);
+        assert_eq!((report.input, report.output), (5, 3));
+        assert_eq!(report.dedup, stats);
+    }
+
+    #[tokio::test]
+    async fn test_api_errors_share_one_shape() {
+        use rust_scraper_pro::output::ApiError;
+
+        setup();
+
+        let data: SharedData = IndexedStore::from(Vec::new()).into_shared();
+        let app = ApiServer::new(data, None, None).create_app();
+
+        let malformed = axum::http::Request::post("/api/items")
+            .header("content-type", "application/json")
+            .body(axum::body::Body::from("[{\"title\": "))
+            .unwrap();
+        let not_an_array = json_request("POST", "/api/update", &serde_json::json!({"items": []}));
+
+        let cases = [
+            (get_request("/api/cache/stats"), axum::http::StatusCode::NOT_FOUND, "not_found"),
+            (get_request("/api/engine-stats"), axum::http::StatusCode::NOT_FOUND, "not_found"),
+            (axum::http::Request::post("/api/cache/clear").body(axum::body::Body::empty()).unwrap(), axum::http::StatusCode::NOT_FOUND, "not_found"),
+            (malformed, axum::http::StatusCode::BAD_REQUEST, "bad_request"),
+            (not_an_array, axum::http::StatusCode::UNPROCESSABLE_ENTITY, "unprocessable_entity"),
+        ];
+
+        for (request, status, code) in cases {
+            let uri = request.uri().to_string();
+            let (actual, headers, body) = api_request(app.clone(), request).await;
+            assert_eq!(actual, status, "{}", uri);
+            assert_eq!(headers[axum::http::header::CONTENT_TYPE], "application/json", "{}", uri);
+
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            let object = body.as_object().unwrap();
+            assert!(object.keys().all(|key| ["code", "message", "details"].contains(&key.as_str())), "{}: {}", uri, body);
+            assert_eq!(body["code"], code, "{}", uri);
+            assert!(!body["message"].as_str().unwrap().is_empty(), "{}", uri);
+
+            let error: ApiError = serde_json::from_value(body).unwrap();
+            assert_eq!(error.code, code);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_query_limits_are_clamped_and_malformed_params_rejected() {
+        use rust_scraper_pro::output::api::MAX_PAGE_SIZE;
+
+        setup();
+
+        let items: Vec<ScrapedData> = (0..MAX_PAGE_SIZE + 5).map(|i| api_item(&i.to_string(), &format!("Item {}", i))).collect();
+        let data: SharedData = IndexedStore::from(items).into_shared();
+        let app = ApiServer::new(data, None, None).create_app();
+
+        for uri in ["/api/data?limit=100000000", "/api/search?limit=100000000", "/api/search"] {
+            let (status, _, body) = api_request(app.clone(), get_request(uri)).await;
+            assert_eq!(status, axum::http::StatusCode::OK, "{}", uri);
+            let page: Vec<ScrapedData> = serde_json::from_slice(&body).unwrap();
+            assert_eq!(page.len(), MAX_PAGE_SIZE, "{}", uri);
+        }
+
+        let (_, _, body) = api_request(app.clone(), get_request("/api/search?limit=3&offset=2")).await;
+        let page: Vec<ScrapedData> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(page.iter().map(|item| item.id.as_str()).collect::<Vec<_>>(), vec!["2", "3", "4"]);
+
+        for uri in ["/api/data?limit=ten", "/api/data?offset=-1", "/api/search?limit=1.5"] {
+            let (status, _, body) = api_request(app.clone(), get_request(uri)).await;
+            assert_eq!(status, axum::http::StatusCode::BAD_REQUEST, "{}", uri);
+            let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(error["code"], "bad_request", "{}", uri);
+            assert!(error["message"].as_str().unwrap().contains(if uri.contains("offset") { "offset" } else { "limit" }), "{}", error);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_item_by_id() {
+        setup();
+
+        let data: SharedData = IndexedStore::from(vec![api_item("a", "First"), api_item("b", "Second")]).into_shared();
+        let app = ApiServer::new(data, None, None).create_app();
+
+        let (status, _, body) = api_request(app.clone(), get_request("/api/item/b")).await;
+        assert_eq!(status, axum::http::StatusCode::OK);
+        let item: ScrapedData = serde_json::from_slice(&body).unwrap();
+        assert_eq!(item.id, "b");
+        assert_eq!(item.title.as_deref(), Some("Second"));
+
+        let (status, _, body) = api_request(app, get_request("/api/item/missing")).await;
+        assert_eq!(status, axum::http::StatusCode::NOT_FOUND);
+        let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error["code"], "not_found");
+        assert!(error["message"].as_str().unwrap().contains("missing"));
+    }
+
+    #[tokio::test]
+    async fn test_patch_item_updates_only_the_price() {
+        setup();
+
+        let mut original = api_item("a", "Priced");
+        original.price = Some(20.0);
+        original.content = Some("Unchanged content".to_string());
+        let data: SharedData = IndexedStore::from(vec![original.clone(), api_item("b", "Other")]).into_shared();
+        let app = ApiServer::new(data.clone(), None, None).create_app();
+
+        let (status, _, body) = api_request(app.clone(), json_request("PATCH", "/api/item/a", &serde_json::json!({"price": 12.5}))).await;
+        assert_eq!(status, axum::http::StatusCode::OK);
+        let updated: ScrapedData = serde_json::from_slice(&body).unwrap();
+        assert_eq!(updated.price, Some(12.5));
+        assert_eq!(updated.title, original.title);
+        assert_eq!(updated.content, original.content);
+        assert_eq!(updated.url, original.url);
+
+        // The stored copy changed too, and nothing else did
+        let stored = data.read().await;
+        assert_eq!(stored.items()[0].price, Some(12.5));
+        assert_eq!(stored.items()[0].content.as_deref(), Some("Unchanged content"));
+        assert_eq!(stored.items()[1].price, None);
+        drop(stored);
+
+        let (status, _, _) = api_request(app.clone(), json_request("PATCH", "/api/item/missing", &serde_json::json!({"price": 1.0}))).await;
+        assert_eq!(status, axum::http::StatusCode::NOT_FOUND);
+
+        for (body, expected) in [
+            (serde_json::json!({}), axum::http::StatusCode::BAD_REQUEST),
+            (serde_json::json!({"id": "new-id"}), axum::http::StatusCode::UNPROCESSABLE_ENTITY),
+            (serde_json::json!({"price": -5.0}), axum::http::StatusCode::UNPROCESSABLE_ENTITY),
+        ] {
+            let (status, _, _) = api_request(app.clone(), json_request("PATCH", "/api/item/a", &body)).await;
+            assert_eq!(status, expected, "{}", body);
+        }
+        assert_eq!(data.read().await.items()[0].price, Some(12.5));
+    }
+
+    #[tokio::test]
+    async fn test_patch_item_changes_nothing_when_the_database_update_fails() {
+        setup();
+        let Some(db) = test_postgres().await else {
+            return;
+        };
+
+        let mut original = api_item("a", "Priced");
+        original.price = Some(20.0);
+        db.save(std::slice::from_ref(&original)).await.unwrap();
+        let table = db.table_name().to_string();
+        let db = Arc::new(db);
+        let data: SharedData = IndexedStore::from(vec![original]).into_shared();
+        let app = ApiServer::new(data.clone(), Some(db.clone()), None).create_app();
+
+        // Every later write to the table fails
+        drop_postgres_table(&db, &table).await;
+        let (status, _, body) = api_request(app, json_request("PATCH", "/api/item/a", &serde_json::json!({"price": 12.5}))).await;
+        assert_eq!(status, axum::http::StatusCode::INTERNAL_SERVER_ERROR);
+        let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(error["message"].as_str().unwrap().contains("Failed to update item"));
+        assert_eq!(data.read().await.items()[0].price, Some(20.0));
+    }
+
+    #[tokio::test]
+    async fn test_append_leaves_memory_alone_when_the_database_save_fails() {
+        setup();
+        let Some(db) = test_postgres().await else {
+            return;
+        };
+
+        let table = db.table_name().to_string();
+        let db = Arc::new(db);
+        let data: SharedData = IndexedStore::from(Vec::new()).into_shared();
+        let app = ApiServer::new(data.clone(), Some(db.clone()), None).create_app();
+
+        let (status, headers, _) = api_request(app.clone(), get_request("/api/data")).await;
+        assert_eq!(status, axum::http::StatusCode::OK);
+        let etag = headers["etag"].to_str().unwrap().to_string();
+
+        // `author` is VARCHAR(255), so the batch fails to save
+        let mut too_long = api_item("bad", "Too long");
+        too_long.author = Some("x".repeat(300));
+        let batch = vec![api_item("good", "Fine"), too_long];
+        let (status, _, _) = api_request(app.clone(), json_request("POST", "/api/items", &batch)).await;
+        assert_eq!(status, axum::http::StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(data.read().await.is_empty());
+        assert_eq!(db.count().await.unwrap(), 0);
+        let (_, headers, _) = api_request(app.clone(), get_request("/api/data")).await;
+        assert_eq!(headers["etag"].to_str().unwrap(), etag);
+
+        let (status, _, body) = api_request(app, json_request("POST", "/api/items", &vec![api_item("good", "Fine")])).await;
+        assert_eq!(status, axum::http::StatusCode::OK);
+        let appended: AppendResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(appended.inserted, 1);
+        assert!(data.read().await.contains("good"));
+        assert_eq!(db.count().await.unwrap(), 1);
+
+        drop_postgres_table(&db, &table).await;
+    }
+
+    #[tokio::test]
+    async fn test_patching_a_database_only_item_changes_the_etag() {
+        setup();
+        let Some(db) = test_postgres().await else {
+            return;
+        };
+
+        let mut original = api_item("a", "Priced");
+        original.price = Some(20.0);
+        db.save(std::slice::from_ref(&original)).await.unwrap();
+        let table = db.table_name().to_string();
+        let db = Arc::new(db);
+        // Nothing in memory, as after the capped store evicted the item
+        let data: SharedData = IndexedStore::from(Vec::new()).into_shared();
+        let app = ApiServer::new(data, Some(db.clone()), None).create_app();
+
+        let (status, headers, _) = api_request(app.clone(), get_request("/api/data")).await;
+        assert_eq!(status, axum::http::StatusCode::OK);
+        let etag = headers["etag"].to_str().unwrap().to_string();
+
+        let (status, _, _) = api_request(app.clone(), json_request("PATCH", "/api/item/a", &serde_json::json!({"price": 12.5}))).await;
+        assert_eq!(status, axum::http::StatusCode::OK);
+
+        let request = axum::http::Request::get("/api/data")
+            .header("if-none-match", &etag)
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let (status, headers, body) = api_request(app, request).await;
+        assert_eq!(status, axum::http::StatusCode::OK);
+        assert_ne!(headers["etag"].to_str().unwrap(), etag);
+        let items: Vec<ScrapedData> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(items[0].price, Some(12.5));
+
+        drop_postgres_table(&db, &table).await;
+    }
+
+    #[tokio::test]
+    async fn test_cors_preflight_allows_patch() {
+        setup();
+        let data: SharedData = IndexedStore::from(vec![api_item("a", "Item")]).into_shared();
+        let app = ApiServer::new(data, None, None).create_app();
+
+        let request = axum::http::Request::builder()
+            .method("OPTIONS")
+            .uri("/api/item/a")
+            .header("origin", "http://localhost:5173")
+            .header("access-control-request-method", "PATCH")
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let (status, headers, _) = api_request(app, request).await;
+        assert_eq!(status, axum::http::StatusCode::OK);
+        assert!(headers["access-control-allow-methods"].to_str().unwrap().contains("PATCH"));
+    }
+
+    #[tokio::test]
+    async fn test_content_truncation_at_the_boundary() {
+        use rust_scraper_pro::processors::{Normalizer, CONTENT_LEN_KEY};
+
+        setup();
+
+        let item = |content: &str| {
+            ScrapedData::new("test".to_string(), "https://example.com/a".to_string()).with_content(content.to_string())
+        };
+        let normalizer = Normalizer::new().with_max_content_len(10);
+
+        let at_limit = normalizer.normalize(vec![item("0123456789")]).await.unwrap().remove(0);
+        assert_eq!(at_limit.content.as_deref(), Some("0123456789"));
+        assert!(!at_limit.metadata.contains_key(CONTENT_LEN_KEY));
+
+        let over = normalizer.normalize(vec![item("0123456789A")]).await.unwrap().remove(0);
+        assert_eq!(over.content.as_deref(), Some("0123456789…"));
+        assert_eq!(over.metadata[CONTENT_LEN_KEY], "11");
+
+        // No dangling space before the ellipsis
+        let words = normalizer.normalize(vec![item("Ten chars and then some")]).await.unwrap().remove(0);
+        assert_eq!(words.content.as_deref(), Some("Ten chars…"));
+        assert_eq!(words.metadata[CONTENT_LEN_KEY], "23");
+
+        // Off by default
+        let long = "x".repeat(10_000);
+        let untouched = Normalizer::new().normalize(vec![item(&long)]).await.unwrap().remove(0);
+        assert_eq!(untouched.content.unwrap().len(), 10_000);
+    }
+
+    #[tokio::test]
+    async fn test_sources_config_headers_and_cookies_are_sent() {
+        use rust_scraper_pro::core::SourcesConfig;
+        use rust_scraper_pro::sources::Source;
+
+        setup();
+
+        let mut server = mockito::Server::new_async().await;
+        let page = server
+            .mock("GET", "/")
+            .match_header("accept-language", "de-DE")
+            .match_header("x-client", "scraper")
+            .match_header("cookie", "consent=yes; region=eu")
+            .with_header("content-type", "text/html")
+            .with_body(article_page("Nachrichten", &[]))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let path = std::env::temp_dir().join(format!("sources-{}.toml", uuid::Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            format!(
+                r#"
+                [[news]]
+                name = "Localized"
+                url = "{}"
+                headers = {{ "Accept-Language" = "de-DE", "X-Client" = "scraper" }}
+                cookies = {{ region = "eu", consent = "yes" }}
+
+                [[news]]
+                name = "Disabled"
+                url = "https://disabled.example.com"
+                enabled = false
+
+                [[api]]
+                name = "Ignored"
+                url = "https://api.example.com"
+                "#,
+                server.url()
+            ),
+        )
+        .unwrap();
+
+        let sources = SourcesConfig::load(&path).await.unwrap().into_sources().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(sources.len(), 1);
+        assert_eq!(sources[0].name(), "Localized");
+
+        let mut engine = test_engine();
+        let items = engine.scrape_source(sources.into_iter().next().unwrap()).await.unwrap();
+        assert!(!items.is_empty());
+        page.assert_async().await;
+
+        let bad: SourcesConfig =
+            toml::from_str("[[news]]\nname = \"Bad\"\nurl = \"https://example.com\"\nheaders = { \"Bad Header\" = \"x\" }").unwrap();
+        assert!(bad.into_sources().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_empty_result_is_retried_until_items_appear() {
+        setup();
+
+        let mut server = mockito::Server::new_async().await;
+        let empty = server
+            .mock("GET", "/")
+            .with_header("content-type", "text/html")
+            .with_body("<html><body><div id=\"app\"></div></body></html>")
+            .expect(1)
+            .create_async()
+            .await;
+        let populated = server
+            .mock("GET", "/")
+            .with_header("content-type", "text/html")
+            .with_body(article_page("Loaded", &[]))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config = Config {
+            scraping: ScrapingConfig {
+                rate_limit_ms: 0,
+                max_retries: 0,
+                follow_robots_txt: false,
+                empty_result_retries: 2,
+                empty_result_retry_delay_ms: 0,
+                ..ScrapingConfig::default()
+            },
+            sources: Vec::new(),
+            pipeline: PipelineConfig::default(),
+            alerts: Default::default(),
+        };
+        let mut engine = ScraperEngine::new(config, ProcessingPipeline::new(), None);
+        let items = engine.scrape_source(NewsSource::new(&server.url())).await.unwrap();
+
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].title.as_deref(), Some("Loaded"));
+        empty.assert_async().await;
+        populated.assert_async().await;
+
+        // Without retries the empty page is the answer
+        let mut engine = test_engine_with_rate_limit(0);
+        assert!(engine.scrape_source(NewsSource::new(&server.url())).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_pre_clean_strips_scripts_and_keeps_content() {
+        setup();
+
+        let html = r#"<html><head><style>body { color: red; }</style>
+<script type="text/javascript">var tracking = "ad-network";</script></head>
+<body><!-- banner slot --><noscript>Enable JavaScript</noscript>
+<main><p>Actual article text</p><SCRIPT>pushAd()</SCRIPT></main></body></html>"#;
+
+        let cleaned = rust_scraper_pro::utils::html::strip_noise(html);
+        for noise in ["tracking", "color: red", "banner slot", "Enable JavaScript", "pushAd"] {
+            assert!(!cleaned.contains(noise), "{} survived: {}", noise, cleaned);
+        }
+        assert!(cleaned.contains("<main><p>Actual article text</p></main>"));
+
+        let mut server = mockito::Server::new_async().await;
+        let _page = server
+            .mock("GET", "/")
+            .with_header("content-type", "text/html")
+            .with_body(html)
+            .create_async()
+            .await;
+
+        let source = |pre_clean| {
+            rust_scraper_pro::sources::CustomSource::new(&server.url(), "Noisy")
+                .with_selectors(vec!["body".to_string()])
+                .with_pre_clean(pre_clean)
+        };
+        let mut engine = test_engine_with_rate_limit(0);
+        let raw = engine.scrape_source(source(false)).await.unwrap();
+        assert!(raw[0].content.as_deref().unwrap().contains("Enable JavaScript"));
+
+        let mut engine = test_engine_with_rate_limit(0);
+        let clean = engine.scrape_source(source(true)).await.unwrap();
+        assert_eq!(clean[0].content.as_deref(), Some("Actual article text"));
+    }
+
+    #[tokio::test]
+    async fn test_server_errors_are_retried_with_backoff() {
+        setup();
+
+        let mut server = mockito::Server::new_async().await;
+        let unavailable = server.mock("GET", "/").with_status(503).expect(2).create_async().await;
+        let recovered = server
+            .mock("GET", "/")
+            .with_header("content-type", "text/html")
+            .with_body(article_page("Recovered", &[]))
+            .expect(1)
+            .create_async()
+            .await;
+        let missing = server.mock("GET", "/gone").with_status(404).expect(1).create_async().await;
+
+        let config = Config {
+            scraping: ScrapingConfig {
+                rate_limit_ms: 0,
+                max_retries: 3,
+                follow_robots_txt: false,
+                ..ScrapingConfig::default()
+            },
+            sources: Vec::new(),
+            pipeline: PipelineConfig::default(),
+            alerts: Default::default(),
+        };
+        let mut engine = ScraperEngine::new(config, ProcessingPipeline::new(), None)
+            .with_retry_backoff(Backoff::new(std::time::Duration::from_millis(1), std::time::Duration::from_millis(5)).with_jitter(Jitter::none()));
+
+        let items = engine.scrape_source(NewsSource::new(&server.url())).await.unwrap();
+        assert_eq!(items[0].title.as_deref(), Some("Recovered"));
+        unavailable.assert_async().await;
+        recovered.assert_async().await;
+
+        // A 404 isn't going to change, so it isn't retried
+        let err = engine
+            .scrape_source(NewsSource::new(&format!("{}/gone", server.url())))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ScraperError>(),
+            Some(ScraperError::HttpError(status)) if status.as_u16() == 404
+        ));
+        missing.assert_async().await;
+    }
+
+    const ARTICLE_WITH_SIDEBARS: &str = r#"<html><body>
+<header><nav><a href="/">Home</a> <a href="/world">World</a> <a href="/tech">Tech</a></nav></header>
+<div class="layout">
+  <div class="sidebar-left"><p>Subscribe to our newsletter for the latest headlines, every morning.</p></div>
+  <main>
+    <article>
+      <h1>Rust ships a new edition</h1>
+      <p class="byline">By Jane Doe</p>
+      <div class="article-body">
+        <p>The Rust project released its new edition today, bringing let chains, async closures and a refreshed prelude to every crate that opts in.</p>
+        <p>Migration is mostly automatic: cargo fix rewrites the affected code, and crates on different editions keep working together.</p>
+        <div class="related-links"><p><a href="/a">Related: How editions work, explained in depth</a></p></div>
+        <p>Maintainers said the next edition is already being planned, with a focus on compile times.</p>
+      </div>
+    </article>
+  </main>
+  <aside><p>Trending: ten gadgets you need this year, ranked by our editors.</p></aside>
+</div>
+<div id="comments"><p>First! Great article, thanks for writing it, really enjoyed the read.</p></div>
+<footer><p>Copyright 2026 Example News, all rights reserved worldwide.</p></footer>
+</body></html>"#;
+
+    #[tokio::test]
+    async fn test_news_content_uses_readable_article_body() {
+        setup();
+
+        let items = NewsSource::new("https://news.example.com/rust-edition").scrape(ARTICLE_WITH_SIDEBARS).await.unwrap();
+        assert_eq!(items.len(), 1);
+
+        let content = items[0].content.as_deref().unwrap();
+        let paragraphs: Vec<&str> = content.split("\n\n").collect();
+        assert_eq!(paragraphs.len(), 3, "{}", content);
+        assert!(paragraphs[0].starts_with("The Rust project released its new edition today"));
+        assert!(paragraphs[2].starts_with("Maintainers said"));
+        for boilerplate in ["newsletter", "Trending", "First!", "Copyright", "Related:", "Jane Doe"] {
+            assert!(!content.contains(boilerplate), "{} leaked into {}", boilerplate, content);
+        }
+
+        // Too little prose to be an article: back to the first paragraph
+        let short = article_page("Brief", &[]);
+        let items = NewsSource::new("https://news.example.com/brief").scrape(&short).await.unwrap();
+        assert_eq!(items[0].content.as_deref(), Some("Body of Brief"));
+    }
+
+    #[tokio::test]
+    async fn test_robots_txt_disallowed_urls_are_skipped() {
+        setup();
+
+        let robots_txt = "User-agent: *\nDisallow: /\n\nUser-agent: RustScraperPro\nDisallow: /private\nAllow: /private/*.html$\nCrawl-delay: 0.01\n";
+        let mut server = mockito::Server::new_async().await;
+        let robots = server.mock("GET", "/robots.txt").with_body(robots_txt).expect(1).create_async().await;
+        let _home = server.mock("GET", "/").with_header("content-type", "text/html").with_body(article_page("Home", &[])).create_async().await;
+        let private = server
+            .mock("GET", "/private")
+            .with_header("content-type", "text/html")
+            .with_body(article_page("Private", &[]))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config = |follow_robots_txt| Config {
+            scraping: ScrapingConfig {
+                rate_limit_ms: 0,
+                max_retries: 0,
+                follow_robots_txt,
+                user_agent: "Mozilla/5.0 (compatible; RustScraperPro/1.0)".to_string(),
+                ..ScrapingConfig::default()
+            },
+            sources: Vec::new(),
+            pipeline: PipelineConfig::default(),
+            alerts: Default::default(),
+        };
+        let mut engine = ScraperEngine::new(config(true), ProcessingPipeline::new(), None);
+
+        // Our own group applies, not the catch-all that disallows everything
+        let home = engine.scrape_source(NewsSource::new(&server.url())).await.unwrap();
+        assert_eq!(home.len(), 1);
+        let skipped = engine.scrape_source(NewsSource::new(&format!("{}/private", server.url()))).await.unwrap();
+        assert!(skipped.is_empty());
+        robots.assert_async().await;
+        assert_eq!(engine.politeness().effective_delay(&server.url()), std::time::Duration::from_millis(10));
+
+        // Turned off, robots.txt isn't consulted
+        let mut engine = ScraperEngine::new(config(false), ProcessingPipeline::new(), None);
+        let fetched = engine.scrape_source(NewsSource::new(&format!("{}/private", server.url()))).await.unwrap();
+        assert_eq!(fetched[0].title.as_deref(), Some("Private"));
+        private.assert_async().await;
+        robots.assert_async().await;
+
+        let rules = RobotsRules::parse(robots_txt, "RustScraperPro/1.0");
+        let allowed = |path: &str| rules.is_allowed(&url::Url::parse(&format!("https://example.com{}", path)).unwrap());
+        assert!(allowed("/news"));
+        assert!(!allowed("/private/report.pdf"));
+        assert!(allowed("/private/page.html"));
+        assert!(!allowed("/private/page.html?print=1"));
+        assert!(!RobotsRules::parse(robots_txt, "OtherBot").is_allowed(&url::Url::parse("https://example.com/news").unwrap()));
+    }
+
+    fn store_item(id: &str, source: &str, category: Option<&str>) -> ScrapedData {
+        let mut item = api_item(id, id);
+        item.source = source.to_string();
+        item.category = category.map(str::to_string);
+        item
+    }
+
+    #[test]
+    fn test_indexed_store_keeps_indexes_in_step_with_updates() {
+        let mut store = IndexedStore::from(vec![
+            store_item("a", "Books", Some("Science")),
+            store_item("b", "News", Some("Tech")),
+            store_item("c", "Books", Some("science")),
+            store_item("a", "Books", Some("Fiction")),
+        ]);
+
+        // The repeated id replaced the first copy in place
+        assert_eq!(store.len(), 3);
+        assert_eq!(store.items().iter().map(|item| item.id.as_str()).collect::<Vec<_>>(), vec!["a", "b", "c"]);
+        assert_eq!(store.get("a").unwrap().category.as_deref(), Some("Fiction"));
+        assert_eq!(store.by_category("SCIENCE").iter().map(|item| item.id.as_str()).collect::<Vec<_>>(), vec!["c"]);
+        assert_eq!(store.sources(), vec!["Books", "News"]);
+
+        assert!(!store.upsert(store_item("b", "Books", None)));
+        assert!(store.upsert(store_item("d", "News", Some("Tech"))));
+        assert_eq!(store.by_source("Books").len(), 3);
+        assert_eq!(store.by_source("News").iter().map(|item| item.id.as_str()).collect::<Vec<_>>(), vec!["d"]);
+        assert_eq!(store.select(Some("book"), Some("fic")).len(), 1);
+        assert_eq!(store.select(None, Some("tech")).iter().map(|item| item.id.as_str()).collect::<Vec<_>>(), vec!["d"]);
+
+        assert_eq!(store.remove("a").map(|item| item.id), Some("a".to_string()));
+        assert!(store.get("a").is_none());
+        assert_eq!(store.get("d").unwrap().id, "d");
+        assert_eq!(store.by_source("Books").iter().map(|item| item.id.as_str()).collect::<Vec<_>>(), vec!["b", "c"]);
+        assert!(store.remove("a").is_none());
+    }
+
+    #[test]
+    fn test_indexed_store_lookups_beat_linear_scans() {
+        const ITEMS: usize = 20_000;
+        const LOOKUPS: usize = 200;
+
+        let items: Vec<ScrapedData> = (0..ITEMS)
+            .map(|i| store_item(&format!("item-{}", i), &format!("source-{}", i % 50), Some("General")))
+            .collect();
+        let store = IndexedStore::from(items.clone());
+        let wanted: Vec<String> = (0..LOOKUPS).map(|i| format!("item-{}", ITEMS - 1 - i)).collect();
+
+        let started = std::time::Instant::now();
+        let scanned = wanted.iter().filter(|id| items.iter().any(|item| &item.id == *id)).count();
+        let linear = started.elapsed();
+
+        let started = std::time::Instant::now();
+        let indexed_hits = wanted.iter().filter(|id| store.get(id).is_some()).count();
+        let indexed = started.elapsed();
+
+        assert_eq!(scanned, LOOKUPS);
+        assert_eq!(indexed_hits, LOOKUPS);
+        assert!(indexed * 10 < linear, "indexed {:?} vs linear {:?}", indexed, linear);
+
+        let started = std::time::Instant::now();
+        let scanned = items.iter().filter(|item| item.source == "source-7").count();
+        let linear = started.elapsed();
+        let started = std::time::Instant::now();
+        let indexed_hits = store.by_source("source-7").len();
+        let indexed = started.elapsed();
+        assert_eq!(scanned, ITEMS / 50);
+        assert_eq!(indexed_hits, ITEMS / 50);
+        assert!(indexed < linear, "indexed {:?} vs linear {:?}", indexed, linear);
+    }
+
+    #[tokio::test]
+    async fn test_api_reads_the_whole_dataset_from_the_database_when_memory_is_capped() {
+        setup();
+        let Some(db) = test_postgres().await else {
+            return;
+        };
+
+        let start = chrono::Utc::now();
+        let items: Vec<ScrapedData> = (0..5)
+            .map(|i| {
+                let mut item = api_item(&format!("item-{}", i), &format!("Capped {}", i));
+                item.timestamp = start + chrono::Duration::seconds(i);
+                item.price = Some(i as f64 + 1.0);
+                if i % 2 == 1 {
+                    item.content = Some(format!("Body {}", i));
+                }
+                item.record_extraction();
+                item
+            })
+            .collect();
+        db.save(&items).await.unwrap();
+        let table = db.table_name().to_string();
+        let db = Arc::new(db);
+
+        // Memory only keeps the newest two
+        let data: SharedData = IndexedStore::from(items.clone()).with_max_items(2).into_shared();
+        assert_eq!(data.read().await.len(), 2);
+        let app = ApiServer::new(data.clone(), Some(db.clone()), None).create_app();
+
+        let (_, headers, body) = api_request(app.clone(), get_request("/api/export/json")).await;
+        let exported: Vec<ScrapedData> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(exported.len(), 5);
+        let request = axum::http::Request::get("/api/export/json")
+            .header("if-none-match", headers["etag"].clone())
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let (status, _, _) = api_request(app.clone(), request).await;
+        assert_eq!(status, axum::http::StatusCode::NOT_MODIFIED);
+
+        let (_, _, body) = api_request(app.clone(), get_request("/api/stats")).await;
+        let stats: std::collections::HashMap<String, usize> = serde_json::from_slice(&body).unwrap();
+        assert_eq!((stats["total_items"], stats["items_with_price"]), (5, 5));
+        assert_eq!((stats["unique_sources"], stats["items_with_content"]), (1, 2));
+
+        let search = |uri: &'static str| {
+            let app = app.clone();
+            async move {
+                let (status, _, body) = api_request(app, get_request(uri)).await;
+                assert_eq!(status, axum::http::StatusCode::OK, "{}", uri);
+                let found: Vec<ScrapedData> = serde_json::from_slice(&body).unwrap();
+                found.into_iter().map(|item| item.id).collect::<Vec<_>>()
+            }
+        };
+        assert_eq!(search("/api/search?query=capped%200").await, vec!["item-0"]);
+        // Filtered, ordered and paged by the database, newest first
+        assert_eq!(search("/api/search?query=CAPPED&source=AP&limit=2&offset=1").await, vec!["item-3", "item-2"]);
+        assert_eq!(search("/api/search?query=body&limit=1").await, vec!["item-3"]);
+        assert!(search("/api/search?source=other").await.is_empty());
+        // LIKE wildcards in the query match literally
+        assert!(search("/api/search?query=%25").await.is_empty());
+        assert!(search("/api/search?query=capped_").await.is_empty());
+
+        let (_, _, body) = api_request(app.clone(), get_request("/api/extraction")).await;
+        let rates: Vec<ExtractionRate> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(rates, ExtractionRate::summarize(&items));
+
+        let (status, _, body) = api_request(app.clone(), get_request("/api/export/bundle")).await;
+        assert_eq!(status, axum::http::StatusCode::OK);
+        let mut bundle = zip::ZipArchive::new(std::io::Cursor::new(body)).unwrap();
+        let bundled: serde_json::Value = serde_json::from_reader(bundle.by_name("data.json").unwrap()).unwrap();
+        assert_eq!(bundled["items"].as_array().unwrap().len(), 5);
+        let csv = std::io::read_to_string(bundle.by_name("data.csv").unwrap()).unwrap();
+        assert_eq!(csv.lines().count(), 6);
+        let metadata: serde_json::Value = serde_json::from_reader(bundle.by_name("metadata.json").unwrap()).unwrap();
+        assert_eq!(metadata["item_count"], 5);
+
+        // An evicted id is still stored, so posting it again isn't an insert
+        let (_, _, body) = api_request(app.clone(), json_request("POST", "/api/items", &vec![items[0].clone()])).await;
+        let appended: AppendResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!((appended.inserted, appended.skipped), (0, 1));
+        assert!(!data.read().await.contains("item-0"));
+
+        drop_postgres_table(&db, &table).await;
+    }
+
+    #[test]
+    fn test_capped_store_evicts_in_batches_past_its_slack() {
+        let start = chrono::Utc::now();
+        let item = |i: i64| {
+            let mut item = store_item(&format!("item-{}", i), &format!("source-{}", i % 3), Some("General"));
+            item.timestamp = start + chrono::Duration::seconds(i);
+            item
+        };
+
+        // A cap of 64 may run 4 over before it drops back to 64
+        let mut store = IndexedStore::new().with_max_items(64);
+        for i in 0..68 {
+            store.upsert(item(i));
+        }
+        assert_eq!(store.len(), 68);
+        store.upsert(item(68));
+        assert_eq!(store.len(), 64);
+        assert!((0..5).all(|i| !store.contains(&format!("item-{}", i))));
+        assert!((5..69).all(|i| store.contains(&format!("item-{}", i))));
+
+        // Indexes still line up after compaction, and ages follow updates
+        assert_eq!(store.by_source("source-0").len() + store.by_source("source-1").len() + store.by_source("source-2").len(), 64);
+        assert_eq!(store.get("item-40").unwrap().id, "item-40");
+        let mut refreshed = item(5);
+        refreshed.timestamp = start + chrono::Duration::seconds(1000);
+        store.upsert(refreshed);
+        for i in 69..74 {
+            store.upsert(item(i));
+        }
+        assert_eq!(store.len(), 64);
+        assert!(store.contains("item-5"));
+        assert!((6..11).all(|i| !store.contains(&format!("item-{}", i))));
+    }
+
+    #[test]
+    fn test_capped_store_upserts_stay_cheap() {
+        const ITEMS: i64 = 20_000;
+
+        let items: Vec<ScrapedData> = (0..ITEMS)
+            .map(|i| {
+                let mut item = store_item(&format!("item-{}", i), "source", None);
+                item.timestamp = chrono::Utc::now() + chrono::Duration::seconds(i);
+                item
+            })
+            .collect();
+
+        let mut uncapped = IndexedStore::new();
+        let started = std::time::Instant::now();
+        for item in items.iter().cloned() {
+            uncapped.upsert(item);
+        }
+        let unbounded = started.elapsed();
+
+        // Sorting and rebuilding on every insert past the cap would be
+        // hundreds of times slower than this
+        let mut capped = IndexedStore::new().with_max_items(2_000);
+        let started = std::time::Instant::now();
+        for item in items.iter().cloned() {
+            capped.upsert(item);
+        }
+        let bounded = started.elapsed();
+
+        assert!(capped.len() <= 2_000 + 2_000 / 16);
+        assert!(capped.contains(&format!("item-{}", ITEMS - 1)));
+        assert!(bounded < unbounded * 50, "capped {:?} vs uncapped {:?}", bounded, unbounded);
+    }
+
+    fn catalogue_page(titles: &[&str], next: Option<&str>) -> String {
+        let products: String = titles
+            .iter()
+            .map(|title| {
+                format!(
+                    "<article class=\"product_pod\"><h3><a href=\"{0}.html\" title=\"{0}\">{0}</a></h3><p class=\"price_color\">£10.00</p></article>",
+                    title
+                )
+            })
+            .collect();
+        let pager = next
+            .map(|href| format!("<ul class=\"pager\"><li class=\"next\"><a href=\"{}\">next</a></li></ul>", href))
+            .unwrap_or_default();
+        format!("<html><body>{}{}</body></html>", products, pager)
+    }
+
+    #[tokio::test]
+    async fn test_ecommerce_source_follows_next_page_links() {
+        setup();
+
+        let mut server = mockito::Server::new_async().await;
+        let first = server
+            .mock("GET", "/")
+            .with_header("content-type", "text/html")
+            .with_body(catalogue_page(&["One", "Two"], Some("catalogue/page-2.html")))
+            .expect(3)
+            .create_async()
+            .await;
+        let second = server
+            .mock("GET", "/catalogue/page-2.html")
+            .with_header("content-type", "text/html")
+            .with_body(catalogue_page(&["Three"], Some("page-3.html")))
+            .expect(2)
+            .create_async()
+            .await;
+        let third = server
+            .mock("GET", "/catalogue/page-3.html")
+            .with_header("content-type", "text/html")
+            .with_body(catalogue_page(&["Four"], None))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let titles = |items: &[ScrapedData]| items.iter().filter_map(|item| item.title.clone()).collect::<Vec<_>>();
+        let mut engine = test_engine();
+
+        // Runs out of next links before max_pages
+        let items = engine.scrape_source(EcommerceSource::new(&server.url()).with_max_pages(5)).await.unwrap();
+        assert_eq!(titles(&items), vec!["One", "Two", "Three", "Four"]);
+
+        let items = engine.scrape_source(EcommerceSource::new(&server.url()).with_max_pages(2)).await.unwrap();
+        assert_eq!(titles(&items), vec!["One", "Two", "Three"]);
+
+        // Without max_pages only the first page is scraped
+        let items = engine.scrape_source(EcommerceSource::new(&server.url())).await.unwrap();
+        assert_eq!(titles(&items), vec!["One", "Two"]);
+
+        first.assert_async().await;
+        second.assert_async().await;
+        third.assert_async().await;
+
+        let source = EcommerceSource::new("https://shop.example/catalogue/page-2.html");
+        assert_eq!(
+            source.next_page(&catalogue_page(&[], Some("page-3.html")), "https://shop.example/catalogue/page-2.html"),
+            Some("https://shop.example/catalogue/page-3.html".to_string())
+        );
+        assert_eq!(source.next_page(&catalogue_page(&["Last"], None), "https://shop.example/catalogue/page-2.html"), None);
+    }
+
+    #[tokio::test]
+    async fn test_scrape_sources_keeps_order_and_skips_failures() {
+        setup();
+
+        let mut first = mockito::Server::new_async().await;
+        let mut broken = mockito::Server::new_async().await;
+        let mut last = mockito::Server::new_async().await;
+        let _first = first.mock("GET", "/").with_header("content-type", "text/html").with_body(article_page("First", &[])).create_async().await;
+        let _broken = broken.mock("GET", "/").with_status(500).create_async().await;
+        let _last = last.mock("GET", "/").with_header("content-type", "text/html").with_body(article_page("Last", &[])).create_async().await;
+
+        let sources = vec![
+            SourceType::News(NewsSource::new(&first.url()).with_name("First")),
+            SourceType::News(NewsSource::new(&broken.url()).with_name("Broken")),
+            SourceType::News(NewsSource::new(&last.url()).with_name("Last")),
+        ];
+        let mut engine = test_engine();
+        let items = engine.scrape_sources(sources, 3).await.unwrap();
+
+        assert_eq!(
+            items.iter().map(|item| (item.source.as_str(), item.title.as_deref())).collect::<Vec<_>>(),
+            vec![("First", Some("First")), ("Last", Some("Last"))]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_capped_store_evicts_oldest_items_but_database_keeps_them() {
+        setup();
+        let path = std::env::temp_dir().join(format!("capped-{}.db", uuid::Uuid::new_v4()));
+        let db = SqliteOutput::new(&format!("sqlite://{}?mode=rwc", path.display()), None).await.unwrap();
+        db.init().await.unwrap();
+
+        let start = chrono::Utc::now();
+        let items: Vec<ScrapedData> = (0..5)
+            .map(|i| {
+                let mut item = api_item(&format!("item-{}", i), "Item");
+                item.timestamp = start + chrono::Duration::seconds(i);
+                item
+            })
+            .collect();
+
+        let mut store = IndexedStore::new().with_max_items(3);
+        // Arrives late but is the oldest, so it goes first once the cap is hit
+        let mut late = items[0].clone();
+        late.id = "late".to_string();
+        late.timestamp = start - chrono::Duration::seconds(60);
+        for item in items.iter().cloned().chain([late.clone()]) {
+            db.save(std::slice::from_ref(&item)).await.unwrap();
+            store.upsert(item);
+        }
+
+        assert_eq!(store.len(), 3);
+        let kept: Vec<&str> = store.items().iter().map(|item| item.id.as_str()).collect();
+        assert_eq!(kept, vec!["item-2", "item-3", "item-4"]);
+        assert!(!store.contains("late") && !store.contains("item-0"));
+        assert_eq!(store.by_source(&items[0].source).len(), 3);
+
+        for id in ["late", "item-0", "item-1"] {
+            let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM scraped_data WHERE id = ?")
+                .bind(id)
+                .fetch_one(db.get_pool())
+                .await
+                .unwrap();
+            assert_eq!(stored, 1, "{} should still be in the database", id);
+        }
+        let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM scraped_data")
+            .fetch_one(db.get_pool())
+            .await
+            .unwrap();
+        assert_eq!(total, 6);
+
+        // Capping an existing store trims it straight away
+        assert_eq!(IndexedStore::from(items).with_max_items(2).len(), 2);
+
+        db.get_pool().close().await;
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[tokio::test]
+    async fn test_scrape_process_persist_writes_every_output_and_isolates_failures() {
+        use rust_scraper_pro::output::JsonOutput;
+        setup();
+
+        let mut server = mockito::Server::new_async().await;
+        let _page = server
+            .mock("GET", "/")
+            .with_header("content-type", "text/html")
+            .with_body(article_page("Persisted", &[]))
+            .create_async()
+            .await;
+
+        let dir = std::env::temp_dir().join(format!("persist-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let json_path = dir.join("items.json");
+        // A regular file where a directory should be, so this export fails
+        let blocker = dir.join("not-a-dir");
+        std::fs::write(&blocker, "").unwrap();
+
+        let outputs: Vec<Box<dyn Output>> = vec![
+            Box::new(FileOutput::csv(blocker.join("items.csv"))),
+            Box::new(FileOutput::json(&json_path)),
+        ];
+        let mut engine = test_engine();
+        let persisted = engine
+            .scrape_process_persist(vec![NewsSource::new(&server.url()).with_name("Persist")], outputs)
+            .await
+            .unwrap();
+
+        assert_eq!(persisted.items.len(), 1);
+        assert_eq!(persisted.outcomes.len(), 2);
+        assert_eq!(persisted.failures().count(), 1);
+        assert!(persisted.outcomes[0].output.starts_with("CSV "));
+        assert_eq!(persisted.outcomes[1].result, Ok(1));
+
+        let written = JsonOutput::new().import(&json_path).await.unwrap();
+        assert_eq!(written.items.len(), 1);
+        assert_eq!(written.items[0].title.as_deref(), Some("Persisted"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn query_items() -> Vec<ScrapedData> {
+        ["cheap", "mid", "dear"]
+            .iter()
+            .zip([5.0, 12.5, 40.0])
+            .map(|(id, price)| {
+                let mut item = api_item(id, &format!("Book {}", id));
+                item.price = Some(price);
+                item.category = Some("Books".to_string());
+                item.metadata.insert("currency".to_string(), "GBP".to_string());
+                item
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_query_maps_rows_back_into_items() {
+        setup();
+        let db = SqliteOutput::new("sqlite::memory:", None).await.unwrap();
+        db.init().await.unwrap();
+        let items = query_items();
+        db.save(&items).await.unwrap();
+
+        let found = db.query("SELECT * FROM scraped_data WHERE price > 10 ORDER BY price").await.unwrap();
+        assert_eq!(found.iter().map(|item| item.id.as_str()).collect::<Vec<_>>(), vec!["mid", "dear"]);
+        assert_eq!(found[0].title.as_deref(), Some("Book mid"));
+        assert_eq!(found[0].price, Some(12.5));
+        assert_eq!(found[0].metadata.get("currency").map(String::as_str), Some("GBP"));
+        assert_eq!(found[0].timestamp, items[1].timestamp);
+        assert_eq!(found[0].source_url, items[1].source_url);
+
+        let bound = db
+            .query_with_args(
+                "SELECT * FROM scraped_data WHERE category = ? AND price < ?",
+                &[SqlArg::from("Books"), SqlArg::from(20.0)],
+            )
+            .await
+            .unwrap();
+        assert_eq!(bound.len(), 2);
+
+        // A bound value is only ever data, never SQL
+        let hostile = db
+            .query_with_args("SELECT * FROM scraped_data WHERE id = ?", &["x'; DROP TABLE scraped_data; --".into()])
+            .await
+            .unwrap();
+        assert!(hostile.is_empty());
+        assert_eq!(db.query("SELECT * FROM scraped_data").await.unwrap().len(), 3);
+
+        assert!(db.query("SELECT * FROM missing_table").await.is_err());
+        db.get_pool().close().await;
+    }
+
+    #[tokio::test]
+    async fn test_postgres_query_maps_rows_back_into_items() {
+        setup();
+        let Some(db) = test_postgres().await else {
+            return;
+        };
+        db.save(&query_items()).await.unwrap();
+
+        let table = db.table_name().to_string();
+        let found = db
+            .query_with_args(
+                &format!(
+                    "SELECT id, source, url, source_url, title, content, price::FLOAT8 AS price, image_url, author, timestamp, category, metadata \
+                     FROM {} WHERE price > $1 ORDER BY price",
+                    table
+                ),
+                &[SqlArg::from(10.0)],
+            )
+            .await
+            .unwrap();
+        assert_eq!(found.iter().map(|item| item.id.as_str()).collect::<Vec<_>>(), vec!["mid", "dear"]);
+        assert_eq!(found[0].price, Some(12.5));
+        assert_eq!(found[0].metadata.get("currency").map(String::as_str), Some("GBP"));
+
+        drop_postgres_table(&db, &table).await;
+    }
+
+    #[tokio::test]
+    async fn test_outputs_can_be_written_through_one_list() {
+        setup();
+        let dir = std::env::temp_dir().join(format!("outputs-{}", uuid::Uuid::new_v4()));
+        let db = Arc::new(SqliteOutput::new("sqlite::memory:", None).await.unwrap());
+        db.init().await.unwrap();
+
+        let outputs: Vec<Box<dyn Output>> = vec![
+            Box::new(FileOutput::json(dir.join("items.json"))),
+            Box::new(FileOutput::csv(dir.join("items.csv"))),
+            Box::new(FileOutput::bundle(dir.join("items.zip"))),
+            Box::new(db.clone()),
+        ];
+        let items = vec![api_item("a", "First"), api_item("b", "Second")];
+
+        let mut written = Vec::new();
+        for output in &outputs {
+            written.push((output.describe(), output.write(&items).await.unwrap()));
+        }
+
+        assert_eq!(
+            written,
+            vec![
+                (format!("JSON {}", dir.join("items.json").display()), 2),
+                (format!("CSV {}", dir.join("items.csv").display()), 2),
+                (format!("Zip bundle {}", dir.join("items.zip").display()), 2),
+                ("SQLite table scraped_data".to_string(), 2),
+            ]
+        );
+        for file in ["items.json", "items.csv", "items.zip"] {
+            assert!(std::fs::metadata(dir.join(file)).unwrap().len() > 0, "{} is empty", file);
+        }
+        assert_eq!(db.query("SELECT * FROM scraped_data").await.unwrap().len(), 2);
+
+        db.get_pool().close().await;
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}